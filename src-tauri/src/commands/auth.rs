@@ -0,0 +1,247 @@
+use super::credentials::{host_token_key, lookup_secret, ssh_passphrase_key};
+use super::ssh::KEY_TYPES;
+use crate::error::AppError;
+use git2::{Cred, CredentialType, ErrorClass, ErrorCode, RemoteCallbacks, Repository};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+
+/// Upper bound on credential callbacks per operation, so a rejected credential can't loop forever
+const MAX_ATTEMPTS: usize = 8;
+
+type SecretLookup = Box<dyn Fn(&str) -> Option<String>>;
+
+/// Which credential sources have already been offered during the current operation
+#[derive(Default)]
+struct AuthState {
+    attempts: usize,
+    tried_agent: bool,
+    next_key: usize,
+    tried_explicit: bool,
+    tried_token: bool,
+    tried_helper: bool,
+    tried_default: bool,
+    exhausted: bool,
+    passphrase_needed: Option<PathBuf>,
+}
+
+/// Credential resolution shared by every network operation (clone, fetch, pull, push).
+///
+/// Sources are tried once each, in order: ssh-agent, `~/.ssh` keys, explicit
+/// username/password, keyring token for the host, then the git credential helper.
+pub(crate) struct GitAuth {
+    config: Option<git2::Config>,
+    username: Option<String>,
+    password: Option<String>,
+    secrets: Option<SecretLookup>,
+    ssh_keys: Vec<PathBuf>,
+    state: RefCell<AuthState>,
+}
+
+impl GitAuth {
+    pub(crate) fn new(config: Option<git2::Config>, username: Option<String>, password: Option<String>) -> Self {
+        Self {
+            config,
+            username: username.filter(|u| !u.is_empty()),
+            password: password.filter(|p| !p.is_empty()),
+            secrets: None,
+            ssh_keys: default_ssh_keys(),
+            state: RefCell::new(AuthState::default()),
+        }
+    }
+
+    /// Auth for an existing repository, using its config for the credential helper
+    pub(crate) fn for_repo(repo: &Repository, username: Option<String>, password: Option<String>) -> Self {
+        Self::new(repo.config().ok(), username, password)
+    }
+
+    /// Look up host tokens and key passphrases in the system keyring
+    pub(crate) fn with_keyring(mut self, app: &tauri::AppHandle) -> Self {
+        let app = app.clone();
+        self.secrets = Some(Box::new(move |key: &str| lookup_secret(&app, key)));
+        self
+    }
+
+    /// Remote callbacks with credential handling installed; resets the attempt state
+    pub(crate) fn callbacks(&self) -> RemoteCallbacks<'_> {
+        *self.state.borrow_mut() = AuthState::default();
+
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.credentials(move |url, username_from_url, allowed_types| {
+            self.credentials(url, username_from_url, allowed_types)
+        });
+        callbacks
+    }
+
+    /// Turn a failed network operation into a typed error the UI can prompt on
+    pub(crate) fn map_error(&self, err: git2::Error) -> AppError {
+        let state = self.state.borrow();
+
+        if state.exhausted {
+            if let Some(key) = &state.passphrase_needed {
+                return AppError::PassphraseRequired(key.display().to_string());
+            }
+        }
+
+        if state.exhausted || err.code() == ErrorCode::Auth || (err.class() == ErrorClass::Http && err.message().contains("401")) {
+            return AppError::AuthRequired(err.message().to_string());
+        }
+
+        AppError::Git(err)
+    }
+
+    fn credentials(
+        &self,
+        url: &str,
+        username_from_url: Option<&str>,
+        allowed_types: CredentialType,
+    ) -> std::result::Result<Cred, git2::Error> {
+        let mut state = self.state.borrow_mut();
+        state.attempts += 1;
+        if state.attempts > MAX_ATTEMPTS {
+            state.exhausted = true;
+            return Err(git2::Error::from_str("too many authentication attempts"));
+        }
+
+        let user = username_from_url
+            .map(str::to_string)
+            .or_else(|| self.username.clone())
+            .unwrap_or_else(|| "git".to_string());
+
+        // SSH asks for the user name on its own before offering key auth
+        if allowed_types.contains(CredentialType::USERNAME) {
+            return Cred::username(&user);
+        }
+
+        if allowed_types.contains(CredentialType::SSH_KEY) {
+            if !state.tried_agent {
+                state.tried_agent = true;
+                if let Ok(cred) = Cred::ssh_key_from_agent(&user) {
+                    return Ok(cred);
+                }
+            }
+
+            while state.next_key < self.ssh_keys.len() {
+                let key = &self.ssh_keys[state.next_key];
+                state.next_key += 1;
+
+                let passphrase = self.secret(&ssh_passphrase_key(&key.to_string_lossy()));
+                if passphrase.is_none() && is_encrypted_key(key) {
+                    state.passphrase_needed.get_or_insert_with(|| key.clone());
+                    continue;
+                }
+
+                let public = PathBuf::from(format!("{}.pub", key.display()));
+                let public = public.exists().then_some(public);
+                if let Ok(cred) = Cred::ssh_key(&user, public.as_deref(), key, passphrase.as_deref()) {
+                    return Ok(cred);
+                }
+            }
+        }
+
+        if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if !state.tried_explicit {
+                state.tried_explicit = true;
+                if let (Some(user), Some(pass)) = (&self.username, &self.password) {
+                    return Cred::userpass_plaintext(user, pass);
+                }
+            }
+
+            if !state.tried_token {
+                state.tried_token = true;
+                if let Some(token) = host_of(url).and_then(|host| self.host_token(&host)) {
+                    let user = self
+                        .username
+                        .clone()
+                        .or_else(|| username_from_url.map(str::to_string))
+                        .or_else(|| self.secret("git-username"))
+                        .unwrap_or_else(|| "git".to_string());
+                    return Cred::userpass_plaintext(&user, &token);
+                }
+            }
+
+            if !state.tried_helper {
+                state.tried_helper = true;
+                if let Some(config) = &self.config {
+                    if let Ok(cred) = Cred::credential_helper(config, url, username_from_url) {
+                        return Ok(cred);
+                    }
+                }
+            }
+        }
+
+        if allowed_types.contains(CredentialType::DEFAULT) && !state.tried_default {
+            state.tried_default = true;
+            return Cred::default();
+        }
+
+        state.exhausted = true;
+        Err(git2::Error::from_str("no authentication method available"))
+    }
+
+    fn secret(&self, key: &str) -> Option<String> {
+        self.secrets.as_ref().and_then(|lookup| lookup(key)).filter(|s| !s.is_empty())
+    }
+
+    fn host_token(&self, host: &str) -> Option<String> {
+        self.secret(&host_token_key(host)).or_else(|| match host {
+            // Tokens saved from the settings page before per-host entries existed
+            "github.com" => self.secret("github-token"),
+            "gitlab.com" => self.secret("gitlab-token"),
+            _ => None,
+        })
+    }
+}
+
+/// Order to offer the `KEY_TYPES` keys in, like ssh's own default
+const KEY_PREFERENCE: [&str; 4] = ["ed25519", "ecdsa", "rsa", "dsa"];
+
+/// Private keys in `~/.ssh` that exist on disk, in preference order
+fn default_ssh_keys() -> Vec<PathBuf> {
+    let Some(ssh_dir) = dirs::home_dir().map(|home| home.join(".ssh")) else {
+        return Vec::new();
+    };
+
+    let mut key_types = KEY_TYPES;
+    key_types.sort_by_key(|key_type| KEY_PREFERENCE.iter().position(|preferred| preferred == key_type));
+    key_types
+        .iter()
+        .map(|key_type| ssh_dir.join(format!("id_{}", key_type)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Whether a private key needs a passphrase, judged from the file header
+fn is_encrypted_key(path: &Path) -> bool {
+    let Ok(content) = std::fs::read_to_string(path) else {
+        return false;
+    };
+
+    // PEM keys carry a Proc-Type header when encrypted
+    if content.contains("ENCRYPTED") {
+        return true;
+    }
+
+    // OpenSSH keys start with "openssh-key-v1\0" followed by the cipher name,
+    // which is "none" for unencrypted keys
+    let body: String = content
+        .lines()
+        .filter(|line| !line.starts_with("-----"))
+        .collect();
+    body.starts_with("b3BlbnNzaC1rZXktdjE") && !body.starts_with("b3BlbnNzaC1rZXktdjEAAAAABG5vbmU")
+}
+
+/// Host part of an https or scp-style remote URL
+fn host_of(url: &str) -> Option<String> {
+    if let Ok(parsed) = url::Url::parse(url) {
+        return parsed.host_str().map(str::to_string);
+    }
+
+    // git@github.com:owner/repo.git
+    let (authority, _) = url.split_once(':')?;
+    let host = authority.rsplit('@').next()?;
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+#[cfg(test)]
+#[path = "auth_tests.rs"]
+mod auth_tests;
@@ -0,0 +1,460 @@
+use crate::domain::BisectState;
+use crate::error::{AppError, Result};
+use super::progress::Operation;
+use super::rebase::ensure_clean_worktree;
+use git2::{Oid, Repository};
+use std::collections::{HashMap, HashSet};
+
+// Bisect state is kept where git keeps it: verdicts as `refs/bisect/bad`,
+// `refs/bisect/good-<oid>` and `refs/bisect/skip-<oid>`, the branch to return
+// to in BISECT_START, the commit under test in BISECT_EXPECTED_REV and a
+// replayable BISECT_LOG. The estimate is recomputed from the refs on demand,
+// so a session started with the git CLI can be continued here and vice versa.
+const BISECT_START: &str = "BISECT_START";
+const BISECT_LOG: &str = "BISECT_LOG";
+const BISECT_TERMS: &str = "BISECT_TERMS";
+const BISECT_EXPECTED_REV: &str = "BISECT_EXPECTED_REV";
+const BAD_REF: &str = "refs/bisect/bad";
+
+/// Exit code of a `bisect run` command meaning "cannot test this commit"
+const SKIP_EXIT_CODE: i32 = 125;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Verdict {
+    Good,
+    Bad,
+    Skip,
+}
+
+impl Verdict {
+    fn parse(verdict: &str) -> Result<Self> {
+        match verdict {
+            "good" | "old" => Ok(Verdict::Good),
+            "bad" | "new" => Ok(Verdict::Bad),
+            "skip" => Ok(Verdict::Skip),
+            _ => Err(AppError::InvalidInput(format!("Unknown bisect verdict: {}", verdict))),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Verdict::Good => "good",
+            Verdict::Bad => "bad",
+            Verdict::Skip => "skip",
+        }
+    }
+
+    /// Classify a `bisect run` exit code like git does; None aborts the run
+    fn from_exit_code(code: Option<i32>) -> Option<Self> {
+        match code {
+            Some(0) => Some(Verdict::Good),
+            Some(SKIP_EXIT_CODE) => Some(Verdict::Skip),
+            Some(code) if (1..128).contains(&code) => Some(Verdict::Bad),
+            _ => None,
+        }
+    }
+}
+
+fn is_bisecting(repo: &Repository) -> bool {
+    repo.path().join(BISECT_START).exists()
+}
+
+fn append_log(repo: &Repository, text: &str) -> Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(repo.path().join(BISECT_LOG))?;
+    file.write_all(text.as_bytes())?;
+    Ok(())
+}
+
+fn describe(repo: &Repository, oid: Oid) -> String {
+    let summary = repo
+        .find_commit(oid)
+        .ok()
+        .and_then(|c| c.summary().map(|s| s.to_string()))
+        .unwrap_or_default();
+    format!("[{}] {}", oid, summary)
+}
+
+/// Start bisecting between a bad and any number of good revisions
+#[tauri::command]
+pub async fn start_bisect(
+    path: String,
+    bad: Option<String>,
+    good: Vec<String>,
+) -> std::result::Result<BisectState, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    start_bisect_impl(&repo, bad.as_deref(), &good).map_err(|e| e.to_string())
+}
+
+fn start_bisect_impl(repo: &Repository, bad: Option<&str>, good: &[String]) -> Result<BisectState> {
+    if is_bisecting(repo) || repo.state() != git2::RepositoryState::Clean {
+        return Err(AppError::InvalidInput(
+            "Cannot bisect: another operation (bisect, merge, rebase, ...) is in progress".to_string()
+        ));
+    }
+    ensure_clean_worktree(repo, "bisect")?;
+
+    // Resolve everything before writing any state
+    let bad = bad
+        .map(|rev| repo.revparse_single(rev).and_then(|o| o.peel_to_commit()))
+        .transpose()?
+        .map(|c| c.id());
+    let good = good
+        .iter()
+        .map(|rev| repo.revparse_single(rev).and_then(|o| o.peel_to_commit()).map(|c| c.id()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let head = repo.head()?;
+    let start = if head.is_branch() {
+        head.shorthand().unwrap_or("HEAD").to_string()
+    } else {
+        head.peel_to_commit()?.id().to_string()
+    };
+
+    std::fs::write(repo.path().join(BISECT_START), format!("{}\n", start))?;
+    std::fs::write(repo.path().join(BISECT_TERMS), "bad\ngood\n")?;
+    append_log(repo, "git bisect start\n")?;
+
+    if let Some(bad) = bad {
+        record(repo, Verdict::Bad, bad)?;
+    }
+    for oid in good {
+        record(repo, Verdict::Good, oid)?;
+    }
+
+    advance(repo)
+}
+
+/// Store a verdict as a bisect ref and in the log
+fn record(repo: &Repository, verdict: Verdict, oid: Oid) -> Result<()> {
+    let refname = match verdict {
+        Verdict::Bad => BAD_REF.to_string(),
+        Verdict::Good => format!("refs/bisect/good-{}", oid),
+        Verdict::Skip => format!("refs/bisect/skip-{}", oid),
+    };
+    repo.reference(&refname, oid, true, &format!("bisect {}", verdict.as_str()))?;
+    append_log(repo, &format!(
+        "# {}: {}\ngit bisect {} {}\n",
+        verdict.as_str(),
+        describe(repo, oid),
+        verdict.as_str(),
+        oid
+    ))
+}
+
+/// Pick and check out the next commit to test, or log the result when done
+fn advance(repo: &Repository) -> Result<BisectState> {
+    let state = bisect_state(repo, true)?;
+
+    if let Some(current) = &state.current {
+        let oid = Oid::from_str(current)?;
+        if repo.head().ok().and_then(|h| h.target()) != Some(oid) {
+            let commit = repo.find_commit(oid)?;
+            repo.checkout_tree(commit.as_object(), None)?;
+            repo.set_head_detached(oid)?;
+        }
+        std::fs::write(repo.path().join(BISECT_EXPECTED_REV), format!("{}\n", current))?;
+    } else {
+        let expected = repo.path().join(BISECT_EXPECTED_REV);
+        if expected.exists() {
+            std::fs::remove_file(expected)?;
+        }
+        if let Some(first_bad) = &state.first_bad {
+            append_log(repo, &format!("# first bad commit: {}\n", describe(repo, Oid::from_str(first_bad)?)))?;
+        }
+    }
+
+    Ok(state)
+}
+
+/// Mark a commit (default: the checked out one) as good, bad or skipped
+#[tauri::command]
+pub async fn bisect_mark(
+    path: String,
+    verdict: String,
+    commit: Option<String>,
+) -> std::result::Result<BisectState, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    let verdict = Verdict::parse(&verdict).map_err(|e| e.to_string())?;
+    bisect_mark_impl(&repo, verdict, commit.as_deref()).map_err(|e| e.to_string())
+}
+
+fn bisect_mark_impl(repo: &Repository, verdict: Verdict, commit: Option<&str>) -> Result<BisectState> {
+    if !is_bisecting(repo) {
+        return Err(AppError::InvalidInput("No bisect in progress".to_string()));
+    }
+
+    let oid = repo.revparse_single(commit.unwrap_or("HEAD"))?.peel_to_commit()?.id();
+    record(repo, verdict, oid)?;
+    advance(repo)
+}
+
+/// Get the current bisect state
+#[tauri::command]
+pub async fn get_bisect_state(path: String) -> std::result::Result<BisectState, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_bisect_state_impl(&repo).map_err(|e| e.to_string())
+}
+
+fn get_bisect_state_impl(repo: &Repository) -> Result<BisectState> {
+    bisect_state(repo, false)
+}
+
+/// The state from the bisect refs. The commit under test is the one picked
+/// when the last verdict was recorded, unless `choose_next` asks for a new pick
+fn bisect_state(repo: &Repository, choose_next: bool) -> Result<BisectState> {
+    let mut state = BisectState {
+        is_bisecting: is_bisecting(repo),
+        bad: None,
+        good: Vec::new(),
+        skipped: Vec::new(),
+        current: None,
+        remaining: 0,
+        steps_left: 0,
+        first_bad: None,
+    };
+    if !state.is_bisecting {
+        return Ok(state);
+    }
+
+    let mut good = Vec::new();
+    let mut skipped = HashSet::new();
+    for reference in repo.references_glob("refs/bisect/*")? {
+        let reference = reference?;
+        let (Some(name), Some(target)) = (reference.name(), reference.target()) else {
+            continue;
+        };
+        if name == BAD_REF {
+            state.bad = Some(target.to_string());
+        } else if name.starts_with("refs/bisect/good-") {
+            good.push(target);
+        } else if name.starts_with("refs/bisect/skip-") {
+            skipped.insert(target);
+        }
+    }
+    state.good = good.iter().map(|oid| oid.to_string()).collect();
+    state.skipped = skipped.iter().map(|oid| oid.to_string()).collect();
+    state.skipped.sort();
+
+    // Waiting for both ends to be marked
+    let Some(bad) = state.bad.as_deref().map(Oid::from_str).transpose()? else {
+        return Ok(state);
+    };
+    if good.is_empty() {
+        return Ok(state);
+    }
+
+    // Candidates: reachable from bad but from no good commit
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    revwalk.push(bad)?;
+    for oid in &good {
+        revwalk.hide(*oid)?;
+    }
+    let candidates: Vec<Oid> = revwalk.collect::<std::result::Result<_, _>>()?;
+
+    if candidates.is_empty() {
+        return Err(AppError::InvalidInput(format!(
+            "The bad commit {} is an ancestor of a good commit",
+            bad
+        )));
+    }
+
+    let untested: Vec<Oid> = candidates
+        .iter()
+        .copied()
+        .filter(|oid| *oid != bad && !skipped.contains(oid))
+        .collect();
+
+    state.remaining = untested.len();
+    state.steps_left = steps_for(untested.len());
+
+    if candidates.len() == 1 {
+        state.first_bad = Some(bad.to_string());
+    } else if !untested.is_empty() {
+        let expected = std::fs::read_to_string(repo.path().join(BISECT_EXPECTED_REV))
+            .ok()
+            .and_then(|rev| Oid::from_str(rev.trim()).ok())
+            .filter(|oid| !choose_next && untested.contains(oid));
+        let current = match expected {
+            Some(oid) => oid,
+            None => best_midpoint(repo, &candidates, &untested)?,
+        };
+        state.current = Some(current.to_string());
+    }
+    // Otherwise only skipped commits are left and the first bad one can't be determined
+
+    Ok(state)
+}
+
+/// Tests needed to narrow `remaining` untested commits down to one answer
+fn steps_for(remaining: usize) -> usize {
+    let mut steps = 0;
+    while (1usize << steps) <= remaining {
+        steps += 1;
+    }
+    steps
+}
+
+/// The untested commit whose ancestors make up closest to half the candidates.
+///
+/// `candidates` is in topological order, children first. Walking it from the
+/// oldest end, a commit with one parent in range weighs its parent's weight
+/// plus one; only merges need their ancestors counted (git's `count_distance`).
+fn best_midpoint(repo: &Repository, candidates: &[Oid], untested: &[Oid]) -> Result<Oid> {
+    let in_range: HashSet<Oid> = candidates.iter().copied().collect();
+    let mut parents: HashMap<Oid, Vec<Oid>> = HashMap::new();
+    for oid in candidates {
+        let commit = repo.find_commit(*oid)?;
+        parents.insert(
+            *oid,
+            commit.parent_ids().filter(|p| in_range.contains(p)).collect(),
+        );
+    }
+
+    let mut weights: HashMap<Oid, usize> = HashMap::with_capacity(candidates.len());
+    for oid in candidates.iter().rev() {
+        let weight = match parents[oid].as_slice() {
+            [] => 1,
+            [parent] => weights[parent] + 1,
+            _ => count_distance(&parents, *oid),
+        };
+        weights.insert(*oid, weight);
+    }
+
+    let total = candidates.len();
+    let mut best = (untested[0], 0);
+    for oid in untested {
+        let score = weights[oid].min(total - weights[oid]);
+        if score > best.1 {
+            best = (*oid, score);
+        }
+    }
+
+    Ok(best.0)
+}
+
+/// Commits in range that are `oid` or its ancestors
+fn count_distance(parents: &HashMap<Oid, Vec<Oid>>, oid: Oid) -> usize {
+    let mut seen = HashSet::from([oid]);
+    let mut stack = vec![oid];
+    while let Some(next) = stack.pop() {
+        for parent in &parents[&next] {
+            if seen.insert(*parent) {
+                stack.push(*parent);
+            }
+        }
+    }
+    seen.len()
+}
+
+/// Get the bisect log (`git bisect log` format)
+#[tauri::command]
+pub async fn get_bisect_log(path: String) -> std::result::Result<String, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    if !is_bisecting(&repo) {
+        return Err("No bisect in progress".to_string());
+    }
+    std::fs::read_to_string(repo.path().join(BISECT_LOG)).map_err(|e| e.to_string())
+}
+
+/// End bisecting and return to the branch or commit it was started from
+#[tauri::command]
+pub async fn reset_bisect(path: String) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    reset_bisect_impl(&repo).map_err(|e| e.to_string())
+}
+
+fn reset_bisect_impl(repo: &Repository) -> Result<()> {
+    if !is_bisecting(repo) {
+        return Err(AppError::InvalidInput("No bisect in progress".to_string()));
+    }
+
+    let start = std::fs::read_to_string(repo.path().join(BISECT_START))?.trim().to_string();
+    let branch_ref = format!("refs/heads/{}", start);
+    let target = match repo.refname_to_id(&branch_ref) {
+        Ok(oid) => oid,
+        Err(_) => Oid::from_str(&start)?,
+    };
+
+    let commit = repo.find_commit(target)?;
+    repo.checkout_tree(commit.as_object(), None)?;
+    if repo.find_reference(&branch_ref).is_ok() {
+        repo.set_head(&branch_ref)?;
+    } else {
+        repo.set_head_detached(target)?;
+    }
+
+    for reference in repo.references_glob("refs/bisect/*")? {
+        reference?.delete()?;
+    }
+    for name in [BISECT_START, BISECT_LOG, BISECT_TERMS, BISECT_EXPECTED_REV] {
+        let path = repo.path().join(name);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Run a shell command at each step and mark the commit by its exit code:
+/// 0 good, 125 skip, 1-127 bad; anything else (e.g. killed) stops the run.
+#[tauri::command]
+pub async fn run_bisect(
+    app: tauri::AppHandle,
+    path: String,
+    command: String,
+    operation_id: Option<String>,
+) -> std::result::Result<BisectState, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    let operation = Operation::start(Some(&app), operation_id, "bisect");
+    run_bisect_impl(&repo, &command, &operation).map_err(|e| e.to_string())
+}
+
+fn run_bisect_impl(repo: &Repository, command: &str, operation: &Operation) -> Result<BisectState> {
+    if !is_bisecting(repo) {
+        return Err(AppError::InvalidInput("No bisect in progress".to_string()));
+    }
+    let workdir = repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))?;
+
+    let mut state = get_bisect_state_impl(repo)?;
+    if state.current.is_some() {
+        append_log(repo, &format!("# bisect run: {}\n", command))?;
+    }
+
+    while let Some(current) = state.current.clone() {
+        if operation.is_cancelled() {
+            return Err(AppError::Cancelled("bisect run".to_string()));
+        }
+
+        let status = shell(command).current_dir(workdir).status()?;
+        let verdict = Verdict::from_exit_code(status.code()).ok_or_else(|| {
+            AppError::InvalidInput(format!("bisect run failed: command exited with {}", status))
+        })?;
+
+        state = bisect_mark_impl(repo, verdict, Some(&current))?;
+        operation.bisect_step(&current, verdict.as_str(), state.remaining);
+    }
+
+    Ok(state)
+}
+
+fn shell(command: &str) -> std::process::Command {
+    if cfg!(target_os = "windows") {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    }
+}
+
+#[cfg(test)]
+#[path = "bisect_tests.rs"]
+mod bisect_tests;
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::bisect::{
+        bisect_mark_impl, get_bisect_state_impl, reset_bisect_impl, run_bisect_impl, start_bisect_impl,
+        steps_for, Verdict,
+    };
+    use crate::commands::progress::Operation;
+    use crate::commands::test_support::{commit_file, init_repo};
+    use git2::{Oid, Repository, Signature};
+    use tempfile::TempDir;
+
+    /// Linear history of `count` commits where the bug appears in commit `bug_at` (1-based)
+    fn history(count: usize, bug_at: usize) -> (TempDir, Repository, Vec<Oid>) {
+        let (dir, repo) = init_repo();
+        let oids = (1..=count)
+            .map(|i| {
+                let content = if i >= bug_at { format!("{} bug\n", i) } else { format!("{}\n", i) };
+                commit_file(&repo, "file.txt", &content, &format!("commit {}", i))
+            })
+            .collect();
+        (dir, repo, oids)
+    }
+
+    fn has_bug(dir: &TempDir) -> bool {
+        std::fs::read_to_string(dir.path().join("file.txt")).unwrap().contains("bug")
+    }
+
+    #[test]
+    fn test_manual_bisect_finds_first_bad_commit() {
+        let (dir, repo, oids) = history(8, 5);
+        let mut state = start_bisect_impl(&repo, Some(&oids[7].to_string()), &[oids[0].to_string()]).unwrap();
+
+        assert!(state.is_bisecting);
+        assert_eq!(state.remaining, 6);
+        assert_eq!(state.steps_left, 3);
+
+        let mut steps = 0;
+        while let Some(current) = state.current.clone() {
+            assert_eq!(repo.head().unwrap().target().unwrap().to_string(), current);
+            let verdict = if has_bug(&dir) { Verdict::Bad } else { Verdict::Good };
+            state = bisect_mark_impl(&repo, verdict, None).unwrap();
+            steps += 1;
+        }
+
+        assert_eq!(state.first_bad, Some(oids[4].to_string()));
+        assert!(steps <= 3, "took {} steps", steps);
+        assert_eq!(repo.state(), git2::RepositoryState::Bisect);
+
+        let log = std::fs::read_to_string(repo.path().join("BISECT_LOG")).unwrap();
+        assert!(log.starts_with("git bisect start\n"));
+        assert!(log.contains(&format!("# first bad commit: [{}] commit 5", oids[4])));
+    }
+
+    #[test]
+    fn test_skipped_commits_are_not_proposed() {
+        let (_dir, repo, oids) = history(4, 3);
+        let state = start_bisect_impl(&repo, Some(&oids[3].to_string()), &[oids[0].to_string()]).unwrap();
+        let first = state.current.clone().unwrap();
+
+        let state = bisect_mark_impl(&repo, Verdict::Skip, None).unwrap();
+
+        assert_eq!(state.skipped, vec![first.clone()]);
+        assert_ne!(state.current, Some(first));
+        assert!(state.current.is_some());
+    }
+
+    #[test]
+    fn test_state_keeps_the_commit_under_test() {
+        let (_dir, repo, oids) = history(8, 5);
+        let state = start_bisect_impl(&repo, Some(&oids[7].to_string()), &[oids[0].to_string()]).unwrap();
+        assert_ne!(state.current, Some(oids[1].to_string()));
+
+        // Whatever was picked when the verdict was recorded stays current
+        std::fs::write(repo.path().join("BISECT_EXPECTED_REV"), format!("{}\n", oids[1])).unwrap();
+
+        assert_eq!(get_bisect_state_impl(&repo).unwrap().current, Some(oids[1].to_string()));
+    }
+
+    #[test]
+    fn test_midpoint_counts_both_sides_of_a_merge() {
+        let (_dir, repo, oids) = history(1, 2);
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let base = repo.find_commit(oids[0]).unwrap();
+        let tree = base.tree().unwrap();
+
+        // Two branches of three commits each off the good commit, then merged
+        let mut tips = Vec::new();
+        for side in ["left", "right"] {
+            let mut parent = base.clone();
+            for i in 1..=3 {
+                let oid = repo.commit(None, &sig, &sig, &format!("{} {}", side, i), &tree, &[&parent]).unwrap();
+                parent = repo.find_commit(oid).unwrap();
+            }
+            tips.push(parent);
+        }
+        let merge = repo.commit(None, &sig, &sig, "merge", &tree, &[&tips[0], &tips[1]]).unwrap();
+
+        let state = start_bisect_impl(&repo, Some(&merge.to_string()), &[oids[0].to_string()]).unwrap();
+
+        assert_eq!(state.remaining, 6);
+        let tips: Vec<String> = tips.iter().map(|tip| tip.id().to_string()).collect();
+        assert!(tips.contains(state.current.as_ref().unwrap()));
+    }
+
+    #[test]
+    fn test_state_waits_for_good_commit() {
+        let (_dir, repo, oids) = history(3, 2);
+        let state = start_bisect_impl(&repo, Some(&oids[2].to_string()), &[]).unwrap();
+
+        assert!(state.is_bisecting);
+        assert_eq!(state.bad, Some(oids[2].to_string()));
+        assert!(state.current.is_none());
+        assert!(state.first_bad.is_none());
+    }
+
+    #[test]
+    fn test_reset_returns_to_branch_and_clears_state() {
+        let (_dir, repo, oids) = history(6, 3);
+        let branch = repo.head().unwrap().name().unwrap().to_string();
+        start_bisect_impl(&repo, Some(&oids[5].to_string()), &[oids[0].to_string()]).unwrap();
+        assert!(repo.head_detached().unwrap());
+        assert!(start_bisect_impl(&repo, None, &[]).is_err());
+
+        reset_bisect_impl(&repo).unwrap();
+
+        assert_eq!(repo.head().unwrap().name(), Some(branch.as_str()));
+        assert_eq!(repo.head().unwrap().target(), Some(oids[5]));
+        assert!(repo.references_glob("refs/bisect/*").unwrap().next().is_none());
+        assert!(!get_bisect_state_impl(&repo).unwrap().is_bisecting);
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bisect_run_classifies_by_exit_code() {
+        let (_dir, repo, oids) = history(10, 7);
+        start_bisect_impl(&repo, Some(&oids[9].to_string()), &[oids[0].to_string()]).unwrap();
+        let operation = Operation::start(None, None, "bisect");
+
+        let state = run_bisect_impl(&repo, "! grep -q bug file.txt", &operation).unwrap();
+
+        assert_eq!(state.first_bad, Some(oids[6].to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bisect_run_stops_on_abnormal_exit() {
+        let (_dir, repo, oids) = history(4, 2);
+        start_bisect_impl(&repo, Some(&oids[3].to_string()), &[oids[0].to_string()]).unwrap();
+        let operation = Operation::start(None, None, "bisect");
+
+        assert!(run_bisect_impl(&repo, "exit 200", &operation).is_err());
+        assert!(get_bisect_state_impl(&repo).unwrap().current.is_some());
+    }
+
+    #[test]
+    fn test_exit_codes_and_step_estimate() {
+        assert_eq!(Verdict::from_exit_code(Some(0)), Some(Verdict::Good));
+        assert_eq!(Verdict::from_exit_code(Some(1)), Some(Verdict::Bad));
+        assert_eq!(Verdict::from_exit_code(Some(125)), Some(Verdict::Skip));
+        assert_eq!(Verdict::from_exit_code(Some(128)), None);
+        assert_eq!(Verdict::from_exit_code(None), None);
+
+        assert_eq!(steps_for(0), 0);
+        assert_eq!(steps_for(1), 1);
+        assert_eq!(steps_for(6), 3);
+        assert_eq!(steps_for(8), 4);
+    }
+}
@@ -1,6 +1,7 @@
+use super::auth::GitAuth;
 use crate::error::Result;
 use git2::build::RepoBuilder;
-use git2::{Cred, FetchOptions, RemoteCallbacks, Progress};
+use git2::{FetchOptions, Progress};
 use std::path::Path;
 use tauri::{AppHandle, Emitter};
 
@@ -33,43 +34,9 @@ fn clone_repository_impl(
     username: Option<String>,
     password: Option<String>,
 ) -> Result<String> {
-    let mut cb = RemoteCallbacks::new();
-
-    // Clone the username/password for the callback
-    let auth_username = username.clone();
-    let auth_password = password.clone();
-    
-    // Handle credentials
-    cb.credentials(move |url, username_from_url, allowed_types| {
-        let default_username = username_from_url.unwrap_or("git");
-
-        if let (Some(user), Some(pass)) = (&auth_username, &auth_password) {
-            return Cred::userpass_plaintext(user, pass);
-        }
-
-        if allowed_types.contains(git2::CredentialType::SSH_KEY) {
-            Cred::ssh_key_from_agent(default_username)
-        } else if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-             // Try to use git-credential-helper if available, otherwise fail if no auth provided
-             // We can't access repo config here easily as repo doesn't exist yet, 
-             // but maybe we can use default config?
-             // For now, let's rely on passed credentials or agent.
-             // If we want to use helper, we might need Config::open_default()
-             if let Ok(config) = git2::Config::open_default() {
-                 Cred::credential_helper(&config, url, Some(default_username))
-             } else {
-                 Err(git2::Error::from_str("no authentication method available"))
-             }
-        } else if allowed_types.contains(git2::CredentialType::DEFAULT) {
-            if let Ok(config) = git2::Config::open_default() {
-                Cred::credential_helper(&config, url, Some(default_username))
-            } else {
-                 Err(git2::Error::from_str("no authentication method available"))
-            }
-        } else {
-            Err(git2::Error::from_str("no authentication method available"))
-        }
-    });
+    // Credentials come from the shared auth chain; there is no repo config yet
+    let auth = GitAuth::new(git2::Config::open_default().ok(), username, password).with_keyring(app);
+    let mut cb = auth.callbacks();
 
     // Handle progress
     let app_handle = app.clone();
@@ -94,7 +61,8 @@ fn clone_repository_impl(
     let mut builder = RepoBuilder::new();
     builder.fetch_options(fo);
 
-    let _repo = builder.clone(url, Path::new(destination))?;
+    let _repo = builder.clone(url, Path::new(destination))
+        .map_err(|e| auth.map_error(e))?;
 
     Ok(destination.to_string())
 }
@@ -1,5 +1,6 @@
-use crate::domain::{CommitSuggestion, BatchCommitResult, BatchFailure};
+use crate::domain::{CommitSuggestion, BatchCommitResult, BatchFailure, RenameOptions};
 use crate::error::{AppError, Result};
+use super::diff::status_options;
 use git2::Repository;
 use serde_json::json;
 
@@ -231,12 +232,22 @@ fn commit_impl(repo: &Repository, message: &str) -> Result<String> {
     let tree_id = index.write_tree()?;
     let tree = repo.find_tree(tree_id)?;
 
+    // A pending squash merge offers its message, like `git commit` does
+    let squash_msg = repo.path().join("SQUASH_MSG");
+    let squash_message = if message.trim().is_empty() {
+        std::fs::read_to_string(&squash_msg).ok()
+    } else {
+        None
+    };
+    let message = squash_message.as_deref().unwrap_or(message);
+
     // Check if this is the initial commit (unborn branch)
     let oid = if repo.head().is_ok() {
         // Normal case: we have existing commits
         let head = repo.head()?;
         let parent_commit = head.peel_to_commit()?;
-        repo.commit(
+        create_commit(
+            repo,
             Some("HEAD"),
             &signature,
             &signature,
@@ -246,7 +257,8 @@ fn commit_impl(repo: &Repository, message: &str) -> Result<String> {
         )?
     } else {
         // Initial commit: no parents
-        repo.commit(
+        create_commit(
+            repo,
             Some("HEAD"),
             &signature,
             &signature,
@@ -256,9 +268,76 @@ fn commit_impl(repo: &Repository, message: &str) -> Result<String> {
         )?
     };
 
+    // The squash is concluded by this commit
+    if squash_msg.exists() {
+        std::fs::remove_file(squash_msg)?;
+    }
+
     Ok(format!("{:.7}", oid))
 }
 
+/// Create a commit, signing it when `commit.gpgsign` is enabled.
+///
+/// Drop-in replacement for `Repository::commit`: every commit the app writes
+/// should go through here so that the user's signing configuration is honoured.
+pub(crate) fn create_commit(
+    repo: &Repository,
+    update_ref: Option<&str>,
+    author: &git2::Signature,
+    committer: &git2::Signature,
+    message: &str,
+    tree: &git2::Tree,
+    parents: &[&git2::Commit],
+) -> Result<git2::Oid> {
+    let config = repo.config()?;
+
+    if !super::gpg::commit_signing_enabled(&config) {
+        return Ok(repo.commit(update_ref, author, committer, message, tree, parents)?);
+    }
+
+    let buffer = repo.commit_create_buffer(author, committer, message, tree, parents)?;
+    let content = std::str::from_utf8(&buffer)
+        .map_err(|_| AppError::Signing("Commit buffer is not valid UTF-8".to_string()))?;
+    let signature = super::gpg::sign_commit_buffer(&config, content)?;
+    let oid = repo.commit_signed(content, &signature, Some("gpgsig"))?;
+
+    if let Some(ref_name) = update_ref {
+        let summary = message.lines().next().unwrap_or("");
+        let reflog_msg = if parents.is_empty() {
+            format!("commit (initial): {}", summary)
+        } else {
+            format!("commit: {}", summary)
+        };
+        let parent = parents.first().map(|parent| parent.id());
+        update_reference(repo, ref_name, oid, parent, &reflog_msg)?;
+    }
+
+    Ok(oid)
+}
+
+/// Point `ref_name` at `oid`, following HEAD to the branch it refers to.
+/// Like `Repository::commit`, the ref must still be at `parent`, or not
+/// exist yet for a root commit, so a concurrent commit isn't overwritten
+fn update_reference(
+    repo: &Repository,
+    ref_name: &str,
+    oid: git2::Oid,
+    parent: Option<git2::Oid>,
+    reflog_msg: &str,
+) -> Result<()> {
+    let head = repo.find_reference("HEAD")?;
+    let ref_name = match head.symbolic_target() {
+        Some(target) if ref_name == "HEAD" => target.to_string(),
+        _ => ref_name.to_string(),
+    };
+    match parent {
+        Some(parent) => repo.reference_matching(&ref_name, oid, true, parent, reflog_msg)?,
+        // Works for unborn branches: the branch ref is created here
+        None => repo.reference(&ref_name, oid, false, reflog_msg)?,
+    };
+    Ok(())
+}
+
 /// Revoke the latest commit (soft reset to HEAD~1)
 #[tauri::command]
 pub async fn revoke_latest_commit(path: String) -> std::result::Result<(), String> {
@@ -363,10 +442,7 @@ pub async fn generate_commit_message(
 }
 
 fn get_diff_summary(repo: &Repository) -> Result<String> {
-    let mut status_opts = git2::StatusOptions::new();
-    status_opts.include_untracked(true);
-    status_opts.recurse_untracked_dirs(true);
-
+    let mut status_opts = status_options(&RenameOptions::default());
     let statuses = repo.statuses(Some(&mut status_opts))?;
     let mut summary = String::new();
 
@@ -378,15 +454,25 @@ fn get_diff_summary(repo: &Repository) -> Result<String> {
                 "A"
             } else if status.is_index_deleted() || status.is_wt_deleted() {
                 "D"
-            } else if status.is_index_modified() || status.is_wt_modified() {
-                "M"
             } else if status.is_index_renamed() || status.is_wt_renamed() {
                 "R"
+            } else if status.is_index_modified() || status.is_wt_modified() {
+                "M"
             } else {
                 "?"
             };
 
-            summary.push_str(&format!("{} {}\n", status_str, path));
+            // Renames are listed as "R old -> new"
+            let renamed_to = [entry.head_to_index(), entry.index_to_workdir()]
+                .into_iter()
+                .flatten()
+                .filter(|d| d.status() == git2::Delta::Renamed)
+                .filter_map(|d| d.new_file().path().map(|p| p.to_string_lossy().to_string()))
+                .last();
+            match renamed_to {
+                Some(new_path) => summary.push_str(&format!("{} {} -> {}\n", status_str, path, new_path)),
+                None => summary.push_str(&format!("{} {}\n", status_str, path)),
+            }
         }
     }
 
@@ -1211,3 +1297,7 @@ async fn parse_common_string_response(response: reqwest::Response) -> std::resul
     Ok(content.to_string())
 }
 
+
+#[cfg(test)]
+#[path = "commit_tests.rs"]
+mod commit_tests;
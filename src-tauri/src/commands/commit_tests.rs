@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::commit::{commit_impl, create_commit};
+    use crate::commands::test_support::init_repo;
+    use git2::{Repository, Signature};
+    use tempfile::TempDir;
+
+    fn commit_empty_tree(repo: &Repository) -> git2::Oid {
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        create_commit(repo, Some("HEAD"), &sig, &sig, "initial", &tree, &[]).unwrap()
+    }
+
+    #[test]
+    fn test_create_commit_unsigned_by_default() {
+        let (_dir, repo) = init_repo();
+        let oid = commit_empty_tree(&repo);
+
+        assert_eq!(repo.head().unwrap().target(), Some(oid));
+        assert!(repo.extract_signature(&oid, None).is_err());
+    }
+
+    #[test]
+    fn test_commit_concludes_pending_squash_with_its_message() {
+        let (dir, repo) = init_repo();
+        commit_empty_tree(&repo);
+        std::fs::write(dir.path().join("a.txt"), "a\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+        std::fs::write(repo.path().join("SQUASH_MSG"), "Squashed commit of the following:\n").unwrap();
+
+        commit_impl(&repo, "").unwrap();
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message(), Some("Squashed commit of the following:\n"));
+        assert!(!repo.path().join("SQUASH_MSG").exists());
+    }
+
+    /// Fake gpg that emits a fixed signature for whatever it reads on stdin
+    #[cfg(unix)]
+    fn use_fake_gpg(dir: &TempDir, repo: &Repository) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let program = dir.path().join("fake-gpg.sh");
+        std::fs::write(&program, "#!/bin/sh\ncat > /dev/null\necho '-----BEGIN PGP SIGNATURE-----'\necho 'fake'\necho '-----END PGP SIGNATURE-----'\n").unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut config = repo.config().unwrap();
+        config.set_bool("commit.gpgsign", true).unwrap();
+        config.set_str("user.signingkey", "ABCDEF").unwrap();
+        config.set_str("gpg.program", program.to_str().unwrap()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_commit_signed_with_configured_program() {
+        let (dir, repo) = init_repo();
+        use_fake_gpg(&dir, &repo);
+
+        let oid = commit_empty_tree(&repo);
+
+        assert_eq!(repo.head().unwrap().target(), Some(oid));
+        let (signature, _) = repo.extract_signature(&oid, None).unwrap();
+        assert!(signature.as_str().unwrap().contains("fake"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_commit_reports_signing_failure() {
+        let (_dir, repo) = init_repo();
+        let mut config = repo.config().unwrap();
+        config.set_bool("commit.gpgsign", true).unwrap();
+        config.set_str("gpg.program", "false").unwrap();
+
+        let tree_id = repo.index().unwrap().write_tree().unwrap();
+        let tree = repo.find_tree(tree_id).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let err = create_commit(&repo, Some("HEAD"), &sig, &sig, "initial", &tree, &[]).unwrap_err();
+
+        assert!(err.to_string().starts_with("Commit signing failed"));
+        assert!(repo.head().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_signed_commit_refuses_to_move_a_branch_that_moved_on() {
+        let (dir, repo) = init_repo();
+        use_fake_gpg(&dir, &repo);
+        let first = commit_empty_tree(&repo);
+        let first = repo.find_commit(first).unwrap();
+        let tree = first.tree().unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let second = create_commit(&repo, Some("HEAD"), &sig, &sig, "second", &tree, &[&first]).unwrap();
+
+        // Built on a stale parent
+        assert!(create_commit(&repo, Some("HEAD"), &sig, &sig, "stale", &tree, &[&first]).is_err());
+        assert_eq!(repo.head().unwrap().target(), Some(second));
+
+        // A detached HEAD is checked the same way
+        repo.set_head_detached(second).unwrap();
+        let second = repo.find_commit(second).unwrap();
+        let third = create_commit(&repo, Some("HEAD"), &sig, &sig, "third", &tree, &[&second]).unwrap();
+        assert!(repo.head_detached().unwrap());
+        assert_eq!(repo.head().unwrap().target(), Some(third));
+    }
+}
@@ -0,0 +1,446 @@
+use crate::domain::{
+    ConflictBlob, ConflictFavor, ConflictInfo, ConflictKind, ConflictMergeOptions, ConflictSide, ConflictStyle,
+    MergedFile, MergeRegion, RegionChoice,
+};
+use crate::error::{AppError, Result};
+use git2::{FileFavor, Index, IndexConflict, IndexEntry, MergeFileOptions, Repository};
+use std::path::Path;
+
+/// Labels written after the conflict markers; the same ones must be handed
+/// to `parse_regions` to find the markers again
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct MarkerLabels<'a> {
+    pub ours: &'a str,
+    pub base: &'a str,
+    pub theirs: &'a str,
+}
+
+impl MarkerLabels<'_> {
+    fn apply(&self, opts: &mut MergeFileOptions) {
+        opts.ancestor_label(self.base).our_label(self.ours).their_label(self.theirs);
+    }
+}
+
+const LABELS: MarkerLabels<'static> = MarkerLabels { ours: "ours", base: "base", theirs: "theirs" };
+
+/// Which stage of a conflicted entry to read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConflictStage {
+    Ancestor,
+    Ours,
+    Theirs,
+}
+
+impl ConflictStage {
+    /// Accepts the names used by `resolve_conflict` as well as git's
+    pub(crate) fn parse(side: &str) -> Result<Self> {
+        match side {
+            "ancestor" | "base" => Ok(ConflictStage::Ancestor),
+            "current" | "ours" => Ok(ConflictStage::Ours),
+            "incoming" | "theirs" => Ok(ConflictStage::Theirs),
+            _ => Err(AppError::InvalidInput(format!("Unknown conflict side: {}", side))),
+        }
+    }
+
+    pub(crate) fn side(self, conflict: &ConflictInfo) -> Option<&ConflictSide> {
+        match self {
+            ConflictStage::Ancestor => conflict.ancestor.as_ref(),
+            ConflictStage::Ours => conflict.ours.as_ref(),
+            ConflictStage::Theirs => conflict.theirs.as_ref(),
+        }
+    }
+}
+
+fn side(entry: Option<&IndexEntry>) -> Option<ConflictSide> {
+    entry.map(|entry| ConflictSide {
+        path: String::from_utf8_lossy(&entry.path).to_string(),
+        id: entry.id.to_string(),
+        mode: entry.mode,
+    })
+}
+
+fn kind_of(ancestor: bool, ours: bool, theirs: bool) -> ConflictKind {
+    match (ancestor, ours, theirs) {
+        (true, true, true) => ConflictKind::BothModified,
+        (false, true, true) => ConflictKind::BothAdded,
+        (true, false, true) => ConflictKind::DeletedByUs,
+        (true, true, false) => ConflictKind::DeletedByThem,
+        (false, true, false) => ConflictKind::AddedByUs,
+        (false, false, true) => ConflictKind::AddedByThem,
+        _ => ConflictKind::BothDeleted,
+    }
+}
+
+fn conflict_path(conflict: &IndexConflict) -> String {
+    let entry = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref());
+    entry.map(|e| String::from_utf8_lossy(&e.path).to_string()).unwrap_or_default()
+}
+
+/// Read the conflicts recorded in `index`, one entry per conflicted path.
+///
+/// libgit2 records a rename/rename conflict as three separate paths: the
+/// ancestor alone at the old path, and our and their copies alone at the new
+/// ones. Those are folded back into a single `RenameRename` entry.
+pub(crate) fn read_conflicts(repo: &Repository, index: &Index) -> Result<Vec<ConflictInfo>> {
+    let mut conflicts = Vec::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        let ancestor = side(conflict.ancestor.as_ref());
+        let ours = side(conflict.our.as_ref());
+        let theirs = side(conflict.their.as_ref());
+        conflicts.push(ConflictInfo {
+            path: conflict_path(&conflict),
+            kind: kind_of(ancestor.is_some(), ours.is_some(), theirs.is_some()),
+            ancestor,
+            ours,
+            theirs,
+            is_binary: false,
+            conflict_markers: false,
+        });
+    }
+
+    pair_renames(&mut conflicts);
+
+    for conflict in &mut conflicts {
+        conflict.is_binary = is_binary(repo, conflict)?;
+        conflict.conflict_markers = !conflict.is_binary && has_markers(repo, &conflict.path);
+    }
+
+    Ok(conflicts)
+}
+
+/// Fold `BothDeleted` + `AddedByUs` + `AddedByThem` triples that share the
+/// ancestor's content back into rename/rename conflicts
+fn pair_renames(conflicts: &mut Vec<ConflictInfo>) {
+    let take = |conflicts: &mut Vec<ConflictInfo>, kind: ConflictKind, id: &str| {
+        let candidates: Vec<usize> = conflicts
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.kind == kind)
+            .map(|(i, _)| i)
+            .collect();
+        let same_content = candidates.iter().copied().find(|&i| {
+            let side = conflicts[i].ours.as_ref().or(conflicts[i].theirs.as_ref());
+            side.is_some_and(|s| s.id == id)
+        });
+        // An edited rename can only be matched when it is the sole candidate
+        let index = same_content.or(if candidates.len() == 1 { Some(candidates[0]) } else { None })?;
+        Some(conflicts.remove(index))
+    };
+
+    let mut i = 0;
+    while i < conflicts.len() {
+        if conflicts[i].kind != ConflictKind::BothDeleted {
+            i += 1;
+            continue;
+        }
+        let id = conflicts[i].ancestor.as_ref().map(|a| a.id.clone()).unwrap_or_default();
+        let mut deleted = conflicts.remove(i);
+        let ours = take(conflicts, ConflictKind::AddedByUs, &id);
+        let theirs = take(conflicts, ConflictKind::AddedByThem, &id);
+        match (ours, theirs) {
+            (Some(ours), Some(theirs)) => {
+                deleted.kind = ConflictKind::RenameRename;
+                deleted.ours = ours.ours;
+                deleted.theirs = theirs.theirs;
+                conflicts.insert(i, deleted);
+            }
+            (ours, theirs) => {
+                // No match: put everything back as it was
+                conflicts.insert(i, deleted);
+                conflicts.extend(ours);
+                conflicts.extend(theirs);
+            }
+        }
+        i += 1;
+    }
+}
+
+fn is_binary(repo: &Repository, conflict: &ConflictInfo) -> Result<bool> {
+    for side in [&conflict.ancestor, &conflict.ours, &conflict.theirs].into_iter().flatten() {
+        // Submodule (gitlink) entries have no blob to look at
+        if side.mode == 0o160000 {
+            return Ok(true);
+        }
+        if repo.find_blob(git2::Oid::from_str(&side.id)?)?.is_binary() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn has_markers(repo: &Repository, path: &str) -> bool {
+    let Some(content) = repo.workdir().and_then(|w| std::fs::read(w.join(path)).ok()) else {
+        return false;
+    };
+    let mut opened = false;
+    for line in content.split(|&b| b == b'\n') {
+        if line.starts_with(b"<<<<<<<") {
+            opened = true;
+        } else if opened && line.starts_with(b">>>>>>>") {
+            return true;
+        }
+    }
+    false
+}
+
+/// Load one side of a conflicted file from the object database
+#[tauri::command]
+pub async fn get_conflict_blob(
+    path: String,
+    file_path: String,
+    side: String,
+) -> std::result::Result<Option<ConflictBlob>, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    let stage = ConflictStage::parse(&side).map_err(|e| e.to_string())?;
+    get_conflict_blob_impl(&repo, &file_path, stage).map_err(|e| e.to_string())
+}
+
+pub(crate) fn find_conflict(repo: &Repository, file_path: &str) -> Result<ConflictInfo> {
+    let index = repo.index()?;
+    read_conflicts(repo, &index)?
+        .into_iter()
+        .find(|c| {
+            c.path == file_path
+                || [&c.ancestor, &c.ours, &c.theirs].into_iter().flatten().any(|s| s.path == file_path)
+        })
+        .ok_or_else(|| AppError::InvalidInput(format!("{} is not conflicted", file_path)))
+}
+
+fn get_conflict_blob_impl(repo: &Repository, file_path: &str, stage: ConflictStage) -> Result<Option<ConflictBlob>> {
+    let conflict = find_conflict(repo, file_path)?;
+    let Some(side) = stage.side(&conflict) else {
+        return Ok(None);
+    };
+    if side.mode == 0o160000 {
+        return Ok(Some(ConflictBlob { id: side.id.clone(), size: 0, is_binary: true, content: None }));
+    }
+
+    let blob = repo.find_blob(git2::Oid::from_str(&side.id)?)?;
+    let content = if blob.is_binary() {
+        None
+    } else {
+        String::from_utf8(blob.content().to_vec()).ok()
+    };
+    Ok(Some(ConflictBlob {
+        id: side.id.clone(),
+        size: blob.size(),
+        is_binary: blob.is_binary(),
+        content,
+    }))
+}
+
+/// Merge the stages of a conflicted file line by line and split the result
+/// into clean and conflicting regions
+#[tauri::command]
+pub async fn merge_conflict_file(
+    path: String,
+    file_path: String,
+    options: Option<ConflictMergeOptions>,
+) -> std::result::Result<MergedFile, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    merge_conflict_file_impl(&repo, &file_path, &options.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+pub(crate) fn merge_conflict_file_impl(
+    repo: &Repository,
+    file_path: &str,
+    options: &ConflictMergeOptions,
+) -> Result<MergedFile> {
+    let conflict = find_conflict(repo, file_path)?;
+    if conflict.is_binary {
+        return Err(AppError::InvalidInput(format!("{} is binary and cannot be merged line by line", conflict.path)));
+    }
+    if !matches!(conflict.kind, ConflictKind::BothModified | ConflictKind::BothAdded) {
+        return Err(AppError::InvalidInput(format!(
+            "{} was not changed on both sides; pick a whole version instead",
+            conflict.path
+        )));
+    }
+
+    let index = repo.index()?;
+    let stage = |n| {
+        index
+            .get_path(Path::new(&conflict.path), n)
+            .ok_or_else(|| AppError::InvalidInput(format!("{} has no stage {}", conflict.path, n)))
+    };
+    let ours = stage(2)?;
+    let theirs = stage(3)?;
+    // Added on both sides: merge against an empty file, as `git merge-file` would
+    let ancestor = match stage(1) {
+        Ok(entry) => entry,
+        Err(_) => IndexEntry {
+            id: repo.blob(b"")?,
+            file_size: 0,
+            path: ours.path.clone(),
+            flags: 0,
+            flags_extended: 0,
+            ..ours
+        },
+    };
+
+    let mut merge_opts = MergeFileOptions::new();
+    LABELS.apply(&mut merge_opts);
+    merge_opts.ignore_whitespace(options.ignore_whitespace);
+    match options.style {
+        ConflictStyle::Merge => merge_opts.style_standard(true),
+        ConflictStyle::Diff3 => merge_opts.style_diff3(true),
+        ConflictStyle::Zdiff3 => merge_opts.style_zdiff3(true),
+    };
+    merge_opts.favor(match options.favor {
+        ConflictFavor::None => FileFavor::Normal,
+        ConflictFavor::Ours => FileFavor::Ours,
+        ConflictFavor::Theirs => FileFavor::Theirs,
+        ConflictFavor::Union => FileFavor::Union,
+    });
+
+    let result = repo.merge_file_from_index(&ancestor, &ours, &theirs, Some(&mut merge_opts))?;
+    let content = String::from_utf8(result.content().to_vec())
+        .map_err(|_| AppError::InvalidInput(format!("{} is not valid UTF-8", conflict.path)))?;
+    let regions = parse_regions(&content, &LABELS);
+
+    Ok(MergedFile {
+        path: conflict.path,
+        is_automergeable: result.is_automergeable(),
+        conflict_count: regions.iter().filter(|r| matches!(r, MergeRegion::Conflict { .. })).count(),
+        regions,
+    })
+}
+
+fn marker(line: &str, prefix: char, label: &str) -> bool {
+    let line = line.trim_end_matches(['\n', '\r']);
+    line.len() == 8 + label.len()
+        && line.starts_with(&prefix.to_string().repeat(7))
+        && line[7..].strip_prefix(' ') == Some(label)
+}
+
+/// Split merge output with conflict markers, written with `labels`, into regions
+pub(crate) fn parse_regions(content: &str, labels: &MarkerLabels) -> Vec<MergeRegion> {
+    #[derive(PartialEq)]
+    enum Section {
+        Clean,
+        Ours,
+        Base,
+        Theirs,
+    }
+
+    let mut regions = Vec::new();
+    let mut section = Section::Clean;
+    let (mut clean, mut ours, mut base, mut theirs) = (String::new(), String::new(), None::<String>, String::new());
+
+    for line in content.split_inclusive('\n') {
+        match section {
+            Section::Clean if marker(line, '<', labels.ours) => {
+                if !clean.is_empty() {
+                    regions.push(MergeRegion::Clean { content: std::mem::take(&mut clean) });
+                }
+                section = Section::Ours;
+            }
+            Section::Ours if marker(line, '|', labels.base) => {
+                base = Some(String::new());
+                section = Section::Base;
+            }
+            Section::Ours | Section::Base if line.trim_end_matches(['\n', '\r']) == "=======" => {
+                section = Section::Theirs;
+            }
+            Section::Theirs if marker(line, '>', labels.theirs) => {
+                regions.push(MergeRegion::Conflict {
+                    ours: std::mem::take(&mut ours),
+                    base: base.take(),
+                    theirs: std::mem::take(&mut theirs),
+                });
+                section = Section::Clean;
+            }
+            Section::Clean => clean.push_str(line),
+            Section::Ours => ours.push_str(line),
+            Section::Base => base.get_or_insert_with(String::new).push_str(line),
+            Section::Theirs => theirs.push_str(line),
+        }
+    }
+    if !clean.is_empty() {
+        regions.push(MergeRegion::Clean { content: clean });
+    }
+    regions
+}
+
+/// Apply one choice per conflicting region, in order, and join the result
+pub(crate) fn apply_choices(regions: &[MergeRegion], choices: &[RegionChoice]) -> Result<String> {
+    let conflicts = regions.iter().filter(|r| matches!(r, MergeRegion::Conflict { .. })).count();
+    if choices.len() != conflicts {
+        return Err(AppError::InvalidInput(format!(
+            "Expected one choice per conflicting region ({}), got {}",
+            conflicts,
+            choices.len()
+        )));
+    }
+
+    let mut choices = choices.iter();
+    let mut resolved = String::new();
+    for region in regions {
+        match region {
+            MergeRegion::Clean { content } => resolved.push_str(content),
+            MergeRegion::Conflict { ours, base, theirs } => match choices.next() {
+                Some(RegionChoice::Ours) => resolved.push_str(ours),
+                Some(RegionChoice::Theirs) => resolved.push_str(theirs),
+                Some(RegionChoice::Base) => match base {
+                    Some(base) => resolved.push_str(base),
+                    None => {
+                        return Err(AppError::InvalidInput(
+                            "The base version is only available with the diff3 or zdiff3 style".to_string(),
+                        ))
+                    }
+                },
+                Some(RegionChoice::OursThenTheirs) => {
+                    resolved.push_str(ours);
+                    resolved.push_str(theirs);
+                }
+                Some(RegionChoice::TheirsThenOurs) => {
+                    resolved.push_str(theirs);
+                    resolved.push_str(ours);
+                }
+                Some(RegionChoice::Custom { content }) => resolved.push_str(content),
+                None => unreachable!("choices were counted above"),
+            },
+        }
+    }
+    Ok(resolved)
+}
+
+/// Resolve a conflicted file region by region, write it and stage it.
+///
+/// `choices` holds one entry per conflicting region returned by
+/// `merge_conflict_file` with the same `options`.
+#[tauri::command]
+pub async fn resolve_conflict_regions(
+    path: String,
+    file_path: String,
+    options: Option<ConflictMergeOptions>,
+    choices: Vec<RegionChoice>,
+) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    resolve_conflict_regions_impl(&repo, &file_path, &options.unwrap_or_default(), &choices)
+        .map_err(|e| e.to_string())
+}
+
+fn resolve_conflict_regions_impl(
+    repo: &Repository,
+    file_path: &str,
+    options: &ConflictMergeOptions,
+    choices: &[RegionChoice],
+) -> Result<()> {
+    let workdir = repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))?;
+    let merged = merge_conflict_file_impl(repo, file_path, options)?;
+    let resolved = apply_choices(&merged.regions, choices)?;
+
+    std::fs::write(workdir.join(&merged.path), resolved)?;
+
+    // Adding the path drops its conflict stages
+    let mut index = repo.index()?;
+    index.add_path(Path::new(&merged.path))?;
+    index.write()?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "conflict_tests.rs"]
+mod conflict_tests;
@@ -0,0 +1,287 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::conflict::{
+        apply_choices, find_conflict, get_conflict_blob_impl, merge_conflict_file_impl, parse_regions, read_conflicts,
+        resolve_conflict_regions_impl, ConflictStage, MarkerLabels,
+    };
+    use crate::domain::{ConflictFavor, ConflictInfo, ConflictKind, ConflictMergeOptions, ConflictStyle, MergeRegion, RegionChoice};
+    use git2::{Oid, Repository, Signature};
+    use tempfile::TempDir;
+
+    struct Fixture {
+        dir: TempDir,
+        repo: Repository,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let dir = TempDir::new().unwrap();
+            let repo = Repository::init(dir.path()).unwrap();
+            Self { dir, repo }
+        }
+
+        /// Commit on top of `parent` without touching HEAD or the working tree;
+        /// `None` content deletes the file
+        fn commit(&self, parent: Option<Oid>, files: &[(&str, Option<&[u8]>)], message: &str) -> Oid {
+            let mut index = git2::Index::new().unwrap();
+            let parents: Vec<git2::Commit> = parent.map(|p| self.repo.find_commit(p).unwrap()).into_iter().collect();
+            if let Some(parent) = parents.first() {
+                index.read_tree(&parent.tree().unwrap()).unwrap();
+            }
+            for (name, content) in files {
+                match content {
+                    Some(content) => {
+                        let id = self.repo.blob(content).unwrap();
+                        let entry = git2::IndexEntry {
+                            ctime: git2::IndexTime::new(0, 0),
+                            mtime: git2::IndexTime::new(0, 0),
+                            dev: 0,
+                            ino: 0,
+                            mode: 0o100644,
+                            uid: 0,
+                            gid: 0,
+                            file_size: content.len() as u32,
+                            id,
+                            flags: 0,
+                            flags_extended: 0,
+                            path: name.as_bytes().to_vec(),
+                        };
+                        index.add(&entry).unwrap();
+                    }
+                    None => index.remove_path(std::path::Path::new(name)).unwrap(),
+                }
+            }
+            let tree = self.repo.find_tree(index.write_tree_to(&self.repo).unwrap()).unwrap();
+            let sig = Signature::now("Test User", "test@example.com").unwrap();
+            let parents: Vec<&git2::Commit> = parents.iter().collect();
+            self.repo.commit(None, &sig, &sig, message, &tree, &parents).unwrap()
+        }
+
+        /// Check out `ours` on a branch and merge `theirs` into it
+        fn merge(&self, ours: Oid, theirs: Oid) -> Vec<ConflictInfo> {
+            self.repo.branch("ours", &self.repo.find_commit(ours).unwrap(), true).unwrap();
+            self.repo.set_head("refs/heads/ours").unwrap();
+            self.repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+            let theirs = self.repo.find_annotated_commit(theirs).unwrap();
+            self.repo.merge(&[&theirs], None, None).unwrap();
+            read_conflicts(&self.repo, &self.repo.index().unwrap()).unwrap()
+        }
+    }
+
+    #[test]
+    fn test_reads_stages_and_kinds_from_index() {
+        let f = Fixture::new();
+        let base = f.commit(None, &[("both.txt", Some(b"base\n")), ("gone.txt", Some(b"gone\n"))], "base");
+        let ours = f.commit(
+            Some(base),
+            &[("both.txt", Some(b"ours\n")), ("gone.txt", None), ("new.txt", Some(b"ours new\n"))],
+            "ours",
+        );
+        let theirs = f.commit(
+            Some(base),
+            &[("both.txt", Some(b"theirs\n")), ("gone.txt", Some(b"edited\n")), ("new.txt", Some(b"theirs new\n"))],
+            "theirs",
+        );
+
+        let conflicts = f.merge(ours, theirs);
+
+        let kinds: Vec<(&str, ConflictKind)> = conflicts.iter().map(|c| (c.path.as_str(), c.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("both.txt", ConflictKind::BothModified),
+                ("gone.txt", ConflictKind::DeletedByUs),
+                ("new.txt", ConflictKind::BothAdded),
+            ]
+        );
+
+        let both = &conflicts[0];
+        let ours_id = f.repo.blob(b"ours\n").unwrap().to_string();
+        assert_eq!(both.ours.as_ref().unwrap().id, ours_id);
+        assert_eq!(both.ours.as_ref().unwrap().mode, 0o100644);
+        assert_eq!(both.ancestor.as_ref().unwrap().path, "both.txt");
+        assert!(both.conflict_markers);
+        assert!(!both.is_binary);
+
+        let gone = &conflicts[1];
+        assert!(gone.ours.is_none());
+        assert!(!gone.conflict_markers);
+    }
+
+    #[test]
+    fn test_binary_conflict_has_no_markers() {
+        let f = Fixture::new();
+        let base = f.commit(None, &[("image.bin", Some(b"\x00\x01base"))], "base");
+        let ours = f.commit(Some(base), &[("image.bin", Some(b"\x00\x01ours"))], "ours");
+        let theirs = f.commit(Some(base), &[("image.bin", Some(b"\x00\x01theirs"))], "theirs");
+
+        let conflicts = f.merge(ours, theirs);
+
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].is_binary);
+        assert!(!conflicts[0].conflict_markers);
+
+        let blob = get_conflict_blob_impl(&f.repo, "image.bin", ConflictStage::Theirs).unwrap().unwrap();
+        assert!(blob.is_binary);
+        assert!(blob.content.is_none());
+        assert_eq!(blob.size, 8);
+    }
+
+    #[test]
+    fn test_rename_rename_is_reported_once() {
+        let f = Fixture::new();
+        let content: &[u8] = b"1\n2\n3\n4\n5\n6\n7\n8\n";
+        let base = f.commit(None, &[("a.txt", Some(content))], "base");
+        let ours = f.commit(Some(base), &[("a.txt", None), ("b.txt", Some(content))], "ours");
+        let theirs = f.commit(Some(base), &[("a.txt", None), ("c.txt", Some(content))], "theirs");
+
+        let conflicts = f.merge(ours, theirs);
+
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.kind, ConflictKind::RenameRename);
+        assert_eq!(conflict.path, "a.txt");
+        assert_eq!(conflict.ours.as_ref().unwrap().path, "b.txt");
+        assert_eq!(conflict.theirs.as_ref().unwrap().path, "c.txt");
+
+        // Any of the three paths finds it
+        assert_eq!(find_conflict(&f.repo, "c.txt").unwrap().path, "a.txt");
+    }
+
+    #[test]
+    fn test_conflict_blob_is_loaded_on_demand() {
+        let f = Fixture::new();
+        let base = f.commit(None, &[("file.txt", Some(b"base\n"))], "base");
+        let ours = f.commit(Some(base), &[("file.txt", None)], "ours");
+        let theirs = f.commit(Some(base), &[("file.txt", Some(b"theirs\n"))], "theirs");
+        f.merge(ours, theirs);
+        std::fs::write(f.dir.path().join("file.txt"), "edited in workdir\n").unwrap();
+
+        let ancestor = get_conflict_blob_impl(&f.repo, "file.txt", ConflictStage::Ancestor).unwrap().unwrap();
+        assert_eq!(ancestor.content.as_deref(), Some("base\n"));
+        let theirs = get_conflict_blob_impl(&f.repo, "file.txt", ConflictStage::Theirs).unwrap().unwrap();
+        assert_eq!(theirs.content.as_deref(), Some("theirs\n"));
+        assert!(get_conflict_blob_impl(&f.repo, "file.txt", ConflictStage::Ours).unwrap().is_none());
+
+        assert!(get_conflict_blob_impl(&f.repo, "other.txt", ConflictStage::Ours).is_err());
+        assert!(ConflictStage::parse("mine").is_err());
+        assert_eq!(ConflictStage::parse("incoming").unwrap(), ConflictStage::Theirs);
+    }
+
+    const BASE: &[u8] = b"one\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+
+    /// Both sides edit lines two and six; only we touch line four
+    fn two_conflicts() -> Fixture {
+        let f = Fixture::new();
+        let base = f.commit(None, &[("file.txt", Some(BASE))], "base");
+        let ours = f.commit(Some(base), &[("file.txt", Some(b"one\nTWO\nthree\n4\nfive\nSIX\nseven\n"))], "ours");
+        let theirs = f.commit(Some(base), &[("file.txt", Some(b"one\n2\nthree\nfour\nfive\n6\nseven\n"))], "theirs");
+        f.merge(ours, theirs);
+        f
+    }
+
+    fn conflict(ours: &str, base: Option<&str>, theirs: &str) -> MergeRegion {
+        MergeRegion::Conflict { ours: ours.to_string(), base: base.map(|b| b.to_string()), theirs: theirs.to_string() }
+    }
+
+    fn clean(content: &str) -> MergeRegion {
+        MergeRegion::Clean { content: content.to_string() }
+    }
+
+    #[test]
+    fn test_merge_file_splits_clean_and_conflicting_regions() {
+        let f = two_conflicts();
+
+        let merged = merge_conflict_file_impl(&f.repo, "file.txt", &ConflictMergeOptions::default()).unwrap();
+
+        assert!(!merged.is_automergeable);
+        assert_eq!(merged.conflict_count, 2);
+        assert_eq!(
+            merged.regions,
+            vec![
+                clean("one\n"),
+                conflict("TWO\n", None, "2\n"),
+                clean("three\n4\nfive\n"),
+                conflict("SIX\n", None, "6\n"),
+                clean("seven\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_file_diff3_styles_include_base() {
+        let f = two_conflicts();
+
+        for style in [ConflictStyle::Diff3, ConflictStyle::Zdiff3] {
+            let options = ConflictMergeOptions { style, ..Default::default() };
+            let merged = merge_conflict_file_impl(&f.repo, "file.txt", &options).unwrap();
+            assert_eq!(merged.regions[1], conflict("TWO\n", Some("two\n"), "2\n"));
+        }
+    }
+
+    #[test]
+    fn test_merge_file_favor_settles_conflicts() {
+        let f = two_conflicts();
+
+        let options = ConflictMergeOptions { favor: ConflictFavor::Union, ..Default::default() };
+        let merged = merge_conflict_file_impl(&f.repo, "file.txt", &options).unwrap();
+
+        assert!(merged.is_automergeable);
+        assert_eq!(merged.conflict_count, 0);
+        assert_eq!(merged.regions, vec![clean("one\nTWO\n2\nthree\n4\nfive\nSIX\n6\nseven\n")]);
+    }
+
+    #[test]
+    fn test_resolve_regions_writes_and_stages_file() {
+        let f = two_conflicts();
+        let options = ConflictMergeOptions { style: ConflictStyle::Diff3, ..Default::default() };
+
+        assert!(resolve_conflict_regions_impl(&f.repo, "file.txt", &options, &[RegionChoice::Ours]).is_err());
+
+        let choices = [RegionChoice::Base, RegionChoice::Custom { content: "sixty\n".to_string() }];
+        resolve_conflict_regions_impl(&f.repo, "file.txt", &options, &choices).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(f.dir.path().join("file.txt")).unwrap(),
+            "one\ntwo\nthree\n4\nfive\nsixty\nseven\n"
+        );
+        assert!(!f.repo.index().unwrap().has_conflicts());
+    }
+
+    #[test]
+    fn test_base_choice_needs_diff3_and_whole_file_conflicts_are_refused() {
+        let regions = vec![conflict("a\n", None, "b\n")];
+        assert!(apply_choices(&regions, &[RegionChoice::Base]).is_err());
+        assert_eq!(apply_choices(&regions, &[RegionChoice::TheirsThenOurs]).unwrap(), "b\na\n");
+
+        let f = Fixture::new();
+        let base = f.commit(None, &[("file.txt", Some(b"base\n"))], "base");
+        let ours = f.commit(Some(base), &[("file.txt", None)], "ours");
+        let theirs = f.commit(Some(base), &[("file.txt", Some(b"theirs\n"))], "theirs");
+        f.merge(ours, theirs);
+        assert!(merge_conflict_file_impl(&f.repo, "file.txt", &ConflictMergeOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_parse_regions_ignores_marker_lookalikes() {
+        let labels = MarkerLabels { ours: "ours", base: "base", theirs: "theirs" };
+        let content = "=======\n<<<<<<< ours\na\n=======\nb\n>>>>>>> theirs\n>>>>>>> other\n";
+
+        assert_eq!(
+            parse_regions(content, &labels),
+            vec![clean("=======\n"), conflict("a\n", None, "b\n"), clean(">>>>>>> other\n")]
+        );
+    }
+
+    #[test]
+    fn test_parse_regions_uses_the_given_labels() {
+        let labels = MarkerLabels { ours: "HEAD", base: "merged common ancestors", theirs: "feature" };
+        let content = "<<<<<<< HEAD\na\n||||||| merged common ancestors\no\n=======\nb\n>>>>>>> feature\n";
+
+        assert_eq!(parse_regions(content, &labels), vec![conflict("a\n", Some("o\n"), "b\n")]);
+        assert_eq!(
+            parse_regions("<<<<<<< ours\na\n", &labels),
+            vec![clean("<<<<<<< ours\na\n")]
+        );
+    }
+}
@@ -2,6 +2,21 @@ use tauri_plugin_keyring::KeyringExt;
 
 const SERVICE_NAME: &str = "com.gat.git.credentials";
 
+/// Keyring entry holding an access token for a git host, e.g. `git-token:github.com`
+pub(crate) fn host_token_key(host: &str) -> String {
+    format!("git-token:{}", host)
+}
+
+/// Keyring entry holding the passphrase of an SSH private key
+pub(crate) fn ssh_passphrase_key(key_path: &str) -> String {
+    format!("ssh-passphrase:{}", key_path)
+}
+
+/// Read a secret from the system keyring, treating errors as "not stored"
+pub(crate) fn lookup_secret(app: &tauri::AppHandle, key: &str) -> Option<String> {
+    app.keyring().get_password(SERVICE_NAME, key).ok().flatten()
+}
+
 #[tauri::command]
 pub async fn save_credential(
     app: tauri::AppHandle,
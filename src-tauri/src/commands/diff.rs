@@ -0,0 +1,662 @@
+use crate::domain::{
+    ChangeRange, DiffContentKind, DiffFileInfo, DiffHunk, DiffLine, DiffMode, DiffSource, FileDiff, FileDiffOptions,
+    IntraLineMode, RenameOptions, SideBySideRow,
+};
+use crate::error::{AppError, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use encoding_rs::Encoding;
+use git2::{AttrCheckFlags, Delta, Diff, DiffFindOptions, ObjectType, Oid, Patch, Repository, StatusOptions};
+use std::path::Path;
+
+// Above this many token pairs a line pair is highlighted as a whole instead
+// of running the quadratic LCS
+const MAX_INTRA_LINE_CELLS: usize = 250_000;
+
+// Files above this size only get ids and sizes
+const MAX_DIFF_BYTES: u64 = 16 * 1024 * 1024;
+
+const DEFAULT_MAX_LINES: u32 = 10_000;
+
+// Same heuristic as git: a NUL byte in the first 8000 bytes means binary
+const BINARY_CHECK_BYTES: usize = 8000;
+
+/// Where one end of a diff is read from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Head,
+    Index,
+    Workdir,
+}
+
+/// Get diff for a specific file
+#[tauri::command]
+pub async fn get_file_diff(
+    path: String,
+    file_path: String,
+    options: Option<FileDiffOptions>,
+) -> std::result::Result<FileDiff, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_file_diff_impl(&repo, &file_path, &options.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+pub(crate) fn get_file_diff_impl(repo: &Repository, file_path: &str, options: &FileDiffOptions) -> Result<FileDiff> {
+    let (paths, untracked) = side_paths(repo, file_path, &options.renames)?;
+    let pairs: &[(Side, Side, DiffSource)] = match (options.mode, untracked) {
+        (DiffMode::Staged, _) => &[(Side::Head, Side::Index, DiffSource::Staged)],
+        (_, true) => &[(Side::Index, Side::Workdir, DiffSource::Untracked)],
+        (DiffMode::Unstaged, false) => &[(Side::Index, Side::Workdir, DiffSource::Unstaged)],
+        (DiffMode::Combined, false) => &[
+            (Side::Head, Side::Index, DiffSource::Staged),
+            (Side::Index, Side::Workdir, DiffSource::Unstaged),
+        ],
+    };
+    let (old_side, new_side) = (pairs[0].0, pairs[pairs.len() - 1].1);
+
+    let mut file_diff = FileDiff {
+        path: file_path.to_string(),
+        old_path: Some(paths.get(old_side)).filter(|p| *p != file_path).map(|p| p.to_string()),
+        kind: DiffContentKind::Text,
+        encoding: None,
+        mime_type: image_mime_type(file_path).map(|m| m.to_string()),
+        old_file: side_file(repo, paths.get(old_side), old_side)?,
+        new_file: side_file(repo, paths.get(new_side), new_side)?,
+        hunks: Vec::new(),
+        truncated: false,
+    };
+    if over_size_limit(&mut file_diff) {
+        return Ok(file_diff);
+    }
+
+    let old = side_content(repo, paths.get(old_side), old_side)?;
+    let new = side_content(repo, paths.get(new_side), new_side)?;
+    let encoding = inspect_content(repo, &mut file_diff, &old, &new);
+    if file_diff.kind != DiffContentKind::Text {
+        return Ok(file_diff);
+    }
+
+    let mut opts = diff_options(options);
+    for path in [&paths.head, &paths.index, &paths.workdir] {
+        opts.pathspec(path);
+    }
+
+    // libgit2 diffs raw bytes, so decoded text is diffed from buffers instead.
+    // The same goes for files that are not in the index yet, including ones
+    // renamed in the working tree
+    if encoding != encoding_rs::UTF_8 || untracked || paths.index != paths.workdir {
+        let text = |side| -> Result<String> {
+            Ok(side_content(repo, paths.get(side), side)?.map(|data| decode(&data, encoding)).unwrap_or_default())
+        };
+        for &(from, to, source) in pairs {
+            let (old_text, new_text) = (text(from)?, text(to)?);
+            if old_text == new_text && paths.get(from) == paths.get(to) {
+                continue;
+            }
+            let (old_path, new_path) = (Path::new(paths.get(from)), Path::new(paths.get(to)));
+            let patch =
+                Patch::from_buffers(old_text.as_bytes(), Some(old_path), new_text.as_bytes(), Some(new_path), Some(&mut opts))?;
+            collect_patch(&patch, source, options, &mut file_diff)?;
+        }
+        return Ok(file_diff);
+    }
+
+    for &(from, to, source) in pairs {
+        let mut diff = if from == Side::Head {
+            let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+            repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))?
+        } else {
+            repo.diff_index_to_workdir(None, Some(&mut opts))?
+        };
+        if paths.get(from) != paths.get(to) {
+            find_similar(&mut diff, &options.renames)?;
+        }
+        // Files marked `binary` or `-diff` in .gitattributes
+        if collect_diff(&diff, source, options, &mut file_diff)? {
+            file_diff.kind = DiffContentKind::Binary;
+            file_diff.hunks.clear();
+            file_diff.truncated = false;
+            return Ok(file_diff);
+        }
+    }
+
+    Ok(file_diff)
+}
+
+/// Get the diff of one file in a commit against one of its parents, the
+/// first one by default
+#[tauri::command]
+pub async fn get_commit_file_diff(
+    path: String,
+    oid: String,
+    file_path: String,
+    parent_index: Option<usize>,
+    options: Option<FileDiffOptions>,
+) -> std::result::Result<FileDiff, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_commit_file_diff_impl(&repo, &oid, &file_path, parent_index.unwrap_or(0), &options.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn get_commit_file_diff_impl(
+    repo: &Repository,
+    oid: &str,
+    file_path: &str,
+    parent_index: usize,
+    options: &FileDiffOptions,
+) -> Result<FileDiff> {
+    let commit = repo.find_commit(Oid::from_str(oid)?)?;
+    let tree = commit.tree()?;
+    let parent_tree = if parent_index == 0 && commit.parent_count() == 0 {
+        None
+    } else {
+        Some(commit.parent(parent_index)?.tree()?)
+    };
+
+    // Renames need the whole tree diff to find where the file came from
+    let mut opts = rename_diff_options(&options.renames);
+    if !options.renames.renames && !options.renames.copies {
+        opts.pathspec(file_path).disable_pathspec_match(true);
+    }
+    let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))?;
+    find_similar(&mut diff, &options.renames)?;
+
+    let target = Some(Path::new(file_path));
+    let delta = diff
+        .deltas()
+        .find(|d| d.new_file().path() == target)
+        .or_else(|| diff.deltas().find(|d| d.old_file().path() == target));
+    let blob = |file: git2::DiffFile| Some(file.id()).filter(|id| !id.is_zero());
+    let path_of = |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().to_string());
+    let (new_path, old_path, old_id, new_id) = match &delta {
+        Some(delta) => (
+            path_of(delta.new_file()).unwrap_or_else(|| file_path.to_string()),
+            path_of(delta.old_file()).unwrap_or_else(|| file_path.to_string()),
+            blob(delta.old_file()),
+            blob(delta.new_file()),
+        ),
+        // Unchanged by this commit
+        None => {
+            let id = tree.get_path(Path::new(file_path)).ok().map(|entry| entry.id());
+            (file_path.to_string(), file_path.to_string(), id, id)
+        }
+    };
+
+    let mut file_diff = FileDiff {
+        old_path: Some(old_path.clone()).filter(|p| *p != new_path),
+        path: new_path,
+        kind: DiffContentKind::Text,
+        encoding: None,
+        mime_type: image_mime_type(file_path).map(|m| m.to_string()),
+        old_file: old_id.map(|id| blob_info(repo, id)).transpose()?,
+        new_file: new_id.map(|id| blob_info(repo, id)).transpose()?,
+        hunks: Vec::new(),
+        truncated: false,
+    };
+    if over_size_limit(&mut file_diff) {
+        return Ok(file_diff);
+    }
+
+    let content = |id: Option<Oid>| -> Result<Option<Vec<u8>>> {
+        Ok(match id {
+            Some(id) => Some(repo.find_blob(id)?.content().to_vec()),
+            None => None,
+        })
+    };
+    let (old, new) = (content(old_id)?, content(new_id)?);
+    let encoding = inspect_content(repo, &mut file_diff, &old, &new);
+    // Buffers are diffed without looking at .gitattributes, so check `-diff` here
+    let diff_attr = repo.get_attr(Path::new(&file_diff.path), "diff", AttrCheckFlags::FILE_THEN_INDEX)?;
+    if git2::AttrValue::from_string(diff_attr) == git2::AttrValue::False {
+        file_diff.kind = DiffContentKind::Binary;
+    }
+    if file_diff.kind != DiffContentKind::Text || old_id == new_id {
+        return Ok(file_diff);
+    }
+
+    let text = |data: Option<Vec<u8>>| data.map(|data| decode(&data, encoding)).unwrap_or_default();
+    let (old_text, new_text) = (text(old), text(new));
+    let new_path = file_diff.path.clone();
+    let patch = Patch::from_buffers(
+        old_text.as_bytes(),
+        Some(Path::new(&old_path)),
+        new_text.as_bytes(),
+        Some(Path::new(&new_path)),
+        Some(&mut diff_options(options)),
+    )?;
+    collect_patch(&patch, DiffSource::Commit, options, &mut file_diff)?;
+    Ok(file_diff)
+}
+
+fn diff_options(options: &FileDiffOptions) -> git2::DiffOptions {
+    let mut opts = git2::DiffOptions::new();
+    opts.disable_pathspec_match(true)
+        .context_lines(options.context_lines.unwrap_or(3))
+        .ignore_whitespace(options.ignore_whitespace)
+        .ignore_whitespace_change(options.ignore_whitespace_change)
+        .ignore_blank_lines(options.ignore_blank_lines);
+    opts
+}
+
+/// Status options for the whole working tree with rename detection
+pub(crate) fn status_options(renames: &RenameOptions) -> StatusOptions {
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .renames_head_to_index(renames.renames)
+        .renames_index_to_workdir(renames.renames && renames.worktree)
+        .rename_threshold(renames.threshold);
+    opts
+}
+
+/// Diff options that keep unmodified files around when copies of them
+/// should be found
+pub(crate) fn rename_diff_options(renames: &RenameOptions) -> git2::DiffOptions {
+    let mut opts = git2::DiffOptions::new();
+    opts.include_unmodified(renames.copies);
+    opts
+}
+
+/// Turn deleted/added pairs into renames and, with `copies`, added files
+/// into copies of existing ones
+pub(crate) fn find_similar(diff: &mut Diff, renames: &RenameOptions) -> Result<()> {
+    // Without any flag libgit2 falls back to `diff.renames`
+    if !renames.renames && !renames.copies {
+        return Ok(());
+    }
+    let mut opts = DiffFindOptions::new();
+    opts.renames(renames.renames)
+        .copies(renames.copies)
+        .copies_from_unmodified(renames.copies)
+        .rename_threshold(renames.threshold)
+        .copy_threshold(renames.threshold)
+        .remove_unmodified(true);
+    diff.find_similar(Some(&mut opts))?;
+    Ok(())
+}
+
+/// Where the file is in HEAD, the index and the working tree
+struct SidePaths {
+    head: String,
+    index: String,
+    workdir: String,
+}
+
+impl SidePaths {
+    fn get(&self, side: Side) -> &str {
+        match side {
+            Side::Head => &self.head,
+            Side::Index => &self.index,
+            Side::Workdir => &self.workdir,
+        }
+    }
+}
+
+/// Follow renames status detects back from `file_path`; also returns
+/// whether the file is untracked
+fn side_paths(repo: &Repository, file_path: &str, renames: &RenameOptions) -> Result<(SidePaths, bool)> {
+    let mut paths = SidePaths { head: file_path.to_string(), index: file_path.to_string(), workdir: file_path.to_string() };
+    let status = repo.status_file(Path::new(file_path))?;
+    // Only a file that is new somewhere can be the target of a rename
+    if !renames.renames || !(status.is_index_new() || status.is_wt_new()) {
+        return Ok((paths, status.is_wt_new()));
+    }
+
+    let statuses = repo.statuses(Some(&mut status_options(renames)))?;
+    let renamed = |delta: git2::DiffDelta, to: &str| {
+        let to_path = delta.new_file().path().and_then(|p| p.to_str()) == Some(to);
+        let from = delta.old_file().path().and_then(|p| p.to_str()).map(|p| p.to_string());
+        if to_path && delta.status() == Delta::Renamed { from } else { None }
+    };
+
+    let mut untracked = false;
+    for entry in statuses.iter() {
+        let Some(delta) = entry.index_to_workdir() else { continue };
+        if delta.new_file().path().and_then(|p| p.to_str()) != Some(file_path) {
+            continue;
+        }
+        untracked = delta.status() == Delta::Untracked;
+        if let Some(from) = renamed(delta, file_path) {
+            paths.index = from.clone();
+            paths.head = from;
+        }
+    }
+    for entry in statuses.iter() {
+        if let Some(from) = entry.head_to_index().and_then(|d| renamed(d, &paths.index)) {
+            paths.head = from;
+        }
+    }
+    Ok((paths, untracked))
+}
+
+fn workdir(repo: &Repository) -> Result<&Path> {
+    repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))
+}
+
+/// Blob id of the file in HEAD or the index; gitlinks are skipped
+fn blob_id(repo: &Repository, file_path: &str, side: Side) -> Result<Option<Oid>> {
+    let path = Path::new(file_path);
+    Ok(match side {
+        Side::Head => repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_tree().ok())
+            .and_then(|tree| tree.get_path(path).ok())
+            .filter(|entry| entry.kind() == Some(ObjectType::Blob))
+            .map(|entry| entry.id()),
+        Side::Index => repo.index()?.get_path(path, 0).filter(|e| e.mode != 0o160000).map(|e| e.id),
+        Side::Workdir => None,
+    })
+}
+
+/// Id and size of one side without loading its content
+fn side_file(repo: &Repository, file_path: &str, side: Side) -> Result<Option<DiffFileInfo>> {
+    if side == Side::Workdir {
+        let full = workdir(repo)?.join(file_path);
+        if !full.is_file() {
+            return Ok(None);
+        }
+        return Ok(Some(DiffFileInfo {
+            id: Oid::hash_file(ObjectType::Blob, &full)?.to_string(),
+            size: std::fs::metadata(&full)?.len(),
+            data: None,
+        }));
+    }
+    blob_id(repo, file_path, side)?.map(|id| blob_info(repo, id)).transpose()
+}
+
+fn blob_info(repo: &Repository, id: Oid) -> Result<DiffFileInfo> {
+    let (size, _) = repo.odb()?.read_header(id)?;
+    Ok(DiffFileInfo { id: id.to_string(), size: size as u64, data: None })
+}
+
+fn side_content(repo: &Repository, file_path: &str, side: Side) -> Result<Option<Vec<u8>>> {
+    if side == Side::Workdir {
+        let full = workdir(repo)?.join(file_path);
+        return Ok(if full.is_file() { Some(std::fs::read(full)?) } else { None });
+    }
+    match blob_id(repo, file_path, side)? {
+        Some(id) => Ok(Some(repo.find_blob(id)?.content().to_vec())),
+        None => Ok(None),
+    }
+}
+
+fn looks_binary(data: &[u8]) -> bool {
+    data[..data.len().min(BINARY_CHECK_BYTES)].contains(&0)
+}
+
+fn image_mime_type(file_path: &str) -> Option<&'static str> {
+    let extension = Path::new(file_path).extension()?.to_str()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "ico" => "image/x-icon",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    })
+}
+
+/// `working-tree-encoding` from .gitattributes, else a byte order mark.
+/// None means UTF-8
+fn detect_encoding(repo: &Repository, file_path: &str, samples: [&Option<Vec<u8>>; 2]) -> Option<&'static Encoding> {
+    repo.get_attr(Path::new(file_path), "working-tree-encoding", AttrCheckFlags::FILE_THEN_INDEX)
+        .ok()
+        .flatten()
+        .and_then(|label| Encoding::for_label(label.as_bytes()))
+        .or_else(|| samples.into_iter().flatten().find_map(|data| Encoding::for_bom(data).map(|(e, _)| e)))
+        .filter(|&e| e != encoding_rs::UTF_8)
+}
+
+/// git stores files with `working-tree-encoding` as UTF-8 and only converts
+/// the working tree copy, so content that is already UTF-8 is kept as is
+fn decode(data: &[u8], encoding: &'static Encoding) -> String {
+    match std::str::from_utf8(data) {
+        Ok(text) if !data.contains(&0) => text.to_string(),
+        _ => encoding.decode(data).0.into_owned(),
+    }
+}
+
+/// Mark `file_diff` as too large when either side is over the limit
+fn over_size_limit(file_diff: &mut FileDiff) -> bool {
+    let too_large = [&file_diff.old_file, &file_diff.new_file].into_iter().flatten().any(|f| f.size > MAX_DIFF_BYTES);
+    if too_large {
+        file_diff.kind = DiffContentKind::TooLarge;
+    }
+    too_large
+}
+
+/// Attach image data, then either mark `file_diff` binary or return the
+/// encoding its text is decoded with
+fn inspect_content(
+    repo: &Repository,
+    file_diff: &mut FileDiff,
+    old: &Option<Vec<u8>>,
+    new: &Option<Vec<u8>>,
+) -> &'static Encoding {
+    if file_diff.mime_type.is_some() {
+        for (info, data) in [(&mut file_diff.old_file, old), (&mut file_diff.new_file, new)] {
+            if let (Some(info), Some(data)) = (info, data) {
+                info.data = Some(STANDARD.encode(data));
+            }
+        }
+    }
+
+    let encoding = detect_encoding(repo, &file_diff.path, [old, new]);
+    file_diff.encoding = encoding.map(|e| e.name().to_string());
+    if encoding.is_none() && [old, new].into_iter().flatten().any(|data| looks_binary(data)) {
+        file_diff.kind = DiffContentKind::Binary;
+    }
+    encoding.unwrap_or(encoding_rs::UTF_8)
+}
+
+/// Add the hunks of a libgit2 diff; returns true when a file is binary
+fn collect_diff(diff: &git2::Diff, source: DiffSource, options: &FileDiffOptions, file_diff: &mut FileDiff) -> Result<bool> {
+    for index in 0..diff.deltas().len() {
+        let patch = Patch::from_diff(diff, index)?;
+        if diff.get_delta(index).is_some_and(|d| d.flags().is_binary()) {
+            return Ok(true);
+        }
+        if let Some(patch) = patch {
+            collect_patch(&patch, source, options, file_diff)?;
+        }
+    }
+    Ok(false)
+}
+
+/// Add the hunks of a patch, stopping once `max_lines` lines were added
+fn collect_patch(patch: &Patch, source: DiffSource, options: &FileDiffOptions, file_diff: &mut FileDiff) -> Result<()> {
+    let used: usize = file_diff.hunks.iter().map(|h| h.lines.len()).sum();
+    let mut budget = (options.max_lines.unwrap_or(DEFAULT_MAX_LINES) as usize).saturating_sub(used);
+
+    for hunk_index in 0..patch.num_hunks() {
+        let (hunk, line_count) = patch.hunk(hunk_index)?;
+        if line_count > budget {
+            file_diff.truncated = true;
+        }
+        if budget == 0 {
+            break;
+        }
+        let mut lines = Vec::with_capacity(line_count.min(budget));
+        for line_index in 0..line_count.min(budget) {
+            let line = patch.line_in_hunk(hunk_index, line_index)?;
+            let content = String::from_utf8_lossy(line.content());
+            lines.push(DiffLine {
+                content: content.trim_end_matches('\n').trim_end_matches('\r').to_string(),
+                origin: line.origin(),
+                old_lineno: line.old_lineno(),
+                new_lineno: line.new_lineno(),
+                changes: Vec::new(),
+            });
+        }
+        budget -= lines.len();
+
+        if options.intra_line != IntraLineMode::None {
+            for (old, new) in modified_pairs(&lines) {
+                let (removed, added) = intra_line_changes(&lines[old].content, &lines[new].content, options.intra_line);
+                lines[old].changes = removed;
+                lines[new].changes = added;
+            }
+        }
+
+        file_diff.hunks.push(DiffHunk {
+            header: String::from_utf8_lossy(hunk.header()).trim().to_string(),
+            source,
+            old_start: hunk.old_start(),
+            old_lines: hunk.old_lines(),
+            new_start: hunk.new_start(),
+            new_lines: hunk.new_lines(),
+            rows: if options.side_by_side { side_by_side_rows(&lines) } else { Vec::new() },
+            lines,
+        });
+    }
+    Ok(())
+}
+
+/// Runs of removed lines directly followed by runs of added lines; returns
+/// (start, removed count, added count) for each block
+fn change_blocks(lines: &[DiffLine]) -> Vec<(usize, usize, usize)> {
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        if !matches!(lines[i].origin, '-' | '+') {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i < lines.len() && lines[i].origin == '-' {
+            i += 1;
+        }
+        let removed = i - start;
+        while i < lines.len() && lines[i].origin == '+' {
+            i += 1;
+        }
+        blocks.push((start, removed, i - start - removed));
+    }
+    blocks
+}
+
+/// Removed/added line indices that line up as modifications of each other
+pub(crate) fn modified_pairs(lines: &[DiffLine]) -> Vec<(usize, usize)> {
+    change_blocks(lines)
+        .into_iter()
+        .flat_map(|(start, removed, added)| (0..removed.min(added)).map(move |i| (start + i, start + removed + i)))
+        .collect()
+}
+
+/// Align context lines and pair removed with added lines in change blocks
+pub(crate) fn side_by_side_rows(lines: &[DiffLine]) -> Vec<SideBySideRow> {
+    let blocks = change_blocks(lines);
+    let mut rows = Vec::with_capacity(lines.len());
+    let mut blocks = blocks.into_iter().peekable();
+    let mut i = 0;
+    while i < lines.len() {
+        if let Some(&(start, removed, added)) = blocks.peek().filter(|b| b.0 == i) {
+            for row in 0..removed.max(added) {
+                rows.push(SideBySideRow {
+                    left: (row < removed).then_some(start + row),
+                    right: (row < added).then_some(start + removed + row),
+                });
+            }
+            blocks.next();
+            i = start + removed + added;
+            continue;
+        }
+        // Context; "no newline at end of file" markers get no row
+        if lines[i].origin == ' ' {
+            rows.push(SideBySideRow { left: Some(i), right: Some(i) });
+        }
+        i += 1;
+    }
+    rows
+}
+
+/// Split a line into tokens with their UTF-16 offsets
+fn tokenize(line: &str, mode: IntraLineMode) -> Vec<(&str, u32)> {
+    let mut tokens = Vec::new();
+    let mut offset = 0u32;
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let class = |c: char| {
+            if c.is_alphanumeric() || c == '_' {
+                1
+            } else if c.is_whitespace() {
+                2
+            } else {
+                0
+            }
+        };
+        if mode == IntraLineMode::Word && class(c) != 0 {
+            while let Some(&(next_start, next)) = chars.peek() {
+                if class(next) != class(c) {
+                    break;
+                }
+                end = next_start + next.len_utf8();
+                chars.next();
+            }
+        }
+        let token = &line[start..end];
+        tokens.push((token, offset));
+        offset += token.encode_utf16().count() as u32;
+    }
+    tokens
+}
+
+/// Changed ranges of `old` and `new`: the tokens outside their longest
+/// common subsequence, with adjacent tokens merged
+pub(crate) fn intra_line_changes(old: &str, new: &str, mode: IntraLineMode) -> (Vec<ChangeRange>, Vec<ChangeRange>) {
+    let a = tokenize(old, mode);
+    let b = tokenize(new, mode);
+    let utf16_len = |s: &str| s.encode_utf16().count() as u32;
+
+    if a.len().saturating_mul(b.len()) > MAX_INTRA_LINE_CELLS {
+        let whole = |s: &str| match utf16_len(s) {
+            0 => Vec::new(),
+            end => vec![ChangeRange { start: 0, end }],
+        };
+        return (whole(old), whole(new));
+    }
+
+    // lcs[i][j] = LCS length of a[i..] and b[j..]
+    let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i].0 == b[j].0 { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+
+    let mut a_changed = vec![true; a.len()];
+    let mut b_changed = vec![true; b.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i].0 == b[j].0 {
+            a_changed[i] = false;
+            b_changed[j] = false;
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+
+    let ranges = |tokens: &[(&str, u32)], changed: &[bool]| {
+        let mut ranges: Vec<ChangeRange> = Vec::new();
+        for (&(token, start), _) in tokens.iter().zip(changed).filter(|(_, &c)| c) {
+            let end = start + utf16_len(token);
+            match ranges.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => ranges.push(ChangeRange { start, end }),
+            }
+        }
+        ranges
+    };
+    (ranges(&a, &a_changed), ranges(&b, &b_changed))
+}
+
+#[cfg(test)]
+#[path = "diff_tests.rs"]
+mod diff_tests;
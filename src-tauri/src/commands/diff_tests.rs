@@ -0,0 +1,361 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::diff::{get_commit_file_diff_impl, get_file_diff_impl, intra_line_changes, side_by_side_rows};
+    use crate::domain::{
+        ChangeRange, DiffContentKind, DiffLine, DiffMode, DiffSource, FileDiffOptions, IntraLineMode, RenameOptions, SideBySideRow,
+    };
+    use crate::commands::test_support::{self, commit_index, stage};
+    use git2::{Repository, Signature};
+    use tempfile::TempDir;
+
+    fn init_repo(content: &str) -> (TempDir, Repository) {
+        init_repo_with(&[("file.txt", content.as_bytes())])
+    }
+
+    fn init_repo_with(files: &[(&str, &[u8])]) -> (TempDir, Repository) {
+        let (dir, repo) = test_support::init_repo();
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+            stage(&repo, name);
+        }
+        commit_index(&repo, "initial");
+        (dir, repo)
+    }
+
+    fn range(start: u32, end: u32) -> ChangeRange {
+        ChangeRange { start, end }
+    }
+
+    fn line(origin: char) -> DiffLine {
+        DiffLine { content: String::new(), origin, old_lineno: None, new_lineno: None, changes: Vec::new() }
+    }
+
+    const BASE: &str = "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\nnine\nten\n";
+
+    #[test]
+    fn test_modes_separate_staged_and_unstaged_hunks() {
+        let (dir, repo) = init_repo(BASE);
+        std::fs::write(dir.path().join("file.txt"), BASE.replace("two", "TWO")).unwrap();
+        stage(&repo, "file.txt");
+        std::fs::write(dir.path().join("file.txt"), BASE.replace("two", "TWO").replace("nine", "NINE")).unwrap();
+
+        let options = |mode| FileDiffOptions { mode, ..Default::default() };
+        let combined = get_file_diff_impl(&repo, "file.txt", &options(DiffMode::Combined)).unwrap();
+        let sources: Vec<DiffSource> = combined.hunks.iter().map(|h| h.source).collect();
+        assert_eq!(sources, vec![DiffSource::Staged, DiffSource::Unstaged]);
+
+        let staged = get_file_diff_impl(&repo, "file.txt", &options(DiffMode::Staged)).unwrap();
+        assert_eq!(staged.hunks.len(), 1);
+        assert!(staged.hunks[0].lines.iter().any(|l| l.origin == '+' && l.content == "TWO"));
+
+        let unstaged = get_file_diff_impl(&repo, "file.txt", &options(DiffMode::Unstaged)).unwrap();
+        assert_eq!(unstaged.hunks.len(), 1);
+        assert_eq!(unstaged.hunks[0].new_start, 6);
+        assert!(unstaged.hunks[0].lines.iter().any(|l| l.origin == '+' && l.content == "NINE"));
+    }
+
+    #[test]
+    fn test_context_and_whitespace_options() {
+        let (dir, repo) = init_repo(BASE);
+        std::fs::write(dir.path().join("file.txt"), BASE.replace("five", "five  ").replace("eight", "EIGHT")).unwrap();
+
+        let options = FileDiffOptions { context_lines: Some(0), ..Default::default() };
+        let diff = get_file_diff_impl(&repo, "file.txt", &options).unwrap();
+        assert_eq!(diff.hunks.len(), 2);
+        assert!(diff.hunks.iter().all(|h| h.lines.iter().all(|l| l.origin != ' ')));
+
+        let options = FileDiffOptions { context_lines: Some(0), ignore_whitespace_change: true, ..Default::default() };
+        let diff = get_file_diff_impl(&repo, "file.txt", &options).unwrap();
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.hunks[0].old_start, 8);
+    }
+
+    #[test]
+    fn test_modified_lines_get_word_ranges_and_rows() {
+        let (dir, repo) = init_repo("let x = 1;\nkeep\n");
+        std::fs::write(dir.path().join("file.txt"), "let y = 1;\nkeep\nadded\n").unwrap();
+
+        let options = FileDiffOptions { side_by_side: true, ..Default::default() };
+        let diff = get_file_diff_impl(&repo, "file.txt", &options).unwrap();
+        let hunk = &diff.hunks[0];
+
+        let origins: Vec<char> = hunk.lines.iter().map(|l| l.origin).collect();
+        assert_eq!(origins, vec!['-', '+', ' ', '+']);
+        assert_eq!(hunk.lines[0].changes, vec![range(4, 5)]);
+        assert_eq!(hunk.lines[1].changes, vec![range(4, 5)]);
+        assert!(hunk.lines[3].changes.is_empty());
+
+        assert_eq!(
+            hunk.rows,
+            vec![
+                SideBySideRow { left: Some(0), right: Some(1) },
+                SideBySideRow { left: Some(2), right: Some(2) },
+                SideBySideRow { left: None, right: Some(3) },
+            ]
+        );
+
+        let plain = get_file_diff_impl(&repo, "file.txt", &FileDiffOptions { intra_line: IntraLineMode::None, ..Default::default() }).unwrap();
+        assert!(plain.hunks[0].lines.iter().all(|l| l.changes.is_empty()));
+        assert!(plain.hunks[0].rows.is_empty());
+    }
+
+    #[test]
+    fn test_intra_line_offsets_are_utf16() {
+        let (old, new) = intra_line_changes("a 😀 b", "a 😀 c", IntraLineMode::Word);
+        assert_eq!(old, vec![range(5, 6)]);
+        assert_eq!(new, vec![range(5, 6)]);
+
+        let (old, new) = intra_line_changes("colour", "color", IntraLineMode::Char);
+        assert_eq!(old, vec![range(4, 5)]);
+        assert!(new.is_empty());
+
+        let (old, new) = intra_line_changes("foo_bar(1)", "foo_baz(1)", IntraLineMode::Word);
+        assert_eq!(old, vec![range(0, 7)]);
+        assert_eq!(new, vec![range(0, 7)]);
+    }
+
+    #[test]
+    fn test_side_by_side_pads_uneven_blocks() {
+        let lines: Vec<DiffLine> = ['-', '-', '+', ' ', '+'].into_iter().map(line).collect();
+
+        assert_eq!(
+            side_by_side_rows(&lines),
+            vec![
+                SideBySideRow { left: Some(0), right: Some(2) },
+                SideBySideRow { left: Some(1), right: None },
+                SideBySideRow { left: Some(3), right: Some(3) },
+                SideBySideRow { left: None, right: Some(4) },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_untracked_file_is_one_addition_hunk() {
+        let (dir, repo) = init_repo(BASE);
+        std::fs::write(dir.path().join("new.txt"), "a\nb\n").unwrap();
+
+        let diff = get_file_diff_impl(&repo, "new.txt", &FileDiffOptions::default()).unwrap();
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.hunks[0].source, DiffSource::Untracked);
+        assert_eq!(diff.hunks[0].new_lines, 2);
+
+        let staged = get_file_diff_impl(&repo, "new.txt", &FileDiffOptions { mode: DiffMode::Staged, ..Default::default() }).unwrap();
+        assert!(staged.hunks.is_empty());
+    }
+
+    #[test]
+    fn test_binary_file_reports_ids_and_sizes() {
+        let (dir, repo) = init_repo_with(&[("data.bin", b"\x00\x01\x02")]);
+        std::fs::write(dir.path().join("data.bin"), b"\x00\x01\x02\x03").unwrap();
+
+        let diff = get_file_diff_impl(&repo, "data.bin", &FileDiffOptions::default()).unwrap();
+
+        assert_eq!(diff.kind, DiffContentKind::Binary);
+        assert!(diff.hunks.is_empty());
+        assert!(diff.mime_type.is_none());
+        let (old, new) = (diff.old_file.unwrap(), diff.new_file.unwrap());
+        assert_eq!((old.size, new.size), (3, 4));
+        assert_ne!(old.id, new.id);
+        assert_eq!(new.id, git2::Oid::hash_object(git2::ObjectType::Blob, b"\x00\x01\x02\x03").unwrap().to_string());
+        assert!(old.data.is_none());
+    }
+
+    #[test]
+    fn test_diff_attribute_unset_is_binary() {
+        let (dir, repo) = init_repo_with(&[(".gitattributes", b"*.lock -diff\n"), ("deps.lock", b"a\n")]);
+        std::fs::write(dir.path().join("deps.lock"), "b\n").unwrap();
+
+        let diff = get_file_diff_impl(&repo, "deps.lock", &FileDiffOptions::default()).unwrap();
+
+        assert_eq!(diff.kind, DiffContentKind::Binary);
+        assert!(diff.hunks.is_empty());
+    }
+
+    #[test]
+    fn test_images_carry_base64_content() {
+        let png: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00";
+        let (dir, repo) = init_repo_with(&[("logo.png", png), ("icon.svg", b"<svg>\n</svg>\n")]);
+        std::fs::write(dir.path().join("logo.png"), b"\x89PNG\r\n\x1a\n\x00\x01").unwrap();
+        std::fs::write(dir.path().join("icon.svg"), "<svg>\n<circle/>\n</svg>\n").unwrap();
+
+        let diff = get_file_diff_impl(&repo, "logo.png", &FileDiffOptions::default()).unwrap();
+        assert_eq!(diff.kind, DiffContentKind::Binary);
+        assert_eq!(diff.mime_type.as_deref(), Some("image/png"));
+        assert_eq!(diff.old_file.unwrap().data.as_deref(), Some("iVBORw0KGgoAAA=="));
+        assert_eq!(diff.new_file.unwrap().data.as_deref(), Some("iVBORw0KGgoAAQ=="));
+
+        let diff = get_file_diff_impl(&repo, "icon.svg", &FileDiffOptions::default()).unwrap();
+        assert_eq!(diff.kind, DiffContentKind::Text);
+        assert_eq!(diff.mime_type.as_deref(), Some("image/svg+xml"));
+        assert!(diff.new_file.unwrap().data.is_some());
+        assert!(diff.hunks[0].lines.iter().any(|l| l.origin == '+' && l.content == "<circle/>"));
+    }
+
+    #[test]
+    fn test_working_tree_encoding_is_decoded() {
+        // As git stores it: UTF-8 in the index, GBK in the working tree
+        let (dir, repo) = init_repo_with(&[
+            (".gitattributes", b"*.txt working-tree-encoding=GBK\n"),
+            ("zh.txt", "你好\n".as_bytes()),
+        ]);
+        let gbk = encoding_rs::GBK.encode("你好\n世界\n").0;
+        std::fs::write(dir.path().join("zh.txt"), &gbk).unwrap();
+
+        let diff = get_file_diff_impl(&repo, "zh.txt", &FileDiffOptions::default()).unwrap();
+
+        assert_eq!(diff.kind, DiffContentKind::Text);
+        assert_eq!(diff.encoding.as_deref(), Some("GBK"));
+        let changed: Vec<(char, &str)> =
+            diff.hunks[0].lines.iter().filter(|l| l.origin != ' ').map(|l| (l.origin, l.content.as_str())).collect();
+        assert_eq!(changed, vec![('+', "世界")]);
+    }
+
+    #[test]
+    fn test_utf16_with_bom_is_text() {
+        let (dir, repo) = init_repo(BASE);
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend("héllo\nwörld\n".encode_utf16().flat_map(|u| u.to_le_bytes()));
+        std::fs::write(dir.path().join("notes.txt"), &utf16).unwrap();
+
+        let diff = get_file_diff_impl(&repo, "notes.txt", &FileDiffOptions::default()).unwrap();
+
+        assert_eq!(diff.kind, DiffContentKind::Text);
+        assert_eq!(diff.encoding.as_deref(), Some("UTF-16LE"));
+        let contents: Vec<&str> = diff.hunks[0].lines.iter().map(|l| l.content.as_str()).collect();
+        assert_eq!(contents, vec!["héllo", "wörld"]);
+    }
+
+    #[test]
+    fn test_long_diffs_are_truncated() {
+        let (dir, repo) = init_repo(BASE);
+        std::fs::write(dir.path().join("file.txt"), BASE.to_uppercase()).unwrap();
+
+        let options = FileDiffOptions { max_lines: Some(5), ..Default::default() };
+        let diff = get_file_diff_impl(&repo, "file.txt", &options).unwrap();
+
+        assert!(diff.truncated);
+        assert_eq!(diff.hunks.iter().map(|h| h.lines.len()).sum::<usize>(), 5);
+
+        let diff = get_file_diff_impl(&repo, "file.txt", &FileDiffOptions::default()).unwrap();
+        assert!(!diff.truncated);
+        assert_eq!(diff.hunks[0].lines.len(), 20);
+    }
+
+    #[test]
+    fn test_huge_files_only_get_metadata() {
+        let (dir, repo) = init_repo(BASE);
+        std::fs::write(dir.path().join("file.txt"), "x\n".repeat(9 * 1024 * 1024)).unwrap();
+
+        let diff = get_file_diff_impl(&repo, "file.txt", &FileDiffOptions::default()).unwrap();
+
+        assert_eq!(diff.kind, DiffContentKind::TooLarge);
+        assert!(diff.hunks.is_empty());
+        assert_eq!(diff.new_file.unwrap().size, 18 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_staged_rename_diffs_against_old_path() {
+        let (dir, repo) = init_repo(BASE);
+        std::fs::rename(dir.path().join("file.txt"), dir.path().join("moved.txt")).unwrap();
+        std::fs::write(dir.path().join("moved.txt"), BASE.replace("five", "FIVE")).unwrap();
+        let mut index = repo.index().unwrap();
+        index.remove_path(std::path::Path::new("file.txt")).unwrap();
+        index.add_path(std::path::Path::new("moved.txt")).unwrap();
+        index.write().unwrap();
+
+        let diff = get_file_diff_impl(&repo, "moved.txt", &FileDiffOptions::default()).unwrap();
+
+        assert_eq!(diff.old_path.as_deref(), Some("file.txt"));
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.hunks[0].source, DiffSource::Staged);
+        let changed: Vec<&str> = diff.hunks[0].lines.iter().filter(|l| l.origin != ' ').map(|l| l.content.as_str()).collect();
+        assert_eq!(changed, vec!["five", "FIVE"]);
+    }
+
+    #[test]
+    fn test_working_tree_rename_diffs_against_old_path() {
+        let (dir, repo) = init_repo(BASE);
+        std::fs::rename(dir.path().join("file.txt"), dir.path().join("moved.txt")).unwrap();
+        std::fs::write(dir.path().join("moved.txt"), BASE.replace("five", "FIVE")).unwrap();
+
+        let options = FileDiffOptions { renames: RenameOptions { worktree: true, ..Default::default() }, ..Default::default() };
+        let diff = get_file_diff_impl(&repo, "moved.txt", &options).unwrap();
+        assert_eq!(diff.old_path.as_deref(), Some("file.txt"));
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.hunks[0].source, DiffSource::Unstaged);
+        assert_eq!(diff.hunks[0].lines.iter().filter(|l| l.origin != ' ').count(), 2);
+
+        // Like status, unstaged moves are a new file by default
+        let diff = get_file_diff_impl(&repo, "moved.txt", &FileDiffOptions::default()).unwrap();
+        assert!(diff.old_path.is_none());
+        assert_eq!(diff.hunks[0].source, DiffSource::Untracked);
+        assert_eq!(diff.hunks[0].new_lines, 10);
+    }
+
+    fn commit_all(repo: &Repository, message: &str) -> String {
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent]).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_commit_file_diff_follows_renames_within_the_commit() {
+        let (dir, repo) = init_repo(BASE);
+        std::fs::rename(dir.path().join("file.txt"), dir.path().join("moved.txt")).unwrap();
+        std::fs::write(dir.path().join("moved.txt"), BASE.replace("five", "FIVE")).unwrap();
+        let oid = commit_all(&repo, "move");
+
+        let diff = get_commit_file_diff_impl(&repo, &oid, "moved.txt", 0, &FileDiffOptions::default()).unwrap();
+
+        assert_eq!(diff.old_path.as_deref(), Some("file.txt"));
+        assert_eq!(diff.hunks.len(), 1);
+        assert_eq!(diff.hunks[0].source, DiffSource::Commit);
+        let changed: Vec<(char, &str)> =
+            diff.hunks[0].lines.iter().filter(|l| l.origin != ' ').map(|l| (l.origin, l.content.as_str())).collect();
+        assert_eq!(changed, vec![('-', "five"), ('+', "FIVE")]);
+        assert_eq!(diff.hunks[0].lines[0].old_lineno, Some(2));
+
+        // Looking the file up by its old name finds the same rename
+        let diff = get_commit_file_diff_impl(&repo, &oid, "file.txt", 0, &FileDiffOptions::default()).unwrap();
+        assert_eq!((diff.path.as_str(), diff.old_path.as_deref()), ("moved.txt", Some("file.txt")));
+    }
+
+    #[test]
+    fn test_commit_file_diff_against_second_parent_of_merge() {
+        let (dir, repo) = init_repo(BASE);
+        let base = repo.head().unwrap().peel_to_commit().unwrap();
+        std::fs::write(dir.path().join("file.txt"), BASE.replace("two", "TWO")).unwrap();
+        let ours = commit_all(&repo, "ours");
+
+        let theirs_tree = {
+            let mut index = repo.index().unwrap();
+            index.read_tree(&base.tree().unwrap()).unwrap();
+            let blob = repo.blob(BASE.replace("nine", "NINE").as_bytes()).unwrap();
+            let mut entry = index.get_path(std::path::Path::new("file.txt"), 0).unwrap();
+            entry.id = blob;
+            index.add(&entry).unwrap();
+            repo.find_tree(index.write_tree().unwrap()).unwrap()
+        };
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let theirs = repo.commit(None, &sig, &sig, "theirs", &theirs_tree, &[&base]).unwrap();
+
+        let merged = repo.blob(BASE.replace("two", "TWO").replace("nine", "NINE").as_bytes()).unwrap();
+        let mut builder = repo.treebuilder(Some(&theirs_tree)).unwrap();
+        builder.insert("file.txt", merged, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let parents = [&repo.find_commit(git2::Oid::from_str(&ours).unwrap()).unwrap(), &repo.find_commit(theirs).unwrap()];
+        let merge = repo.commit(None, &sig, &sig, "merge", &tree, &parents).unwrap().to_string();
+
+        let added = |parent_index| {
+            let diff = get_commit_file_diff_impl(&repo, &merge, "file.txt", parent_index, &FileDiffOptions::default()).unwrap();
+            diff.hunks.iter().flat_map(|h| h.lines.iter()).filter(|l| l.origin == '+').map(|l| l.content.clone()).collect::<Vec<_>>()
+        };
+        assert_eq!(added(0), vec!["NINE"]);
+        assert_eq!(added(1), vec!["TWO"]);
+    }
+}
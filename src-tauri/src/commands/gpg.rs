@@ -1,4 +1,7 @@
-use std::process::Command;
+use crate::domain::{CommitSignature, SignatureStatus};
+use crate::error::{AppError, Result as AppResult};
+use std::io::Write;
+use std::process::{Command, Stdio};
 
 #[tauri::command]
 pub async fn get_gpg_keys() -> Result<Vec<GpgKeyInfo>, String> {
@@ -153,6 +156,281 @@ fn parse_gpg_date(date_str: &str) -> Option<String> {
     }
 }
 
+/// Whether commits in this repository should be signed (`commit.gpgsign`)
+pub(crate) fn commit_signing_enabled(config: &git2::Config) -> bool {
+    config.get_bool("commit.gpgsign").unwrap_or(false)
+}
+
+/// Sign a raw commit buffer according to `gpg.format` and `user.signingkey`.
+///
+/// Returns the armored signature to be stored in the commit's `gpgsig` header.
+pub(crate) fn sign_commit_buffer(config: &git2::Config, buffer: &str) -> AppResult<String> {
+    let format = config.get_string("gpg.format").unwrap_or_else(|_| "openpgp".to_string());
+    let signing_key = config.get_string("user.signingkey").ok();
+
+    match format.as_str() {
+        "openpgp" | "x509" => {
+            let (program_key, default_program) = if format == "x509" {
+                ("gpg.x509.program", "gpgsm")
+            } else {
+                ("gpg.openpgp.program", "gpg")
+            };
+            let program = config.get_string(program_key)
+                .or_else(|_| config.get_string("gpg.program"))
+                .unwrap_or_else(|_| default_program.to_string());
+
+            // Like git, fall back to the committer identity when no key is configured
+            let key = match signing_key {
+                Some(key) => key,
+                None => committer_ident(config)?,
+            };
+
+            let mut command = Command::new(&program);
+            command.args(["--status-fd=2", "-bsau", &key]);
+            run_signing_program(&program, command, buffer)
+        }
+        "ssh" => {
+            let program = config.get_string("gpg.ssh.program")
+                .unwrap_or_else(|_| "ssh-keygen".to_string());
+            let key = signing_key.ok_or_else(|| AppError::Signing(
+                "gpg.format is 'ssh' but user.signingkey is not set".to_string()
+            ))?;
+
+            // The key can be a path to a key file or a literal public key
+            // (optionally prefixed with "key::"), in which case the private
+            // half is expected to be in ssh-agent.
+            let literal = key.strip_prefix("key::").map(|s| s.to_string())
+                .or_else(|| if key.starts_with("ssh-") || key.starts_with("ecdsa-") {
+                    Some(key.clone())
+                } else {
+                    None
+                });
+
+            let mut command = Command::new(&program);
+            command.args(["-Y", "sign", "-n", "git"]);
+
+            let literal_key_file = if let Some(public_key) = literal {
+                let path = temp_path(".gat_signing_key", ".pub");
+                std::fs::write(&path, format!("{}\n", public_key.trim()))?;
+                command.arg("-U").arg("-f").arg(&path);
+                Some(path)
+            } else {
+                command.arg("-f").arg(expand_home(&key));
+                None
+            };
+
+            let result = run_signing_program(&program, command, buffer);
+            if let Some(path) = literal_key_file {
+                let _ = std::fs::remove_file(path);
+            }
+            result
+        }
+        other => Err(AppError::Signing(format!("Unsupported gpg.format: {}", other))),
+    }
+}
+
+/// A temp file path of this call's own, so that concurrent signing and
+/// verifying don't overwrite or delete each other's files
+fn temp_path(prefix: &str, extension: &str) -> std::path::PathBuf {
+    static NEXT_FILE: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+    let n = NEXT_FILE.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    std::env::temp_dir().join(format!("{}_{}_{}{}", prefix, std::process::id(), n, extension))
+}
+
+fn run_signing_program(program: &str, mut command: Command, buffer: &str) -> AppResult<String> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AppError::Signing(format!("Failed to run '{}': {}", program, e)))?;
+
+    // A program that fails early stops reading; its exit status says why
+    if let Some(mut stdin) = child.stdin.take() {
+        match stdin.write_all(buffer.as_bytes()) {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => return Err(e.into()),
+            _ => {}
+        }
+    }
+
+    let output = child.wait_with_output()?;
+    let signature = String::from_utf8_lossy(&output.stdout).to_string();
+
+    if !output.status.success() || signature.trim().is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .filter(|line| !line.starts_with("[GNUPG:]"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        return Err(AppError::Signing(format!("'{}' failed to sign the commit: {}", program, reason.trim())));
+    }
+
+    Ok(signature)
+}
+
+/// Signature format from the armor header of a `gpgsig` value
+pub(crate) fn signature_format(signature: &str) -> &'static str {
+    if signature.starts_with("-----BEGIN SSH SIGNATURE-----") {
+        "ssh"
+    } else if signature.starts_with("-----BEGIN SIGNED MESSAGE-----") {
+        "x509"
+    } else {
+        "openpgp"
+    }
+}
+
+/// Verify a commit's `gpgsig` against the signed part of the commit with the
+/// program git would use for the signature's format. Anything that keeps
+/// the check from running is reported as `Unverified`
+pub(crate) fn verify_commit_signature(
+    config: &git2::Config,
+    signature: &str,
+    signed_data: &[u8],
+    committer_email: &str,
+) -> CommitSignature {
+    let format = signature_format(signature);
+    let mut result = CommitSignature {
+        format: format.to_string(),
+        status: SignatureStatus::Unverified,
+        signer: None,
+        key: None,
+    };
+
+    // The verifiers read the signature from a file and the data from stdin
+    let sig_path = temp_path(".gat_signature", "");
+    if std::fs::write(&sig_path, signature).is_err() {
+        return result;
+    }
+
+    if format == "ssh" {
+        let program = config.get_string("gpg.ssh.program").unwrap_or_else(|_| "ssh-keygen".to_string());
+        let mut command = Command::new(&program);
+        command.args(["-Y", "check-novalidate", "-n", "git", "-s"]).arg(&sig_path);
+        if let Some((valid, output)) = run_verify_program(command, signed_data) {
+            result.status = if valid { SignatureStatus::Untrusted } else { SignatureStatus::Bad };
+            result.key = parse_ssh_key(&output);
+        }
+
+        // Only signers listed in gpg.ssh.allowedSignersFile are trusted
+        if let (SignatureStatus::Untrusted, Ok(allowed)) =
+            (result.status, config.get_path("gpg.ssh.allowedSignersFile"))
+        {
+            let mut command = Command::new(&program);
+            command
+                .args(["-Y", "verify", "-n", "git", "-I", committer_email, "-f"])
+                .arg(allowed)
+                .arg("-s")
+                .arg(&sig_path);
+            if let Some((true, _)) = run_verify_program(command, signed_data) {
+                result.status = SignatureStatus::Good;
+                result.signer = Some(committer_email.to_string());
+            }
+        }
+    } else {
+        let (program_key, default_program) = if format == "x509" {
+            ("gpg.x509.program", "gpgsm")
+        } else {
+            ("gpg.openpgp.program", "gpg")
+        };
+        let program = config.get_string(program_key)
+            .or_else(|_| config.get_string("gpg.program"))
+            .unwrap_or_else(|_| default_program.to_string());
+
+        let mut command = Command::new(&program);
+        command.args(["--status-fd=1", "--verify"]).arg(&sig_path).arg("-");
+        if let Some((_, output)) = run_verify_program(command, signed_data) {
+            (result.status, result.signer, result.key) = parse_gpg_status(&output);
+        }
+    }
+
+    let _ = std::fs::remove_file(&sig_path);
+    result
+}
+
+/// Run a verifier with `data` on stdin; returns whether it succeeded and
+/// its stdout and stderr, or None if it could not be run
+fn run_verify_program(mut command: Command, data: &[u8]) -> Option<(bool, String)> {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(data).ok()?;
+    }
+    let output = child.wait_with_output().ok()?;
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    Some((output.status.success(), text))
+}
+
+/// Read `[GNUPG:]` status lines; returns the status, signer and key id
+pub(crate) fn parse_gpg_status(output: &str) -> (SignatureStatus, Option<String>, Option<String>) {
+    let mut status = SignatureStatus::Unverified;
+    let mut signer = None;
+    let mut key = None;
+    let mut trusted = true;
+
+    for line in output.lines() {
+        let Some(line) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let (id, user) = match rest.split_once(' ') {
+            Some((id, user)) => (Some(id.to_string()), Some(user.to_string())),
+            None => (Some(rest.to_string()).filter(|r| !r.is_empty()), None),
+        };
+        let found = match keyword {
+            "GOODSIG" => SignatureStatus::Good,
+            "BADSIG" => SignatureStatus::Bad,
+            "EXPSIG" | "EXPKEYSIG" => SignatureStatus::Expired,
+            "REVKEYSIG" => SignatureStatus::Revoked,
+            "ERRSIG" => SignatureStatus::Unverified,
+            "TRUST_UNDEFINED" | "TRUST_NEVER" => {
+                trusted = false;
+                continue;
+            }
+            _ => continue,
+        };
+        status = found;
+        key = id;
+        if found != SignatureStatus::Unverified {
+            signer = user;
+        }
+    }
+
+    if status == SignatureStatus::Good && !trusted {
+        status = SignatureStatus::Untrusted;
+    }
+    (status, signer, key)
+}
+
+/// Key fingerprint from ssh-keygen output such as
+/// `Good "git" signature with ED25519 key SHA256:...`
+fn parse_ssh_key(output: &str) -> Option<String> {
+    output.split_whitespace().find(|word| word.starts_with("SHA256:")).map(|word| word.to_string())
+}
+
+fn committer_ident(config: &git2::Config) -> AppResult<String> {
+    let name = config.get_string("user.name").ok();
+    let email = config.get_string("user.email").ok();
+    match (name, email) {
+        (Some(name), Some(email)) => Ok(format!("{} <{}>", name, email)),
+        (None, Some(email)) => Ok(email),
+        _ => Err(AppError::Signing("No user.signingkey or user.email configured".to_string())),
+    }
+}
+
+fn expand_home(path: &str) -> std::path::PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    std::path::PathBuf::from(path)
+}
+
 #[derive(serde::Serialize, Clone)]
 pub struct GpgKeyInfo {
     pub key_id: String,
@@ -161,3 +439,7 @@ pub struct GpgKeyInfo {
     pub created_at: Option<String>,
     pub has_private_key: bool,
 }
+
+#[cfg(test)]
+#[path = "gpg_tests.rs"]
+mod gpg_tests;
@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::gpg::{parse_gpg_status, signature_format};
+    use crate::domain::SignatureStatus;
+
+    #[test]
+    fn test_parse_gpg_status_good_and_trusted() {
+        let output = "[GNUPG:] NEWSIG\n\
+                      [GNUPG:] GOODSIG 0123456789ABCDEF Test User <test@example.com>\n\
+                      [GNUPG:] VALIDSIG ABCDEF0123456789ABCDEF0123456789ABCDEF01 2024-01-01\n\
+                      [GNUPG:] TRUST_ULTIMATE 0 pgp\n";
+
+        let (status, signer, key) = parse_gpg_status(output);
+
+        assert_eq!(status, SignatureStatus::Good);
+        assert_eq!(signer.as_deref(), Some("Test User <test@example.com>"));
+        assert_eq!(key.as_deref(), Some("0123456789ABCDEF"));
+    }
+
+    #[test]
+    fn test_parse_gpg_status_failures() {
+        let status = |output: &str| parse_gpg_status(output).0;
+
+        assert_eq!(status("[GNUPG:] BADSIG 0123456789ABCDEF Test User\n"), SignatureStatus::Bad);
+        assert_eq!(status("[GNUPG:] EXPKEYSIG 0123456789ABCDEF Test User\n"), SignatureStatus::Expired);
+        assert_eq!(status("[GNUPG:] REVKEYSIG 0123456789ABCDEF Test User\n"), SignatureStatus::Revoked);
+        assert_eq!(status("gpg: Can't check signature: No public key\n"), SignatureStatus::Unverified);
+
+        let (status, signer, key) = parse_gpg_status("[GNUPG:] ERRSIG 0123456789ABCDEF 1 8 00 1700000000 9 -\n");
+        assert_eq!(status, SignatureStatus::Unverified);
+        assert!(signer.is_none());
+        assert_eq!(key.as_deref(), Some("0123456789ABCDEF"));
+    }
+
+    #[test]
+    fn test_signature_format_from_armor() {
+        assert_eq!(signature_format("-----BEGIN PGP SIGNATURE-----\n"), "openpgp");
+        assert_eq!(signature_format("-----BEGIN SSH SIGNATURE-----\n"), "ssh");
+        assert_eq!(signature_format("-----BEGIN SIGNED MESSAGE-----\n"), "x509");
+    }
+}
@@ -0,0 +1,218 @@
+use crate::domain::{CommitGraphPage, GraphEdge, GraphEdgeKind, GraphLane, GraphOptions, GraphRow, GraphState};
+use crate::error::Result;
+use super::history::commit_info;
+use super::history_cache::{scoped_order, WalkScope};
+use git2::{Oid, Repository};
+use std::collections::{HashMap, HashSet};
+
+/// Get a page of commit history with the branch graph laid out. Pass the
+/// `state` returned with the previous page to continue its lanes; without
+/// it the layout of the skipped commits is computed first
+#[tauri::command]
+pub async fn get_commit_graph(
+    path: String,
+    skip: usize,
+    limit: usize,
+    options: Option<GraphOptions>,
+    state: Option<GraphState>,
+) -> std::result::Result<CommitGraphPage, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_commit_graph_impl(&repo, skip, limit, &options.unwrap_or_default(), state).map_err(|e| e.to_string())
+}
+
+pub(crate) fn get_commit_graph_impl(
+    repo: &Repository,
+    skip: usize,
+    limit: usize,
+    options: &GraphOptions,
+    state: Option<GraphState>,
+) -> Result<CommitGraphPage> {
+    let refs = commit_refs(repo, options.all_refs);
+    let order = scoped_order(repo, WalkScope { all_refs: options.all_refs, first_parent: options.first_parent })?;
+    // A state from another page would misplace every lane, so start over
+    // and lay out the skipped rows again
+    let (mut state, mut index) = match state.filter(|state| state.rows == skip && state.position <= order.oids.len()) {
+        Some(state) => (state, skip),
+        None => (GraphState::default(), 0),
+    };
+
+    let mut rows = Vec::new();
+    let mut has_more = false;
+    let mut position = state.position;
+    while let Some(&oid) = order.oids.get(position) {
+        if options.simplify_by_decoration && !refs.contains_key(&oid) {
+            position += 1;
+            continue;
+        }
+        if rows.len() == limit {
+            has_more = true;
+            break;
+        }
+        position += 1;
+        index += 1;
+
+        let commit = repo.find_commit(oid)?;
+        let parents = if options.simplify_by_decoration {
+            decorated_parents(repo, &commit, &refs, options.first_parent)?
+        } else if options.first_parent {
+            commit.parent_ids().take(1).collect()
+        } else {
+            commit.parent_ids().collect()
+        };
+        let (column, color, edges) = place(&mut state, oid, &parents);
+        if index <= skip {
+            continue;
+        }
+
+        let mut info = commit_info(&commit);
+        info.parents = parents.iter().map(|id| id.to_string()).collect();
+        info.refs = refs.get(&oid).cloned().unwrap_or_default();
+        rows.push(GraphRow { commit: info, column, color, edges });
+    }
+
+    state.rows = skip + rows.len();
+    state.position = position;
+    Ok(CommitGraphPage { rows, state, has_more })
+}
+
+/// Short names of the branches and tags pointing at each commit. Remote
+/// branches are only included with `include_remotes`
+pub(crate) fn commit_refs(repo: &Repository, include_remotes: bool) -> HashMap<Oid, Vec<String>> {
+    let mut refs: HashMap<Oid, Vec<String>> = HashMap::new();
+    let Ok(references) = repo.references() else {
+        return refs;
+    };
+    for reference in references.flatten() {
+        // Symbolic refs such as origin/HEAD only repeat a branch
+        if reference.target().is_none() || (reference.is_remote() && !include_remotes) {
+            continue;
+        }
+        let (Some(name), Ok(commit)) = (reference.shorthand(), reference.peel_to_commit()) else {
+            continue;
+        };
+        refs.entry(commit.id()).or_default().push(name.to_string());
+    }
+    refs
+}
+
+/// Nearest ancestors of `commit` that a ref points at, searching each
+/// parent in order
+fn decorated_parents(
+    repo: &Repository,
+    commit: &git2::Commit,
+    refs: &HashMap<Oid, Vec<String>>,
+    first_parent: bool,
+) -> Result<Vec<Oid>> {
+    let parents = |commit: &git2::Commit| -> Vec<Oid> {
+        commit.parent_ids().take(if first_parent { 1 } else { usize::MAX }).collect()
+    };
+    let mut found = Vec::new();
+    let mut seen = HashSet::new();
+    let mut stack: Vec<Oid> = parents(commit).into_iter().rev().collect();
+    while let Some(oid) = stack.pop() {
+        if !seen.insert(oid) {
+            continue;
+        }
+        if refs.contains_key(&oid) {
+            found.push(oid);
+            continue;
+        }
+        stack.extend(parents(&repo.find_commit(oid)?).into_iter().rev());
+    }
+    Ok(found)
+}
+
+/// Lay out the next commit: put it in the lane of its first child seen, or
+/// a free column if it starts a branch, then route its parents into lanes.
+/// Lanes only move when the first parent's lane is pulled left into the
+/// commit's column, so every edge bends at most once inside its row
+pub(crate) fn place(state: &mut GraphState, oid: Oid, parents: &[Oid]) -> (usize, usize, Vec<GraphEdge>) {
+    let id = oid.to_string();
+    let children: Vec<usize> = lanes_heading_for(&state.lanes, &id).collect();
+    let column = match children.first() {
+        Some(&column) => column,
+        None => free_column(&mut state.lanes),
+    };
+    let color = match state.lanes[column].as_ref() {
+        Some(lane) => lane.color,
+        None => next_color(state),
+    };
+
+    let mut edges: Vec<GraphEdge> = state
+        .lanes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, lane)| {
+            let lane = lane.as_ref()?;
+            Some(if lane.oid == id {
+                GraphEdge { from: i, to: column, color: lane.color, kind: GraphEdgeKind::Child }
+            } else {
+                GraphEdge { from: i, to: i, color: lane.color, kind: GraphEdgeKind::Pass }
+            })
+        })
+        .collect();
+    for &child in &children {
+        state.lanes[child] = None;
+    }
+
+    for (n, parent) in parents.iter().enumerate() {
+        let parent = parent.to_string();
+        // A parent that already has a lane is joined rather than duplicated
+        let existing = lanes_heading_for(&state.lanes, &parent).next();
+        if let Some(lane) = existing {
+            // Pull the first parent's lane left into this column so the
+            // branch carries on straight down
+            if n == 0 && lane > column && state.lanes[column].is_none() {
+                state.lanes[lane] = None;
+                state.lanes[column] = Some(GraphLane { oid: parent, color });
+                for edge in edges.iter_mut().filter(|e| e.kind == GraphEdgeKind::Pass && e.from == lane) {
+                    edge.to = column;
+                }
+                edges.push(GraphEdge { from: column, to: column, color, kind: GraphEdgeKind::Parent });
+                continue;
+            }
+            let color = state.lanes[lane].as_ref().map_or(color, |l| l.color);
+            edges.push(GraphEdge { from: column, to: lane, color, kind: GraphEdgeKind::Parent });
+            continue;
+        }
+        let (lane, lane_color) = if state.lanes[column].is_none() {
+            (column, color)
+        } else {
+            (free_column(&mut state.lanes), next_color(state))
+        };
+        state.lanes[lane] = Some(GraphLane { oid: parent, color: lane_color });
+        edges.push(GraphEdge { from: column, to: lane, color: lane_color, kind: GraphEdgeKind::Parent });
+    }
+
+    while state.lanes.last().is_some_and(|lane| lane.is_none()) {
+        state.lanes.pop();
+    }
+    (column, color, edges)
+}
+
+fn lanes_heading_for<'a>(lanes: &'a [Option<GraphLane>], oid: &'a str) -> impl Iterator<Item = usize> + 'a {
+    lanes
+        .iter()
+        .enumerate()
+        .filter(move |(_, lane)| lane.as_ref().is_some_and(|lane| lane.oid == oid))
+        .map(|(i, _)| i)
+}
+
+fn free_column(lanes: &mut Vec<Option<GraphLane>>) -> usize {
+    match lanes.iter().position(|lane| lane.is_none()) {
+        Some(column) => column,
+        None => {
+            lanes.push(None);
+            lanes.len() - 1
+        }
+    }
+}
+
+fn next_color(state: &mut GraphState) -> usize {
+    state.next_color += 1;
+    state.next_color - 1
+}
+
+#[cfg(test)]
+#[path = "graph_tests.rs"]
+mod graph_tests;
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::graph::get_commit_graph_impl;
+    use crate::commands::test_support::{commit_at, init_repo};
+    use crate::domain::{CommitGraphPage, GraphEdge, GraphEdgeKind, GraphOptions};
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    /// root, then "main" on main and "topic" on topic, merged into main
+    fn merged_branch_repo() -> (TempDir, Repository) {
+        let (dir, repo) = init_repo();
+        let root = commit_at(&repo, Some("refs/heads/main"), &[], 1, "root");
+        let main = commit_at(&repo, Some("refs/heads/main"), &[root], 2, "main");
+        let topic = commit_at(&repo, Some("refs/heads/topic"), &[root], 3, "topic");
+        commit_at(&repo, Some("refs/heads/main"), &[main, topic], 4, "merge");
+        repo.set_head("refs/heads/main").unwrap();
+        (dir, repo)
+    }
+
+    fn edge(kind: GraphEdgeKind, from: usize, to: usize, color: usize) -> GraphEdge {
+        GraphEdge { from, to, color, kind }
+    }
+
+    fn layout(page: &CommitGraphPage) -> Vec<(&str, usize, usize, Vec<GraphEdge>)> {
+        page.rows
+            .iter()
+            .map(|row| (row.commit.message.as_str(), row.column, row.color, row.edges.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_linear_history_stays_in_one_column() {
+        let (_dir, repo) = init_repo();
+        let a = commit_at(&repo, Some("HEAD"), &[], 1, "a");
+        let b = commit_at(&repo, Some("HEAD"), &[a], 2, "b");
+        commit_at(&repo, Some("HEAD"), &[b], 3, "c");
+
+        let page = get_commit_graph_impl(&repo, 0, 10, &GraphOptions::default(), None).unwrap();
+
+        use GraphEdgeKind::*;
+        assert_eq!(
+            layout(&page),
+            vec![
+                ("c", 0, 0, vec![edge(Parent, 0, 0, 0)]),
+                ("b", 0, 0, vec![edge(Child, 0, 0, 0), edge(Parent, 0, 0, 0)]),
+                ("a", 0, 0, vec![edge(Child, 0, 0, 0)]),
+            ]
+        );
+        assert!(!page.has_more);
+        assert!(page.state.lanes.is_empty());
+    }
+
+    #[test]
+    fn test_merge_and_fork_connectors() {
+        let (_dir, repo) = merged_branch_repo();
+
+        let page = get_commit_graph_impl(&repo, 0, 10, &GraphOptions::default(), None).unwrap();
+
+        use GraphEdgeKind::*;
+        assert_eq!(
+            layout(&page),
+            vec![
+                ("merge", 0, 0, vec![edge(Parent, 0, 0, 0), edge(Parent, 0, 1, 1)]),
+                ("topic", 1, 1, vec![edge(Pass, 0, 0, 0), edge(Child, 1, 1, 1), edge(Parent, 1, 1, 1)]),
+                // root already has topic's lane, which is pulled into main's column
+                ("main", 0, 0, vec![edge(Child, 0, 0, 0), edge(Pass, 1, 0, 1), edge(Parent, 0, 0, 0)]),
+                ("root", 0, 0, vec![edge(Child, 0, 0, 0)]),
+            ]
+        );
+        assert_eq!(page.rows[1].commit.refs, vec!["topic"]);
+    }
+
+    #[test]
+    fn test_pages_continue_the_layout() {
+        let (_dir, repo) = merged_branch_repo();
+        let options = GraphOptions::default();
+        let full = get_commit_graph_impl(&repo, 0, 10, &options, None).unwrap();
+
+        let first = get_commit_graph_impl(&repo, 0, 2, &options, None).unwrap();
+        assert!(first.has_more);
+        assert_eq!(first.state.rows, 2);
+        let second = get_commit_graph_impl(&repo, 2, 2, &options, Some(first.state.clone())).unwrap();
+        assert!(!second.has_more);
+
+        let mut paged = layout(&first);
+        paged.extend(layout(&second));
+        assert_eq!(paged, layout(&full));
+
+        // Without the state, or with one from another page, the skipped rows
+        // are laid out again
+        let fresh = get_commit_graph_impl(&repo, 2, 2, &options, None).unwrap();
+        assert_eq!(layout(&fresh), layout(&second));
+        let stale = get_commit_graph_impl(&repo, 2, 2, &options, Some(second.state.clone())).unwrap();
+        assert_eq!(layout(&stale), layout(&second));
+    }
+
+    #[test]
+    fn test_first_parent_hides_merged_branches() {
+        let (_dir, repo) = merged_branch_repo();
+
+        let options = GraphOptions { first_parent: true, ..Default::default() };
+        let page = get_commit_graph_impl(&repo, 0, 10, &options, None).unwrap();
+
+        // topic is still shown as a branch of its own, but not merged in
+        let rows: Vec<(&str, usize)> = page.rows.iter().map(|r| (r.commit.message.as_str(), r.column)).collect();
+        assert_eq!(rows, vec![("merge", 0), ("topic", 1), ("main", 0), ("root", 0)]);
+        assert_eq!(page.rows[0].commit.parents, vec![page.rows[2].commit.id.clone()]);
+        assert_eq!(page.rows[0].edges, vec![edge(GraphEdgeKind::Parent, 0, 0, 0)]);
+    }
+
+    #[test]
+    fn test_simplify_by_decoration_connects_decorated_commits() {
+        let (_dir, repo) = merged_branch_repo();
+        let root = repo.revparse_single("topic~1").unwrap().id();
+        repo.tag_lightweight("v1", &repo.find_object(root, None).unwrap(), false).unwrap();
+
+        let options = GraphOptions { simplify_by_decoration: true, ..Default::default() };
+        let page = get_commit_graph_impl(&repo, 0, 10, &options, None).unwrap();
+
+        let rows: Vec<(&str, Vec<String>)> =
+            page.rows.iter().map(|r| (r.commit.message.as_str(), r.commit.refs.clone())).collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("merge", vec!["main".to_string()]),
+                ("topic", vec!["topic".to_string()]),
+                ("root", vec!["v1".to_string()]),
+            ]
+        );
+        // The undecorated "main" commit is skipped over to the tagged root
+        let topic = repo.revparse_single("topic").unwrap().id().to_string();
+        assert_eq!(page.rows[0].commit.parents, vec![root.to_string(), topic]);
+    }
+
+    #[test]
+    fn test_decorated_pages_resume_after_hidden_commits() {
+        let (_dir, repo) = merged_branch_repo();
+        let root = repo.revparse_single("topic~1").unwrap().id();
+        repo.tag_lightweight("v1", &repo.find_object(root, None).unwrap(), false).unwrap();
+        let options = GraphOptions { simplify_by_decoration: true, ..Default::default() };
+        let full = get_commit_graph_impl(&repo, 0, 10, &options, None).unwrap();
+
+        let first = get_commit_graph_impl(&repo, 0, 2, &options, None).unwrap();
+        // The hidden "main" commit is passed over, the page stops at the root
+        assert_eq!(first.state.position, 3);
+        let second = get_commit_graph_impl(&repo, 2, 2, &options, Some(first.state.clone())).unwrap();
+        assert_eq!(second.state.position, 4);
+
+        let mut paged = layout(&first);
+        paged.extend(layout(&second));
+        assert_eq!(paged, layout(&full));
+    }
+
+    #[test]
+    fn test_all_refs_includes_remote_branches() {
+        let (_dir, repo) = merged_branch_repo();
+        let main = repo.revparse_single("main").unwrap().id();
+        commit_at(&repo, Some("refs/remotes/origin/feature"), &[main], 5, "remote only");
+
+        let page = get_commit_graph_impl(&repo, 0, 10, &GraphOptions::default(), None).unwrap();
+        assert_eq!(page.rows.len(), 4);
+
+        let options = GraphOptions { all_refs: true, ..Default::default() };
+        let page = get_commit_graph_impl(&repo, 0, 10, &options, None).unwrap();
+        assert_eq!(page.rows.len(), 5);
+        assert_eq!(page.rows[0].commit.message, "remote only");
+        assert_eq!(page.rows[0].commit.refs, vec!["origin/feature"]);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::domain::{
+    CommitDetails, CommitDetailsOptions, CommitFileChange, CommitIdentity, CommitInfo, CommitTrailer, FileHistoryEntry,
+    FileHistoryOptions, FileStatus,
+};
+use crate::error::Result;
+use super::diff::{find_similar, rename_diff_options};
+use super::gpg::verify_commit_signature;
+use git2::{Commit, Delta, Oid, Patch, Repository, Tree};
+use std::path::Path;
+
+/// Get the identities, message, trailers and signature of a commit and the
+/// files it changed against one of its parents
+#[tauri::command]
+pub async fn get_commit_details(
+    path: String,
+    oid: String,
+    options: Option<CommitDetailsOptions>,
+) -> std::result::Result<CommitDetails, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_commit_details_impl(&repo, &oid, &options.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+pub(crate) fn get_commit_details_impl(repo: &Repository, oid: &str, options: &CommitDetailsOptions) -> Result<CommitDetails> {
+    let commit = repo.find_commit(Oid::from_str(oid)?)?;
+    let message = String::from_utf8_lossy(commit.message_bytes()).to_string();
+    let trailers = git2::message_trailers_strs(&message)?
+        .iter()
+        .map(|(key, value)| CommitTrailer { key: key.to_string(), value: value.to_string() })
+        .collect();
+
+    // Unsigned commits have no gpgsig header to extract
+    let signature = match repo.extract_signature(&commit.id(), None) {
+        Ok((signature, signed_data)) => Some(verify_commit_signature(
+            &repo.config()?,
+            &String::from_utf8_lossy(&signature),
+            &signed_data,
+            commit.committer().email().unwrap_or(""),
+        )),
+        Err(_) => None,
+    };
+
+    let parent_tree = if options.parent_index == 0 && commit.parent_count() == 0 {
+        None
+    } else {
+        Some(commit.parent(options.parent_index)?.tree()?)
+    };
+    let mut opts = rename_diff_options(&options.renames);
+    let mut diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), Some(&mut opts))?;
+    find_similar(&mut diff, &options.renames)?;
+
+    let mut files = Vec::new();
+    for index in 0..diff.deltas().len() {
+        let patch = Patch::from_diff(&diff, index)?;
+        let Some(delta) = diff.get_delta(index) else {
+            continue;
+        };
+        let (insertions, deletions) = match &patch {
+            Some(patch) => {
+                let (_, insertions, deletions) = patch.line_stats()?;
+                (insertions, deletions)
+            }
+            None => (0, 0),
+        };
+        let path_of = |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+        let status = delta_status(delta.status());
+        files.push(CommitFileChange {
+            path: path_of(delta.new_file()),
+            old_path: matches!(status, FileStatus::Renamed | FileStatus::Copied).then(|| path_of(delta.old_file())),
+            status,
+            insertions,
+            deletions,
+            is_binary: delta.flags().is_binary(),
+        });
+    }
+
+    let (author, committer) = (identity(&commit.author()), identity(&commit.committer()));
+    Ok(CommitDetails {
+        id: commit.id().to_string(),
+        short_id: format!("{:.7}", commit.id()),
+        summary: commit.summary().unwrap_or("").to_string(),
+        message,
+        trailers,
+        author,
+        committer,
+        parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+        signature,
+        insertions: files.iter().map(|f| f.insertions).sum(),
+        deletions: files.iter().map(|f| f.deletions).sum(),
+        files,
+    })
+}
+
+fn identity(signature: &git2::Signature) -> CommitIdentity {
+    CommitIdentity {
+        name: signature.name().unwrap_or("Unknown").to_string(),
+        email: signature.email().unwrap_or("").to_string(),
+        timestamp: signature.when().seconds(),
+        offset_minutes: signature.when().offset_minutes(),
+    }
+}
+
+fn delta_status(delta: Delta) -> FileStatus {
+    match delta {
+        Delta::Added => FileStatus::Added,
+        Delta::Deleted => FileStatus::Deleted,
+        Delta::Renamed => FileStatus::Renamed,
+        Delta::Copied => FileStatus::Copied,
+        Delta::Modified | Delta::Typechange => FileStatus::Modified,
+        Delta::Conflicted => FileStatus::Unmerged,
+        _ => FileStatus::Unknown,
+    }
+}
+
+/// List the commits that changed a file, newest first. With `follow` the
+/// walk continues under the old path once it reaches the commit that
+/// renamed or copied the file, like `git log --follow`
+#[tauri::command]
+pub async fn get_file_history(
+    path: String,
+    file_path: String,
+    options: Option<FileHistoryOptions>,
+) -> std::result::Result<Vec<FileHistoryEntry>, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_file_history_impl(&repo, &file_path, &options.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+pub(crate) fn get_file_history_impl(
+    repo: &Repository,
+    file_path: &str,
+    options: &FileHistoryOptions,
+) -> Result<Vec<FileHistoryEntry>> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    revwalk.push_head()?;
+
+    let mut current = file_path.to_string();
+    let mut entries = Vec::new();
+    let mut skipped = 0;
+
+    for oid in revwalk {
+        if options.limit.is_some_and(|limit| entries.len() >= limit) {
+            break;
+        }
+        let commit = repo.find_commit(oid?)?;
+        let Some((status, old_path)) = file_change(repo, &commit, &current, options)? else {
+            continue;
+        };
+
+        let path = current.clone();
+        if options.follow && matches!(status, FileStatus::Renamed | FileStatus::Copied) {
+            if let Some(old_path) = &old_path {
+                current = old_path.clone();
+            }
+        }
+
+        if skipped < options.skip {
+            skipped += 1;
+            continue;
+        }
+        entries.push(FileHistoryEntry { commit: commit_info(&commit), path, status, old_path });
+    }
+
+    Ok(entries)
+}
+
+fn blob_at(tree: &Tree, path: &str) -> Option<git2::Oid> {
+    tree.get_path(Path::new(path)).ok().map(|entry| entry.id())
+}
+
+/// How `commit` changed `path` against its first parent, or None if it did
+/// not. A merge that took the file unchanged from one of its parents is
+/// skipped, as in git's default history simplification
+fn file_change(
+    repo: &Repository,
+    commit: &Commit,
+    path: &str,
+    options: &FileHistoryOptions,
+) -> Result<Option<(FileStatus, Option<String>)>> {
+    let tree = commit.tree()?;
+    let after = blob_at(&tree, path);
+
+    let parents: Vec<Commit> = commit.parents().collect();
+    if parents.len() > 1 {
+        for parent in &parents {
+            if blob_at(&parent.tree()?, path) == after {
+                return Ok(None);
+            }
+        }
+    }
+    let parent_tree = match parents.first() {
+        Some(parent) => Some(parent.tree()?),
+        None => None,
+    };
+    let before = parent_tree.as_ref().and_then(|t| blob_at(t, path));
+
+    Ok(match (before, after) {
+        (before, after) if before == after => None,
+        (Some(_), None) => Some((FileStatus::Deleted, None)),
+        (Some(_), Some(_)) => Some((FileStatus::Modified, None)),
+        (None, _) => {
+            // Only an added file can be the target of a rename or copy
+            let Some(parent_tree) = parent_tree else {
+                return Ok(Some((FileStatus::Added, None)));
+            };
+            let mut opts = rename_diff_options(&options.renames);
+            let mut diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut opts))?;
+            find_similar(&mut diff, &options.renames)?;
+
+            let source = diff.deltas().find_map(|delta| {
+                let status = match delta.status() {
+                    Delta::Renamed => FileStatus::Renamed,
+                    Delta::Copied => FileStatus::Copied,
+                    _ => return None,
+                };
+                (delta.new_file().path() == Some(Path::new(path)))
+                    .then(|| delta.old_file().path().map(|p| (status, p.to_string_lossy().to_string())))
+                    .flatten()
+            });
+            Some(match source {
+                Some((status, old_path)) => (status, Some(old_path)),
+                None => (FileStatus::Added, None),
+            })
+        }
+    })
+}
+
+pub(crate) fn commit_info(commit: &Commit) -> CommitInfo {
+    CommitInfo {
+        id: commit.id().to_string(),
+        short_id: format!("{:.7}", commit.id()),
+        message: commit.message().unwrap_or("").to_string(),
+        author: commit.author().name().unwrap_or("Unknown").to_string(),
+        timestamp: commit.time().seconds(),
+        parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+        refs: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+#[path = "history_tests.rs"]
+mod history_tests;
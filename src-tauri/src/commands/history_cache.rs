@@ -0,0 +1,298 @@
+use crate::domain::CommitInfo;
+use crate::error::{AppError, Result};
+use git2::{Oid, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+const CACHE_DIR: &str = ".gat";
+const INDEX_FILE: &str = "history-index.json";
+const INDEX_VERSION: u32 = 1;
+/// Repositories whose history order is kept in memory
+const MAX_CACHED_WALKS: usize = 4;
+
+/// Commit metadata kept in the on-disk index, enough to list and search
+/// history without loading the commits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct IndexedCommit {
+    pub id: String,
+    pub parents: Vec<String>,
+    pub author: String,
+    pub timestamp: i64,
+    pub message: String,
+}
+
+impl IndexedCommit {
+    fn new(commit: &git2::Commit) -> Self {
+        Self {
+            id: commit.id().to_string(),
+            parents: commit.parent_ids().map(|id| id.to_string()).collect(),
+            author: commit.author().name().unwrap_or("Unknown").to_string(),
+            timestamp: commit.time().seconds(),
+            message: commit.message().unwrap_or("").to_string(),
+        }
+    }
+
+    pub(crate) fn to_info(&self) -> CommitInfo {
+        CommitInfo {
+            id: self.id.clone(),
+            short_id: self.id.chars().take(7).collect(),
+            message: self.message.clone(),
+            author: self.author.clone(),
+            timestamp: self.timestamp,
+            parents: self.parents.clone(),
+            refs: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct HistoryIndex {
+    version: u32,
+    /// Ref name -> commit id the index was built from
+    tips: Vec<(String, String)>,
+    commits: Vec<IndexedCommit>,
+}
+
+/// Which refs a walk starts from and which parents it follows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct WalkScope {
+    /// Remote branches and tags as well as HEAD and the local branches
+    pub all_refs: bool,
+    /// Only follow the first parent of merges
+    pub first_parent: bool,
+}
+
+/// Commits reachable from the refs of a `WalkScope`, in history order
+pub(crate) struct CommitOrder {
+    scope: WalkScope,
+    tips: Vec<(String, Oid)>,
+    pub(crate) oids: Vec<Oid>,
+    positions: HashMap<Oid, usize>,
+    /// Metadata for every commit in `oids`, when served from the index
+    pub(crate) commits: Option<Vec<IndexedCommit>>,
+}
+
+impl CommitOrder {
+    fn new(scope: WalkScope, tips: Vec<(String, Oid)>, oids: Vec<Oid>, commits: Option<Vec<IndexedCommit>>) -> Self {
+        let positions = oids.iter().enumerate().map(|(i, oid)| (*oid, i)).collect();
+        Self { scope, tips, oids, positions, commits }
+    }
+
+    /// Position just after the commit `cursor`, or the start without one
+    pub(crate) fn resume(&self, cursor: Option<&str>) -> Result<usize> {
+        let Some(cursor) = cursor else {
+            return Ok(0);
+        };
+        Oid::from_str(cursor)
+            .ok()
+            .and_then(|oid| self.positions.get(&oid))
+            .map(|i| i + 1)
+            .ok_or_else(|| AppError::InvalidInput(format!("Commit {} is not in the history", cursor)))
+    }
+
+    /// Commit info for the commit at `index`, from the index when loaded
+    pub(crate) fn info(&self, repo: &Repository, index: usize) -> Result<CommitInfo> {
+        match &self.commits {
+            Some(commits) => Ok(commits[index].to_info()),
+            None => Ok(super::history::commit_info(&repo.find_commit(self.oids[index])?)),
+        }
+    }
+}
+
+/// Git directory -> history order, most recently used first
+type Walks = Mutex<Vec<(PathBuf, Arc<CommitOrder>)>>;
+
+fn walks() -> &'static Walks {
+    static WALKS: OnceLock<Walks> = OnceLock::new();
+    WALKS.get_or_init(Default::default)
+}
+
+/// HEAD and the branch tips history starts from, sorted by name. Any
+/// change to them gives a different history order
+fn history_tips(repo: &Repository, scope: WalkScope) -> Vec<(String, Oid)> {
+    let mut tips = Vec::new();
+    if let Some(head) = repo.head().ok().and_then(|head| head.target()) {
+        tips.push(("HEAD".to_string(), head));
+    }
+    if let Ok(branches) = repo.branches(Some(git2::BranchType::Local)) {
+        for (branch, _) in branches.flatten() {
+            if let (Some(name), Some(target)) = (branch.get().name(), branch.get().target()) {
+                tips.push((name.to_string(), target));
+            }
+        }
+    }
+    if scope.all_refs {
+        for glob in ["refs/remotes/*", "refs/tags/*"] {
+            let Ok(references) = repo.references_glob(glob) else {
+                continue;
+            };
+            for reference in references.flatten() {
+                // Symbolic refs such as origin/HEAD only repeat a branch
+                if reference.target().is_none() {
+                    continue;
+                }
+                // Tags of trees or blobs have no history
+                if let (Some(name), Ok(commit)) = (reference.name(), reference.peel_to_commit()) {
+                    tips.push((name.to_string(), commit.id()));
+                }
+            }
+        }
+    }
+    tips.sort();
+    tips
+}
+
+/// The history order for HEAD and the local branches. A walk is reused
+/// while the refs it started from are unchanged; with `disk_cache` it is
+/// read from, and kept up to date in, the index under `.gat/`
+pub(crate) fn commit_order(repo: &Repository, disk_cache: bool) -> Result<Arc<CommitOrder>> {
+    cached_order(repo, WalkScope::default(), disk_cache)
+}
+
+/// The history order for `scope`, reused like `commit_order`'s but only
+/// kept in memory
+pub(crate) fn scoped_order(repo: &Repository, scope: WalkScope) -> Result<Arc<CommitOrder>> {
+    cached_order(repo, scope, false)
+}
+
+fn cached_order(repo: &Repository, scope: WalkScope, disk_cache: bool) -> Result<Arc<CommitOrder>> {
+    let key = repo.path().to_path_buf();
+    let tips = history_tips(repo, scope);
+    if let Ok(walks) = walks().lock() {
+        let cached = walks.iter().find(|(path, order)| {
+            *path == key && order.scope == scope && order.tips == tips && (!disk_cache || order.commits.is_some())
+        });
+        if let Some((_, order)) = cached {
+            return Ok(order.clone());
+        }
+    }
+
+    let order = Arc::new(if disk_cache && scope == WalkScope::default() {
+        indexed_order(repo, tips)?
+    } else {
+        let oids = walk(repo, &tips, &[], scope.first_parent)?;
+        CommitOrder::new(scope, tips, oids, None)
+    });
+    if let Ok(mut walks) = walks().lock() {
+        walks.retain(|(path, cached)| *path != key || cached.scope != scope);
+        walks.insert(0, (key, order.clone()));
+        walks.truncate(MAX_CACHED_WALKS);
+    }
+    Ok(order)
+}
+
+fn walk(repo: &Repository, tips: &[(String, Oid)], hide: &[Oid], first_parent: bool) -> Result<Vec<Oid>> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+    if first_parent {
+        revwalk.simplify_first_parent()?;
+    }
+    for (_, oid) in tips {
+        revwalk.push(*oid)?;
+    }
+    for oid in hide {
+        revwalk.hide(*oid)?;
+    }
+    Ok(revwalk.collect::<std::result::Result<Vec<_>, _>>()?)
+}
+
+fn cache_dir(repo: &Repository) -> PathBuf {
+    repo.workdir().unwrap_or_else(|| repo.path()).join(CACHE_DIR)
+}
+
+/// Load the index and bring it up to date. When every ref the index was
+/// built from is still reachable only the new commits are walked and put
+/// in front; otherwise the index is rebuilt
+fn indexed_order(repo: &Repository, tips: Vec<(String, Oid)>) -> Result<CommitOrder> {
+    let path = cache_dir(repo).join(INDEX_FILE);
+    let index = std::fs::read(&path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<HistoryIndex>(&data).ok())
+        .filter(|index| index.version == INDEX_VERSION);
+    let old_tips: Option<Vec<Oid>> = index
+        .as_ref()
+        .and_then(|index| index.tips.iter().map(|(_, id)| Oid::from_str(id).ok()).collect());
+
+    let tip_ids: Vec<(String, String)> = tips.iter().map(|(name, oid)| (name.clone(), oid.to_string())).collect();
+    let commits = match (index, old_tips) {
+        (Some(index), _) if index.tips == tip_ids => index.commits,
+        (Some(index), Some(old_tips)) if old_tips.iter().all(|old| reachable(repo, &tips, *old)) => {
+            let mut commits = new_commits(repo, &walk(repo, &tips, &old_tips, false)?)?;
+            commits.extend(index.commits);
+            save_index(repo, tip_ids, commits)
+        }
+        _ => save_index(repo, tip_ids, new_commits(repo, &walk(repo, &tips, &[], false)?)?),
+    };
+
+    let oids = commits.iter().map(|c| Oid::from_str(&c.id)).collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(CommitOrder::new(WalkScope::default(), tips, oids, Some(commits)))
+}
+
+fn reachable(repo: &Repository, tips: &[(String, Oid)], oid: Oid) -> bool {
+    tips.iter().any(|(_, tip)| *tip == oid || repo.graph_descendant_of(*tip, oid).unwrap_or(false))
+}
+
+fn new_commits(repo: &Repository, oids: &[Oid]) -> Result<Vec<IndexedCommit>> {
+    oids.iter().map(|oid| Ok(IndexedCommit::new(&repo.find_commit(*oid)?))).collect()
+}
+
+/// Store the index for the next walk. The walked commits are returned either
+/// way: a read-only or full disk only costs the cache, not the history
+fn save_index(repo: &Repository, tips: Vec<(String, String)>, commits: Vec<IndexedCommit>) -> Vec<IndexedCommit> {
+    let index = HistoryIndex { version: INDEX_VERSION, tips, commits };
+    if let Err(e) = write_index(repo, &index) {
+        eprintln!("Warning: Failed to write the history index: {}", e);
+    }
+    index.commits
+}
+
+fn write_index(repo: &Repository, index: &HistoryIndex) -> Result<()> {
+    static TMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let dir = cache_dir(repo);
+    std::fs::create_dir_all(&dir)?;
+    // Keep the cache out of `git status`
+    let ignore = dir.join(".gitignore");
+    if !ignore.exists() {
+        std::fs::write(&ignore, "*\n")?;
+    }
+
+    let data = serde_json::to_vec(index).map_err(|e| AppError::InvalidInput(e.to_string()))?;
+    // Write and rename so a reader never sees half an index. The temporary
+    // name is unique so concurrent writers do not rename each other's file
+    let tmp = dir.join(format!(
+        "{}.{}-{}.tmp",
+        INDEX_FILE,
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let written = std::fs::write(&tmp, data).and_then(|()| std::fs::rename(&tmp, dir.join(INDEX_FILE)));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+    Ok(written?)
+}
+
+/// Forget the cached history order of a repository and delete its index
+#[tauri::command]
+pub async fn clear_history_cache(path: String) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    clear_history_cache_impl(&repo).map_err(|e| e.to_string())
+}
+
+pub(crate) fn clear_history_cache_impl(repo: &Repository) -> Result<()> {
+    if let Ok(mut walks) = walks().lock() {
+        walks.retain(|(path, _)| path != repo.path());
+    }
+    match std::fs::remove_file(cache_dir(repo).join(INDEX_FILE)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+#[path = "history_cache_tests.rs"]
+mod history_cache_tests;
@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::history_cache::{clear_history_cache_impl, commit_order};
+    use crate::commands::repo::get_commit_history_page_impl;
+    use crate::commands::test_support::{commit_at, init_repo};
+    use crate::domain::{CommitInfo, HistoryPageOptions};
+    use git2::{Oid, Repository};
+    use tempfile::TempDir;
+
+    /// Commit an empty tree at `time` seconds on top of HEAD
+    fn commit(repo: &Repository, time: i64, message: &str) -> Oid {
+        let parents: Vec<Oid> = repo.head().ok().and_then(|h| h.target()).into_iter().collect();
+        commit_at(repo, Some("HEAD"), &parents, time, message)
+    }
+
+    fn linear_repo(count: i64) -> (TempDir, Repository) {
+        let (dir, repo) = init_repo();
+        for i in 1..=count {
+            commit(&repo, i, &format!("commit {}", i));
+        }
+        (dir, repo)
+    }
+
+    /// Follow cursors from the start, returning the messages of each page
+    fn pages(repo: &Repository, limit: usize, options: &HistoryPageOptions) -> Vec<Vec<String>> {
+        let messages = |commits: &[CommitInfo]| commits.iter().map(|c| c.message.clone()).collect::<Vec<_>>();
+        let mut pages = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = get_commit_history_page_impl(repo, cursor.as_deref(), limit, options).unwrap();
+            pages.push(messages(&page.commits));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => return pages,
+            }
+        }
+    }
+
+    #[test]
+    fn test_cursors_page_through_history() {
+        let (_dir, repo) = linear_repo(5);
+
+        let expected = vec![
+            vec!["commit 5".to_string(), "commit 4".to_string()],
+            vec!["commit 3".to_string(), "commit 2".to_string()],
+            vec!["commit 1".to_string()],
+        ];
+        assert_eq!(pages(&repo, 2, &HistoryPageOptions::default()), expected);
+        assert_eq!(pages(&repo, 2, &HistoryPageOptions { disk_cache: true }), expected);
+
+        let page = get_commit_history_page_impl(&repo, None, 5, &HistoryPageOptions::default()).unwrap();
+        assert!(page.next_cursor.is_none());
+        let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+        assert_eq!(page.commits[0].refs, vec![branch]);
+
+        let unknown = Oid::zero().to_string();
+        assert!(get_commit_history_page_impl(&repo, Some(&unknown), 2, &HistoryPageOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_new_commits_invalidate_the_cached_walk() {
+        let (_dir, repo) = linear_repo(2);
+        let before = commit_order(&repo, false).unwrap();
+        assert_eq!(before.oids.len(), 2);
+
+        let cursor = before.oids[0].to_string();
+        let tip = commit(&repo, 3, "commit 3");
+
+        let after = commit_order(&repo, false).unwrap();
+        assert_eq!(after.oids[0], tip);
+        // Cursors from before the change still resume after their commit
+        let page = get_commit_history_page_impl(&repo, Some(&cursor), 10, &HistoryPageOptions::default()).unwrap();
+        assert_eq!(page.commits.iter().map(|c| c.message.as_str()).collect::<Vec<_>>(), vec!["commit 1"]);
+    }
+
+    #[test]
+    fn test_disk_index_is_extended_and_rebuilt() {
+        let (dir, repo) = linear_repo(3);
+        let options = HistoryPageOptions { disk_cache: true };
+        get_commit_history_page_impl(&repo, None, 1, &options).unwrap();
+
+        let index = dir.path().join(".gat").join("history-index.json");
+        assert!(index.exists());
+        let mut status_opts = git2::StatusOptions::new();
+        status_opts.include_untracked(true).include_ignored(false);
+        let status = repo.statuses(Some(&mut status_opts)).unwrap();
+        assert!(status.is_empty(), "the cache directory is ignored");
+
+        // New commits on top are put in front of the stored ones
+        commit(&repo, 4, "commit 4");
+        let order = commit_order(&repo, true).unwrap();
+        let messages: Vec<&str> = order.commits.as_ref().unwrap().iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(messages, vec!["commit 4", "commit 3", "commit 2", "commit 1"]);
+
+        // Moving the branch back drops commits, so the index starts over
+        let first = repo.revparse_single("HEAD~3").unwrap();
+        repo.reset(&first, git2::ResetType::Soft, None).unwrap();
+        let order = commit_order(&repo, true).unwrap();
+        assert_eq!(order.commits.as_ref().unwrap().len(), 1);
+
+        let stored = std::fs::read_to_string(&index).unwrap();
+        assert!(!stored.contains("commit 4"));
+
+        clear_history_cache_impl(&repo).unwrap();
+        assert!(!index.exists());
+        clear_history_cache_impl(&repo).unwrap();
+    }
+
+    #[test]
+    fn test_unwritable_index_still_returns_the_walk() {
+        let (dir, repo) = linear_repo(2);
+        // A file where the cache directory should be
+        std::fs::write(dir.path().join(".gat"), "").unwrap();
+
+        let order = commit_order(&repo, true).unwrap();
+
+        let messages: Vec<&str> = order.commits.as_ref().unwrap().iter().map(|c| c.message.as_str()).collect();
+        assert_eq!(messages, vec!["commit 2", "commit 1"]);
+    }
+
+    #[test]
+    fn test_search_uses_the_history_index() {
+        let (_dir, repo) = linear_repo(3);
+        let query = |disk_cache| crate::domain::CommitSearchQuery {
+            query: Some("COMMIT 2".to_string()),
+            author: None,
+            date_from: None,
+            date_to: None,
+            path: None,
+            limit: None,
+            disk_cache: Some(disk_cache),
+        };
+
+        for disk_cache in [false, true] {
+            let found = crate::commands::repo::search_commits_impl(&repo, query(disk_cache)).unwrap();
+            assert_eq!(found.len(), 1);
+            assert_eq!(found[0].message, "commit 2");
+        }
+    }
+}
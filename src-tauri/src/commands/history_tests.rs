@@ -0,0 +1,238 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::commit::create_commit;
+    use crate::commands::history::{get_commit_details_impl, get_file_history_impl};
+    use crate::commands::test_support::{commit_index, init_repo};
+    use crate::domain::{
+        CommitDetailsOptions, CommitTrailer, FileHistoryEntry, FileHistoryOptions, FileStatus, RenameOptions,
+        SignatureStatus,
+    };
+    use git2::{Oid, Repository, Signature, Time};
+    use tempfile::TempDir;
+
+    const CONTENT: &str = "alpha\nbeta\ngamma\ndelta\nepsilon\nzeta\neta\ntheta\n";
+
+    /// Write `files`, remove `removed` and commit the result
+    fn commit(dir: &TempDir, repo: &Repository, files: &[(&str, &str)], removed: &[&str], message: &str) -> Oid {
+        let mut index = repo.index().unwrap();
+        for (name, content) in files {
+            std::fs::write(dir.path().join(name), content).unwrap();
+            index.add_path(std::path::Path::new(name)).unwrap();
+        }
+        for name in removed {
+            std::fs::remove_file(dir.path().join(name)).unwrap();
+            index.remove_path(std::path::Path::new(name)).unwrap();
+        }
+        index.write().unwrap();
+        commit_index(repo, message)
+    }
+
+    fn summary(entries: &[FileHistoryEntry]) -> Vec<(&str, &str, FileStatus, Option<&str>)> {
+        entries
+            .iter()
+            .map(|e| (e.commit.message.as_str(), e.path.as_str(), e.status.clone(), e.old_path.as_deref()))
+            .collect()
+    }
+
+    /// a.txt is added and edited, renamed to b.txt with a small edit, then
+    /// edited again
+    fn renamed_file_repo() -> (TempDir, Repository) {
+        let (dir, repo) = init_repo();
+        commit(&dir, &repo, &[("a.txt", CONTENT), ("other.txt", "other\n")], &[], "add a");
+        commit(&dir, &repo, &[("a.txt", &CONTENT.replace("beta", "BETA"))], &[], "edit a");
+        commit(&dir, &repo, &[("other.txt", "other 2\n")], &[], "edit other");
+        commit(&dir, &repo, &[("b.txt", &CONTENT.replace("beta", "BETA").replace("eta\n", "ETA\n"))], &["a.txt"], "rename a to b");
+        commit(&dir, &repo, &[("b.txt", &CONTENT.replace("beta", "BETA").replace("zeta", "ZETA"))], &[], "edit b");
+        (dir, repo)
+    }
+
+    #[test]
+    fn test_follow_continues_across_renames() {
+        let (_dir, repo) = renamed_file_repo();
+
+        let history = get_file_history_impl(&repo, "b.txt", &FileHistoryOptions::default()).unwrap();
+
+        assert_eq!(
+            summary(&history),
+            vec![
+                ("edit b", "b.txt", FileStatus::Modified, None),
+                ("rename a to b", "b.txt", FileStatus::Renamed, Some("a.txt")),
+                ("edit a", "a.txt", FileStatus::Modified, None),
+                ("add a", "a.txt", FileStatus::Added, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_follow_history_stops_at_the_rename() {
+        let (_dir, repo) = renamed_file_repo();
+
+        let options = FileHistoryOptions { follow: false, ..Default::default() };
+        let history = get_file_history_impl(&repo, "b.txt", &options).unwrap();
+        assert_eq!(
+            summary(&history),
+            vec![
+                ("edit b", "b.txt", FileStatus::Modified, None),
+                ("rename a to b", "b.txt", FileStatus::Renamed, Some("a.txt")),
+            ]
+        );
+
+        let options = FileHistoryOptions { renames: RenameOptions { renames: false, ..Default::default() }, ..Default::default() };
+        let history = get_file_history_impl(&repo, "b.txt", &options).unwrap();
+        assert_eq!(history[1].status, FileStatus::Added);
+        assert_eq!(history.len(), 2);
+    }
+
+    #[test]
+    fn test_skip_and_limit_page_through_history() {
+        let (_dir, repo) = renamed_file_repo();
+
+        let options = FileHistoryOptions { skip: 1, limit: Some(2), ..Default::default() };
+        let history = get_file_history_impl(&repo, "b.txt", &options).unwrap();
+
+        let messages: Vec<&str> = history.iter().map(|e| e.commit.message.as_str()).collect();
+        assert_eq!(messages, vec!["rename a to b", "edit a"]);
+    }
+
+    #[test]
+    fn test_copies_are_followed_when_enabled() {
+        let (dir, repo) = init_repo();
+        commit(&dir, &repo, &[("a.txt", CONTENT)], &[], "add a");
+        commit(&dir, &repo, &[("c.txt", CONTENT)], &[], "copy a to c");
+
+        let history = get_file_history_impl(&repo, "c.txt", &FileHistoryOptions::default()).unwrap();
+        assert_eq!(summary(&history), vec![("copy a to c", "c.txt", FileStatus::Added, None)]);
+
+        let options = FileHistoryOptions { renames: RenameOptions { copies: true, ..Default::default() }, ..Default::default() };
+        let history = get_file_history_impl(&repo, "c.txt", &options).unwrap();
+        assert_eq!(
+            summary(&history),
+            vec![
+                ("copy a to c", "c.txt", FileStatus::Copied, Some("a.txt")),
+                ("add a", "a.txt", FileStatus::Added, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commit_details_lists_identities_trailers_and_files() {
+        let (dir, repo) = init_repo();
+        commit(&dir, &repo, &[("a.txt", CONTENT), ("gone.txt", "bye\n"), ("old.txt", CONTENT)], &[], "base");
+
+        std::fs::write(dir.path().join("a.txt"), CONTENT.replace("gamma", "GAMMA").replace("theta\n", "theta\nextra\n")).unwrap();
+        std::fs::write(dir.path().join("new.txt"), "hello\n").unwrap();
+        std::fs::rename(dir.path().join("old.txt"), dir.path().join("moved.txt")).unwrap();
+        std::fs::remove_file(dir.path().join("gone.txt")).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None).unwrap();
+        index.update_all(["*"], None).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let author = Signature::new("Ann Author", "ann@example.com", &Time::new(1_700_000_000, 120)).unwrap();
+        let committer = Signature::new("Cid Committer", "cid@example.com", &Time::new(1_700_000_600, -300)).unwrap();
+        let parent = repo.head().unwrap().peel_to_commit().unwrap();
+        let message = "Rework files\n\nLonger body.\n\nSigned-off-by: Ann Author <ann@example.com>\nRefs: #42\n";
+        let oid = repo.commit(Some("HEAD"), &author, &committer, message, &tree, &[&parent]).unwrap();
+
+        let details = get_commit_details_impl(&repo, &oid.to_string(), &CommitDetailsOptions::default()).unwrap();
+
+        assert_eq!(details.summary, "Rework files");
+        assert_eq!(details.message, message);
+        assert_eq!(
+            details.trailers,
+            vec![
+                CommitTrailer { key: "Signed-off-by".to_string(), value: "Ann Author <ann@example.com>".to_string() },
+                CommitTrailer { key: "Refs".to_string(), value: "#42".to_string() },
+            ]
+        );
+        assert_eq!((details.author.name.as_str(), details.author.offset_minutes), ("Ann Author", 120));
+        assert_eq!((details.committer.email.as_str(), details.committer.timestamp), ("cid@example.com", 1_700_000_600));
+        assert_eq!(details.committer.offset_minutes, -300);
+        assert!(details.signature.is_none());
+
+        let mut files: Vec<(&str, Option<&str>, FileStatus, usize, usize)> = details
+            .files
+            .iter()
+            .map(|f| (f.path.as_str(), f.old_path.as_deref(), f.status.clone(), f.insertions, f.deletions))
+            .collect();
+        files.sort_by_key(|f| f.0);
+        assert_eq!(
+            files,
+            vec![
+                ("a.txt", None, FileStatus::Modified, 2, 1),
+                ("gone.txt", None, FileStatus::Deleted, 0, 1),
+                ("moved.txt", Some("old.txt"), FileStatus::Renamed, 0, 0),
+                ("new.txt", None, FileStatus::Added, 1, 0),
+            ]
+        );
+        assert_eq!((details.insertions, details.deletions), (3, 2));
+    }
+
+    #[test]
+    fn test_commit_details_of_merge_against_either_parent() {
+        let (dir, repo) = init_repo();
+        let base = commit(&dir, &repo, &[("a.txt", "a\n")], &[], "base");
+        let main_ref = repo.head().unwrap().name().unwrap().to_string();
+        repo.branch("topic", &repo.find_commit(base).unwrap(), false).unwrap();
+        repo.set_head("refs/heads/topic").unwrap();
+        let topic = commit(&dir, &repo, &[("topic.txt", "t\n")], &[], "topic change");
+        repo.set_head(&main_ref).unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        let main = commit(&dir, &repo, &[("main.txt", "m\n")], &[], "main change");
+
+        let mut index = repo.merge_commits(&repo.find_commit(main).unwrap(), &repo.find_commit(topic).unwrap(), None).unwrap();
+        let tree = repo.find_tree(index.write_tree_to(&repo).unwrap()).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let parents = [&repo.find_commit(main).unwrap(), &repo.find_commit(topic).unwrap()];
+        let merge = repo.commit(None, &sig, &sig, "merge topic", &tree, &parents).unwrap().to_string();
+
+        let paths = |parent_index| {
+            let options = CommitDetailsOptions { parent_index, ..Default::default() };
+            let details = get_commit_details_impl(&repo, &merge, &options).unwrap();
+            details.files.into_iter().map(|f| f.path).collect::<Vec<_>>()
+        };
+        assert_eq!(paths(0), vec!["topic.txt"]);
+        assert_eq!(paths(1), vec!["main.txt"]);
+
+        let options = CommitDetailsOptions { parent_index: 2, ..Default::default() };
+        assert!(get_commit_details_impl(&repo, &merge, &options).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_commit_details_verify_signature_with_configured_program() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (dir, repo) = init_repo();
+        // Fake gpg: signs anything, and reports a good signature from a key
+        // without ownertrust when asked to verify
+        let program = dir.path().join("fake-gpg.sh");
+        std::fs::write(
+            &program,
+            "#!/bin/sh\ncat > /dev/null\nif [ \"$2\" = --verify ]; then\n\
+             echo '[GNUPG:] GOODSIG 0123456789ABCDEF Test User <test@example.com>'\n\
+             echo '[GNUPG:] TRUST_UNDEFINED 0 pgp'\nexit 0\nfi\n\
+             echo '-----BEGIN PGP SIGNATURE-----'\necho 'fake'\necho '-----END PGP SIGNATURE-----'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&program, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_bool("commit.gpgsign", true).unwrap();
+        config.set_str("user.signingkey", "0123456789ABCDEF").unwrap();
+        config.set_str("gpg.program", program.to_str().unwrap()).unwrap();
+
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let oid = create_commit(&repo, Some("HEAD"), &sig, &sig, "signed", &tree, &[]).unwrap();
+
+        let signature = get_commit_details_impl(&repo, &oid.to_string(), &CommitDetailsOptions::default())
+            .unwrap()
+            .signature
+            .unwrap();
+
+        assert_eq!(signature.format, "openpgp");
+        assert_eq!(signature.status, SignatureStatus::Untrusted);
+        assert_eq!(signature.key.as_deref(), Some("0123456789ABCDEF"));
+        assert_eq!(signature.signer.as_deref(), Some("Test User <test@example.com>"));
+    }
+}
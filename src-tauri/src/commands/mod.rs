@@ -1,4 +1,15 @@
 pub mod repo;
+pub mod conflict;
+pub mod diff;
+pub mod history;
+pub mod graph;
+pub mod history_cache;
+pub mod operation;
+pub mod rebase;
+pub mod reflog;
+pub mod sequencer;
+pub mod bisect;
+pub mod worktree;
 pub mod commit;
 pub mod stash;
 pub mod clone;
@@ -8,13 +19,30 @@ pub mod extensions;
 pub mod logging;
 pub mod credentials;
 pub mod ssh;
+pub mod auth;
+pub mod progress;
 pub mod gpg;
 
+#[cfg(test)]
+pub(crate) mod test_support;
+
 pub use repo::*;
+pub use diff::{get_commit_file_diff, get_file_diff};
+pub use history::{get_commit_details, get_file_history};
+pub use graph::get_commit_graph;
+pub use history_cache::clear_history_cache;
+pub use conflict::{get_conflict_blob, merge_conflict_file, resolve_conflict_regions};
+pub use operation::get_repository_operation_state;
+pub use rebase::{get_rebase_state, start_interactive_rebase, continue_rebase, skip_rebase, abort_rebase, amend_rebase_commit};
+pub use reflog::{get_reflog_refs, get_reflog, restore_from_reflog};
+pub use sequencer::{cherry_pick_commits, revert_commits, continue_cherry_pick, continue_revert, skip_cherry_pick, skip_revert, abort_cherry_pick, abort_revert};
+pub use bisect::{start_bisect, bisect_mark, get_bisect_state, get_bisect_log, reset_bisect, run_bisect};
+pub use worktree::{list_worktrees, add_worktree, lock_worktree, unlock_worktree, prune_worktrees, remove_worktree};
 pub use commit::{stage_files, unstage_files, stage_all, unstage_all, discard_files, commit, revoke_latest_commit, batch_commit, generate_commit_message, review_code, apply_patch};
 pub use stash::{get_stash_list, stash_save, stash_apply, stash_pop, stash_drop};
 pub use clone::clone_repository;
-pub use provider::{fetch_pr_list, fetch_issue_list, create_pr, create_issue, fetch_commit_status, fetch_job_logs};
+pub use progress::cancel_operation;
+pub use provider::{fetch_pr_list, fetch_issue_list, create_pr, create_issue, fetch_commit_status, fetch_job_logs, fetch_pr_details, fetch_pr_files, fetch_pr_commits, fetch_pr_comments, create_pr_review, reply_to_pr_thread, resolve_pr_thread, set_provider_host};
 pub use editor::open_in_external_editor;
 
 // Export security-related commands when needed
@@ -0,0 +1,177 @@
+use crate::domain::{OperationAction, OperationState, OperationStep, RepositoryOperation};
+use crate::error::Result;
+use git2::{Repository, RepositoryState};
+use std::path::Path;
+
+fn operation_of(state: RepositoryState) -> RepositoryOperation {
+    match state {
+        RepositoryState::Clean => RepositoryOperation::None,
+        RepositoryState::Merge => RepositoryOperation::Merge,
+        RepositoryState::Revert => RepositoryOperation::Revert,
+        RepositoryState::RevertSequence => RepositoryOperation::RevertSequence,
+        RepositoryState::CherryPick => RepositoryOperation::CherryPick,
+        RepositoryState::CherryPickSequence => RepositoryOperation::CherryPickSequence,
+        RepositoryState::Bisect => RepositoryOperation::Bisect,
+        RepositoryState::Rebase => RepositoryOperation::Rebase,
+        RepositoryState::RebaseInteractive => RepositoryOperation::RebaseInteractive,
+        RepositoryState::RebaseMerge => RepositoryOperation::RebaseMerge,
+        RepositoryState::ApplyMailbox => RepositoryOperation::ApplyMailbox,
+        RepositoryState::ApplyMailboxOrRebase => RepositoryOperation::ApplyMailboxOrRebase,
+    }
+}
+
+fn read_file(dir: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(name)).ok()
+}
+
+/// First line of a file such as MERGE_HEAD or rebase-merge/onto
+fn read_first_line(dir: &Path, name: &str) -> Option<String> {
+    read_file(dir, name)?.lines().next().map(|l| l.trim().to_string()).filter(|l| !l.is_empty())
+}
+
+/// Parse a `git-rebase-todo` or `done` file, skipping comments and blank lines
+pub(crate) fn parse_todo(content: &str) -> Vec<OperationStep> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let mut parts = line.splitn(3, ' ');
+            let command = parts.next().unwrap_or_default().to_string();
+            let rest: Vec<&str> = parts.collect();
+            // exec/label/reset/break lines carry no commit
+            match command.as_str() {
+                "pick" | "p" | "reword" | "r" | "edit" | "e" | "squash" | "s" | "fixup" | "f" | "drop" | "d" => {
+                    OperationStep {
+                        command,
+                        commit: rest.first().map(|c| c.to_string()),
+                        summary: rest.get(1).unwrap_or(&"").to_string(),
+                    }
+                }
+                _ => OperationStep { command, commit: None, summary: rest.join(" ") },
+            }
+        })
+        .collect()
+}
+
+/// Read the mailbox-style patches `git am` and the apply backend of
+/// `git rebase` keep in `rebase-apply/0001`, `0002`, ...
+fn read_apply_steps(dir: &Path) -> (Vec<OperationStep>, Vec<OperationStep>) {
+    let next: usize = read_first_line(dir, "next").and_then(|n| n.parse().ok()).unwrap_or(1);
+    let last: usize = read_first_line(dir, "last").and_then(|n| n.parse().ok()).unwrap_or(0);
+
+    let step = |n: usize| {
+        let patch = read_file(dir, &format!("{:04}", n)).unwrap_or_default();
+        // "From <sha> Mon Sep 17 00:00:00 2001"
+        let commit = patch
+            .lines()
+            .next()
+            .and_then(|l| l.strip_prefix("From "))
+            .and_then(|l| l.split(' ').next())
+            .map(|s| s.to_string());
+        let summary = patch
+            .lines()
+            .find_map(|l| l.strip_prefix("Subject: "))
+            .map(|s| match s.strip_prefix("[PATCH") {
+                Some(rest) => rest.split_once("] ").map(|(_, s)| s).unwrap_or(rest),
+                None => s,
+            })
+            .unwrap_or_default()
+            .to_string();
+        OperationStep { command: "apply".to_string(), commit, summary }
+    };
+
+    let done = (1..next.min(last + 1)).map(step).collect();
+    let todo = (next..=last).map(step).collect();
+    (todo, done)
+}
+
+/// Get the in-progress operation (merge, cherry-pick, revert, rebase, am,
+/// bisect), its commits, message, todo lists and valid follow-up actions
+#[tauri::command]
+pub async fn get_repository_operation_state(path: String) -> std::result::Result<OperationState, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_repository_operation_state_impl(&repo).map_err(|e| e.to_string())
+}
+
+pub(crate) fn get_repository_operation_state_impl(repo: &Repository) -> Result<OperationState> {
+    let git_dir = repo.path();
+    let operation = match operation_of(repo.state()) {
+        // A cherry-pick or revert stopped by an error rather than a conflict
+        // has no CHERRY_PICK_HEAD or REVERT_HEAD, only its todo list
+        RepositoryOperation::None => match read_first_line(&git_dir.join("sequencer"), "todo") {
+            Some(line) if line.starts_with("revert ") => RepositoryOperation::RevertSequence,
+            Some(_) => RepositoryOperation::CherryPickSequence,
+            // `merge --squash` leaves no MERGE_HEAD, only SQUASH_MSG
+            None if super::repo::is_squash_pending(repo) => RepositoryOperation::Merge,
+            None => RepositoryOperation::None,
+        },
+        operation => operation,
+    };
+    let conflict_count = repo.index()?.conflicts()?.count();
+
+    let head = repo.head().ok().and_then(|h| h.target()).map(|oid| oid.to_string());
+    let mut head_name = repo.head().ok().filter(|h| h.is_branch()).and_then(|h| h.name().map(|n| n.to_string()));
+    let mut target = None;
+    let mut todo = Vec::new();
+    let mut done = Vec::new();
+
+    match operation {
+        RepositoryOperation::Merge => target = read_first_line(git_dir, "MERGE_HEAD"),
+        RepositoryOperation::CherryPick | RepositoryOperation::CherryPickSequence => {
+            target = read_first_line(git_dir, "CHERRY_PICK_HEAD");
+        }
+        RepositoryOperation::Revert | RepositoryOperation::RevertSequence => {
+            target = read_first_line(git_dir, "REVERT_HEAD");
+        }
+        RepositoryOperation::Bisect => {
+            target = repo.refname_to_id("refs/bisect/bad").ok().map(|oid| oid.to_string());
+        }
+        RepositoryOperation::RebaseInteractive | RepositoryOperation::RebaseMerge => {
+            let dir = git_dir.join("rebase-merge");
+            head_name = read_first_line(&dir, "head-name").or(head_name);
+            target = read_first_line(&dir, "onto");
+            todo = parse_todo(&read_file(&dir, "git-rebase-todo").unwrap_or_default());
+            done = parse_todo(&read_file(&dir, "done").unwrap_or_default());
+        }
+        RepositoryOperation::Rebase | RepositoryOperation::ApplyMailbox | RepositoryOperation::ApplyMailboxOrRebase => {
+            let dir = git_dir.join("rebase-apply");
+            head_name = read_first_line(&dir, "head-name").or(head_name);
+            target = read_first_line(&dir, "onto");
+            (todo, done) = read_apply_steps(&dir);
+        }
+        RepositoryOperation::None => {}
+    }
+
+    let merge_message = match operation {
+        RepositoryOperation::None | RepositoryOperation::Bisect => None,
+        _ => read_file(git_dir, "MERGE_MSG").or_else(|| read_file(git_dir, "SQUASH_MSG")),
+    };
+
+    let resolved = conflict_count == 0;
+    let actions = match operation {
+        RepositoryOperation::None => vec![],
+        RepositoryOperation::Bisect => vec![OperationAction::Abort],
+        // Concluding a merge is a commit; there is nothing to skip
+        RepositoryOperation::Merge if resolved => vec![OperationAction::Continue, OperationAction::Abort],
+        RepositoryOperation::Merge => vec![OperationAction::Abort],
+        _ if resolved => vec![OperationAction::Continue, OperationAction::Skip, OperationAction::Abort],
+        _ => vec![OperationAction::Skip, OperationAction::Abort],
+    };
+
+    Ok(OperationState {
+        operation,
+        head,
+        head_name,
+        target,
+        merge_message,
+        conflict_count,
+        todo,
+        done,
+        actions,
+    })
+}
+
+#[cfg(test)]
+#[path = "operation_tests.rs"]
+mod operation_tests;
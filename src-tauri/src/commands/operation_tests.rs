@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::operation::{get_repository_operation_state_impl, parse_todo};
+    use crate::commands::rebase::start_pick_rebase;
+    use crate::commands::test_support::{commit_file, init_repo, stage};
+    use crate::domain::{OperationAction, OperationStep, RepositoryOperation};
+    use git2::{Oid, Repository};
+    use tempfile::TempDir;
+
+    struct Fixture {
+        dir: TempDir,
+        repo: Repository,
+        main_ref: String,
+    }
+
+    impl Fixture {
+        /// Base commit on the default branch plus a `topic` branch that
+        /// changes a.txt; HEAD stays on the default branch
+        fn new() -> (Self, Oid) {
+            let (dir, repo) = init_repo();
+            let base = commit_file(&repo, "a.txt", "base\n", "base");
+            let main_ref = repo.head().unwrap().name().unwrap().to_string();
+            let f = Self { dir, repo, main_ref };
+
+            f.repo.branch("topic", &f.repo.find_commit(base).unwrap(), false).unwrap();
+            f.checkout("refs/heads/topic");
+            let topic = f.commit_file("a.txt", "topic\n", "change a on topic");
+            f.checkout(&f.main_ref);
+            (f, topic)
+        }
+
+        fn commit_file(&self, name: &str, content: &str, message: &str) -> Oid {
+            commit_file(&self.repo, name, content, message)
+        }
+
+        fn checkout(&self, refname: &str) {
+            self.repo.set_head(refname).unwrap();
+            self.repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        }
+
+        fn merge(&self, commit: Oid) {
+            let annotated = self.repo.find_annotated_commit(commit).unwrap();
+            self.repo.merge(&[&annotated], None, None).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_clean_repository_has_no_operation() {
+        let (f, _) = Fixture::new();
+
+        let state = get_repository_operation_state_impl(&f.repo).unwrap();
+
+        assert_eq!(state.operation, RepositoryOperation::None);
+        assert_eq!(state.head_name.as_deref(), Some(f.main_ref.as_str()));
+        assert!(state.target.is_none());
+        assert!(state.merge_message.is_none());
+        assert!(state.actions.is_empty());
+    }
+
+    #[test]
+    fn test_conflicted_merge_reports_target_message_and_actions() {
+        let (f, topic) = Fixture::new();
+        f.commit_file("a.txt", "main\n", "change a on main");
+        f.merge(topic);
+
+        let state = get_repository_operation_state_impl(&f.repo).unwrap();
+
+        assert_eq!(state.operation, RepositoryOperation::Merge);
+        assert_eq!(state.target, Some(topic.to_string()));
+        assert_eq!(state.conflict_count, 1);
+        assert!(state.merge_message.unwrap().starts_with("Merge commit"));
+        assert_eq!(state.actions, vec![OperationAction::Abort]);
+
+        std::fs::write(f.dir.path().join("a.txt"), "resolved\n").unwrap();
+        stage(&f.repo, "a.txt");
+
+        let state = get_repository_operation_state_impl(&f.repo).unwrap();
+        assert_eq!(state.conflict_count, 0);
+        assert_eq!(state.actions, vec![OperationAction::Continue, OperationAction::Abort]);
+    }
+
+    #[test]
+    fn test_clean_merge_waiting_for_commit_is_reported() {
+        let (f, topic) = Fixture::new();
+        f.commit_file("b.txt", "b\n", "add b on main");
+        f.merge(topic);
+
+        let state = get_repository_operation_state_impl(&f.repo).unwrap();
+
+        assert_eq!(state.operation, RepositoryOperation::Merge);
+        assert_eq!(state.conflict_count, 0);
+        assert_eq!(state.actions, vec![OperationAction::Continue, OperationAction::Abort]);
+    }
+
+    #[test]
+    fn test_pending_squash_is_reported_as_merge() {
+        let (f, topic) = Fixture::new();
+        f.commit_file("b.txt", "b\n", "add b on main");
+        // What `merge --squash` leaves behind: a staged result and SQUASH_MSG
+        f.merge(topic);
+        f.repo.cleanup_state().unwrap();
+        std::fs::write(f.repo.path().join("SQUASH_MSG"), "Squashed commit of the following:\n").unwrap();
+
+        let state = get_repository_operation_state_impl(&f.repo).unwrap();
+
+        assert_eq!(state.operation, RepositoryOperation::Merge);
+        assert_eq!(state.merge_message.as_deref(), Some("Squashed commit of the following:\n"));
+        assert_eq!(state.actions, vec![OperationAction::Continue, OperationAction::Abort]);
+    }
+
+    #[test]
+    fn test_stopped_rebase_lists_todo_and_done() {
+        let (f, topic) = Fixture::new();
+        f.checkout("refs/heads/topic");
+        let extra = f.commit_file("c.txt", "c\n", "add c");
+        f.checkout(&f.main_ref);
+        let main_change = f.commit_file("a.txt", "main\n", "change a on main");
+        f.checkout("refs/heads/topic");
+
+        start_pick_rebase(&f.repo, main_change, &[topic, extra]).unwrap();
+        let state = get_repository_operation_state_impl(&f.repo).unwrap();
+
+        assert_eq!(state.operation, RepositoryOperation::RebaseInteractive);
+        assert_eq!(state.head_name.as_deref(), Some("refs/heads/topic"));
+        assert_eq!(state.target, Some(main_change.to_string()));
+        assert_eq!(state.done.len(), 1);
+        assert_eq!(state.done[0].commit, Some(topic.to_string()));
+        assert_eq!(state.todo.len(), 1);
+        assert_eq!(state.todo[0].summary, "add c");
+        assert_eq!(state.actions, vec![OperationAction::Skip, OperationAction::Abort]);
+    }
+
+    #[test]
+    fn test_rebase_apply_patches_are_listed() {
+        let (f, topic) = Fixture::new();
+        let dir = f.repo.path().join("rebase-apply");
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::write(dir.join("applying"), "").unwrap();
+        std::fs::write(dir.join("next"), "2\n").unwrap();
+        std::fs::write(dir.join("last"), "3\n").unwrap();
+        for (n, subject) in [(1, "[PATCH 1/3] first"), (2, "[PATCH 2/3] second"), (3, "third")] {
+            let patch = format!("From {} Mon Sep 17 00:00:00 2001\nFrom: Test User\nSubject: {}\n\n---\n", topic, subject);
+            std::fs::write(dir.join(format!("{:04}", n)), patch).unwrap();
+        }
+
+        let state = get_repository_operation_state_impl(&f.repo).unwrap();
+
+        assert_eq!(state.operation, RepositoryOperation::ApplyMailbox);
+        let summaries = |steps: &[OperationStep]| steps.iter().map(|s| s.summary.clone()).collect::<Vec<_>>();
+        assert_eq!(summaries(&state.done), vec!["first"]);
+        assert_eq!(summaries(&state.todo), vec!["second", "third"]);
+        assert_eq!(state.todo[0].commit, Some(topic.to_string()));
+    }
+
+    #[test]
+    fn test_parse_todo_handles_comments_and_commands_without_commit() {
+        let steps = parse_todo("pick abc123 first change\n\n# comment\nexec make test\nbreak\nf def456\n");
+
+        assert_eq!(
+            steps,
+            vec![
+                OperationStep { command: "pick".to_string(), commit: Some("abc123".to_string()), summary: "first change".to_string() },
+                OperationStep { command: "exec".to_string(), commit: None, summary: "make test".to_string() },
+                OperationStep { command: "break".to_string(), commit: None, summary: String::new() },
+                OperationStep { command: "f".to_string(), commit: Some("def456".to_string()), summary: String::new() },
+            ]
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use super::auth::GitAuth;
+use crate::error::AppError;
+use git2::{Oid, PackBuilderStage, Progress, PushUpdate, RemoteCallbacks};
+use serde::Serialize;
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+/// Event carrying progress for fetch/pull/push/publish/bisect run, keyed by operation id
+pub(crate) const PROGRESS_EVENT: &str = "operation-progress";
+
+/// Minimum gap between two transfer events, so large fetches don't flood the UI
+const TRANSFER_THROTTLE: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OperationProgressPayload<'a> {
+    operation_id: &'a str,
+    operation: &'a str,
+    #[serde(flatten)]
+    progress: ProgressKind,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+enum ProgressKind {
+    Transfer {
+        total_objects: usize,
+        indexed_objects: usize,
+        received_objects: usize,
+        local_objects: usize,
+        total_deltas: usize,
+        indexed_deltas: usize,
+        received_bytes: usize,
+    },
+    PackBuilder {
+        stage: &'static str,
+        current: usize,
+        total: usize,
+    },
+    PushTransfer {
+        current: usize,
+        total: usize,
+        bytes: usize,
+    },
+    RefUpdate {
+        refname: String,
+        status: Option<String>,
+    },
+    Sideband {
+        message: String,
+    },
+    BisectStep {
+        commit: String,
+        verdict: &'static str,
+        remaining: usize,
+    },
+}
+
+fn registry() -> &'static Mutex<HashMap<String, Arc<AtomicBool>>> {
+    static OPERATIONS: OnceLock<Mutex<HashMap<String, Arc<AtomicBool>>>> = OnceLock::new();
+    OPERATIONS.get_or_init(Default::default)
+}
+
+/// A running network operation: emits progress events and can be cancelled by id
+pub(crate) struct Operation {
+    id: String,
+    kind: &'static str,
+    app: Option<AppHandle>,
+    cancelled: Arc<AtomicBool>,
+    last_transfer: Cell<Option<Instant>>,
+    /// Remote ref -> rejection message (None when accepted), from the last push
+    ref_updates: RefCell<Vec<(String, Option<String>)>>,
+    /// Remote ref -> value it must still have for a force-with-lease push
+    leases: RefCell<HashMap<String, Oid>>,
+}
+
+impl Operation {
+    /// Register an operation; without an id from the caller one is generated
+    pub(crate) fn start(app: Option<&AppHandle>, id: Option<String>, kind: &'static str) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        let id = id.unwrap_or_else(|| format!("{}-{}", kind, NEXT_ID.fetch_add(1, Ordering::Relaxed)));
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Ok(mut operations) = registry().lock() {
+            operations.insert(id.clone(), cancelled.clone());
+        }
+
+        Self {
+            id,
+            kind,
+            app: app.cloned(),
+            cancelled,
+            last_transfer: Cell::new(None),
+            ref_updates: RefCell::new(Vec::new()),
+            leases: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Install progress and cancellation callbacks next to the credential ones.
+    ///
+    /// Cancellation takes effect at the next callback that can abort the transfer
+    /// (transfer, sideband, push negotiation or ref update).
+    pub(crate) fn attach<'a>(&'a self, callbacks: &mut RemoteCallbacks<'a>) {
+        callbacks.transfer_progress(move |progress: Progress| {
+            if self.is_cancelled() {
+                return false;
+            }
+            if self.transfer_due(&progress) {
+                self.emit(ProgressKind::Transfer {
+                    total_objects: progress.total_objects(),
+                    indexed_objects: progress.indexed_objects(),
+                    received_objects: progress.received_objects(),
+                    local_objects: progress.local_objects(),
+                    total_deltas: progress.total_deltas(),
+                    indexed_deltas: progress.indexed_deltas(),
+                    received_bytes: progress.received_bytes(),
+                });
+            }
+            true
+        });
+
+        callbacks.sideband_progress(move |data| {
+            let message = String::from_utf8_lossy(data).trim_end().to_string();
+            if !message.is_empty() {
+                self.emit(ProgressKind::Sideband { message });
+            }
+            !self.is_cancelled()
+        });
+
+        callbacks.pack_progress(move |stage, current, total| {
+            let stage = match stage {
+                PackBuilderStage::AddingObjects => "addingObjects",
+                PackBuilderStage::Deltafication => "deltafication",
+            };
+            self.emit(ProgressKind::PackBuilder { stage, current, total });
+        });
+
+        callbacks.push_transfer_progress(move |current, total, bytes| {
+            self.emit(ProgressKind::PushTransfer { current, total, bytes });
+        });
+
+        callbacks.push_negotiation(move |updates| {
+            self.check_cancelled()?;
+            self.check_leases(updates)
+        });
+
+        callbacks.push_update_reference(move |refname, status| {
+            self.ref_updates
+                .borrow_mut()
+                .push((refname.to_string(), status.map(str::to_string)));
+            self.emit(ProgressKind::RefUpdate {
+                refname: refname.to_string(),
+                status: status.map(str::to_string),
+            });
+            self.check_cancelled()
+        });
+    }
+
+    /// Only push `remote_ref` if the remote still has `expected` (zero: must not exist)
+    pub(crate) fn require_lease(&self, remote_ref: &str, expected: Oid) {
+        self.leases.borrow_mut().insert(remote_ref.to_string(), expected);
+    }
+
+    /// Per-ref verdicts reported during the last push
+    pub(crate) fn take_ref_updates(&self) -> Vec<(String, Option<String>)> {
+        std::mem::take(&mut *self.ref_updates.borrow_mut())
+    }
+
+    /// Report the verdict for one commit tested by `bisect run`
+    pub(crate) fn bisect_step(&self, commit: &str, verdict: &'static str, remaining: usize) {
+        self.emit(ProgressKind::BisectStep {
+            commit: commit.to_string(),
+            verdict,
+            remaining,
+        });
+    }
+
+    /// Map a failed transfer, reporting cancellation ahead of whatever libgit2 said
+    pub(crate) fn map_error(&self, err: git2::Error, auth: &GitAuth) -> AppError {
+        if self.is_cancelled() {
+            AppError::Cancelled(self.id.clone())
+        } else {
+            auth.map_error(err)
+        }
+    }
+
+    fn check_cancelled(&self) -> std::result::Result<(), git2::Error> {
+        if self.is_cancelled() {
+            Err(git2::Error::from_str("operation cancelled"))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Abort the push when a leased ref moved on the remote since we last fetched it
+    fn check_leases(&self, updates: &[PushUpdate<'_>]) -> std::result::Result<(), git2::Error> {
+        let leases = self.leases.borrow();
+        let mut stale = false;
+
+        for update in updates {
+            let Some(remote_ref) = update.dst_refname() else {
+                continue;
+            };
+            if let Some(expected) = leases.get(remote_ref) {
+                if update.src() != *expected {
+                    self.ref_updates
+                        .borrow_mut()
+                        .push((remote_ref.to_string(), Some("stale info".to_string())));
+                    stale = true;
+                }
+            }
+        }
+
+        if stale {
+            Err(git2::Error::from_str("remote ref changed since last fetch"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn transfer_due(&self, progress: &Progress) -> bool {
+        let finished = progress.total_objects() > 0
+            && progress.indexed_objects() == progress.total_objects()
+            && progress.indexed_deltas() == progress.total_deltas();
+        let now = Instant::now();
+        let due = finished
+            || self
+                .last_transfer
+                .get()
+                .is_none_or(|last| now.duration_since(last) >= TRANSFER_THROTTLE);
+        if due {
+            self.last_transfer.set(Some(now));
+        }
+        due
+    }
+
+    fn emit(&self, progress: ProgressKind) {
+        if let Some(app) = &self.app {
+            let _ = app.emit(
+                PROGRESS_EVENT,
+                OperationProgressPayload {
+                    operation_id: &self.id,
+                    operation: self.kind,
+                    progress,
+                },
+            );
+        }
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        if let Ok(mut operations) = registry().lock() {
+            operations.remove(&self.id);
+        }
+    }
+}
+
+/// Cancel a running fetch/pull/push/bisect run by the id passed when starting it.
+/// Returns false when no such operation is running.
+#[tauri::command]
+pub async fn cancel_operation(operation_id: String) -> std::result::Result<bool, String> {
+    let operations = registry().lock().map_err(|e| e.to_string())?;
+    match operations.get(&operation_id) {
+        Some(cancelled) => {
+            cancelled.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+#[path = "progress_tests.rs"]
+mod progress_tests;
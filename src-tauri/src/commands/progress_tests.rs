@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::auth::GitAuth;
+    use crate::commands::progress::{cancel_operation, Operation};
+    use crate::error::AppError;
+    use git2::{Repository, Signature};
+    use tempfile::TempDir;
+
+    /// A bare "remote" with one commit, and an empty clone-to-be that has it as origin
+    fn remote_and_local() -> (TempDir, Repository) {
+        let dir = TempDir::new().unwrap();
+        let remote = Repository::init_bare(dir.path().join("remote.git")).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let tree_id = remote.treebuilder(None).unwrap().write().unwrap();
+        let tree = remote.find_tree(tree_id).unwrap();
+        remote
+            .commit(Some("refs/heads/main"), &sig, &sig, "initial", &tree, &[])
+            .unwrap();
+
+        let local = Repository::init(dir.path().join("local")).unwrap();
+        let url = format!("file://{}", dir.path().join("remote.git").display());
+        local.remote("origin", &url).unwrap();
+        (dir, local)
+    }
+
+    fn fetch(repo: &Repository, operation: &Operation) -> crate::error::Result<()> {
+        let auth = GitAuth::new(None, None, None);
+        let mut callbacks = auth.callbacks();
+        operation.attach(&mut callbacks);
+        let mut options = git2::FetchOptions::new();
+        options.remote_callbacks(callbacks);
+        repo.find_remote("origin")?
+            .fetch(&["refs/heads/*:refs/remotes/origin/*"], Some(&mut options), None)
+            .map_err(|e| operation.map_error(e, &auth))
+    }
+
+    #[test]
+    fn test_fetch_with_operation_succeeds() {
+        let (_dir, repo) = remote_and_local();
+        let operation = Operation::start(None, Some("fetch-ok".to_string()), "fetch");
+
+        fetch(&repo, &operation).unwrap();
+        assert!(repo.find_reference("refs/remotes/origin/main").is_ok());
+    }
+
+    #[test]
+    fn test_cancel_operation_aborts_fetch() {
+        let (_dir, repo) = remote_and_local();
+        let operation = Operation::start(None, Some("fetch-cancel".to_string()), "fetch");
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        assert!(runtime.block_on(cancel_operation("fetch-cancel".to_string())).unwrap());
+        assert!(!runtime.block_on(cancel_operation("unknown".to_string())).unwrap());
+
+        match fetch(&repo, &operation) {
+            Err(AppError::Cancelled(id)) => assert_eq!(id, "fetch-cancel"),
+            other => panic!("expected cancellation, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_finished_operation_is_unregistered() {
+        let operation = Operation::start(None, Some("fetch-done".to_string()), "fetch");
+        drop(operation);
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        assert!(!runtime.block_on(cancel_operation("fetch-done".to_string())).unwrap());
+    }
+}
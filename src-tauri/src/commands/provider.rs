@@ -1,4 +1,9 @@
-use crate::domain::{GitProvider, PullRequest, Issue, CreatePullRequest, CreateIssue, CommitStatus};
+use crate::domain::{
+    GitProvider, PullRequest, Issue, CreatePullRequest, CreateIssue, CommitStatus, ListPage, ListQuery, ProviderKind,
+    CreateReview, PullRequestComment, PullRequestCommit, PullRequestDetails, PullRequestFile,
+};
+use crate::infrastructure::bitbucket::{BitbucketCloudProvider, BitbucketServerProvider};
+use crate::infrastructure::gitea::GiteaProvider;
 use crate::infrastructure::github::GitHubProvider;
 use crate::infrastructure::gitlab::GitLabProvider;
 use crate::error::{AppError, Result};
@@ -10,9 +15,10 @@ pub async fn fetch_pr_list(
     path: String,
     token: String,
     domain: Option<String>,
-) -> std::result::Result<Vec<PullRequest>, String> {
+    query: Option<ListQuery>,
+) -> std::result::Result<ListPage<PullRequest>, String> {
     let (provider, owner, repo) = get_provider_and_repo(&path, token, domain).map_err(|e| e.to_string())?;
-    provider.get_pr_list(&owner, &repo).await.map_err(|e| e.to_string())
+    provider.get_pr_list(&owner, &repo, &query.unwrap_or_default()).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -20,9 +26,10 @@ pub async fn fetch_issue_list(
     path: String,
     token: String,
     domain: Option<String>,
-) -> std::result::Result<Vec<Issue>, String> {
+    query: Option<ListQuery>,
+) -> std::result::Result<ListPage<Issue>, String> {
     let (provider, owner, repo) = get_provider_and_repo(&path, token, domain).map_err(|e| e.to_string())?;
-    provider.get_issue_list(&owner, &repo).await.map_err(|e| e.to_string())
+    provider.get_issue_list(&owner, &repo, &query.unwrap_or_default()).await.map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -68,6 +75,88 @@ pub async fn fetch_job_logs(
     provider.get_job_logs(&owner, &repo, &job_id).await.map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn fetch_pr_details(
+    path: String,
+    token: String,
+    domain: Option<String>,
+    number: u64,
+) -> std::result::Result<PullRequestDetails, String> {
+    let (provider, owner, repo) = get_provider_and_repo(&path, token, domain).map_err(|e| e.to_string())?;
+    provider.get_pr_details(&owner, &repo, number).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn fetch_pr_files(
+    path: String,
+    token: String,
+    domain: Option<String>,
+    number: u64,
+) -> std::result::Result<Vec<PullRequestFile>, String> {
+    let (provider, owner, repo) = get_provider_and_repo(&path, token, domain).map_err(|e| e.to_string())?;
+    provider.get_pr_files(&owner, &repo, number).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn fetch_pr_commits(
+    path: String,
+    token: String,
+    domain: Option<String>,
+    number: u64,
+) -> std::result::Result<Vec<PullRequestCommit>, String> {
+    let (provider, owner, repo) = get_provider_and_repo(&path, token, domain).map_err(|e| e.to_string())?;
+    provider.get_pr_commits(&owner, &repo, number).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn fetch_pr_comments(
+    path: String,
+    token: String,
+    domain: Option<String>,
+    number: u64,
+) -> std::result::Result<Vec<PullRequestComment>, String> {
+    let (provider, owner, repo) = get_provider_and_repo(&path, token, domain).map_err(|e| e.to_string())?;
+    provider.get_pr_comments(&owner, &repo, number).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_pr_review(
+    path: String,
+    token: String,
+    domain: Option<String>,
+    number: u64,
+    review: CreateReview,
+) -> std::result::Result<(), String> {
+    let (provider, owner, repo) = get_provider_and_repo(&path, token, domain).map_err(|e| e.to_string())?;
+    provider.create_review(&owner, &repo, number, review).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reply_to_pr_thread(
+    path: String,
+    token: String,
+    domain: Option<String>,
+    number: u64,
+    thread_id: String,
+    body: String,
+) -> std::result::Result<PullRequestComment, String> {
+    let (provider, owner, repo) = get_provider_and_repo(&path, token, domain).map_err(|e| e.to_string())?;
+    provider.reply_to_thread(&owner, &repo, number, &thread_id, &body).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn resolve_pr_thread(
+    path: String,
+    token: String,
+    domain: Option<String>,
+    number: u64,
+    thread_id: String,
+    resolved: bool,
+) -> std::result::Result<(), String> {
+    let (provider, owner, repo) = get_provider_and_repo(&path, token, domain).map_err(|e| e.to_string())?;
+    provider.resolve_thread(&owner, &repo, number, &thread_id, resolved).await.map_err(|e| e.to_string())
+}
+
 fn get_provider_and_repo(
     path: &str,
     token: String,
@@ -80,25 +169,118 @@ fn get_provider_and_repo(
     // Parse URL
     // handle git@... and https://...
     let (host, owner, repo_name) = parse_git_url(url_str)?;
+    let (kind, base_url) = resolve_provider(&repo.config()?, url_str, &host, domain.as_deref())?;
+    let self_hosted = || base_url.clone().unwrap_or_else(|| format!("https://{}", host));
 
-    let provider: Box<dyn GitProvider> = if host == "github.com" {
-        Box::new(GitHubProvider::new(token))
-    } else if host == "gitlab.com" || domain.as_ref().map_or(false, |d| host == *d) {
-        // For GitLab, we might need the base URL if it's self-hosted
-        let base_url = if host == "gitlab.com" {
-            None
-        } else {
-            Some(format!("https://{}", host)) // Assuming HTTPS for self-hosted
-        };
-        Box::new(GitLabProvider::new(token, base_url))
-    } else {
-        return Err(AppError::InvalidInput(format!("Unsupported provider host: {}", host)));
+    let provider: Box<dyn GitProvider> = match kind {
+        ProviderKind::GitHub => Box::new(GitHubProvider::new(token, base_url.clone())),
+        ProviderKind::GitLab => Box::new(GitLabProvider::new(token, base_url.clone())),
+        ProviderKind::Gitea => Box::new(GiteaProvider::new(token, self_hosted())),
+        ProviderKind::Bitbucket => Box::new(BitbucketCloudProvider::new(token, base_url.clone())),
+        ProviderKind::BitbucketServer => Box::new(BitbucketServerProvider::new(token, self_hosted())),
     };
 
     Ok((provider, owner, repo_name))
 }
 
-fn parse_git_url(url: &str) -> Result<(String, String, String)> {
+/// Work out which service runs on `host` and the base URL of its API, if not
+/// the public one. A `gat.<host>.provider` entry in git config (with an
+/// optional `gat.<host>.apiUrl`) wins over recognising the host by name
+pub(crate) fn resolve_provider(
+    config: &git2::Config,
+    remote_url: &str,
+    host: &str,
+    domain: Option<&str>,
+) -> Result<(ProviderKind, Option<String>)> {
+    let api_url = config.get_string(&format!("gat.{}.apiUrl", host)).ok();
+    let self_hosted = || Some(api_url.clone().unwrap_or_else(|| server_url(remote_url, host)));
+
+    if let Ok(kind) = config.get_string(&format!("gat.{}.provider", host)) {
+        let kind: ProviderKind = kind.parse()?;
+        let base_url = match kind {
+            // GitHub Enterprise Server serves its API under /api/v3
+            ProviderKind::GitHub if host != "github.com" => {
+                Some(api_url.clone().unwrap_or_else(|| format!("{}/api/v3", server_url(remote_url, host))))
+            }
+            // The public APIs live on their own hosts, not the remote's
+            ProviderKind::GitHub | ProviderKind::Bitbucket => api_url,
+            _ => self_hosted(),
+        };
+        return Ok((kind, base_url));
+    }
+
+    if host == "github.com" {
+        Ok((ProviderKind::GitHub, None))
+    } else if host == "gitlab.com" {
+        Ok((ProviderKind::GitLab, None))
+    } else if host == "bitbucket.org" {
+        Ok((ProviderKind::Bitbucket, None))
+    } else if domain == Some(host) {
+        Ok((ProviderKind::GitLab, self_hosted()))
+    } else {
+        Err(AppError::InvalidInput(format!(
+            "Unsupported provider host: {}. Set gat.{}.provider to github, gitlab, gitea, bitbucket or bitbucket-server",
+            host, host
+        )))
+    }
+}
+
+/// Guess a self-hosted server's web root from the remote: an HTTP(S) remote
+/// keeps its scheme, port and any context path in front of Bitbucket Server's
+/// `/scm/`; otherwise (SSH, whose port is not the web port) assume HTTPS on
+/// the host
+fn server_url(remote_url: &str, host: &str) -> String {
+    if let Ok(url) = Url::parse(remote_url) {
+        if matches!(url.scheme(), "http" | "https") {
+            let mut base = format!("{}://{}", url.scheme(), host);
+            if let Some(port) = url.port() {
+                base.push_str(&format!(":{}", port));
+            }
+            if let Some((context, _)) = url.path().split_once("/scm/") {
+                base.push_str(context);
+            }
+            return base;
+        }
+    }
+    format!("https://{}", host)
+}
+
+/// Record which service runs on `host`, in the repository's git config or,
+/// with `global`, in the user's
+#[tauri::command]
+pub async fn set_provider_host(
+    path: String,
+    host: String,
+    kind: ProviderKind,
+    api_url: Option<String>,
+    global: bool,
+) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    let config = repo.config().map_err(|e| e.to_string())?;
+    let level = if global { git2::ConfigLevel::Global } else { git2::ConfigLevel::Local };
+    let mut config = config.open_level(level).map_err(|e| e.to_string())?;
+    set_provider_host_impl(&mut config, &host, kind, api_url.as_deref()).map_err(|e| e.to_string())
+}
+
+pub(crate) fn set_provider_host_impl(
+    config: &mut git2::Config,
+    host: &str,
+    kind: ProviderKind,
+    api_url: Option<&str>,
+) -> Result<()> {
+    config.set_str(&format!("gat.{}.provider", host), &kind.to_string())?;
+    let api_key = format!("gat.{}.apiUrl", host);
+    match api_url {
+        Some(api_url) => config.set_str(&api_key, api_url)?,
+        // Removing a missing entry is not an error
+        None => {
+            let _ = config.remove(&api_key);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_git_url(url: &str) -> Result<(String, String, String)> {
     // Handle SSH: git@github.com:owner/repo.git
     if url.starts_with("git@") {
         let parts: Vec<&str> = url.split('@').collect();
@@ -130,7 +312,11 @@ fn parse_git_url(url: &str) -> Result<(String, String, String)> {
     if let Ok(parsed_url) = Url::parse(url) {
         let host: &str = parsed_url.host_str().ok_or(AppError::InvalidInput("No host in URL".to_string()))?;
         let path = parsed_url.path().trim_start_matches('/').trim_end_matches(".git");
-        let path_parts: Vec<&str> = path.split('/').collect();
+        let mut path_parts: Vec<&str> = path.split('/').collect();
+        // Bitbucket Server clones over HTTP from [context/]scm/<project>/<repo>
+        if let Some(scm) = path_parts.iter().position(|part| *part == "scm").filter(|&i| path_parts.len() == i + 3) {
+            path_parts.drain(..=scm);
+        }
         if path_parts.len() < 2 {
              return Err(AppError::InvalidInput("Invalid repository path".to_string()));
         }
@@ -143,3 +329,7 @@ fn parse_git_url(url: &str) -> Result<(String, String, String)> {
 
     Err(AppError::InvalidInput("Could not parse remote URL".to_string()))
 }
+
+#[cfg(test)]
+#[path = "provider_tests.rs"]
+mod provider_tests;
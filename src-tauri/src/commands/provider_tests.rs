@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::provider::{fetch_pr_list, parse_git_url, resolve_provider, set_provider_host_impl};
+    use crate::commands::test_support;
+    use crate::domain::ProviderKind;
+    use crate::infrastructure::mock_server::{MockResponse, MockServer};
+    use git2::Repository;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn init_repo(origin: &str) -> (TempDir, Repository) {
+        let (dir, repo) = test_support::init_repo();
+        repo.remote("origin", origin).unwrap();
+        (dir, repo)
+    }
+
+    fn local_config(repo: &Repository) -> git2::Config {
+        repo.config().unwrap().open_level(git2::ConfigLevel::Local).unwrap()
+    }
+
+    #[test]
+    fn test_known_hosts_are_recognised_by_name() {
+        let (_dir, repo) = init_repo("https://github.com/o/r.git");
+        let config = repo.config().unwrap();
+
+        let resolve = |host: &str, domain| resolve_provider(&config, &format!("git@{}:o/r.git", host), host, domain);
+
+        assert_eq!(resolve("github.com", None).unwrap(), (ProviderKind::GitHub, None));
+        assert_eq!(resolve("gitlab.com", None).unwrap(), (ProviderKind::GitLab, None));
+        assert_eq!(resolve("bitbucket.org", None).unwrap(), (ProviderKind::Bitbucket, None));
+        assert_eq!(
+            resolve("gitlab.corp", Some("gitlab.corp")).unwrap(),
+            (ProviderKind::GitLab, Some("https://gitlab.corp".to_string()))
+        );
+
+        let err = resolve("git.example.com", None).unwrap_err();
+        assert!(err.to_string().contains("gat.git.example.com.provider"));
+    }
+
+    #[test]
+    fn test_explicit_host_mapping_wins() {
+        let (_dir, repo) = init_repo("https://git.example.com/team/app.git");
+        let mut config = local_config(&repo);
+        let remote = "https://git.example.com/team/app.git";
+
+        set_provider_host_impl(&mut config, "git.example.com", ProviderKind::Gitea, None).unwrap();
+        assert_eq!(
+            resolve_provider(&repo.config().unwrap(), remote, "git.example.com", None).unwrap(),
+            (ProviderKind::Gitea, Some("https://git.example.com".to_string()))
+        );
+
+        set_provider_host_impl(&mut config, "git.example.com", ProviderKind::Gitea, Some("http://localhost:3000"))
+            .unwrap();
+        assert_eq!(
+            resolve_provider(&repo.config().unwrap(), remote, "git.example.com", None).unwrap(),
+            (ProviderKind::Gitea, Some("http://localhost:3000".to_string()))
+        );
+
+        // A mapping also overrides the guess for a well-known name
+        config.set_str("gat.gitlab.com.provider", "forgejo").unwrap();
+        assert_eq!(resolve_provider(&repo.config().unwrap(), remote, "gitlab.com", None).unwrap().0, ProviderKind::Gitea);
+
+        // GitHub Enterprise Server is reached through its own API, not api.github.com
+        set_provider_host_impl(&mut config, "git.example.com", ProviderKind::GitHub, None).unwrap();
+        assert_eq!(
+            resolve_provider(&repo.config().unwrap(), remote, "git.example.com", None).unwrap(),
+            (ProviderKind::GitHub, Some("https://git.example.com/api/v3".to_string()))
+        );
+        set_provider_host_impl(&mut config, "git.example.com", ProviderKind::GitHub, Some("https://ghe.corp/api/v3"))
+            .unwrap();
+        assert_eq!(
+            resolve_provider(&repo.config().unwrap(), remote, "git.example.com", None).unwrap(),
+            (ProviderKind::GitHub, Some("https://ghe.corp/api/v3".to_string()))
+        );
+
+        config.set_str("gat.gitlab.com.provider", "svn").unwrap();
+        assert!(resolve_provider(&repo.config().unwrap(), remote, "gitlab.com", None).is_err());
+    }
+
+    #[test]
+    fn test_bitbucket_server_remotes() {
+        assert_eq!(
+            parse_git_url("https://bitbucket.corp/scm/PROJ/app.git").unwrap(),
+            ("bitbucket.corp".to_string(), "PROJ".to_string(), "app".to_string())
+        );
+        assert_eq!(
+            parse_git_url("https://alice@bitbucket.corp:8443/bitbucket/scm/~alice/app.git").unwrap(),
+            ("bitbucket.corp".to_string(), "~alice".to_string(), "app".to_string())
+        );
+        assert_eq!(
+            parse_git_url("ssh://git@bitbucket.corp:7999/proj/app.git").unwrap(),
+            ("bitbucket.corp".to_string(), "proj".to_string(), "app".to_string())
+        );
+        // Only a segment right before <project>/<repo> marks the clone path
+        assert_eq!(parse_git_url("https://gitlab.corp/scm/tools/infra/app").unwrap().1, "scm/tools/infra");
+
+        let (_dir, repo) = init_repo("https://bitbucket.corp/scm/PROJ/app.git");
+        let mut config = local_config(&repo);
+        config.set_str("gat.bitbucket.corp.provider", "bitbucket-datacenter").unwrap();
+        let resolve = |remote: &str| resolve_provider(&repo.config().unwrap(), remote, "bitbucket.corp", None).unwrap();
+
+        // The web root comes from HTTP remotes, context path included
+        assert_eq!(
+            resolve("https://alice@bitbucket.corp:8443/bitbucket/scm/proj/app.git"),
+            (ProviderKind::BitbucketServer, Some("https://bitbucket.corp:8443/bitbucket".to_string()))
+        );
+        // but not from the SSH port
+        assert_eq!(
+            resolve("ssh://git@bitbucket.corp:7999/proj/app.git").1,
+            Some("https://bitbucket.corp".to_string())
+        );
+
+        // Bitbucket Cloud keeps its public API unless told otherwise
+        config.set_str("gat.bitbucket.corp.provider", "bitbucket").unwrap();
+        assert_eq!(resolve("git@bitbucket.corp:team/app.git"), (ProviderKind::Bitbucket, None));
+    }
+
+    fn pulls_server() -> MockServer {
+        MockServer::start(|_| {
+            MockResponse::json(
+                200,
+                json!([{
+                    "id": 11,
+                    "number": 1,
+                    "title": "Add feature",
+                    "body": null,
+                    "state": "open",
+                    "merged": false,
+                    "user": { "login": "alice" },
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "updated_at": "2024-01-01T00:00:00Z",
+                    "html_url": "https://git.example.com/team/app/pulls/1",
+                    "head": { "ref": "feature" },
+                    "base": { "ref": "main" }
+                }]),
+            )
+        })
+    }
+
+    #[test]
+    fn test_mapped_gitea_host_is_used_for_provider_commands() {
+        let server = pulls_server();
+        let (dir, repo) = init_repo("git@git.example.com:team/app.git");
+        set_provider_host_impl(&mut local_config(&repo), "git.example.com", ProviderKind::Gitea, Some(&server.url))
+            .unwrap();
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let prs = runtime.block_on(fetch_pr_list(path, "secret".to_string(), None, None)).unwrap().items;
+
+        assert_eq!(prs[0].title, "Add feature");
+        assert_eq!(server.requests()[0].path, "/api/v1/repos/team/app/pulls?state=open");
+    }
+
+    #[test]
+    fn test_mapped_github_host_sends_requests_to_its_api_url() {
+        let server = pulls_server();
+        let (dir, repo) = init_repo("git@ghe.example.com:team/app.git");
+        set_provider_host_impl(&mut local_config(&repo), "ghe.example.com", ProviderKind::GitHub, Some(&server.url))
+            .unwrap();
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let prs = runtime.block_on(fetch_pr_list(path, "secret".to_string(), None, None)).unwrap().items;
+
+        assert_eq!(prs[0].title, "Add feature");
+        assert_eq!(server.requests()[0].path, "/repos/team/app/pulls?state=open&sort=created&direction=desc");
+    }
+}
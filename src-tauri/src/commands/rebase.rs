@@ -0,0 +1,637 @@
+use crate::domain::{RebaseState, RebaseTodo};
+use crate::error::{AppError, Result};
+use super::commit::create_commit;
+use git2::{Oid, Repository, StatusOptions};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+// The in-process rebase keeps its progress in `.git/rebase-merge`, using the
+// same file names as git (head-name, onto, orig-head, msgnum, end, done,
+// git-rebase-todo, stopped-sha) so that `git status` and `get_rebase_state`
+// understand it. The full todo list, including reworded messages, is kept in
+// an extra JSON file that only we read.
+const REBASE_MERGE_DIR: &str = "rebase-merge";
+const SESSION_FILE: &str = "gat-session.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RebaseCommand {
+    Pick,
+    Reword,
+    Edit,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebaseCommand {
+    fn parse(command: &str) -> Result<Self> {
+        match command {
+            "pick" | "p" => Ok(RebaseCommand::Pick),
+            "reword" | "r" => Ok(RebaseCommand::Reword),
+            "edit" | "e" => Ok(RebaseCommand::Edit),
+            "squash" | "s" => Ok(RebaseCommand::Squash),
+            "fixup" | "f" => Ok(RebaseCommand::Fixup),
+            "drop" | "d" => Ok(RebaseCommand::Drop),
+            _ => Err(AppError::InvalidInput(format!("Unknown rebase command: {}", command))),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            RebaseCommand::Pick => "pick",
+            RebaseCommand::Reword => "reword",
+            RebaseCommand::Edit => "edit",
+            RebaseCommand::Squash => "squash",
+            RebaseCommand::Fixup => "fixup",
+            RebaseCommand::Drop => "drop",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RebaseStep {
+    command: RebaseCommand,
+    commit: String,
+    summary: String,
+    new_message: Option<String>,
+}
+
+impl RebaseStep {
+    fn todo_line(&self) -> String {
+        format!("{} {} {}", self.command.as_str(), self.commit, self.summary)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum StopReason {
+    Conflict,
+    Edit,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RebaseSession {
+    /// `refs/heads/<branch>` or "detached HEAD"
+    head_name: String,
+    orig_head: String,
+    onto: String,
+    todo: Vec<RebaseStep>,
+    done: Vec<RebaseStep>,
+    stopped: Option<StopReason>,
+}
+
+/// Outcome of running the todo list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RebaseProgress {
+    Finished,
+    Stopped,
+}
+
+impl RebaseSession {
+    fn dir(repo: &Repository) -> PathBuf {
+        repo.path().join(REBASE_MERGE_DIR)
+    }
+
+    fn exists(repo: &Repository) -> bool {
+        Self::dir(repo).join(SESSION_FILE).exists()
+    }
+
+    fn load(repo: &Repository) -> Result<Self> {
+        let content = std::fs::read_to_string(Self::dir(repo).join(SESSION_FILE))?;
+        serde_json::from_str(&content)
+            .map_err(|e| AppError::InvalidInput(format!("Corrupt rebase state: {}", e)))
+    }
+
+    fn save(&self, repo: &Repository) -> Result<()> {
+        let dir = Self::dir(repo);
+        std::fs::create_dir_all(&dir)?;
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+        std::fs::write(dir.join(SESSION_FILE), json)?;
+
+        // git-compatible files
+        std::fs::write(dir.join("head-name"), format!("{}\n", self.head_name))?;
+        std::fs::write(dir.join("orig-head"), format!("{}\n", self.orig_head))?;
+        std::fs::write(dir.join("onto"), format!("{}\n", self.onto))?;
+        std::fs::write(dir.join("interactive"), "")?;
+        std::fs::write(dir.join("msgnum"), format!("{}\n", self.done.len()))?;
+        std::fs::write(dir.join("end"), format!("{}\n", self.done.len() + self.todo.len()))?;
+        std::fs::write(dir.join("done"), lines(&self.done))?;
+        std::fs::write(dir.join("git-rebase-todo"), lines(&self.todo))?;
+
+        let stopped_sha = dir.join("stopped-sha");
+        match (self.stopped, self.done.last()) {
+            (Some(_), Some(step)) => std::fs::write(&stopped_sha, format!("{}\n", step.commit))?,
+            _ => {
+                if stopped_sha.exists() {
+                    std::fs::remove_file(&stopped_sha)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn remove(repo: &Repository) -> Result<()> {
+        let dir = Self::dir(repo);
+        if dir.exists() {
+            std::fs::remove_dir_all(dir)?;
+        }
+        Ok(())
+    }
+}
+
+fn lines(steps: &[RebaseStep]) -> String {
+    steps.iter().map(|s| s.todo_line() + "\n").collect()
+}
+
+/// Get rebase state
+#[tauri::command]
+pub async fn get_rebase_state(path: String) -> std::result::Result<RebaseState, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_rebase_state_impl(&repo).map_err(|e| e.to_string())
+}
+
+fn get_rebase_state_impl(repo: &Repository) -> Result<RebaseState> {
+    let git_dir = repo.path();
+    let rebase_merge_dir = git_dir.join("rebase-merge");
+    let rebase_apply_dir = git_dir.join("rebase-apply");
+
+    let is_rebase_in_progress = rebase_merge_dir.exists() || rebase_apply_dir.exists();
+
+    if !is_rebase_in_progress {
+        return Ok(RebaseState {
+            is_rebase_in_progress: false,
+            current_branch: None,
+            onto_branch: None,
+            current_step: 0,
+            total_steps: 0,
+            current_commit: None,
+        });
+    }
+
+    // Read rebase state from files
+    let current_branch = read_state_file(&rebase_merge_dir, "head-name");
+
+    let onto_branch = read_state_file(&rebase_merge_dir, "onto")
+        .and_then(|s| Oid::from_str(&s).ok())
+        .and_then(|oid| repo.find_commit(oid).ok())
+        .and_then(|commit| commit.summary().map(|s| s.to_string()));
+
+    let current_step = read_state_file(&rebase_merge_dir, "msgnum")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    let total_steps = read_state_file(&rebase_merge_dir, "end")
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+
+    // git writes `stopped-sha`; older versions of this app wrote `current-commit`
+    let current_commit = read_state_file(&rebase_merge_dir, "stopped-sha")
+        .or_else(|| read_state_file(&rebase_merge_dir, "current-commit"));
+
+    Ok(RebaseState {
+        is_rebase_in_progress: true,
+        current_branch,
+        onto_branch,
+        current_step,
+        total_steps,
+        current_commit,
+    })
+}
+
+fn read_state_file(dir: &Path, name: &str) -> Option<String> {
+    std::fs::read_to_string(dir.join(name)).ok().map(|s| s.trim().to_string())
+}
+
+/// Start an interactive rebase
+#[tauri::command]
+pub async fn start_interactive_rebase(
+    path: String,
+    base_commit: String,
+    commits: Vec<RebaseTodo>,
+) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    start_interactive_rebase_impl(&repo, &base_commit, &commits)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+pub(crate) fn start_interactive_rebase_impl(
+    repo: &Repository,
+    base_commit: &str,
+    commits: &[RebaseTodo],
+) -> Result<RebaseProgress> {
+    let mut steps = Vec::with_capacity(commits.len());
+    for todo in commits {
+        let command = RebaseCommand::parse(&todo.command)?;
+        let commit = repo.find_commit(Oid::from_str(&todo.commit.id)?)?;
+        steps.push(RebaseStep {
+            command,
+            commit: commit.id().to_string(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            new_message: todo.new_message.clone().filter(|m| !m.trim().is_empty()),
+        });
+    }
+
+    let onto = repo.revparse_single(base_commit)?.peel_to_commit()?;
+    start_rebase(repo, onto.id(), steps)
+}
+
+/// Replay `commits` (oldest first) on top of `onto` as a plain pick rebase
+pub(crate) fn start_pick_rebase(repo: &Repository, onto: Oid, commits: &[Oid]) -> Result<RebaseProgress> {
+    let mut steps = Vec::with_capacity(commits.len());
+    for oid in commits {
+        let commit = repo.find_commit(*oid)?;
+        steps.push(RebaseStep {
+            command: RebaseCommand::Pick,
+            commit: oid.to_string(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            new_message: None,
+        });
+    }
+    start_rebase(repo, onto, steps)
+}
+
+fn start_rebase(repo: &Repository, onto: Oid, steps: Vec<RebaseStep>) -> Result<RebaseProgress> {
+    if repo.path().join(REBASE_MERGE_DIR).exists() || repo.path().join("rebase-apply").exists() {
+        return Err(AppError::InvalidInput("A rebase is already in progress".to_string()));
+    }
+
+    if repo.state() != git2::RepositoryState::Clean {
+        return Err(AppError::InvalidInput(
+            "Cannot rebase: another operation (merge, cherry-pick, ...) is in progress".to_string()
+        ));
+    }
+
+    ensure_clean_worktree(repo, "rebase")?;
+
+    // squash/fixup need a commit to fold into
+    if let Some(first) = steps.iter().find(|s| s.command != RebaseCommand::Drop) {
+        if matches!(first.command, RebaseCommand::Squash | RebaseCommand::Fixup) {
+            return Err(AppError::InvalidInput(
+                "Cannot squash or fixup without a previous commit".to_string()
+            ));
+        }
+    }
+
+    let head = repo.head()?;
+    let orig_head = head.peel_to_commit()?.id();
+    let head_name = if head.is_branch() {
+        head.name().unwrap_or("HEAD").to_string()
+    } else {
+        "detached HEAD".to_string()
+    };
+
+    let session = RebaseSession {
+        head_name,
+        orig_head: orig_head.to_string(),
+        onto: onto.to_string(),
+        todo: steps,
+        done: Vec::new(),
+        stopped: None,
+    };
+    session.save(repo)?;
+
+    // Detach HEAD at the new base; the branch itself only moves when we finish
+    let onto_commit = repo.find_commit(onto)?;
+    if let Err(e) = repo.checkout_tree(onto_commit.as_object(), None) {
+        RebaseSession::remove(repo)?;
+        return Err(e.into());
+    }
+    repo.set_head_detached(onto)?;
+
+    run(repo, session)
+}
+
+pub(crate) fn ensure_clean_worktree(repo: &Repository, action: &str) -> Result<()> {
+    let mut status_opts = StatusOptions::new();
+    status_opts.include_untracked(false);
+    status_opts.include_ignored(false);
+
+    let dirty = repo.statuses(Some(&mut status_opts))?
+        .iter()
+        .any(|entry| entry.status() != git2::Status::CURRENT);
+
+    if dirty {
+        return Err(AppError::InvalidInput(format!(
+            "Cannot {}: you have uncommitted changes. Please commit or stash them first.",
+            action
+        )));
+    }
+    Ok(())
+}
+
+/// Execute remaining todo steps until the list is empty or we must stop
+fn run(repo: &Repository, mut session: RebaseSession) -> Result<RebaseProgress> {
+    while !session.todo.is_empty() {
+        let step = session.todo.remove(0);
+        session.done.push(step.clone());
+
+        if step.command == RebaseCommand::Drop {
+            session.save(repo)?;
+            continue;
+        }
+
+        let commit = repo.find_commit(Oid::from_str(&step.commit)?)?;
+        let head = repo.head()?.peel_to_commit()?;
+
+        // Fast-forward: the commit already sits on top of HEAD
+        let fast_forward = matches!(step.command, RebaseCommand::Pick | RebaseCommand::Edit)
+            && commit.parent_count() == 1
+            && commit.parent_id(0)? == head.id();
+
+        if fast_forward {
+            repo.checkout_tree(commit.as_object(), None)?;
+            repo.set_head_detached(commit.id())?;
+        } else {
+            let mut cherrypick_opts = git2::CherrypickOptions::new();
+            if commit.parent_count() > 1 {
+                cherrypick_opts.mainline(1);
+            }
+            repo.cherrypick(&commit, Some(&mut cherrypick_opts))?;
+            clear_sequencer_files(repo)?;
+
+            if repo.index()?.has_conflicts() {
+                session.stopped = Some(StopReason::Conflict);
+                write_stopped_message(repo, &step, &commit)?;
+                session.save(repo)?;
+                return Ok(RebaseProgress::Stopped);
+            }
+
+            commit_step(repo, &step, &commit)?;
+        }
+
+        if step.command == RebaseCommand::Edit {
+            session.stopped = Some(StopReason::Edit);
+            session.save(repo)?;
+            return Ok(RebaseProgress::Stopped);
+        }
+
+        session.save(repo)?;
+    }
+
+    finish(repo, &session)?;
+    Ok(RebaseProgress::Finished)
+}
+
+/// Create the commit for a step whose changes are already in the index
+fn commit_step(repo: &Repository, step: &RebaseStep, commit: &git2::Commit) -> Result<()> {
+    let mut index = repo.index()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let head = repo.head()?.peel_to_commit()?;
+    let committer = repo.signature()?;
+    let original_message = commit.message().unwrap_or("");
+
+    match step.command {
+        RebaseCommand::Squash | RebaseCommand::Fixup => {
+            let message = match (&step.new_message, step.command) {
+                (Some(message), _) => message.clone(),
+                (None, RebaseCommand::Squash) => format!(
+                    "{}\n\n{}",
+                    head.message().unwrap_or("").trim_end(),
+                    original_message.trim_end()
+                ),
+                _ => head.message().unwrap_or("").to_string(),
+            };
+            amend_head(repo, &head, &head.author(), &committer, &message, &tree)?;
+        }
+        _ => {
+            // Patch already upstream: drop it like git does, but keep
+            // commits that were empty to begin with
+            let became_empty = tree.id() == head.tree_id();
+            let was_empty = commit.parent(0).map(|p| p.tree_id() == commit.tree_id()).unwrap_or(false);
+            if became_empty && !was_empty {
+                return Ok(());
+            }
+
+            let message = match step.command {
+                RebaseCommand::Reword => step.new_message.as_deref().unwrap_or(original_message),
+                _ => original_message,
+            };
+            create_commit(
+                repo,
+                Some("HEAD"),
+                &commit.author(),
+                &committer,
+                message,
+                &tree,
+                &[&head],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace HEAD with a commit that has the same parents but a new tree/message
+fn amend_head(
+    repo: &Repository,
+    head: &git2::Commit,
+    author: &git2::Signature,
+    committer: &git2::Signature,
+    message: &str,
+    tree: &git2::Tree,
+) -> Result<Oid> {
+    let parents: Vec<git2::Commit> = head.parents().collect();
+    let parent_refs: Vec<&git2::Commit> = parents.iter().collect();
+    let oid = create_commit(repo, None, author, committer, message, tree, &parent_refs)?;
+
+    let head_ref = repo.head()?;
+    if head_ref.is_branch() {
+        let name = head_ref.name().unwrap_or("HEAD").to_string();
+        repo.reference(&name, oid, true, &format!("commit (amend): {}", message.lines().next().unwrap_or("")))?;
+    } else {
+        repo.set_head_detached(oid)?;
+    }
+    Ok(oid)
+}
+
+/// `Repository::cherrypick` leaves CHERRY_PICK_HEAD/MERGE_MSG behind, which
+/// would make the repository look like a plain cherry-pick is in progress.
+fn clear_sequencer_files(repo: &Repository) -> Result<()> {
+    for name in ["CHERRY_PICK_HEAD", "MERGE_MSG"] {
+        let path = repo.path().join(name);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_stopped_message(repo: &Repository, step: &RebaseStep, commit: &git2::Commit) -> Result<()> {
+    let message = step.new_message.as_deref().unwrap_or(commit.message().unwrap_or(""));
+    std::fs::write(RebaseSession::dir(repo).join("message"), message)?;
+    Ok(())
+}
+
+/// Move the original branch to the rewritten history and drop the state
+fn finish(repo: &Repository, session: &RebaseSession) -> Result<()> {
+    let new_head = repo.head()?.peel_to_commit()?.id();
+
+    if session.head_name.starts_with("refs/") {
+        let branch = session.head_name.trim_start_matches("refs/heads/");
+        let onto_summary = repo.find_commit(Oid::from_str(&session.onto)?)?
+            .summary().unwrap_or("").to_string();
+        let msg = format!("rebase (finish): {} onto {}", session.head_name, onto_summary);
+        repo.reference(&session.head_name, new_head, true, &msg)?;
+        repo.set_head(&session.head_name)
+            .map_err(|e| AppError::InvalidInput(format!("Failed to return to branch {}: {}", branch, e)))?;
+    }
+
+    RebaseSession::remove(repo)
+}
+
+/// Continue rebase after resolving conflicts
+#[tauri::command]
+pub async fn continue_rebase(path: String) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    continue_rebase_impl(&repo).map(|_| ()).map_err(|e| e.to_string())
+}
+
+pub(crate) fn continue_rebase_impl(repo: &Repository) -> Result<RebaseProgress> {
+    if !RebaseSession::exists(repo) {
+        // Rebase started outside the app
+        run_git_rebase(repo, "--continue")?;
+        return Ok(RebaseProgress::Finished);
+    }
+
+    let mut session = RebaseSession::load(repo)?;
+
+    if let Some(reason) = session.stopped.take() {
+        let mut index = repo.index()?;
+        if index.has_conflicts() {
+            return Err(AppError::InvalidInput(
+                "Cannot continue rebase: there are still unresolved conflicts".to_string()
+            ));
+        }
+
+        let step = session.done.last().cloned()
+            .ok_or_else(|| AppError::InvalidInput("Corrupt rebase state: no current step".to_string()))?;
+
+        match reason {
+            StopReason::Conflict => {
+                let commit = repo.find_commit(Oid::from_str(&step.commit)?)?;
+                commit_step(repo, &step, &commit)?;
+            }
+            StopReason::Edit => {
+                // Fold anything the user staged while stopped into the edited commit
+                let head = repo.head()?.peel_to_commit()?;
+                let tree_id = index.write_tree()?;
+                if tree_id != head.tree_id() {
+                    let tree = repo.find_tree(tree_id)?;
+                    let committer = repo.signature()?;
+                    amend_head(repo, &head, &head.author(), &committer, head.message().unwrap_or(""), &tree)?;
+                }
+            }
+        }
+
+        let message_file = RebaseSession::dir(repo).join("message");
+        if message_file.exists() {
+            std::fs::remove_file(message_file)?;
+        }
+        session.save(repo)?;
+    }
+
+    run(repo, session)
+}
+
+/// Skip current commit during rebase
+#[tauri::command]
+pub async fn skip_rebase(path: String) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    skip_rebase_impl(&repo).map(|_| ()).map_err(|e| e.to_string())
+}
+
+pub(crate) fn skip_rebase_impl(repo: &Repository) -> Result<RebaseProgress> {
+    if !RebaseSession::exists(repo) {
+        run_git_rebase(repo, "--skip")?;
+        return Ok(RebaseProgress::Finished);
+    }
+
+    let mut session = RebaseSession::load(repo)?;
+
+    // Throw away whatever the stopped step left in the index and workdir
+    let head = repo.head()?.peel_to_commit()?;
+    repo.reset(head.as_object(), git2::ResetType::Hard, None)?;
+
+    if session.stopped.take() == Some(StopReason::Conflict) {
+        if let Some(step) = session.done.last_mut() {
+            step.command = RebaseCommand::Drop;
+        }
+    }
+    session.save(repo)?;
+
+    run(repo, session)
+}
+
+/// Abort rebase
+#[tauri::command]
+pub async fn abort_rebase(path: String) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    abort_rebase_impl(&repo).map_err(|e| e.to_string())
+}
+
+pub(crate) fn abort_rebase_impl(repo: &Repository) -> Result<()> {
+    if !RebaseSession::exists(repo) {
+        return run_git_rebase(repo, "--abort");
+    }
+
+    let session = RebaseSession::load(repo)?;
+    let orig_head = repo.find_commit(Oid::from_str(&session.orig_head)?)?;
+
+    // The branch ref was never moved, so pointing HEAD back at it is enough
+    if session.head_name.starts_with("refs/") {
+        repo.set_head(&session.head_name)?;
+    } else {
+        repo.set_head_detached(orig_head.id())?;
+    }
+    repo.reset(orig_head.as_object(), git2::ResetType::Hard, None)?;
+
+    clear_sequencer_files(repo)?;
+    RebaseSession::remove(repo)
+}
+
+fn run_git_rebase(repo: &Repository, action: &str) -> Result<()> {
+    let workdir = repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))?;
+
+    let output = std::process::Command::new("git")
+        .arg("rebase")
+        .arg(action)
+        .current_dir(workdir)
+        .env("GIT_EDITOR", "true")
+        .output()?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::Git(git2::Error::from_str(&error)));
+    }
+
+    Ok(())
+}
+
+/// Edit commit message during rebase
+#[tauri::command]
+pub async fn amend_rebase_commit(
+    path: String,
+    new_message: String,
+) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    amend_rebase_commit_impl(&repo, &new_message).map_err(|e| e.to_string())
+}
+
+fn amend_rebase_commit_impl(repo: &Repository, new_message: &str) -> Result<()> {
+    let commit = repo.head()?.peel_to_commit()?;
+    let signature = repo.signature()?;
+
+    // Amend the last commit, keeping its tree, parents and author
+    amend_head(repo, &commit, &commit.author(), &signature, new_message, &commit.tree()?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "rebase_tests.rs"]
+mod rebase_tests;
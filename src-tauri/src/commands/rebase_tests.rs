@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::rebase::{
+        abort_rebase_impl, continue_rebase_impl, skip_rebase_impl, start_interactive_rebase_impl,
+        RebaseProgress,
+    };
+    use crate::commands::test_support::{commit_file, init_repo};
+    use crate::domain::{CommitInfo, RebaseTodo};
+    use git2::{Oid, Repository};
+
+    fn head_messages(repo: &Repository) -> Vec<String> {
+        let mut revwalk = repo.revwalk().unwrap();
+        revwalk.push_head().unwrap();
+        revwalk
+            .map(|oid| repo.find_commit(oid.unwrap()).unwrap().message().unwrap().to_string())
+            .collect()
+    }
+
+    fn todo(repo: &Repository, command: &str, oid: Oid, new_message: Option<&str>) -> RebaseTodo {
+        let commit = repo.find_commit(oid).unwrap();
+        RebaseTodo {
+            id: oid.to_string(),
+            command: command.to_string(),
+            commit: CommitInfo {
+                id: oid.to_string(),
+                short_id: format!("{:.7}", oid),
+                message: commit.message().unwrap_or("").to_string(),
+                author: "Test User".to_string(),
+                timestamp: 0,
+                parents: Vec::new(),
+                refs: Vec::new(),
+            },
+            new_message: new_message.map(|s| s.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_reword_squash_and_drop_without_stopping() {
+        let (dir, repo) = init_repo();
+        let base = commit_file(&repo, "a.txt", "a\n", "base");
+        let c1 = commit_file(&repo, "b.txt", "b\n", "add b");
+        let c2 = commit_file(&repo, "c.txt", "c\n", "add c");
+        let c3 = commit_file(&repo, "d.txt", "d\n", "add d");
+
+        let todos = vec![
+            todo(&repo, "reword", c1, Some("add b (reworded)")),
+            todo(&repo, "squash", c2, Some("add b and c")),
+            todo(&repo, "drop", c3, None),
+        ];
+        let progress = start_interactive_rebase_impl(&repo, &base.to_string(), &todos).unwrap();
+
+        assert_eq!(progress, RebaseProgress::Finished);
+        assert!(repo.head().unwrap().is_branch());
+        assert_eq!(head_messages(&repo), vec!["add b and c".to_string(), "base".to_string()]);
+        assert!(dir.path().join("c.txt").exists());
+        assert!(!dir.path().join("d.txt").exists());
+        assert!(!repo.path().join("rebase-merge").exists());
+    }
+
+    #[test]
+    fn test_conflict_stops_and_continue_finishes() {
+        let (dir, repo) = init_repo();
+        let base = commit_file(&repo, "a.txt", "one\n", "base");
+        let c1 = commit_file(&repo, "a.txt", "two\n", "change to two");
+        let c2 = commit_file(&repo, "a.txt", "three\n", "change to three");
+
+        // Dropping c1 makes c2 conflict with base
+        let todos = vec![
+            todo(&repo, "drop", c1, None),
+            todo(&repo, "pick", c2, None),
+        ];
+        let progress = start_interactive_rebase_impl(&repo, &base.to_string(), &todos).unwrap();
+
+        assert_eq!(progress, RebaseProgress::Stopped);
+        assert!(repo.index().unwrap().has_conflicts());
+        assert_eq!(repo.state(), git2::RepositoryState::RebaseInteractive);
+        assert!(continue_rebase_impl(&repo).is_err());
+
+        std::fs::write(dir.path().join("a.txt"), "resolved\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+
+        assert_eq!(continue_rebase_impl(&repo).unwrap(), RebaseProgress::Finished);
+        assert_eq!(head_messages(&repo), vec!["change to three".to_string(), "base".to_string()]);
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+    }
+
+    #[test]
+    fn test_skip_drops_conflicting_commit() {
+        let (_dir, repo) = init_repo();
+        let base = commit_file(&repo, "a.txt", "one\n", "base");
+        let c1 = commit_file(&repo, "a.txt", "two\n", "change to two");
+        let c2 = commit_file(&repo, "a.txt", "three\n", "change to three");
+
+        let todos = vec![
+            todo(&repo, "drop", c1, None),
+            todo(&repo, "pick", c2, None),
+        ];
+        start_interactive_rebase_impl(&repo, &base.to_string(), &todos).unwrap();
+
+        assert_eq!(skip_rebase_impl(&repo).unwrap(), RebaseProgress::Finished);
+        assert_eq!(head_messages(&repo), vec!["base".to_string()]);
+    }
+
+    #[test]
+    fn test_edit_stops_and_abort_restores_branch() {
+        let (_dir, repo) = init_repo();
+        let base = commit_file(&repo, "a.txt", "a\n", "base");
+        let c1 = commit_file(&repo, "b.txt", "b\n", "add b");
+        let c2 = commit_file(&repo, "c.txt", "c\n", "add c");
+
+        let todos = vec![
+            todo(&repo, "edit", c1, None),
+            todo(&repo, "pick", c2, None),
+        ];
+        let progress = start_interactive_rebase_impl(&repo, &base.to_string(), &todos).unwrap();
+
+        assert_eq!(progress, RebaseProgress::Stopped);
+        assert!(repo.head_detached().unwrap());
+
+        abort_rebase_impl(&repo).unwrap();
+
+        assert!(repo.head().unwrap().is_branch());
+        assert_eq!(repo.head().unwrap().target(), Some(c2));
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+    }
+}
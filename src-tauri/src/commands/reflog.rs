@@ -0,0 +1,176 @@
+use crate::domain::ReflogEntry;
+use crate::error::{AppError, Result};
+use git2::{Oid, Repository};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResetMode {
+    Soft,
+    Mixed,
+    Hard,
+}
+
+impl ResetMode {
+    fn parse(mode: &str) -> Result<Self> {
+        match mode {
+            "soft" => Ok(ResetMode::Soft),
+            "mixed" => Ok(ResetMode::Mixed),
+            "hard" => Ok(ResetMode::Hard),
+            _ => Err(AppError::InvalidInput(format!("Unknown reset mode: {}", mode))),
+        }
+    }
+
+    fn reset_type(self) -> git2::ResetType {
+        match self {
+            ResetMode::Soft => git2::ResetType::Soft,
+            ResetMode::Mixed => git2::ResetType::Mixed,
+            ResetMode::Hard => git2::ResetType::Hard,
+        }
+    }
+}
+
+/// Full name of the reflog for `HEAD`, a branch name or a full ref name
+fn reflog_name(reference: Option<&str>) -> String {
+    match reference {
+        None | Some("") | Some("HEAD") => "HEAD".to_string(),
+        Some(name) if name.starts_with("refs/") => name.to_string(),
+        Some(name) => format!("refs/heads/{}", name),
+    }
+}
+
+fn selector(refname: &str, index: usize) -> String {
+    format!("{}@{{{}}}", refname.trim_start_matches("refs/heads/"), index)
+}
+
+/// List references that have a reflog: HEAD first, then local branches
+#[tauri::command]
+pub async fn get_reflog_refs(path: String) -> std::result::Result<Vec<String>, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_reflog_refs_impl(&repo).map_err(|e| e.to_string())
+}
+
+fn get_reflog_refs_impl(repo: &Repository) -> Result<Vec<String>> {
+    let mut refs = vec!["HEAD".to_string()];
+
+    for branch in repo.branches(Some(git2::BranchType::Local))? {
+        let (branch, _) = branch?;
+        let Some(name) = branch.get().name() else {
+            continue;
+        };
+        if repo.reference_has_log(name)? {
+            if let Some(short) = branch.name()? {
+                refs.push(short.to_string());
+            }
+        }
+    }
+
+    Ok(refs)
+}
+
+/// Get reflog entries of HEAD (default) or a branch, newest first
+#[tauri::command]
+pub async fn get_reflog(
+    path: String,
+    reference: Option<String>,
+    limit: Option<usize>,
+) -> std::result::Result<Vec<ReflogEntry>, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_reflog_impl(&repo, reference.as_deref(), limit).map_err(|e| e.to_string())
+}
+
+pub(crate) fn get_reflog_impl(repo: &Repository, reference: Option<&str>, limit: Option<usize>) -> Result<Vec<ReflogEntry>> {
+    let name = reflog_name(reference);
+    let reflog = repo.reflog(&name)?;
+
+    let entries = reflog
+        .iter()
+        .take(limit.unwrap_or(usize::MAX))
+        .enumerate()
+        .map(|(index, entry)| {
+            let committer = entry.committer();
+            let summary = repo
+                .find_commit(entry.id_new())
+                .ok()
+                .and_then(|commit| commit.summary().map(|s| s.to_string()));
+
+            ReflogEntry {
+                index,
+                selector: selector(&name, index),
+                old_id: entry.id_old().to_string(),
+                new_id: entry.id_new().to_string(),
+                message: entry.message().unwrap_or("").to_string(),
+                committer: committer.name().unwrap_or("Unknown").to_string(),
+                committer_email: committer.email().unwrap_or("").to_string(),
+                timestamp: committer.when().seconds(),
+                summary,
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+/// Move HEAD or a branch back to the commit recorded in a reflog entry.
+///
+/// `reference`/`index` pick the entry (e.g. `HEAD`, 3 for `HEAD@{3}`).
+/// `target` is the branch to move; without it the current HEAD is reset.
+/// `mode` (soft/mixed/hard) applies when the target is checked out; other
+/// branches are just moved, and recreated if they were deleted.
+#[tauri::command]
+pub async fn restore_from_reflog(
+    path: String,
+    reference: Option<String>,
+    index: usize,
+    target: Option<String>,
+    mode: String,
+) -> std::result::Result<String, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    let mode = ResetMode::parse(&mode).map_err(|e| e.to_string())?;
+    restore_from_reflog_impl(&repo, reference.as_deref(), index, target.as_deref(), mode)
+        .map(|oid| oid.to_string())
+        .map_err(|e| e.to_string())
+}
+
+fn restore_from_reflog_impl(
+    repo: &Repository,
+    reference: Option<&str>,
+    index: usize,
+    target: Option<&str>,
+    mode: ResetMode,
+) -> Result<Oid> {
+    let name = reflog_name(reference);
+    let selector = selector(&name, index);
+    let reflog = repo.reflog(&name)?;
+    let entry = reflog
+        .get(index)
+        .ok_or_else(|| AppError::InvalidInput(format!("No reflog entry {}", selector)))?;
+
+    let oid = entry.id_new();
+    if oid.is_zero() {
+        return Err(AppError::InvalidInput(format!("{} records a deleted reference", selector)));
+    }
+    let commit = repo
+        .find_commit(oid)
+        .map_err(|_| AppError::InvalidInput(format!("Commit {} from {} no longer exists", oid, selector)))?;
+
+    let head_branch = repo
+        .head()
+        .ok()
+        .filter(|head| head.is_branch())
+        .and_then(|head| head.name().map(|s| s.to_string()));
+
+    match target.filter(|t| *t != "HEAD").map(|t| reflog_name(Some(t))) {
+        // A branch that is not checked out: only the ref moves
+        Some(refname) if Some(&refname) != head_branch.as_ref() => {
+            repo.reference(&refname, oid, true, &format!("reset: moving to {}", selector))?;
+        }
+        _ => {
+            repo.reset(commit.as_object(), mode.reset_type(), None)?;
+        }
+    }
+
+    Ok(oid)
+}
+
+#[cfg(test)]
+#[path = "reflog_tests.rs"]
+mod reflog_tests;
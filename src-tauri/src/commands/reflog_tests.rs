@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::reflog::{get_reflog_impl, get_reflog_refs_impl, restore_from_reflog_impl, ResetMode};
+    use crate::commands::test_support::{commit_file, init_repo};
+
+    #[test]
+    fn test_reflog_lists_head_entries_newest_first() {
+        let (_dir, repo) = init_repo();
+        let first = commit_file(&repo, "a.txt", "a\n", "first");
+        let second = commit_file(&repo, "b.txt", "b\n", "second");
+
+        let entries = get_reflog_impl(&repo, None, None).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].selector, "HEAD@{0}");
+        assert_eq!(entries[0].new_id, second.to_string());
+        assert_eq!(entries[0].old_id, first.to_string());
+        assert_eq!(entries[0].summary.as_deref(), Some("second"));
+        assert_eq!(entries[0].committer_email, "test@example.com");
+        assert_eq!(entries[1].new_id, first.to_string());
+
+        assert_eq!(get_reflog_impl(&repo, None, Some(1)).unwrap().len(), 1);
+
+        let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+        assert_eq!(get_reflog_refs_impl(&repo).unwrap(), vec!["HEAD".to_string(), branch.clone()]);
+        let branch_entries = get_reflog_impl(&repo, Some(&branch), None).unwrap();
+        assert_eq!(branch_entries[0].selector, format!("{}@{{0}}", branch));
+    }
+
+    #[test]
+    fn test_restore_head_hard_undoes_commit() {
+        let (dir, repo) = init_repo();
+        let first = commit_file(&repo, "a.txt", "a\n", "first");
+        commit_file(&repo, "b.txt", "b\n", "second");
+
+        let restored = restore_from_reflog_impl(&repo, None, 1, None, ResetMode::Hard).unwrap();
+
+        assert_eq!(restored, first);
+        assert_eq!(repo.head().unwrap().target(), Some(first));
+        assert!(!dir.path().join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_restore_branch_mixed_undoes_reset() {
+        let (dir, repo) = init_repo();
+        let first = commit_file(&repo, "a.txt", "a\n", "first");
+        let second = commit_file(&repo, "b.txt", "b\n", "second");
+        let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+
+        // A botched hard reset
+        repo.reset(repo.find_commit(first).unwrap().as_object(), git2::ResetType::Hard, None).unwrap();
+
+        restore_from_reflog_impl(&repo, Some(&branch), 1, Some(&branch), ResetMode::Mixed).unwrap();
+
+        assert_eq!(repo.head().unwrap().target(), Some(second));
+        // Mixed leaves the working tree alone
+        assert!(!dir.path().join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_restore_recreates_deleted_branch_from_head_reflog() {
+        let (_dir, repo) = init_repo();
+        let base = commit_file(&repo, "a.txt", "a\n", "base");
+        let main_ref = repo.head().unwrap().name().unwrap().to_string();
+
+        repo.branch("feature", &repo.find_commit(base).unwrap(), false).unwrap();
+        repo.set_head("refs/heads/feature").unwrap();
+        let feature = commit_file(&repo, "f.txt", "f\n", "feature work");
+
+        repo.set_head(&main_ref).unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        repo.find_branch("feature", git2::BranchType::Local).unwrap().delete().unwrap();
+
+        let index = get_reflog_impl(&repo, None, None).unwrap()
+            .iter()
+            .position(|e| e.new_id == feature.to_string())
+            .unwrap();
+        restore_from_reflog_impl(&repo, None, index, Some("feature"), ResetMode::Hard).unwrap();
+
+        assert_eq!(repo.refname_to_id("refs/heads/feature").unwrap(), feature);
+        // HEAD stays where it was
+        assert_eq!(repo.head().unwrap().name(), Some(main_ref.as_str()));
+        assert_eq!(repo.head().unwrap().target(), Some(base));
+    }
+
+    #[test]
+    fn test_restore_rejects_missing_entry_and_bad_mode() {
+        let (_dir, repo) = init_repo();
+        commit_file(&repo, "a.txt", "a\n", "first");
+
+        assert!(restore_from_reflog_impl(&repo, None, 5, None, ResetMode::Soft).is_err());
+        assert!(ResetMode::parse("keep").is_err());
+    }
+}
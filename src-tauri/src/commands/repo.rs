@@ -1,5 +1,14 @@
-use crate::domain::{RepositoryInfo, RepoStatus, StatusItem, BranchInfo, CommitInfo, LocalBranch, TagInfo, RemoteInfo, ConflictInfo, MergeState, RebaseState, RebaseTodo, DiffLine, DiffHunk, FileDiff};
+use crate::domain::{RepositoryInfo, RepoStatus, StatusItem, FileStatus, RenameOptions, BranchInfo, CommitHistoryPage, CommitInfo, HistoryPageOptions, LocalBranch, TagInfo, RemoteInfo, PushRefResult, MergeState, MergeOptions, FastForward, ConflictFavor};
 use crate::error::{AppError, Result};
+use super::auth::GitAuth;
+use super::progress::Operation;
+use super::commit::create_commit;
+use super::conflict::read_conflicts;
+use super::diff::{find_similar, rename_diff_options, status_options};
+use super::graph::commit_refs;
+use super::history_cache::{commit_order, CommitOrder};
+use super::rebase::{start_pick_rebase, RebaseProgress};
+use super::worktree::main_worktree_path;
 use git2::{Repository, StatusOptions};
 use ignore::WalkBuilder;
 use std::path::Path;
@@ -30,7 +39,9 @@ fn scan_repositories_impl(root_path: &str) -> Result<Vec<RepositoryInfo>> {
     for entry in walk {
         if let Ok(entry) = entry {
             let path = entry.path();
-            if path.join(".git").is_dir() {
+            let dot_git = path.join(".git");
+            // Linked worktrees have a `.git` file; so do submodules, which we skip
+            if dot_git.is_dir() || (dot_git.is_file() && is_linked_worktree(path)) {
                 git_dirs.push(path.to_path_buf());
             }
         }
@@ -42,13 +53,41 @@ fn scan_repositories_impl(root_path: &str) -> Result<Vec<RepositoryInfo>> {
         .map(|path| get_repository_info(path))
         .collect();
 
-    // Sort by name
+    // Point linked worktrees at their main repository as it was scanned
     let mut repos = repos?;
-    repos.sort_by(|a, b| a.name.cmp(&b.name));
+    let scanned: std::collections::HashMap<std::path::PathBuf, (String, String)> = repos
+        .iter()
+        .map(|r| {
+            let path = Path::new(&r.path);
+            let key = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+            (key, (r.path.clone(), r.name.clone()))
+        })
+        .collect();
+    let mut main_names = std::collections::HashMap::new();
+    for repo in repos.iter_mut() {
+        if let Some((path, name)) = repo.main_repository.as_ref().and_then(|main| scanned.get(Path::new(main))) {
+            repo.main_repository = Some(path.clone());
+            main_names.insert(path.clone(), name.clone());
+        }
+    }
+
+    // Sort by name, keeping linked worktrees right after their main repository
+    repos.sort_by_cached_key(|r| {
+        let group = r.main_repository.as_ref()
+            .and_then(|main| main_names.get(main))
+            .unwrap_or(&r.name)
+            .clone();
+        let group_path = r.main_repository.clone().unwrap_or_else(|| r.path.clone());
+        (group, group_path, r.main_repository.is_some(), r.name.clone())
+    });
 
     Ok(repos)
 }
 
+fn is_linked_worktree(path: &Path) -> bool {
+    Repository::open(path).map(|repo| repo.is_worktree()).unwrap_or(false)
+}
+
 /// Get info for a single repository
 fn get_repository_info(path: &Path) -> Result<RepositoryInfo> {
     let repo = Repository::open(path)?;
@@ -67,11 +106,15 @@ fn get_repository_info(path: &Path) -> Result<RepositoryInfo> {
         Err(_) => None,
     };
 
-    let status = get_repo_status_impl(&repo)?;
+    let status = get_repo_status_impl(&repo, &RenameOptions::default())?;
 
     // Get ahead/behind counts
     let (ahead, behind) = get_ahead_behind(&repo)?;
 
+    let main_repository = main_worktree_path(&repo)
+        .map(|main| main.canonicalize().unwrap_or(main))
+        .map(|main| main.to_string_lossy().to_string());
+
     Ok(RepositoryInfo {
         path: path.to_string_lossy().to_string(),
         name,
@@ -82,6 +125,7 @@ fn get_repository_info(path: &Path) -> Result<RepositoryInfo> {
         untracked_count: status.untracked.len(),
         ahead,
         behind,
+        main_repository,
     })
 }
 
@@ -113,15 +157,32 @@ fn get_ahead_behind(repo: &Repository) -> Result<(usize, usize)> {
 
 /// Get detailed status for a specific repository
 #[tauri::command]
-pub async fn get_repo_status(path: String) -> std::result::Result<RepoStatus, String> {
+pub async fn get_repo_status(path: String, renames: Option<RenameOptions>) -> std::result::Result<RepoStatus, String> {
     let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    get_repo_status_impl(&repo).map_err(|e| e.to_string())
+    get_repo_status_impl(&repo, &renames.unwrap_or_default()).map_err(|e| e.to_string())
 }
 
-fn get_repo_status_impl(repo: &Repository) -> Result<RepoStatus> {
-    let mut status_opts = StatusOptions::new();
-    status_opts.include_untracked(true);
-    status_opts.recurse_untracked_dirs(true);
+const INDEX_FLAGS: git2::Status = git2::Status::INDEX_NEW
+    .union(git2::Status::INDEX_MODIFIED)
+    .union(git2::Status::INDEX_DELETED)
+    .union(git2::Status::INDEX_RENAMED)
+    .union(git2::Status::INDEX_TYPECHANGE);
+
+/// Item at the new path of `delta`, with the old path for renames
+fn status_item(path: &str, status: git2::Status, delta: Option<git2::DiffDelta>) -> StatusItem {
+    let path_of = |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().to_string());
+    match delta {
+        Some(delta) => StatusItem {
+            path: path_of(delta.new_file()).unwrap_or_else(|| path.to_string()),
+            status: status.into(),
+            old_path: (delta.status() == git2::Delta::Renamed).then(|| path_of(delta.old_file())).flatten(),
+        },
+        None => StatusItem { path: path.to_string(), status: status.into(), old_path: None },
+    }
+}
+
+fn get_repo_status_impl(repo: &Repository, renames: &RenameOptions) -> Result<RepoStatus> {
+    let mut status_opts = status_options(renames);
     status_opts.recurse_ignored_dirs(false);
 
     let statuses = repo.statuses(Some(&mut status_opts))?;
@@ -130,22 +191,13 @@ fn get_repo_status_impl(repo: &Repository) -> Result<RepoStatus> {
 
     for entry in statuses.iter() {
         let status = entry.status();
-        let path = entry.path().unwrap_or("").to_string();
-
-        let item = StatusItem {
-            path: path.clone(),
-            status: status.into(),
-            old_path: None,
-        };
+        let path = entry.path().unwrap_or("");
 
-        // Categorize the file
-        if status.is_index_deleted()
-            || status.is_index_modified()
-            || status.is_index_renamed()
-            || status.is_index_new()
-            || status.is_index_typechange()
-        {
-            repo_status.staged.push(item.clone());
+        // Categorize the file; staged and unstaged items only look at their
+        // half of the flags so a staged rename shows as modified when it
+        // was also edited afterwards
+        if status.intersects(INDEX_FLAGS) {
+            repo_status.staged.push(status_item(path, status & INDEX_FLAGS, entry.head_to_index()));
         }
 
         if status.is_wt_deleted()
@@ -153,21 +205,44 @@ fn get_repo_status_impl(repo: &Repository) -> Result<RepoStatus> {
             || status.is_wt_renamed()
             || status.is_wt_typechange()
         {
-            repo_status.unstaged.push(item.clone());
+            repo_status.unstaged.push(status_item(path, status - INDEX_FLAGS, entry.index_to_workdir()));
         }
 
         if status.is_wt_new() {
-            repo_status.untracked.push(item.clone());
+            repo_status.untracked.push(status_item(path, status, entry.index_to_workdir()));
         }
 
         if status.is_conflicted() {
-            repo_status.conflicted.push(item);
+            repo_status.conflicted.push(status_item(path, status, None));
         }
     }
 
+    if renames.copies {
+        mark_staged_copies(repo, renames, &mut repo_status.staged)?;
+    }
+
     Ok(repo_status)
 }
 
+/// Status has no copy detection, so look for copies between HEAD and the
+/// index separately
+fn mark_staged_copies(repo: &Repository, renames: &RenameOptions, staged: &mut [StatusItem]) -> Result<()> {
+    let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+    let mut diff = repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut rename_diff_options(renames)))?;
+    find_similar(&mut diff, renames)?;
+
+    for delta in diff.deltas().filter(|d| d.status() == git2::Delta::Copied) {
+        let (Some(from), Some(to)) = (delta.old_file().path(), delta.new_file().path()) else {
+            continue;
+        };
+        if let Some(item) = staged.iter_mut().find(|item| Path::new(&item.path) == to) {
+            item.status = FileStatus::Copied;
+            item.old_path = Some(from.to_string_lossy().to_string());
+        }
+    }
+    Ok(())
+}
+
 /// Get branch info for a repository
 #[tauri::command]
 pub async fn get_branch_info(path: String) -> std::result::Result<BranchInfo, String> {
@@ -242,81 +317,48 @@ pub async fn get_commit_history_paginated(
 }
 
 fn get_commit_history_impl(repo: &Repository, skip: usize, limit: usize) -> Result<Vec<CommitInfo>> {
-    let mut revwalk = repo.revwalk()?;
-    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
-    
-    // Push HEAD
-    if let Ok(head) = repo.head() {
-        if let Ok(target) = head.target().ok_or(AppError::InvalidInput("No head commit".to_string())) {
-            revwalk.push(target)?;
-        }
-    }
-
-    // Push all local branches to graph
-    // This allows us to see commits from other branches
-    if let Ok(branches) = repo.branches(Some(git2::BranchType::Local)) {
-        for branch in branches {
-            if let Ok((branch, _)) = branch {
-                if let Some(target) = branch.get().target() {
-                    revwalk.push(target)?;
-                }
-            }
-        }
-    }
-
-    let mut commits = Vec::new();
-    
-    // Get all references to map commit IDs to branch names/tags
-    let mut ref_map: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
-    if let Ok(references) = repo.references() {
-        for reference in references {
-            if let Ok(reference) = reference {
-                if let Some(target) = reference.target() {
-                    let name = reference.shorthand().unwrap_or("").to_string();
-                    if !name.is_empty() && !name.starts_with("origin/") { // Filter remote refs for now to reduce clutter? Or keep them?
-                         ref_map.entry(target.to_string()).or_default().push(name);
-                    }
-                }
-            }
-        }
-    }
-
-    for (i, oid) in revwalk.enumerate() {
-        // Skip commits for pagination
-        if i < skip {
-            continue;
-        }
-        // Stop when we've collected enough commits
-        if i >= skip + limit {
-            break;
-        }
+    let order = commit_order(repo, false)?;
+    let end = order.oids.len().min(skip.saturating_add(limit));
+    history_slice(repo, &order, skip.min(end), end)
+}
 
-        let oid = oid?;
-        let commit = repo.find_commit(oid)?;
-
-        let timestamp = commit.time().seconds();
-        let author = commit.author().name().unwrap_or("Unknown").to_string();
-        let message = commit.message().unwrap_or("").to_string();
-        let short_id = format!("{:.7}", oid);
-        
-        // Get parents
-        let parents = commit.parent_ids().map(|id| id.to_string()).collect();
-        
-        // Get refs
-        let refs = ref_map.get(&oid.to_string()).cloned().unwrap_or_default();
-
-        commits.push(CommitInfo {
-            id: oid.to_string(),
-            short_id,
-            message,
-            author,
-            timestamp,
-            parents,
-            refs,
-        });
-    }
+/// Page through commit history. Pass the returned `next_cursor` back as
+/// `cursor` to continue after the last commit of the page
+#[tauri::command]
+pub async fn get_commit_history_page(
+    path: String,
+    cursor: Option<String>,
+    limit: usize,
+    options: Option<HistoryPageOptions>,
+) -> std::result::Result<CommitHistoryPage, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    get_commit_history_page_impl(&repo, cursor.as_deref(), limit, &options.unwrap_or_default()).map_err(|e| e.to_string())
+}
 
-    Ok(commits)
+pub(crate) fn get_commit_history_page_impl(
+    repo: &Repository,
+    cursor: Option<&str>,
+    limit: usize,
+    options: &HistoryPageOptions,
+) -> Result<CommitHistoryPage> {
+    let order = commit_order(repo, options.disk_cache)?;
+    let start = order.resume(cursor)?;
+    let end = order.oids.len().min(start.saturating_add(limit));
+    let commits = history_slice(repo, &order, start, end)?;
+    let next_cursor = (end < order.oids.len()).then(|| commits.last().map(|c| c.id.clone())).flatten();
+    Ok(CommitHistoryPage { commits, next_cursor })
+}
+
+fn history_slice(repo: &Repository, order: &CommitOrder, start: usize, end: usize) -> Result<Vec<CommitInfo>> {
+    // Map commit IDs to local branch and tag names
+    let ref_map = commit_refs(repo, false);
+    (start..end)
+        .map(|index| {
+            let mut commit = order.info(repo, index)?;
+            commit.refs = ref_map.get(&order.oids[index]).cloned().unwrap_or_default();
+            Ok(commit)
+        })
+        .collect()
 }
 
 /// Search commits based on query
@@ -326,124 +368,75 @@ pub async fn search_commits(
     query: crate::domain::CommitSearchQuery,
 ) -> std::result::Result<Vec<CommitInfo>, String> {
     let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    
-    // We run the search on a blocking thread because it can be CPU intensive
-    // and we don't want to block the async runtime
-    let result = std::thread::spawn(move || {
-        search_commits_impl(&repo, query)
-    }).join();
-    
-    match result {
-        Ok(res) => res.map_err(|e| e.to_string()),
-        Err(_) => Err("Search thread panicked".to_string()),
-    }
+
+    // Searching can walk the whole history, so keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || search_commits_impl(&repo, query))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
 }
 
-fn search_commits_impl(repo: &Repository, query: crate::domain::CommitSearchQuery) -> Result<Vec<CommitInfo>> {
-    let mut revwalk = repo.revwalk()?;
-    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
-    
-    // Push HEAD
-    if let Ok(head) = repo.head() {
-        if let Ok(target) = head.target().ok_or(AppError::InvalidInput("No head commit".to_string())) {
-            revwalk.push(target)?;
-        }
-    }
-    
-    // Push all local branches to broaden search scope
-    if let Ok(branches) = repo.branches(Some(git2::BranchType::Local)) {
-        for branch in branches {
-            if let Ok((branch, _)) = branch {
-                if let Some(target) = branch.get().target() {
-                    revwalk.push(target)?;
-                }
-            }
-        }
-    }
+pub(crate) fn search_commits_impl(repo: &Repository, query: crate::domain::CommitSearchQuery) -> Result<Vec<CommitInfo>> {
+    // Search the same history as the log: HEAD and all local branches
+    let order = commit_order(repo, query.disk_cache.unwrap_or(false))?;
 
     let mut commits = Vec::new();
     let limit = query.limit.unwrap_or(100);
-    
-    for oid in revwalk {
-        let oid = oid?;
-        let commit = repo.find_commit(oid)?;
-        
+    let author_query = query.author.as_ref().map(|a| a.to_lowercase());
+    let q_lower = query.query.as_ref().map(|q| q.to_lowercase());
+
+    for index in 0..order.oids.len() {
+        // Served from the index without loading the commit when cached
+        let commit = order.info(repo, index)?;
+
         // 1. Date filter
-        let timestamp = commit.time().seconds();
-        if let Some(from) = query.date_from {
-            if timestamp < from {
-                continue;
-            }
-        }
-        if let Some(to) = query.date_to {
-            if timestamp > to {
-                continue;
-            }
+        if query.date_from.is_some_and(|from| commit.timestamp < from)
+            || query.date_to.is_some_and(|to| commit.timestamp > to)
+        {
+            continue;
         }
-        
+
         // 2. Author filter
-        let author = commit.author().name().unwrap_or("Unknown").to_string();
-        if let Some(ref author_query) = query.author {
-            if !author.to_lowercase().contains(&author_query.to_lowercase()) {
+        if let Some(ref author_query) = author_query {
+            if !commit.author.to_lowercase().contains(author_query) {
                 continue;
             }
         }
-        
-        // 3. Message/Query filter
-        let message = commit.message().unwrap_or("").to_string();
-        if let Some(ref q) = query.query {
-            let q_lower = q.to_lowercase();
-            // Search in message
-            let match_msg = message.to_lowercase().contains(&q_lower);
-            // Also search in author if generic query provided
-            let match_author = author.to_lowercase().contains(&q_lower);
-            // Also search in hash
-            let match_hash = oid.to_string().starts_with(&q_lower);
-            
+
+        // 3. Message/Query filter: message, author or hash prefix
+        if let Some(ref q_lower) = q_lower {
+            let match_msg = commit.message.to_lowercase().contains(q_lower);
+            let match_author = commit.author.to_lowercase().contains(q_lower);
+            let match_hash = commit.id.starts_with(q_lower.as_str());
+
             if !match_msg && !match_author && !match_hash {
                 continue;
             }
         }
-        
-        // 4. File Path filter
+
+        // 4. File Path filter: did this commit touch the path?
         // This is expensive as we need to diff against parent
         if let Some(ref path_query) = query.path {
-             let tree = commit.tree()?;
-             // Check if file exists in this commit's tree
-             // This is a simple check: "Does this file exist in this snapshot?"
-             // Or "Did this commit touch this file?" -> We need to diff with parent.
-             // Usually users want "Did this commit touch this file".
-             
-             let parent = commit.parent(0).ok();
-             let parent_tree = parent.as_ref().and_then(|p| p.tree().ok());
-             
-             let mut diff_opts = git2::DiffOptions::new();
-             diff_opts.pathspec(path_query);
-             
-             let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut diff_opts))?;
-             if diff.deltas().len() == 0 {
-                 continue; // No changes to specified path in this commit
-             }
+            let full = repo.find_commit(order.oids[index])?;
+            let parent_tree = full.parent(0).ok().and_then(|p| p.tree().ok());
+
+            let mut diff_opts = git2::DiffOptions::new();
+            diff_opts.pathspec(path_query);
+
+            let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&full.tree()?), Some(&mut diff_opts))?;
+            if diff.deltas().len() == 0 {
+                continue;
+            }
         }
-        
-        let short_id = format!("{:.7}", oid);
-        let parents = commit.parent_ids().map(|id| id.to_string()).collect();
-        
-        commits.push(CommitInfo {
-            id: oid.to_string(),
-            short_id,
-            message,
-            author,
-            timestamp,
-            parents,
-            refs: Vec::new(), // optimizing: don't load refs for search results for now
-        });
-        
+
+        // Refs are not loaded for search results
+        commits.push(commit);
+
         if commits.len() >= limit {
             break;
         }
     }
-    
+
     Ok(commits)
 }
 
@@ -577,14 +570,18 @@ pub async fn create_branch(path: String, new_branch_name: String, base_branch_na
 /// Publish current branch (set upstream)
 #[tauri::command]
 pub async fn publish_branch(
+    app: tauri::AppHandle,
     path: String,
     branch_name: String,
     remote: String,
     username: Option<String>,
     password: Option<String>,
-) -> std::result::Result<(), String> {
+    operation_id: Option<String>,
+) -> std::result::Result<Vec<PushRefResult>, String> {
     let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    publish_branch_impl(&repo, &branch_name, &remote, username, password).map_err(|e| e.to_string())
+    let auth = GitAuth::for_repo(&repo, username, password).with_keyring(&app);
+    let operation = Operation::start(Some(&app), operation_id, "publish");
+    publish_branch_impl(&repo, &branch_name, &remote, &auth, &operation).map_err(|e| e.to_string())
 }
 
 /// Get the default username for git operations from config
@@ -651,9 +648,9 @@ fn publish_branch_impl(
     repo: &Repository,
     branch_name: &str,
     remote: &str,
-    username: Option<String>,
-    password: Option<String>,
-) -> Result<()> {
+    auth: &GitAuth,
+    operation: &Operation,
+) -> Result<Vec<PushRefResult>> {
     // Find the remote
     let mut remote_obj = repo.find_remote(remote)
         .map_err(|_| AppError::InvalidInput(format!("Remote '{}' not found", remote)))?;
@@ -661,436 +658,346 @@ fn publish_branch_impl(
     // Prepare the push refspec
     let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
 
-    // Get repository config for credential helper
-    let config = repo.config()?;
-
-    // Clone credentials for both push and fetch callbacks
-    let auth_username_push = username.clone();
-    let auth_password_push = password.clone();
-    let auth_username_fetch = username.clone();
-    let auth_password_fetch = password.clone();
-
-    // Set up remote callbacks for authentication
-    let mut callbacks = git2::RemoteCallbacks::new();
-
-    // Handle credentials for SSH/HTTPS
-    callbacks.credentials(move |url, username_from_url, allowed_types| {
-        // Get username from URL or default to "git"
-        let default_username = username_from_url.unwrap_or("git");
-
-        // If username and password are provided, use them
-        if let (Some(user), Some(pass)) = (&auth_username_push, &auth_password_push) {
-            return git2::Cred::userpass_plaintext(user, pass);
-        }
-
-        // Try different authentication methods based on what's allowed
-        if allowed_types.contains(git2::CredentialType::SSH_KEY) {
-            // Try SSH agent first for SSH URLs
-            git2::Cred::ssh_key_from_agent(default_username)
-        } else if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            // For HTTPS, try to use git-credential-helper
-            git2::Cred::credential_helper(&config, url, Some(default_username))
-        } else if allowed_types.contains(git2::CredentialType::DEFAULT) {
-            // Try default credential helper
-            git2::Cred::credential_helper(&config, url, Some(default_username))
-        } else {
-            Err(git2::Error::from_str("no authentication method available"))
-        }
-    });
-
-    // Configure push options
-    let mut push_options = git2::PushOptions::new();
-    push_options.remote_callbacks(callbacks);
-
-    // Perform the push
-    remote_obj.push(&[&refspec], Some(&mut push_options))?;
+    // Perform the push; a rejected ref leaves the branch unpublished
+    let results = push_refspecs(&mut remote_obj, &[refspec], auth, operation)?;
+    if results.iter().any(|r| !r.accepted) {
+        return Ok(results);
+    }
 
     // After successful push, set upstream
     let mut branch = repo.find_branch(branch_name, git2::BranchType::Local)?;
     branch.set_upstream(Some(&format!("{}/{}", remote, branch_name)))?;
 
-    // Fetch to update remote-tracking branch instead of manually updating it
-    // This ensures the remote-tracking branch reflects the actual remote state
-    let config_for_fetch = repo.config()?;
-    let mut fetch_callbacks = git2::RemoteCallbacks::new();
-    fetch_callbacks.credentials(move |url, username_from_url, allowed_types| {
-        let default_username = username_from_url.unwrap_or("git");
-        if let (Some(user), Some(pass)) = (&auth_username_fetch, &auth_password_fetch) {
-            return git2::Cred::userpass_plaintext(user, pass);
-        }
-        if allowed_types.contains(git2::CredentialType::SSH_KEY) {
-            git2::Cred::ssh_key_from_agent(default_username)
-        } else if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            git2::Cred::credential_helper(&config_for_fetch, url, Some(default_username))
-        } else if allowed_types.contains(git2::CredentialType::DEFAULT) {
-            git2::Cred::credential_helper(&config_for_fetch, url, Some(default_username))
-        } else {
-            Err(git2::Error::from_str("no authentication method available"))
-        }
-    });
-
-    let mut fetch_options = git2::FetchOptions::new();
-    fetch_options.remote_callbacks(fetch_callbacks);
-
-    // Fetch the specific branch to update the remote-tracking reference
-    let fetch_refspec = format!("{}:{}", branch_name, branch_name);
-    if let Err(e) = remote_obj.fetch(&[&fetch_refspec], Some(&mut fetch_options), None) {
-        // If fetch fails (e.g., remote doesn't allow anonymous fetch), log but don't fail the push
-        eprintln!("Warning: Failed to fetch after push: {}", e);
-    }
-
-    Ok(())
+    fetch_after_push(&mut remote_obj, branch_name, auth, operation);
+    Ok(results)
 }
 
 /// Push commits to remote (for already published branches)
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn push_branch(
+    app: tauri::AppHandle,
     path: String,
     branch_name: String,
     remote: String,
     username: Option<String>,
     password: Option<String>,
-) -> std::result::Result<(), String> {
+    operation_id: Option<String>,
+    force_with_lease: Option<bool>,
+) -> std::result::Result<Vec<PushRefResult>, String> {
     let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    push_branch_impl(&repo, &branch_name, &remote, username, password).map_err(|e| e.to_string())
+    let auth = GitAuth::for_repo(&repo, username, password).with_keyring(&app);
+    let operation = Operation::start(Some(&app), operation_id, "push");
+    push_branch_impl(&repo, &branch_name, &remote, force_with_lease.unwrap_or(false), &auth, &operation)
+        .map_err(|e| e.to_string())
 }
 
 fn push_branch_impl(
     repo: &Repository,
     branch_name: &str,
     remote: &str,
-    username: Option<String>,
-    password: Option<String>,
-) -> Result<()> {
+    force_with_lease: bool,
+    auth: &GitAuth,
+    operation: &Operation,
+) -> Result<Vec<PushRefResult>> {
     // Find the remote
     let mut remote_obj = repo.find_remote(remote)
         .map_err(|_| AppError::InvalidInput(format!("Remote '{}' not found", remote)))?;
 
     // Prepare the push refspec (push to existing remote branch)
-    let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
-
-    // Get repository config for credential helper
-    let config = repo.config()?;
-
-    // Clone credentials for both push and fetch callbacks
-    let auth_username_push = username.clone();
-    let auth_password_push = password.clone();
-    let auth_username_fetch = username.clone();
-    let auth_password_fetch = password.clone();
-
-    // Set up remote callbacks for authentication
-    let mut callbacks = git2::RemoteCallbacks::new();
-
-    // Handle credentials for SSH/HTTPS
-    callbacks.credentials(move |url, username_from_url, allowed_types| {
-        // Get username from URL or default to "git"
-        let default_username = username_from_url.unwrap_or("git");
-
-        // If username and password are provided, use them
-        if let (Some(user), Some(pass)) = (&auth_username_push, &auth_password_push) {
-            return git2::Cred::userpass_plaintext(user, pass);
-        }
+    let remote_ref = format!("refs/heads/{}", branch_name);
+    let mut refspec = format!("refs/heads/{}:{}", branch_name, remote_ref);
+
+    if force_with_lease {
+        // Like --force-with-lease: overwrite only if the remote still has what we last fetched
+        let tracking = format!("refs/remotes/{}/{}", remote, branch_name);
+        let expected = repo.refname_to_id(&tracking).unwrap_or_else(|_| git2::Oid::zero());
+        operation.require_lease(&remote_ref, expected);
+        refspec.insert(0, '+');
+    }
 
-        // Try different authentication methods based on what's allowed
-        if allowed_types.contains(git2::CredentialType::SSH_KEY) {
-            // Try SSH agent first for SSH URLs
-            git2::Cred::ssh_key_from_agent(default_username)
-        } else if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            // For HTTPS, try to use git-credential-helper
-            git2::Cred::credential_helper(&config, url, Some(default_username))
-        } else if allowed_types.contains(git2::CredentialType::DEFAULT) {
-            // Try default credential helper
-            git2::Cred::credential_helper(&config, url, Some(default_username))
-        } else {
-            Err(git2::Error::from_str("no authentication method available"))
-        }
-    });
+    // Perform the push
+    let results = push_refspecs(&mut remote_obj, &[refspec], auth, operation)?;
+    if results.iter().any(|r| !r.accepted) {
+        return Ok(results);
+    }
 
-    // Configure push options
-    let mut push_options = git2::PushOptions::new();
-    push_options.remote_callbacks(callbacks);
+    fetch_after_push(&mut remote_obj, branch_name, auth, operation);
+    Ok(results)
+}
 
-    // Perform the push
-    remote_obj.push(&[&refspec], Some(&mut push_options))?;
-
-    // Fetch to update remote-tracking branch instead of manually updating it
-    // This ensures the remote-tracking branch reflects the actual remote state
-    let config_for_fetch = repo.config()?;
-    let mut fetch_callbacks = git2::RemoteCallbacks::new();
-    fetch_callbacks.credentials(move |url, username_from_url, allowed_types| {
-        let default_username = username_from_url.unwrap_or("git");
-        if let (Some(user), Some(pass)) = (&auth_username_fetch, &auth_password_fetch) {
-            return git2::Cred::userpass_plaintext(user, pass);
-        }
-        if allowed_types.contains(git2::CredentialType::SSH_KEY) {
-            git2::Cred::ssh_key_from_agent(default_username)
-        } else if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            git2::Cred::credential_helper(&config_for_fetch, url, Some(default_username))
-        } else if allowed_types.contains(git2::CredentialType::DEFAULT) {
-            git2::Cred::credential_helper(&config_for_fetch, url, Some(default_username))
-        } else {
-            Err(git2::Error::from_str("no authentication method available"))
-        }
-    });
+/// Fetch to update remote-tracking branch instead of manually updating it
+/// This ensures the remote-tracking branch reflects the actual remote state
+fn fetch_after_push(remote: &mut git2::Remote, branch_name: &str, auth: &GitAuth, operation: &Operation) {
+    let mut fetch_callbacks = auth.callbacks();
+    operation.attach(&mut fetch_callbacks);
 
     let mut fetch_options = git2::FetchOptions::new();
     fetch_options.remote_callbacks(fetch_callbacks);
 
     // Fetch the specific branch to update the remote-tracking reference
     let fetch_refspec = format!("{}:{}", branch_name, branch_name);
-    if let Err(e) = remote_obj.fetch(&[&fetch_refspec], Some(&mut fetch_options), None) {
+    if let Err(e) = remote.fetch(&[&fetch_refspec], Some(&mut fetch_options), None) {
         // If fetch fails (e.g., remote doesn't allow anonymous fetch), log but don't fail the push
-        eprintln!("Warning: Failed to fetch after push: {}", e);
+        eprintln!("Warning: Failed to fetch after push: {}", auth.map_error(e));
     }
-
-    Ok(())
-}
-
-/// Get diff for a specific file
-#[tauri::command]
-pub async fn get_file_diff(path: String, file_path: String) -> std::result::Result<FileDiff, String> {
-    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    get_file_diff_impl(&repo, &file_path).map_err(|e| e.to_string())
 }
 
-fn get_file_diff_impl(repo: &Repository, file_path: &str) -> Result<FileDiff> {
-    let mut opts = git2::DiffOptions::new();
-    opts.pathspec(file_path);
-    opts.context_lines(3); // Default context circles
-
-    let mut file_diff = FileDiff {
-        path: file_path.to_string(),
-        hunks: Vec::new(),
-    };
+/// Push refspecs and collect the remote's verdict for each one.
+///
+/// Rejections (non-fast-forward, protected branch, hook denial, stale lease) come
+/// back as results with `accepted: false`; only transport failures are errors.
+fn push_refspecs(
+    remote_obj: &mut git2::Remote,
+    refspecs: &[String],
+    auth: &GitAuth,
+    operation: &Operation,
+) -> Result<Vec<PushRefResult>> {
+    let mut callbacks = auth.callbacks();
+    operation.attach(&mut callbacks);
 
-    // 1. Check if file is untracked
-    let status = repo.status_file(Path::new(file_path))?;
-    if status.is_wt_new() {
-        // For untracked files, we show the whole content as additions
-        let content = std::fs::read_to_string(repo.workdir().unwrap().join(file_path))?;
-        let mut lines = Vec::new();
-        for (i, line) in content.lines().enumerate() {
-            lines.push(DiffLine {
-                content: line.to_string(),
-                origin: '+',
-                old_lineno: None,
-                new_lineno: Some((i + 1) as u32),
-            });
-        }
-        
-        file_diff.hunks.push(DiffHunk {
-            header: "@@ -0,0 +1,".to_string() + &lines.len().to_string() + " @@",
-            lines,
-        });
-        
-        return Ok(file_diff);
-    }
+    let mut push_options = git2::PushOptions::new();
+    push_options.remote_callbacks(callbacks);
 
-    // 2. Get staged changes (Index vs HEAD)
-    let head = repo.head().ok();
-    let head_tree = head
-        .as_ref()
-        .and_then(|h| h.peel_to_tree().ok());
-    
-    let staged_diff = repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut opts))?;
-    
-    // 3. Get unstaged changes (Workdir vs Index)
-    let unstaged_diff = repo.diff_index_to_workdir(None, Some(&mut opts))?;
-
-    // Helper to process diff
-    let mut process_diff = |diff: git2::Diff| -> Result<()> {
-        let mut current_hunk: Option<DiffHunk> = None;
-
-        diff.print(git2::DiffFormat::Patch, |_delta, hunk, line| {
-            if let Some(h) = hunk {
-                let header = String::from_utf8_lossy(h.header()).trim().to_string();
-                
-                // If we have a current hunk and the header is different, push it
-                if let Some(ref mut ch) = current_hunk {
-                    if ch.header != header {
-                        let finished_hunk = std::mem::replace(ch, DiffHunk {
-                            header: header.clone(),
-                            lines: Vec::new(),
-                        });
-                        file_diff.hunks.push(finished_hunk);
-                    }
-                } else {
-                    current_hunk = Some(DiffHunk {
-                        header: header.clone(),
-                        lines: Vec::new(),
-                    });
-                }
-                
-                // Add line to current hunk
-                if let Some(ref mut ch) = current_hunk {
-                    // git2 gives newlines in content, we might want to trim them or keep them?
-                    // Usually lines in UI don't need newline char at end if they are rendered as blocks.
-                    // But for patch application we need them.
-                    // Let's keep them but maybe trim when creating the struct if needed.
-                    // For now, let's keep exact content for fidelity.
-                    let content_bytes = line.content();
-                    let content = String::from_utf8_lossy(content_bytes);
-                    // Trim the trailing newline for display, but we might need it for patch reconstruction?
-                    // Logic: Frontend usually displays lines without explicit \n. 
-                    // But patch apply needs precise content.
-                    // Let's store content WITHOUT the newline for display, and handle newlines in patch reconstruction if needed.
-                    // Or keep it? The `content` from `git2` includes `\n`.
-                    // The `DiffView` previously split by `\n`.
-                    // Let's keep the newline chars in `content` to be safe for now, 
-                    // OR remove them and let reconstruction handle it.
-                    // Let's remove trailing newline for cleaner JSON and UI.
-                    let content_str = content.trim_end_matches('\n').to_string().trim_end_matches('\r').to_string();
-                    
-                    ch.lines.push(DiffLine {
-                        content: content_str,
-                        origin: line.origin(),
-                        old_lineno: line.old_lineno(),
-                        new_lineno: line.new_lineno(),
-                    });
-                }
-            }
-            true
-        })?;
+    let pushed = remote_obj.push(refspecs, Some(&mut push_options));
+    let updates = operation.take_ref_updates();
 
-        // Push the last hunk
-        if let Some(ch) = current_hunk {
-            file_diff.hunks.push(ch);
-        }
-        
-        Ok(())
+    // libgit2 refuses non-fast-forward pushes itself, before the remote is asked
+    let local_rejection = match pushed {
+        Ok(()) => None,
+        Err(e) if e.code() == git2::ErrorCode::NotFastForward => Some("non-fast-forward".to_string()),
+        Err(e) if !updates.is_empty() && !operation.is_cancelled() => Some(e.message().to_string()),
+        Err(e) => return Err(operation.map_error(e, auth)),
     };
 
-    process_diff(staged_diff)?;
-    process_diff(unstaged_diff)?;
-
-    Ok(file_diff)
+    Ok(refspecs
+        .iter()
+        .map(|refspec| {
+            let remote_ref = refspec
+                .rsplit_once(':')
+                .map(|(_, dst)| dst)
+                .unwrap_or(refspec.trim_start_matches('+'))
+                .to_string();
+            let message = match updates.iter().find(|(name, _)| *name == remote_ref) {
+                Some((_, status)) => status.clone(),
+                None => local_rejection.clone(),
+            };
+            PushRefResult {
+                refspec: refspec.clone(),
+                remote_ref,
+                accepted: message.is_none(),
+                message,
+            }
+        })
+        .collect())
 }
 
 /// Merge a branch into the current branch
 #[tauri::command]
-pub async fn merge_branch(path: String, branch_name: String) -> std::result::Result<(), String> {
+pub async fn merge_branch(
+    path: String,
+    branch_name: String,
+    options: Option<MergeOptions>,
+) -> std::result::Result<(), String> {
     let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    merge_branch_impl(&repo, &branch_name).map_err(|e| e.to_string())
+    merge_branch_impl(&repo, &branch_name, &options.unwrap_or_default()).map_err(|e| e.to_string())
 }
 
-fn merge_branch_impl(repo: &Repository, branch_name: &str) -> Result<()> {
+fn merge_branch_impl(repo: &Repository, branch_name: &str, options: &MergeOptions) -> Result<()> {
     let annotated_commit = repo.find_annotated_commit(
-        repo.revparse_single(branch_name)?.id()
+        repo.revparse_single(branch_name)?.peel_to_commit()?.id()
     )?;
+    let fast_forward = fast_forward_mode(repo, options, &["merge.ff"]);
+    let message = format!("Merge branch '{}'", branch_name);
 
-    let (analysis, _) = repo.merge_analysis(&[&annotated_commit])?;
+    merge_annotated(repo, &annotated_commit, &message, options, fast_forward, "Fast-forward merge")
+}
+
+/// `options.fast_forward`, else the first of `keys` set in config (`true`,
+/// `false` or `only`), else fast-forward when possible
+fn fast_forward_mode(repo: &Repository, options: &MergeOptions, keys: &[&str]) -> FastForward {
+    if let Some(mode) = options.fast_forward {
+        return mode;
+    }
+    let Ok(config) = repo.config() else {
+        return FastForward::Allow;
+    };
+    for key in keys {
+        if let Ok(value) = config.get_string(key) {
+            return match value.to_lowercase().as_str() {
+                "only" => FastForward::Only,
+                "false" | "no" | "off" | "0" => FastForward::Never,
+                _ => FastForward::Allow,
+            };
+        }
+    }
+    FastForward::Allow
+}
+
+/// Merge `annotated` into HEAD following `options`: fast-forward, merge
+/// commit or squash. Conflicts are left in the index for the user to resolve.
+fn merge_annotated(
+    repo: &Repository,
+    annotated: &git2::AnnotatedCommit,
+    default_message: &str,
+    options: &MergeOptions,
+    fast_forward: FastForward,
+    ff_reflog: &str,
+) -> Result<()> {
+    if options.squash && options.fast_forward == Some(FastForward::Never) {
+        return Err(AppError::InvalidInput("Squash cannot be combined with --no-ff".to_string()));
+    }
+
+    let (analysis, _) = repo.merge_analysis(&[annotated])?;
 
     if analysis.is_up_to_date() {
         return Ok(());
-    } else if analysis.is_fast_forward() {
-        // Fast-forward merge
+    }
+    if !analysis.is_fast_forward() && fast_forward == FastForward::Only {
+        return Err(AppError::InvalidInput("Not possible to fast-forward, aborting".to_string()));
+    }
+
+    if analysis.is_fast_forward() && !options.squash && fast_forward != FastForward::Never {
         let mut reference = repo.head()?;
         let name = match reference.name() {
             Some(n) => n.to_string(),
             None => return Err(AppError::InvalidInput("HEAD is not a reference".to_string())),
         };
-        
-        let target_id = annotated_commit.id();
-        reference.set_target(target_id, "Fast-forward merge")?;
-        
+
+        let target_id = annotated.id();
+        reference.set_target(target_id, ff_reflog)?;
+
         // Checkout the new head
         let obj = repo.find_object(target_id, None)?;
         repo.checkout_tree(&obj, None)?;
         repo.set_head(&name)?;
-        
-    } else if analysis.is_normal() {
-        // Merge commit
-        let head_commit = repo.head()?.peel_to_commit()?;
-        let merge_commit = repo.find_commit(annotated_commit.id())?;
-        
-        repo.merge(&[&annotated_commit], None, None)?;
-        let mut index = repo.index()?;
-        
-        if index.has_conflicts() {
-            return Err(AppError::MergeConflict);
-        }
-        
-        let tree_id = index.write_tree_to(repo)?;
-        let tree = repo.find_tree(tree_id)?;
-        
-        let signature = repo.signature()?;
-        let message = format!("Merge branch '{}'", branch_name);
-        
-        repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            &message,
-            &tree,
-            &[&head_commit, &merge_commit],
-        )?;
-        
-        // Cleanup merge state
-        repo.cleanup_state()?;
-        
-        // Checkout the new state
-        repo.checkout_index(None, None)?;
+        return Ok(());
     }
 
-    Ok(())
-}
+    let head_commit = repo.head()?.peel_to_commit()?;
+    let merge_commit = repo.find_commit(annotated.id())?;
+
+    let mut merge_opts = git2::MergeOptions::new();
+    merge_opts
+        .file_favor(match options.favor {
+            ConflictFavor::None => git2::FileFavor::Normal,
+            ConflictFavor::Ours => git2::FileFavor::Ours,
+            ConflictFavor::Theirs => git2::FileFavor::Theirs,
+            ConflictFavor::Union => git2::FileFavor::Union,
+        })
+        .ignore_whitespace(options.ignore_whitespace)
+        .find_renames(!options.no_renames);
+    if let Some(threshold) = options.rename_threshold {
+        merge_opts.rename_threshold(threshold);
+    }
 
-/// Fetch from remote
-#[tauri::command]
-pub async fn fetch_remote(
-    path: String,
-    remote: String,
-    username: Option<String>,
-    password: Option<String>,
-) -> std::result::Result<(), String> {
-    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    fetch_remote_impl(&repo, &remote, username, password).map_err(|e| e.to_string())
-}
+    repo.merge(&[annotated], Some(&mut merge_opts), None)?;
+    let mut index = repo.index()?;
 
-fn fetch_remote_impl(
-    repo: &Repository,
-    remote: &str,
-    username: Option<String>,
-    password: Option<String>,
-) -> Result<()> {
-    // Find the remote
-    let mut remote_obj = repo.find_remote(remote)
-        .map_err(|_| AppError::InvalidInput(format!("Remote '{}' not found", remote)))?;
+    if options.squash {
+        // Like `git merge --squash`: no MERGE_HEAD, the message waits in SQUASH_MSG
+        repo.cleanup_state()?;
+        std::fs::write(repo.path().join("SQUASH_MSG"), squash_message(repo, &head_commit, &merge_commit)?)?;
+        if index.has_conflicts() {
+            return Err(AppError::MergeConflict);
+        }
+        return Ok(());
+    }
 
-    // Get repository config for credential helper
-    let config = repo.config()?;
+    if index.has_conflicts() {
+        if let Some(message) = &options.message {
+            // Picked up again by complete_merge
+            std::fs::write(repo.path().join("MERGE_MSG"), message)?;
+        }
+        return Err(AppError::MergeConflict);
+    }
 
-    // Clone the username/password for the callback
-    let auth_username = username.clone();
-    let auth_password = password.clone();
+    let tree_id = index.write_tree_to(repo)?;
+    let tree = repo.find_tree(tree_id)?;
 
-    // Set up remote callbacks for authentication
-    let mut callbacks = git2::RemoteCallbacks::new();
+    let signature = repo.signature()?;
+    let message = options.message.as_deref().unwrap_or(default_message);
 
-    // Handle credentials for SSH/HTTPS
-    callbacks.credentials(move |url, username_from_url, allowed_types| {
-        // Get username from URL or default to "git"
-        let default_username = username_from_url.unwrap_or("git");
+    create_commit(
+        repo,
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &[&head_commit, &merge_commit],
+    )?;
 
-        // If username and password are provided, use them
-        if let (Some(user), Some(pass)) = (&auth_username, &auth_password) {
-            return git2::Cred::userpass_plaintext(user, pass);
-        }
+    // Cleanup merge state
+    repo.cleanup_state()?;
 
-        // Try different authentication methods based on what's allowed
-        if allowed_types.contains(git2::CredentialType::SSH_KEY) {
-            // Try SSH agent first for SSH URLs
-            git2::Cred::ssh_key_from_agent(default_username)
-        } else if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            // For HTTPS, try to use git-credential-helper
-            git2::Cred::credential_helper(&config, url, Some(default_username))
-        } else if allowed_types.contains(git2::CredentialType::DEFAULT) {
-            // Try default credential helper
-            git2::Cred::credential_helper(&config, url, Some(default_username))
-        } else {
-            Err(git2::Error::from_str("no authentication method available"))
+    // Checkout the new state
+    repo.checkout_index(None, None)?;
+
+    Ok(())
+}
+
+/// A `--squash` merge waiting for its commit: like git, only SQUASH_MSG
+/// records it, until the commit that concludes it
+pub(crate) fn is_squash_pending(repo: &Repository) -> bool {
+    repo.state() == git2::RepositoryState::Clean && repo.path().join("SQUASH_MSG").exists()
+}
+
+/// "Squashed commit of the following:" plus the commits being squashed
+fn squash_message(repo: &Repository, head: &git2::Commit, merged: &git2::Commit) -> Result<String> {
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push(merged.id())?;
+    revwalk.hide(head.id())?;
+
+    let mut message = String::from("Squashed commit of the following:\n");
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        message.push_str(&format!(
+            "\ncommit {}\nAuthor: {} <{}>\n\n",
+            commit.id(),
+            commit.author().name().unwrap_or(""),
+            commit.author().email().unwrap_or("")
+        ));
+        for line in commit.message().unwrap_or("").lines() {
+            if !line.is_empty() {
+                message.push_str("    ");
+            }
+            message.push_str(line);
+            message.push('\n');
         }
-    });
+    }
+    Ok(message)
+}
+
+/// Fetch from remote
+#[tauri::command]
+pub async fn fetch_remote(
+    app: tauri::AppHandle,
+    path: String,
+    remote: String,
+    username: Option<String>,
+    password: Option<String>,
+    operation_id: Option<String>,
+) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    let auth = GitAuth::for_repo(&repo, username, password).with_keyring(&app);
+    let operation = Operation::start(Some(&app), operation_id, "fetch");
+    fetch_remote_impl(&repo, &remote, &auth, &operation).map_err(|e| e.to_string())
+}
+
+fn fetch_remote_impl(
+    repo: &Repository,
+    remote: &str,
+    auth: &GitAuth,
+    operation: &Operation,
+) -> Result<()> {
+    // Find the remote
+    let mut remote_obj = repo.find_remote(remote)
+        .map_err(|_| AppError::InvalidInput(format!("Remote '{}' not found", remote)))?;
+
+    // Credentials come from the shared auth chain (agent, keys, keyring, helper)
+    let mut callbacks = auth.callbacks();
+    operation.attach(&mut callbacks);
 
     // Configure fetch options
     let mut fetch_options = git2::FetchOptions::new();
@@ -1108,23 +1015,49 @@ fn fetch_remote_impl(
 
     // Perform the fetch
     // Default refspec is usually configured for the remote, so we can pass empty refspec list to use default
-    remote_obj.fetch(&[] as &[&str], Some(&mut fetch_options), None)?;
+    remote_obj.fetch(&[] as &[&str], Some(&mut fetch_options), None)
+        .map_err(|e| operation.map_error(e, auth))?;
 
     Ok(())
 }
 
 /// Pull from remote (fetch + merge)
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn pull_branch(
+    app: tauri::AppHandle,
     path: String,
     remote: String,
     branch: String,
-    use_rebase: bool,
+    use_rebase: Option<bool>,
+    merge_options: Option<MergeOptions>,
     username: Option<String>,
     password: Option<String>,
+    operation_id: Option<String>,
 ) -> std::result::Result<(), String> {
     let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    pull_branch_impl(&repo, &remote, &branch, use_rebase, username, password).map_err(|e| e.to_string())
+    let use_rebase = use_rebase.unwrap_or_else(|| pull_rebase_configured(&repo, &branch));
+    let auth = GitAuth::for_repo(&repo, username, password).with_keyring(&app);
+    let operation = Operation::start(Some(&app), operation_id, "pull");
+    pull_branch_impl(&repo, &remote, &branch, use_rebase, &merge_options.unwrap_or_default(), &auth, &operation)
+        .map_err(|e| e.to_string())
+}
+
+/// Resolve the pull strategy from `branch.<name>.rebase`, then `pull.rebase`
+fn pull_rebase_configured(repo: &Repository, branch: &str) -> bool {
+    let config = match repo.config() {
+        Ok(config) => config,
+        Err(_) => return false,
+    };
+
+    for key in [format!("branch.{}.rebase", branch), "pull.rebase".to_string()] {
+        if let Ok(value) = config.get_string(&key) {
+            // "merges" and "interactive" still mean rebase; we always replay linearly
+            return !matches!(value.to_lowercase().as_str(), "false" | "no" | "off" | "0" | "");
+        }
+    }
+
+    false
 }
 
 fn pull_branch_impl(
@@ -1132,11 +1065,12 @@ fn pull_branch_impl(
     remote: &str,
     branch: &str,
     use_rebase: bool,
-    username: Option<String>,
-    password: Option<String>,
+    merge_options: &MergeOptions,
+    auth: &GitAuth,
+    operation: &Operation,
 ) -> Result<()> {
     // 1. Fetch first
-    fetch_remote_impl(repo, remote, username, password)?;
+    fetch_remote_impl(repo, remote, auth, operation)?;
 
     // 2. Prepare for merge/rebase
     let remote_branch_name = format!("{}/{}", remote, branch);
@@ -1149,64 +1083,44 @@ fn pull_branch_impl(
     // 3. Merge analysis
     let (analysis, _) = repo.merge_analysis(&[&annotated_commit])?;
 
-    if analysis.is_up_to_date() {
-        return Ok(());
-    } else if analysis.is_fast_forward() {
-        // Fast-forward merge
-        let mut reference = repo.head()?;
-        let name = match reference.name() {
-            Some(n) => n.to_string(),
-            None => return Err(AppError::InvalidInput("HEAD is not a reference".to_string())),
-        };
-        
-        let target_id = annotated_commit.id();
-        reference.set_target(target_id, "Fast-forward pull")?;
-        
-        // Checkout the new head
-        let obj = repo.find_object(target_id, None)?;
-        repo.checkout_tree(&obj, None)?;
-        repo.set_head(&name)?;
-        
-    } else if analysis.is_normal() {
-        if use_rebase {
-             // For now, we only support merge. Rebase in libgit2 is complex.
-             return Err(AppError::InvalidInput("Rebase strategy not yet fully supported in this backend. Please use Merge strategy.".to_string()));
+    if use_rebase {
+        if analysis.is_normal() && !analysis.is_fast_forward() {
+            return pull_rebase(repo, fetch_head_commit.id());
         }
+        // With nothing local to replay, rebasing is a plain fast-forward:
+        // `pull.ff`, `merge.ff` and the merge options do not apply
+        return merge_annotated(repo, &annotated_commit, "", &MergeOptions::default(), FastForward::Only, "Fast-forward pull");
+    }
 
-        // Merge commit
-        let head_commit = repo.head()?.peel_to_commit()?;
-        let merge_commit = repo.find_commit(annotated_commit.id())?;
-        
-        repo.merge(&[&annotated_commit], None, None)?;
-        let mut index = repo.index()?;
-        
-        if index.has_conflicts() {
-            return Err(AppError::MergeConflict);
+    // `pull.ff` takes precedence over `merge.ff`, as in git
+    let fast_forward = fast_forward_mode(repo, merge_options, &["pull.ff", "merge.ff"]);
+    let message = format!("Merge remote-tracking branch '{}'", remote_branch_name);
+    merge_annotated(repo, &annotated_commit, &message, merge_options, fast_forward, "Fast-forward pull")
+}
+
+/// Replay local commits that are not in `upstream` on top of it
+fn pull_rebase(repo: &Repository, upstream: git2::Oid) -> Result<()> {
+    let head = repo.head()?.peel_to_commit()?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+    revwalk.push(head.id())?;
+    revwalk.hide(upstream)?;
+
+    // Like `git pull --rebase`, merge commits are flattened away
+    let mut local_commits = Vec::new();
+    for oid in revwalk {
+        let oid = oid?;
+        if repo.find_commit(oid)?.parent_count() <= 1 {
+            local_commits.push(oid);
         }
-        
-        let tree_id = index.write_tree_to(repo)?;
-        let tree = repo.find_tree(tree_id)?;
-        
-        let signature = repo.signature()?;
-        let message = format!("Merge remote-tracking branch '{}'", remote_branch_name);
-        
-        repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            &message,
-            &tree,
-            &[&head_commit, &merge_commit],
-        )?;
-        
-        // Cleanup merge state
-        repo.cleanup_state()?;
-        
-        // Checkout the new state
-        repo.checkout_index(None, None)?;
     }
 
-    Ok(())
+    match start_pick_rebase(repo, upstream, &local_commits)? {
+        RebaseProgress::Finished => Ok(()),
+        // Stopped on a conflict: the rebase state is left for continue/skip/abort
+        RebaseProgress::Stopped => Err(AppError::MergeConflict),
+    }
 }
 
 /// Get all tags for a repository
@@ -1305,111 +1219,67 @@ pub async fn delete_tag(path: String, name: String) -> std::result::Result<(), S
 /// Push a tag to remote
 #[tauri::command]
 pub async fn push_tag(
+    app: tauri::AppHandle,
     path: String,
     tag_name: String,
     remote: String,
     username: Option<String>,
     password: Option<String>,
-) -> std::result::Result<(), String> {
+    operation_id: Option<String>,
+) -> std::result::Result<Vec<PushRefResult>, String> {
     let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    push_tag_impl(&repo, &tag_name, &remote, username, password).map_err(|e| e.to_string())
+    let auth = GitAuth::for_repo(&repo, username, password).with_keyring(&app);
+    let operation = Operation::start(Some(&app), operation_id, "push");
+    push_tag_impl(&repo, &tag_name, &remote, &auth, &operation).map_err(|e| e.to_string())
 }
 
 fn push_tag_impl(
     repo: &Repository,
     tag_name: &str,
     remote: &str,
-    username: Option<String>,
-    password: Option<String>,
-) -> Result<()> {
+    auth: &GitAuth,
+    operation: &Operation,
+) -> Result<Vec<PushRefResult>> {
     let mut remote_obj = repo.find_remote(remote)
         .map_err(|_| AppError::InvalidInput(format!("Remote '{}' not found", remote)))?;
 
     // Refspec for pushing a tag
     let refspec = format!("refs/tags/{}:refs/tags/{}", tag_name, tag_name);
 
-    let config = repo.config()?;
-    let auth_username = username.clone();
-    let auth_password = password.clone();
-
-    let mut callbacks = git2::RemoteCallbacks::new();
-    callbacks.credentials(move |url, username_from_url, allowed_types| {
-        let default_username = username_from_url.unwrap_or("git");
-        if let (Some(user), Some(pass)) = (&auth_username, &auth_password) {
-            return git2::Cred::userpass_plaintext(user, pass);
-        }
-        if allowed_types.contains(git2::CredentialType::SSH_KEY) {
-            git2::Cred::ssh_key_from_agent(default_username)
-        } else if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            git2::Cred::credential_helper(&config, url, Some(default_username))
-        } else if allowed_types.contains(git2::CredentialType::DEFAULT) {
-            git2::Cred::credential_helper(&config, url, Some(default_username))
-        } else {
-            Err(git2::Error::from_str("no authentication method available"))
-        }
-    });
-
-    let mut push_options = git2::PushOptions::new();
-    push_options.remote_callbacks(callbacks);
-
-    remote_obj.push(&[&refspec], Some(&mut push_options))?;
-
-    Ok(())
+    push_refspecs(&mut remote_obj, &[refspec], auth, operation)
 }
 
 /// Delete a remote tag
 #[tauri::command]
 pub async fn delete_remote_tag(
+    app: tauri::AppHandle,
     path: String,
     tag_name: String,
     remote: String,
     username: Option<String>,
     password: Option<String>,
-) -> std::result::Result<(), String> {
+    operation_id: Option<String>,
+) -> std::result::Result<Vec<PushRefResult>, String> {
     let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    delete_remote_tag_impl(&repo, &tag_name, &remote, username, password).map_err(|e| e.to_string())
+    let auth = GitAuth::for_repo(&repo, username, password).with_keyring(&app);
+    let operation = Operation::start(Some(&app), operation_id, "push");
+    delete_remote_tag_impl(&repo, &tag_name, &remote, &auth, &operation).map_err(|e| e.to_string())
 }
 
 fn delete_remote_tag_impl(
     repo: &Repository,
     tag_name: &str,
     remote: &str,
-    username: Option<String>,
-    password: Option<String>,
-) -> Result<()> {
+    auth: &GitAuth,
+    operation: &Operation,
+) -> Result<Vec<PushRefResult>> {
     let mut remote_obj = repo.find_remote(remote)
         .map_err(|_| AppError::InvalidInput(format!("Remote '{}' not found", remote)))?;
 
     // Refspec for deleting a remote ref
     let refspec = format!(":refs/tags/{}", tag_name);
 
-    let config = repo.config()?;
-    let auth_username = username.clone();
-    let auth_password = password.clone();
-
-    let mut callbacks = git2::RemoteCallbacks::new();
-    callbacks.credentials(move |url, username_from_url, allowed_types| {
-        let default_username = username_from_url.unwrap_or("git");
-        if let (Some(user), Some(pass)) = (&auth_username, &auth_password) {
-            return git2::Cred::userpass_plaintext(user, pass);
-        }
-        if allowed_types.contains(git2::CredentialType::SSH_KEY) {
-            git2::Cred::ssh_key_from_agent(default_username)
-        } else if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
-            git2::Cred::credential_helper(&config, url, Some(default_username))
-        } else if allowed_types.contains(git2::CredentialType::DEFAULT) {
-            git2::Cred::credential_helper(&config, url, Some(default_username))
-        } else {
-            Err(git2::Error::from_str("no authentication method available"))
-        }
-    });
-
-    let mut push_options = git2::PushOptions::new();
-    push_options.remote_callbacks(callbacks);
-
-    remote_obj.push(&[&refspec], Some(&mut push_options))?;
-
-    Ok(())
+    push_refspecs(&mut remote_obj, &[refspec], auth, operation)
 }
 
 /// Get list of remotes for a repository
@@ -1479,55 +1349,15 @@ pub async fn get_merge_state(path: String) -> std::result::Result<MergeState, St
 
 fn get_merge_state_impl(repo: &Repository) -> Result<MergeState> {
     let index = repo.index()?;
-    let is_merge_in_progress = index.has_conflicts();
-
-    let mut conflicted_files = Vec::new();
-    let mut seen_paths = std::collections::HashSet::new();
-
-    if is_merge_in_progress {
-        // Iterate through index entries to find conflicted files
-        for entry in index.iter() {
-            let path_bytes = &entry.path;
-            let path = String::from_utf8_lossy(path_bytes).to_string();
-
-            if path.is_empty() {
-                continue;
-            }
+    // A merge without conflicts still needs its commit
+    let is_merge_in_progress =
+        index.has_conflicts() || repo.state() == git2::RepositoryState::Merge || is_squash_pending(repo);
 
-            // Skip if we've already processed this file
-            if seen_paths.contains(&path) {
-                continue;
-            }
-
-            // Check if this file has conflicts
-            let has_conflict = index
-                .iter()
-                .any(|e| String::from_utf8_lossy(&e.path).to_string() == path);
-
-            if has_conflict {
-                seen_paths.insert(path.clone());
-
-                // Read conflict content from the file
-                let workdir = repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))?;
-                let file_path = workdir.join(&path);
-
-                let (current, incoming, ancestor, conflict_markers) = if file_path.exists() {
-                    let content = std::fs::read_to_string(&file_path).unwrap_or_default();
-                    parse_conflict_content(&content)
-                } else {
-                    (None, None, None, false)
-                };
-
-                conflicted_files.push(ConflictInfo {
-                    path,
-                    current,
-                    incoming,
-                    ancestor,
-                    conflict_markers,
-                });
-            }
-        }
-    }
+    let conflicted_files = if index.has_conflicts() {
+        read_conflicts(repo, &index)?
+    } else {
+        Vec::new()
+    };
 
     Ok(MergeState {
         is_merge_in_progress,
@@ -1536,62 +1366,6 @@ fn get_merge_state_impl(repo: &Repository) -> Result<MergeState> {
     })
 }
 
-/// Parse conflict markers from file content
-fn parse_conflict_content(content: &str) -> (Option<String>, Option<String>, Option<String>, bool) {
-    let mut current = None;
-    let mut incoming = None;
-    let mut ancestor = None;
-    let has_markers = content.contains("<<<<<<<") || content.contains(">>>>>>>");
-
-    // Simple conflict marker parsing
-    let lines: Vec<&str> = content.lines().collect();
-    let mut state = ConflictParseState::None;
-    let mut current_buf = String::new();
-    let mut incoming_buf = String::new();
-    let ancestor_buf = String::new();
-
-    for line in lines {
-        if line.contains("<<<<<<<") {
-            state = ConflictParseState::Current;
-        } else if line.contains("=======") && !line.contains(">>>>>>>") {
-            state = ConflictParseState::Incoming;
-        } else if line.contains(">>>>>>>") {
-            state = ConflictParseState::None;
-        } else {
-            match state {
-                ConflictParseState::Current => {
-                    current_buf.push_str(line);
-                    current_buf.push('\n');
-                }
-                ConflictParseState::Incoming => {
-                    incoming_buf.push_str(line);
-                    incoming_buf.push('\n');
-                }
-                ConflictParseState::None => {}
-            }
-        }
-    }
-
-    if !current_buf.is_empty() {
-        current = Some(current_buf.trim().to_string());
-    }
-    if !incoming_buf.is_empty() {
-        incoming = Some(incoming_buf.trim().to_string());
-    }
-    if !ancestor_buf.is_empty() {
-        ancestor = Some(ancestor_buf.trim().to_string());
-    }
-
-    (current, incoming, ancestor, has_markers)
-}
-
-#[derive(Debug, Clone, Copy)]
-enum ConflictParseState {
-    None,
-    Current,
-    Incoming,
-}
-
 /// Resolve a conflict file by accepting a specific version
 #[tauri::command]
 pub async fn resolve_conflict(
@@ -1694,22 +1468,119 @@ pub async fn abort_merge(path: String) -> std::result::Result<(), String> {
 }
 
 fn abort_merge_impl(repo: &Repository) -> Result<()> {
-    // Check if merge is in progress
-    let index = repo.index()?;
-    if !index.has_conflicts() {
+    // The conflict panel aborts a stopped cherry-pick or revert the same way
+    if super::sequencer::abort_in_progress(repo)? {
+        return Ok(());
+    }
+    if is_squash_pending(repo) {
+        reset_merge(repo, &repo.head()?.peel_to_commit()?)?;
+        std::fs::remove_file(repo.path().join("SQUASH_MSG"))?;
+        return Ok(());
+    }
+    if repo.state() != git2::RepositoryState::Merge {
         return Err(AppError::InvalidInput("No merge in progress".to_string()));
     }
 
-    // Cleanup merge state
+    // Like `git merge --abort`: back to the commit the merge started from
+    let orig_head = match repo.revparse_single("ORIG_HEAD") {
+        Ok(object) => object.peel_to_commit()?,
+        Err(_) => repo.head()?.peel_to_commit()?,
+    };
+    reset_merge(repo, &orig_head)?;
     repo.cleanup_state()?;
 
-    // Reset to HEAD
-    let head = repo.head()?;
-    let commit = head.peel_to_commit()?;
-    let tree = commit.tree()?;
+    Ok(())
+}
+
+/// Like `git reset --merge`: move HEAD to `target` and restore only the paths
+/// where the index differs from it, conflicts included. Other local changes
+/// are kept; a restored path that also has unstaged changes refuses the reset.
+pub(crate) fn reset_merge(repo: &Repository, target: &git2::Commit) -> Result<()> {
+    let workdir = repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))?;
+    let tree = target.tree()?;
+    let mut index = repo.index()?;
 
-    repo.checkout_tree(tree.as_object(), None)?;
-    repo.set_head(head.name().ok_or(AppError::InvalidInput("No HEAD name".to_string()))?)?;
+    let mut conflicted = std::collections::BTreeSet::new();
+    for conflict in index.conflicts()? {
+        let conflict = conflict?;
+        if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+            conflicted.insert(String::from_utf8_lossy(&entry.path).to_string());
+        }
+    }
+    let mut touched = conflicted.clone();
+    for delta in repo.diff_tree_to_index(Some(&tree), Some(&index), None)?.deltas() {
+        if let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) {
+            touched.insert(path.to_string_lossy().to_string());
+        }
+    }
+    if touched.is_empty() && repo.head()?.target() == Some(target.id()) {
+        return Ok(());
+    }
+
+    // Conflicted files hold markers by design; any other difference from the index is the user's
+    let mut opts = git2::DiffOptions::new();
+    opts.disable_pathspec_match(true);
+    let mut staged = 0;
+    for path in touched.difference(&conflicted) {
+        opts.pathspec(path);
+        staged += 1;
+    }
+    if staged > 0 {
+        let local = repo.diff_index_to_workdir(Some(&index), Some(&mut opts))?;
+        if let Some(path) = local.deltas().next().and_then(|d| d.new_file().path().map(|p| p.display().to_string())) {
+            return Err(AppError::InvalidInput(format!(
+                "Cannot abort: {} has local changes that would be overwritten",
+                path
+            )));
+        }
+    }
+
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout.force();
+    let mut restored = false;
+    for path in &touched {
+        index.remove_path(Path::new(path))?;
+        match tree.get_path(Path::new(path)) {
+            Ok(entry) => {
+                index.add(&git2::IndexEntry {
+                    ctime: git2::IndexTime::new(0, 0),
+                    mtime: git2::IndexTime::new(0, 0),
+                    dev: 0,
+                    ino: 0,
+                    mode: entry.filemode() as u32,
+                    uid: 0,
+                    gid: 0,
+                    file_size: 0,
+                    id: entry.id(),
+                    flags: 0,
+                    flags_extended: 0,
+                    path: path.as_bytes().to_vec(),
+                })?;
+                checkout.path(path);
+                restored = true;
+            }
+            Err(_) => {
+                let file = workdir.join(path);
+                if file.is_file() || file.is_symlink() {
+                    std::fs::remove_file(file)?;
+                }
+            }
+        }
+    }
+    index.write()?;
+    if restored {
+        repo.checkout_index(Some(&mut index), Some(&mut checkout))?;
+    }
+
+    let head = repo.head()?;
+    if head.target() != Some(target.id()) {
+        let message = format!("reset: moving to {}", target.id());
+        if head.is_branch() {
+            head.resolve()?.set_target(target.id(), &message)?;
+        } else {
+            repo.set_head_detached(target.id())?;
+        }
+    }
 
     Ok(())
 }
@@ -1721,24 +1592,34 @@ pub async fn complete_merge(path: String, message: Option<String>) -> std::resul
     complete_merge_impl(&repo, message).map_err(|e| e.to_string())
 }
 
-fn complete_merge_impl(repo: &Repository, message: Option<String>) -> Result<()> {
+pub(crate) fn complete_merge_impl(repo: &Repository, message: Option<String>) -> Result<()> {
     let mut index = repo.index()?;
 
     if index.has_conflicts() {
         return Err(AppError::InvalidInput("Cannot complete merge with unresolved conflicts".to_string()));
     }
 
+    // A stopped cherry-pick or revert concludes with its own commit and
+    // goes on with the rest of its list
+    if super::sequencer::continue_in_progress(repo, message.as_deref())?.is_some() {
+        return Ok(());
+    }
+
     // Write the tree
     let tree_id = index.write_tree_to(repo)?;
     let tree = repo.find_tree(tree_id)?;
 
+    // A squash merge has no MERGE_HEAD; it concludes with a plain commit
+    let squashing = is_squash_pending(repo);
+    let message_file = if squashing { "SQUASH_MSG" } else { "MERGE_MSG" };
+
     // Get the merge message
     let merge_msg = if let Some(msg) = message {
         msg
     } else {
-        // Try to get from .git/MERGE_MSG
+        // Try to get from .git/MERGE_MSG or .git/SQUASH_MSG
         let git_dir = repo.path();
-        let merge_msg_path = git_dir.join("MERGE_MSG");
+        let merge_msg_path = git_dir.join(message_file);
         if merge_msg_path.exists() {
             std::fs::read_to_string(&merge_msg_path).unwrap_or_else(|_| "Merge commit".to_string())
         } else {
@@ -1751,6 +1632,12 @@ fn complete_merge_impl(repo: &Repository, message: Option<String>) -> Result<()>
     // Get HEAD commit
     let head_commit = repo.head()?.peel_to_commit()?;
 
+    if squashing {
+        create_commit(repo, Some("HEAD"), &signature, &signature, &merge_msg, &tree, &[&head_commit])?;
+        std::fs::remove_file(repo.path().join("SQUASH_MSG"))?;
+        return Ok(());
+    }
+
     // Get MERGE_HEAD to find the other parent
     let git_dir = repo.path();
     let merge_head_path = git_dir.join("MERGE_HEAD");
@@ -1763,7 +1650,8 @@ fn complete_merge_impl(repo: &Repository, message: Option<String>) -> Result<()>
     let merge_commit = repo.find_commit(merge_oid)?;
 
     // Create the merge commit
-    repo.commit(
+    create_commit(
+        repo,
         Some("HEAD"),
         &signature,
         &signature,
@@ -1800,235 +1688,6 @@ fn trim(s: &str) -> &str {
     s.trim().trim_end_matches('\n')
 }
 
-/// Get rebase state
-#[tauri::command]
-pub async fn get_rebase_state(path: String) -> std::result::Result<RebaseState, String> {
-    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    get_rebase_state_impl(&repo).map_err(|e| e.to_string())
-}
-
-fn get_rebase_state_impl(repo: &Repository) -> Result<RebaseState> {
-    let git_dir = repo.path();
-    let rebase_merge_dir = git_dir.join("rebase-merge");
-    let rebase_apply_dir = git_dir.join("rebase-apply");
-
-    let is_rebase_in_progress = rebase_merge_dir.exists() || rebase_apply_dir.exists();
-
-    if !is_rebase_in_progress {
-        return Ok(RebaseState {
-            is_rebase_in_progress: false,
-            current_branch: None,
-            onto_branch: None,
-            current_step: 0,
-            total_steps: 0,
-            current_commit: None,
-        });
-    }
-
-    // Read rebase state from files
-    let current_branch = if rebase_merge_dir.join("head-name").exists() {
-        std::fs::read_to_string(rebase_merge_dir.join("head-name")).ok().map(|s| s.trim().to_string())
-    } else {
-        None
-    };
-
-    let onto_branch = if rebase_merge_dir.join("onto").exists() {
-        std::fs::read_to_string(rebase_merge_dir.join("onto")).ok()
-            .and_then(|s| git2::Oid::from_str(trim(&s)).ok())
-            .and_then(|oid| repo.find_commit(oid).ok())
-            .and_then(|commit| commit.summary().map(|s| s.to_string()))
-    } else {
-        None
-    };
-
-    let current_step = if rebase_merge_dir.join("msgnum").exists() {
-        std::fs::read_to_string(rebase_merge_dir.join("msgnum")).ok()
-            .and_then(|s| s.trim().parse().ok())
-            .unwrap_or(0)
-    } else {
-        0
-    };
-
-    let total_steps = if rebase_merge_dir.join("end").exists() {
-        std::fs::read_to_string(rebase_merge_dir.join("end")).ok()
-            .and_then(|s| s.trim().parse().ok())
-            .unwrap_or(0)
-    } else {
-        0
-    };
-
-    let current_commit = if rebase_merge_dir.join("current-commit").exists() {
-        std::fs::read_to_string(rebase_merge_dir.join("current-commit")).ok()
-            .map(|s| s.trim().to_string())
-    } else {
-        None
-    };
-
-    Ok(RebaseState {
-        is_rebase_in_progress: true,
-        current_branch,
-        onto_branch,
-        current_step,
-        total_steps,
-        current_commit,
-    })
-}
-
-/// Start an interactive rebase
-#[tauri::command]
-pub async fn start_interactive_rebase(
-    path: String,
-    base_commit: String,
-    commits: Vec<RebaseTodo>,
-) -> std::result::Result<(), String> {
-    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    start_interactive_rebase_impl(&repo, &base_commit, &commits).map_err(|e| e.to_string())
-}
-
-fn start_interactive_rebase_impl(repo: &Repository, base_commit: &str, commits: &[RebaseTodo]) -> Result<()> {
-    let workdir = repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))?;
-    let git_dir = repo.path();
-
-    // Create rebase-todo file content
-    let todo_content = commits
-        .iter()
-        .map(|todo| {
-            let commit_hash = &todo.commit.id[..7];
-            let message = todo.commit.message.lines().next().unwrap_or("");
-            format!("{} {} {}", todo.command, commit_hash, message)
-        })
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    // Write todo content to a temporary file
-    let todo_path = git_dir.join("rebase-todo-input");
-    std::fs::write(&todo_path, todo_content).map_err(|e| AppError::Io(e))?;
-    
-    // Command to copy our todo content to the file git passes to the editor
-    // On Unix-like systems, we can use 'cp'
-    // On Windows, we might need a different approach, but for now assuming Unix/Mac environment or Git Bash
-    let editor_cmd = format!("cp '{}'", todo_path.to_string_lossy());
-
-    // Use git rebase -i with environment variable to provide the todo list
-    let result = std::process::Command::new("git")
-        .arg("rebase")
-        .arg("-i")
-        .arg(base_commit)
-        .current_dir(workdir)
-        .env("GIT_SEQUENCE_EDITOR", editor_cmd)
-        .output();
-
-    // Clean up temporary file
-    let _ = std::fs::remove_file(todo_path);
-
-    match result {
-        Ok(output) => {
-            if !output.status.success() {
-                let error = String::from_utf8_lossy(&output.stderr);
-                return Err(AppError::Git(git2::Error::from_str(&error)));
-            }
-            Ok(())
-        }
-        Err(e) => Err(AppError::Io(e)),
-    }
-}
-
-/// Continue rebase after resolving conflicts
-#[tauri::command]
-pub async fn continue_rebase(path: String) -> std::result::Result<(), String> {
-    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    continue_rebase_impl(&repo).map_err(|e| e.to_string())
-}
-
-fn continue_rebase_impl(repo: &Repository) -> Result<()> {
-    let workdir = repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))?;
-
-    let output = std::process::Command::new("git")
-        .arg("rebase")
-        .arg("--continue")
-        .current_dir(workdir)
-        .output()?;
-
-    if !output.status.success() {
-        let error = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::Git(git2::Error::from_str(&error)));
-    }
-
-    Ok(())
-}
-
-/// Skip current commit during rebase
-#[tauri::command]
-pub async fn skip_rebase(path: String) -> std::result::Result<(), String> {
-    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    skip_rebase_impl(&repo).map_err(|e| e.to_string())
-}
-
-fn skip_rebase_impl(repo: &Repository) -> Result<()> {
-    let workdir = repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))?;
-
-    let output = std::process::Command::new("git")
-        .arg("rebase")
-        .arg("--skip")
-        .current_dir(workdir)
-        .output()?;
-
-    if !output.status.success() {
-        let error = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::Git(git2::Error::from_str(&error)));
-    }
-
-    Ok(())
-}
-
-/// Abort rebase
-#[tauri::command]
-pub async fn abort_rebase(path: String) -> std::result::Result<(), String> {
-    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    abort_rebase_impl(&repo).map_err(|e| e.to_string())
-}
-
-fn abort_rebase_impl(repo: &Repository) -> Result<()> {
-    let workdir = repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))?;
-
-    let output = std::process::Command::new("git")
-        .arg("rebase")
-        .arg("--abort")
-        .current_dir(workdir)
-        .output()?;
-
-    if !output.status.success() {
-        let error = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::Git(git2::Error::from_str(&error)));
-    }
-
-    Ok(())
-}
-
-/// Edit commit message during rebase
-#[tauri::command]
-pub async fn amend_rebase_commit(
-    path: String,
-    new_message: String,
-) -> std::result::Result<(), String> {
-    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
-    amend_rebase_commit_impl(&repo, &new_message).map_err(|e| e.to_string())
-}
-
-fn amend_rebase_commit_impl(repo: &Repository, new_message: &str) -> Result<()> {
-    let head = repo.head()?;
-    let commit = head.peel_to_commit()?;
-    let signature = repo.signature()?;
-
-    // Amend the last commit
-    repo.commit(
-        Some("HEAD"),
-        &signature,
-        &commit.author(),
-        new_message,
-        &commit.tree()?,
-        &[&commit],
-    )?;
-
-    Ok(())
-}
+#[cfg(test)]
+#[path = "repo_tests.rs"]
+mod repo_tests;
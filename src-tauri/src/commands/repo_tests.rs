@@ -0,0 +1,471 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::auth::GitAuth;
+    use crate::commands::progress::Operation;
+    use crate::commands::repo::{
+        abort_merge_impl, complete_merge_impl, delete_remote_tag_impl, get_merge_state_impl, get_repo_status_impl, merge_branch_impl, pull_branch_impl, pull_rebase, pull_rebase_configured, push_branch_impl,
+        scan_repositories_impl,
+    };
+    use crate::commands::test_support::{commit_file, init_repo};
+    use crate::domain::{ConflictFavor, FastForward, FileStatus, MergeOptions, RenameOptions};
+    use git2::{Oid, Repository, Signature};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pull_rebase_replays_local_commits_on_upstream() {
+        let (dir, repo) = init_repo();
+        let base = commit_file(&repo, "a.txt", "a\n", "base");
+        let local_ref = repo.head().unwrap().name().unwrap().to_string();
+
+        // Simulate the remote side on a separate branch
+        repo.branch("upstream", &repo.find_commit(base).unwrap(), false).unwrap();
+        repo.set_head("refs/heads/upstream").unwrap();
+        let upstream = commit_file(&repo, "remote.txt", "r\n", "remote change");
+
+        repo.set_head(&local_ref).unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        commit_file(&repo, "local.txt", "l\n", "local change");
+
+        pull_rebase(&repo, upstream).unwrap();
+
+        let head = repo.head().unwrap();
+        assert_eq!(head.name(), Some(local_ref.as_str()));
+        let head_commit = head.peel_to_commit().unwrap();
+        assert_eq!(head_commit.message(), Some("local change"));
+        assert_eq!(head_commit.parent_id(0).unwrap(), upstream);
+        assert!(dir.path().join("remote.txt").exists());
+    }
+
+    #[test]
+    fn test_pull_rebase_configured_prefers_branch_setting() {
+        let (_dir, repo) = init_repo();
+        assert!(!pull_rebase_configured(&repo, "main"));
+
+        let mut config = repo.config().unwrap();
+        config.set_str("pull.rebase", "true").unwrap();
+        assert!(pull_rebase_configured(&repo, "main"));
+
+        config.set_str("branch.main.rebase", "false").unwrap();
+        assert!(!pull_rebase_configured(&repo, "main"));
+
+        config.set_str("branch.main.rebase", "merges").unwrap();
+        assert!(pull_rebase_configured(&repo, "main"));
+    }
+
+    /// Local repo with one commit and a bare `origin` next to it
+    fn repo_with_remote() -> (TempDir, Repository, Repository, String) {
+        let (dir, repo) = init_repo();
+        commit_file(&repo, "a.txt", "a\n", "base");
+        let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+
+        let remote_dir = dir.path().join(".remote.git");
+        let remote = Repository::init_bare(&remote_dir).unwrap();
+        repo.remote("origin", &format!("file://{}", remote_dir.display())).unwrap();
+        (dir, repo, remote, branch)
+    }
+
+    fn push(repo: &Repository, branch: &str, force_with_lease: bool) -> Vec<crate::domain::PushRefResult> {
+        let auth = GitAuth::new(None, None, None);
+        let operation = Operation::start(None, None, "push");
+        push_branch_impl(repo, branch, "origin", force_with_lease, &auth, &operation).unwrap()
+    }
+
+    fn amend_head(repo: &Repository, message: &str) -> Oid {
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        head.amend(Some("HEAD"), None, None, None, Some(message), None).unwrap()
+    }
+
+    #[test]
+    fn test_pull_rebase_fast_forwards_despite_merge_ff_false() {
+        let (_dir, repo, remote, branch) = repo_with_remote();
+        push(&repo, &branch, false);
+
+        let theirs = {
+            let head = remote.find_reference(&format!("refs/heads/{}", branch)).unwrap().peel_to_commit().unwrap();
+            let sig = Signature::now("Other", "other@example.com").unwrap();
+            remote
+                .commit(Some(&format!("refs/heads/{}", branch)), &sig, &sig, "theirs", &head.tree().unwrap(), &[&head])
+                .unwrap()
+        };
+
+        let mut config = repo.config().unwrap();
+        config.set_str("merge.ff", "false").unwrap();
+        config.set_str("pull.rebase", "true").unwrap();
+        let use_rebase = pull_rebase_configured(&repo, &branch);
+        let options = MergeOptions { squash: true, ..Default::default() };
+        let auth = GitAuth::new(None, None, None);
+        let operation = Operation::start(None, None, "pull");
+        pull_branch_impl(&repo, "origin", &branch, use_rebase, &options, &auth, &operation).unwrap();
+
+        assert_eq!(repo.head().unwrap().target(), Some(theirs));
+        assert!(!repo.path().join("SQUASH_MSG").exists());
+    }
+
+    #[test]
+    fn test_push_reports_accepted_ref() {
+        let (_dir, repo, remote, branch) = repo_with_remote();
+        let results = push(&repo, &branch, false);
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].accepted);
+        assert_eq!(results[0].remote_ref, format!("refs/heads/{}", branch));
+        assert_eq!(
+            remote.refname_to_id(&results[0].remote_ref).unwrap(),
+            repo.head().unwrap().target().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_delete_remote_tag_reports_the_deleted_ref() {
+        let (_dir, repo, remote, branch) = repo_with_remote();
+        push(&repo, &branch, false);
+        let head = repo.head().unwrap().target().unwrap();
+        remote.reference("refs/tags/v1", head, false, "tag").unwrap();
+
+        let auth = GitAuth::new(None, None, None);
+        let operation = Operation::start(None, None, "push");
+        let results = delete_remote_tag_impl(&repo, "v1", "origin", &auth, &operation).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].accepted, "{:?}", results[0].message);
+        assert_eq!(results[0].remote_ref, "refs/tags/v1");
+        assert!(remote.find_reference("refs/tags/v1").is_err());
+    }
+
+    #[test]
+    fn test_push_reports_non_fast_forward_rejection() {
+        let (_dir, repo, remote, branch) = repo_with_remote();
+        let pushed = repo.head().unwrap().target().unwrap();
+        push(&repo, &branch, false);
+
+        amend_head(&repo, "rewritten");
+        let results = push(&repo, &branch, false);
+
+        assert!(!results[0].accepted);
+        assert!(results[0].message.is_some());
+        assert_eq!(remote.refname_to_id(&format!("refs/heads/{}", branch)).unwrap(), pushed);
+    }
+
+    #[test]
+    fn test_force_with_lease_overwrites_when_remote_unchanged() {
+        let (_dir, repo, remote, branch) = repo_with_remote();
+        push(&repo, &branch, false);
+
+        let rewritten = amend_head(&repo, "rewritten");
+        let results = push(&repo, &branch, true);
+
+        assert!(results[0].accepted, "{:?}", results[0].message);
+        assert_eq!(remote.refname_to_id(&format!("refs/heads/{}", branch)).unwrap(), rewritten);
+    }
+
+    #[test]
+    fn test_force_with_lease_rejects_when_remote_moved() {
+        let (_dir, repo, remote, branch) = repo_with_remote();
+        push(&repo, &branch, false);
+
+        // Someone else pushes to the remote after our last fetch
+        let theirs = {
+            let head = remote.find_reference(&format!("refs/heads/{}", branch)).unwrap().peel_to_commit().unwrap();
+            let sig = Signature::now("Other", "other@example.com").unwrap();
+            remote
+                .commit(Some(&format!("refs/heads/{}", branch)), &sig, &sig, "theirs", &head.tree().unwrap(), &[&head])
+                .unwrap()
+        };
+
+        amend_head(&repo, "rewritten");
+        let results = push(&repo, &branch, true);
+
+        assert!(!results[0].accepted);
+        assert_eq!(results[0].message.as_deref(), Some("stale info"));
+        assert_eq!(remote.refname_to_id(&format!("refs/heads/{}", branch)).unwrap(), theirs);
+    }
+
+    #[test]
+    fn test_scan_groups_linked_worktrees_under_main_repository() {
+        let root = TempDir::new().unwrap();
+        let repo = Repository::init(root.path().join("zeta")).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Test User").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let tree = repo.find_tree(repo.treebuilder(None).unwrap().write().unwrap()).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &[]).unwrap();
+        repo.worktree("alpha-wt", &root.path().join("alpha-wt"), None).unwrap();
+        Repository::init(root.path().join("beta")).unwrap();
+
+        // A submodule-style `.git` file that is not a worktree is ignored
+        std::fs::create_dir(root.path().join("sub")).unwrap();
+        std::fs::write(root.path().join("sub/.git"), "gitdir: ../nowhere\n").unwrap();
+
+        let repos = scan_repositories_impl(&root.path().to_string_lossy()).unwrap();
+        let names: Vec<&str> = repos.iter().map(|r| r.name.as_str()).collect();
+
+        assert_eq!(names, vec!["beta", "zeta", "alpha-wt"]);
+        assert_eq!(repos[1].main_repository, None);
+        assert_eq!(repos[2].main_repository.as_deref(), Some(repos[1].path.as_str()));
+    }
+
+    #[test]
+    fn test_clean_merge_waiting_for_commit_can_be_aborted() {
+        let (dir, repo) = init_repo();
+        let base = commit_file(&repo, "a.txt", "a\n", "base");
+        let main_ref = repo.head().unwrap().name().unwrap().to_string();
+        repo.branch("topic", &repo.find_commit(base).unwrap(), false).unwrap();
+        let main = commit_file(&repo, "b.txt", "b\n", "add b");
+
+        repo.set_head("refs/heads/topic").unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        let topic = commit_file(&repo, "c.txt", "c\n", "add c");
+        repo.set_head(&main_ref).unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        repo.merge(&[&repo.find_annotated_commit(topic).unwrap()], None, None).unwrap();
+
+        let state = get_merge_state_impl(&repo).unwrap();
+        assert!(state.is_merge_in_progress);
+        assert_eq!(state.conflict_count, 0);
+
+        abort_merge_impl(&repo).unwrap();
+
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+        assert_eq!(repo.head().unwrap().target(), Some(main));
+        assert!(!dir.path().join("c.txt").exists());
+    }
+
+    #[test]
+    fn test_abort_merge_keeps_unrelated_local_changes() {
+        let (dir, repo) = init_repo();
+        commit_file(&repo, "b.txt", "b\n", "add b");
+        topic_branch(&repo);
+        let main_ref = repo.head().unwrap().name().unwrap().to_string();
+        repo.set_head("refs/heads/topic").unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        let topic = commit_file(&repo, "a.txt", "theirs\n", "change a on topic");
+        repo.set_head(&main_ref).unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        let main = commit_file(&repo, "a.txt", "ours\n", "change a on main");
+
+        std::fs::write(dir.path().join("b.txt"), "edited\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "untracked\n").unwrap();
+        repo.merge(&[&repo.find_annotated_commit(topic).unwrap()], None, None).unwrap();
+        assert!(repo.index().unwrap().has_conflicts());
+
+        abort_merge_impl(&repo).unwrap();
+
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+        assert_eq!(repo.head().unwrap().target(), Some(main));
+        assert!(!repo.index().unwrap().has_conflicts());
+        assert_eq!(std::fs::read_to_string(dir.path().join("a.txt")).unwrap(), "ours\n");
+        assert!(!dir.path().join("c.txt").exists());
+        assert_eq!(std::fs::read_to_string(dir.path().join("b.txt")).unwrap(), "edited\n");
+        assert_eq!(std::fs::read_to_string(dir.path().join("notes.txt")).unwrap(), "untracked\n");
+    }
+
+    #[test]
+    fn test_abort_merge_refuses_to_overwrite_edits_to_merged_files() {
+        let (dir, repo) = init_repo();
+        let (_, topic) = topic_branch(&repo);
+        commit_file(&repo, "b.txt", "b\n", "add b");
+        repo.merge(&[&repo.find_annotated_commit(topic).unwrap()], None, None).unwrap();
+        std::fs::write(dir.path().join("c.txt"), "edited after merging\n").unwrap();
+
+        assert!(abort_merge_impl(&repo).is_err());
+        assert_eq!(repo.state(), git2::RepositoryState::Merge);
+        assert_eq!(std::fs::read_to_string(dir.path().join("c.txt")).unwrap(), "edited after merging\n");
+
+        // Nothing to abort once the merge state is gone
+        repo.cleanup_state().unwrap();
+        assert!(abort_merge_impl(&repo).is_err());
+    }
+
+    /// Default branch with `base`, and a `topic` branch adding c.txt on top of
+    /// it; HEAD is back on the default branch. Returns (base, topic).
+    fn topic_branch(repo: &Repository) -> (Oid, Oid) {
+        let base = commit_file(repo, "a.txt", "a\n", "base");
+        let main_ref = repo.head().unwrap().name().unwrap().to_string();
+        repo.branch("topic", &repo.find_commit(base).unwrap(), false).unwrap();
+        repo.set_head("refs/heads/topic").unwrap();
+        let topic = commit_file(repo, "c.txt", "c\n", "add c");
+        repo.set_head(&main_ref).unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        (base, topic)
+    }
+
+    #[test]
+    fn test_merge_no_ff_creates_merge_commit_with_message() {
+        let (dir, repo) = init_repo();
+        let (base, topic) = topic_branch(&repo);
+
+        let options = MergeOptions {
+            fast_forward: Some(FastForward::Never),
+            message: Some("Bring in topic".to_string()),
+            ..Default::default()
+        };
+        merge_branch_impl(&repo, "topic", &options).unwrap();
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_ids().collect::<Vec<_>>(), vec![base, topic]);
+        assert_eq!(head.message(), Some("Bring in topic"));
+        assert!(dir.path().join("c.txt").exists());
+    }
+
+    #[test]
+    fn test_merge_ff_config_is_honoured() {
+        let (_dir, repo) = init_repo();
+        let (_, topic) = topic_branch(&repo);
+        repo.config().unwrap().set_str("merge.ff", "false").unwrap();
+
+        merge_branch_impl(&repo, "topic", &MergeOptions::default()).unwrap();
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 2);
+        assert_eq!(head.message(), Some("Merge branch 'topic'"));
+        assert_eq!(head.parent_id(1).unwrap(), topic);
+    }
+
+    #[test]
+    fn test_merge_ff_only_refuses_diverged_branches() {
+        let (_dir, repo) = init_repo();
+        topic_branch(&repo);
+        let main = commit_file(&repo, "b.txt", "b\n", "add b");
+        repo.config().unwrap().set_str("merge.ff", "only").unwrap();
+
+        assert!(merge_branch_impl(&repo, "topic", &MergeOptions::default()).is_err());
+        assert_eq!(repo.head().unwrap().target(), Some(main));
+
+        // An explicit mode overrides the config
+        let options = MergeOptions { fast_forward: Some(FastForward::Allow), ..Default::default() };
+        merge_branch_impl(&repo, "topic", &options).unwrap();
+        assert_eq!(repo.head().unwrap().peel_to_commit().unwrap().parent_count(), 2);
+    }
+
+    #[test]
+    fn test_merge_squash_stages_without_committing() {
+        let (_dir, repo) = init_repo();
+        let (base, _) = topic_branch(&repo);
+
+        let options = MergeOptions { squash: true, ..Default::default() };
+        merge_branch_impl(&repo, "topic", &options).unwrap();
+
+        assert_eq!(repo.head().unwrap().target(), Some(base));
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+        assert!(repo.index().unwrap().get_path(std::path::Path::new("c.txt"), 0).is_some());
+        let message = std::fs::read_to_string(repo.path().join("SQUASH_MSG")).unwrap();
+        assert!(message.starts_with("Squashed commit of the following:\n"));
+        assert!(message.contains("    add c\n"));
+
+        let options = MergeOptions { squash: true, fast_forward: Some(FastForward::Never), ..Default::default() };
+        assert!(merge_branch_impl(&repo, "topic", &options).is_err());
+    }
+
+    #[test]
+    fn test_conflicting_squash_merge_completes_with_squash_message() {
+        let (dir, repo) = init_repo();
+        topic_branch(&repo);
+        let main_ref = repo.head().unwrap().name().unwrap().to_string();
+        let main = commit_file(&repo, "a.txt", "ours\n", "change a on main");
+        repo.set_head("refs/heads/topic").unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        commit_file(&repo, "a.txt", "theirs\n", "change a on topic");
+        repo.set_head(&main_ref).unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+
+        let options = MergeOptions { squash: true, ..Default::default() };
+        assert!(merge_branch_impl(&repo, "topic", &options).is_err());
+        assert!(get_merge_state_impl(&repo).unwrap().is_merge_in_progress);
+
+        std::fs::write(dir.path().join("a.txt"), "resolved\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+        assert!(get_merge_state_impl(&repo).unwrap().is_merge_in_progress);
+
+        complete_merge_impl(&repo, None).unwrap();
+
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_ids().collect::<Vec<_>>(), vec![main]);
+        assert!(head.message().unwrap().starts_with("Squashed commit of the following:\n"));
+        assert!(head.tree().unwrap().get_path(std::path::Path::new("c.txt")).is_ok());
+        assert!(!repo.path().join("SQUASH_MSG").exists());
+        assert!(!get_merge_state_impl(&repo).unwrap().is_merge_in_progress);
+    }
+
+    #[test]
+    fn test_merge_favor_theirs_settles_conflicting_hunks() {
+        let (dir, repo) = init_repo();
+        topic_branch(&repo);
+        let main_ref = repo.head().unwrap().name().unwrap().to_string();
+        commit_file(&repo, "a.txt", "ours\n", "change a on main");
+        repo.set_head("refs/heads/topic").unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        commit_file(&repo, "a.txt", "theirs\n", "change a on topic");
+        repo.set_head(&main_ref).unwrap();
+        repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+
+        let options = MergeOptions { favor: ConflictFavor::Theirs, ..Default::default() };
+        merge_branch_impl(&repo, "topic", &options).unwrap();
+
+        assert_eq!(std::fs::read_to_string(dir.path().join("a.txt")).unwrap(), "theirs\n");
+        assert_eq!(repo.head().unwrap().peel_to_commit().unwrap().parent_count(), 2);
+        assert_eq!(repo.state(), git2::RepositoryState::Clean);
+    }
+
+    const LINES: &str = "one\ntwo\nthree\nfour\nfive\nsix\n";
+
+    #[test]
+    fn test_status_reports_renames_at_their_new_path() {
+        let (dir, repo) = init_repo();
+        commit_file(&repo, "a.txt", LINES, "base");
+        commit_file(&repo, "b.txt", "bee\n", "add b");
+
+        // a.txt renamed in the index and edited afterwards, b.txt only moved on disk
+        std::fs::rename(dir.path().join("a.txt"), dir.path().join("renamed.txt")).unwrap();
+        let mut index = repo.index().unwrap();
+        index.remove_path(std::path::Path::new("a.txt")).unwrap();
+        index.add_path(std::path::Path::new("renamed.txt")).unwrap();
+        index.write().unwrap();
+        std::fs::write(dir.path().join("renamed.txt"), LINES.replace("six", "SIX")).unwrap();
+        std::fs::rename(dir.path().join("b.txt"), dir.path().join("moved.txt")).unwrap();
+
+        let status = get_repo_status_impl(&repo, &RenameOptions::default()).unwrap();
+
+        assert_eq!(status.staged.len(), 1);
+        assert_eq!(status.staged[0].path, "renamed.txt");
+        assert_eq!(status.staged[0].old_path.as_deref(), Some("a.txt"));
+        assert_eq!(status.staged[0].status, FileStatus::Renamed);
+
+        // Unstaged moves stay a deletion and a new file unless asked for
+        let unstaged: Vec<(&str, Option<&str>, FileStatus)> =
+            status.unstaged.iter().map(|i| (i.path.as_str(), i.old_path.as_deref(), i.status.clone())).collect();
+        assert!(unstaged.contains(&("renamed.txt", None, FileStatus::Modified)));
+        assert!(unstaged.contains(&("b.txt", None, FileStatus::Deleted)));
+        assert_eq!(status.untracked[0].path, "moved.txt");
+
+        let status = get_repo_status_impl(&repo, &RenameOptions { worktree: true, ..Default::default() }).unwrap();
+        let unstaged: Vec<(&str, Option<&str>, FileStatus)> =
+            status.unstaged.iter().map(|i| (i.path.as_str(), i.old_path.as_deref(), i.status.clone())).collect();
+        assert!(unstaged.contains(&("moved.txt", Some("b.txt"), FileStatus::Renamed)));
+        assert!(status.untracked.is_empty());
+
+        let status = get_repo_status_impl(&repo, &RenameOptions { renames: false, ..Default::default() }).unwrap();
+        assert_eq!(status.untracked[0].path, "moved.txt");
+        assert!(status.staged.iter().all(|i| i.old_path.is_none()));
+    }
+
+    #[test]
+    fn test_status_marks_staged_copies_when_enabled() {
+        let (dir, repo) = init_repo();
+        commit_file(&repo, "a.txt", LINES, "base");
+        std::fs::write(dir.path().join("copy.txt"), LINES).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new("copy.txt")).unwrap();
+        index.write().unwrap();
+
+        let status = get_repo_status_impl(&repo, &RenameOptions::default()).unwrap();
+        assert_eq!(status.staged[0].status, FileStatus::Added);
+
+        let status = get_repo_status_impl(&repo, &RenameOptions { copies: true, ..Default::default() }).unwrap();
+        assert_eq!(status.staged[0].path, "copy.txt");
+        assert_eq!(status.staged[0].status, FileStatus::Copied);
+        assert_eq!(status.staged[0].old_path.as_deref(), Some("a.txt"));
+    }
+}
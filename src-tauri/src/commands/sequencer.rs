@@ -0,0 +1,568 @@
+use crate::domain::{SequencerOptions, SequencerOutcome};
+use crate::error::{AppError, Result};
+use super::commit::create_commit;
+use super::rebase::ensure_clean_worktree;
+use super::repo::reset_merge;
+use git2::{Oid, Repository};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+// Cherry-pick and revert share one engine. While stopped on a conflict the
+// remaining commits are kept in `.git/sequencer` next to CHERRY_PICK_HEAD or
+// REVERT_HEAD and MERGE_MSG, the files git uses, so `repo.state()`, `git
+// status` and `get_merge_state` all see the operation. As with the rebase
+// engine, the complete state lives in a JSON file that only we read.
+const SEQUENCER_DIR: &str = "sequencer";
+const SESSION_FILE: &str = "gat-sequence.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum SequenceAction {
+    Pick,
+    Revert,
+}
+
+impl SequenceAction {
+    fn as_str(&self) -> &str {
+        match self {
+            SequenceAction::Pick => "pick",
+            SequenceAction::Revert => "revert",
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            SequenceAction::Pick => "cherry-pick",
+            SequenceAction::Revert => "revert",
+        }
+    }
+
+    fn head_file(&self) -> &str {
+        match self {
+            SequenceAction::Pick => "CHERRY_PICK_HEAD",
+            SequenceAction::Revert => "REVERT_HEAD",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SequenceSession {
+    action: SequenceAction,
+    options: SequencerOptions,
+    orig_head: String,
+    /// Commit whose changes are in the index with conflicts
+    current: Option<String>,
+    /// Index tree from before `current` was applied, restored by skip
+    base_tree: Option<String>,
+    todo: Vec<String>,
+    created: Vec<String>,
+}
+
+impl SequenceSession {
+    fn dir(repo: &Repository) -> PathBuf {
+        repo.path().join(SEQUENCER_DIR)
+    }
+
+    fn exists(repo: &Repository) -> bool {
+        Self::dir(repo).join(SESSION_FILE).exists()
+    }
+
+    /// The saved session, of either action
+    fn read(repo: &Repository) -> Result<Option<Self>> {
+        if !Self::exists(repo) {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(Self::dir(repo).join(SESSION_FILE))?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| AppError::InvalidInput(format!("Corrupt cherry-pick/revert state: {}", e)))
+    }
+
+    fn load(repo: &Repository, action: SequenceAction) -> Result<Self> {
+        let session = Self::read(repo)?
+            .ok_or_else(|| AppError::InvalidInput(format!("No {} in progress", action.name())))?;
+
+        if session.action != action {
+            return Err(AppError::InvalidInput(format!(
+                "A {} is in progress, not a {}",
+                session.action.name(),
+                action.name()
+            )));
+        }
+        Ok(session)
+    }
+
+    fn save(&self, repo: &Repository) -> Result<()> {
+        let dir = Self::dir(repo);
+        std::fs::create_dir_all(&dir)?;
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::InvalidInput(e.to_string()))?;
+        std::fs::write(dir.join(SESSION_FILE), json)?;
+
+        // git-compatible files
+        std::fs::write(dir.join("head"), format!("{}\n", self.orig_head))?;
+        let todo: String = self.current.iter()
+            .chain(self.todo.iter())
+            .map(|commit| format!("{} {}\n", self.action.as_str(), commit))
+            .collect();
+        std::fs::write(dir.join("todo"), todo)?;
+
+        Ok(())
+    }
+
+    fn outcome(&self) -> SequencerOutcome {
+        SequencerOutcome {
+            finished: self.current.is_none() && self.todo.is_empty(),
+            created_commits: self.created.clone(),
+            stopped_at: self.current.clone(),
+            remaining: self.todo.len(),
+        }
+    }
+}
+
+/// Remove the sequencer directory and the per-commit state files
+fn clear_state(repo: &Repository) -> Result<()> {
+    let dir = SequenceSession::dir(repo);
+    if dir.exists() {
+        std::fs::remove_dir_all(dir)?;
+    }
+    for name in ["CHERRY_PICK_HEAD", "REVERT_HEAD", "MERGE_MSG"] {
+        let path = repo.path().join(name);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Cherry-pick commits onto HEAD in the given order
+#[tauri::command]
+pub async fn cherry_pick_commits(
+    path: String,
+    ids: Vec<String>,
+    options: Option<SequencerOptions>,
+) -> std::result::Result<SequencerOutcome, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    start_sequence(&repo, SequenceAction::Pick, &ids, options.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+/// Revert commits on top of HEAD in the given order
+#[tauri::command]
+pub async fn revert_commits(
+    path: String,
+    ids: Vec<String>,
+    options: Option<SequencerOptions>,
+) -> std::result::Result<SequencerOutcome, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    start_sequence(&repo, SequenceAction::Revert, &ids, options.unwrap_or_default())
+        .map_err(|e| e.to_string())
+}
+
+fn start_sequence(
+    repo: &Repository,
+    action: SequenceAction,
+    ids: &[String],
+    options: SequencerOptions,
+) -> Result<SequencerOutcome> {
+    if ids.is_empty() {
+        return Err(AppError::InvalidInput(format!("No commits to {}", action.name())));
+    }
+
+    if SequenceSession::exists(repo) || repo.state() != git2::RepositoryState::Clean {
+        return Err(AppError::InvalidInput(format!(
+            "Cannot {}: another operation (merge, rebase, cherry-pick, ...) is in progress",
+            action.name()
+        )));
+    }
+
+    ensure_clean_worktree(repo, action.name())?;
+
+    // Resolve and validate everything up front so a bad id doesn't stop us halfway
+    let mut todo = Vec::with_capacity(ids.len());
+    for id in ids {
+        let commit = repo.revparse_single(id)?.peel_to_commit()?;
+        mainline_parent(&commit, options.mainline)?;
+        todo.push(commit.id().to_string());
+    }
+
+    let session = SequenceSession {
+        action,
+        options,
+        orig_head: repo.head()?.peel_to_commit()?.id().to_string(),
+        current: None,
+        base_tree: None,
+        todo,
+        created: Vec::new(),
+    };
+
+    run(repo, session)
+}
+
+/// The parent a commit is compared against, honouring `mainline` for merges
+fn mainline_parent<'r>(commit: &git2::Commit<'r>, mainline: Option<u32>) -> Result<Option<git2::Commit<'r>>> {
+    match (commit.parent_count(), mainline) {
+        // Like git >= 2.21, a mainline is accepted (and ignored) for ordinary commits
+        (0, _) => Ok(None),
+        (1, _) => Ok(Some(commit.parent(0)?)),
+        (_, None) => Err(AppError::InvalidInput(format!(
+            "Commit {} is a merge but no mainline was given",
+            commit.id()
+        ))),
+        (count, Some(n)) => {
+            if n == 0 || n as usize > count {
+                return Err(AppError::InvalidInput(format!(
+                    "Commit {} does not have parent {}",
+                    commit.id(),
+                    n
+                )));
+            }
+            Ok(Some(commit.parent(n as usize - 1)?))
+        }
+    }
+}
+
+/// Apply remaining commits until the list is empty or one conflicts.
+///
+/// The session is saved after every step, so an error part way through
+/// (an untracked file in the way, a failed signing) stops the operation
+/// with the commits already applied recorded, for continue or abort.
+fn run(repo: &Repository, mut session: SequenceSession) -> Result<SequencerOutcome> {
+    while let Some(next) = session.todo.first() {
+        let oid = Oid::from_str(next)?;
+        let commit = repo.find_commit(oid)?;
+
+        let mut index = repo.index()?;
+        let base_tree = repo.find_tree(index.write_tree()?)?;
+
+        // Nothing of this commit is applied if either fails, so it stays
+        // first in the saved todo list for continue to retry
+        let mut merged = merge_step(repo, session.action, &commit, &base_tree, session.options.mainline)?;
+        let label = format!("{:.7} ({})", commit.id(), commit.summary().unwrap_or(""));
+        apply_step(repo, &base_tree, &mut merged, &label)?;
+        session.todo.remove(0);
+
+        if merged.has_conflicts() {
+            write_stop_files(repo, &session, &commit, &merged)?;
+            session.current = Some(oid.to_string());
+            session.base_tree = Some(base_tree.id().to_string());
+            session.save(repo)?;
+            return Ok(session.outcome());
+        }
+
+        if !session.options.no_commit {
+            let message = step_message(repo, &session, &commit)?;
+            match commit_step(repo, session.action, &commit, &message) {
+                Ok(Some(created)) => session.created.push(created.to_string()),
+                Ok(None) => {}
+                Err(err) => {
+                    // The changes are staged; stop on them like on a conflict
+                    write_stop_files(repo, &session, &commit, &merged)?;
+                    session.current = Some(oid.to_string());
+                    session.base_tree = Some(base_tree.id().to_string());
+                    session.save(repo)?;
+                    return Err(err);
+                }
+            }
+        }
+        session.save(repo)?;
+    }
+
+    clear_state(repo)?;
+    Ok(session.outcome())
+}
+
+/// Finish the stopped step of the cherry-pick or revert in progress, with
+/// `message` for its commit, and go on with the rest. `None` when neither
+/// is in progress
+pub(crate) fn continue_in_progress(repo: &Repository, message: Option<&str>) -> Result<Option<SequencerOutcome>> {
+    let Some(session) = SequenceSession::read(repo)? else {
+        return Ok(None);
+    };
+    if let (Some(message), Some(_)) = (message, &session.current) {
+        std::fs::write(repo.path().join("MERGE_MSG"), message)?;
+    }
+    continue_sequence(repo, session.action).map(Some)
+}
+
+/// Write a step's merge result, conflicts included, to the working tree and index.
+///
+/// Checkout compares against HEAD, which no longer describes the index once
+/// `no_commit` steps have piled up, so we force out just the paths the step
+/// changes; the working tree matches the index before every step.
+fn apply_step(repo: &Repository, base_tree: &git2::Tree, merged: &mut git2::Index, label: &str) -> Result<()> {
+    let workdir = repo.workdir().ok_or(AppError::InvalidInput("No workdir".to_string()))?;
+    let diff = repo.diff_tree_to_index(Some(base_tree), Some(merged), None)?;
+
+    let mut checkout = git2::build::CheckoutBuilder::new();
+    checkout
+        .force()
+        .allow_conflicts(true)
+        .conflict_style_merge(true)
+        .our_label("HEAD")
+        .their_label(label);
+
+    let mut changed = false;
+    for delta in diff.deltas() {
+        let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+            continue;
+        };
+        if delta.status() == git2::Delta::Added && workdir.join(path).exists() {
+            return Err(AppError::InvalidInput(format!(
+                "Untracked file {} would be overwritten",
+                path.display()
+            )));
+        }
+        checkout.path(path);
+        changed = true;
+    }
+
+    if changed {
+        repo.checkout_index(Some(merged), Some(&mut checkout))?;
+    }
+
+    if !merged.has_conflicts() {
+        let tree = repo.find_tree(merged.write_tree_to(repo)?)?;
+        let mut index = repo.index()?;
+        index.read_tree(&tree)?;
+        index.write()?;
+    }
+    Ok(())
+}
+
+/// Three-way merge of one commit's changes into the current index
+fn merge_step(
+    repo: &Repository,
+    action: SequenceAction,
+    commit: &git2::Commit,
+    ours: &git2::Tree,
+    mainline: Option<u32>,
+) -> Result<git2::Index> {
+    let parent_tree = match mainline_parent(commit, mainline)? {
+        Some(parent) => parent.tree()?,
+        None => repo.find_tree(repo.treebuilder(None)?.write()?)?,
+    };
+    let commit_tree = commit.tree()?;
+
+    let (ancestor, theirs) = match action {
+        SequenceAction::Pick => (&parent_tree, &commit_tree),
+        SequenceAction::Revert => (&commit_tree, &parent_tree),
+    };
+
+    Ok(repo.merge_trees(ancestor, ours, theirs, None)?)
+}
+
+fn step_message(repo: &Repository, session: &SequenceSession, commit: &git2::Commit) -> Result<String> {
+    let message = match session.action {
+        SequenceAction::Pick => {
+            let original = commit.message().unwrap_or("").trim_end();
+            if session.options.record_origin {
+                format!("{}\n\n(cherry picked from commit {})\n", original, commit.id())
+            } else {
+                format!("{}\n", original)
+            }
+        }
+        SequenceAction::Revert => {
+            let mut message = format!(
+                "Revert \"{}\"\n\nThis reverts commit {}",
+                commit.summary().unwrap_or(""),
+                commit.id()
+            );
+            match mainline_parent(commit, session.options.mainline)? {
+                Some(parent) if commit.parent_count() > 1 => {
+                    message.push_str(&format!(", reversing\nchanges made to {}.\n", parent.id()));
+                }
+                _ => message.push_str(".\n"),
+            }
+            message
+        }
+    };
+
+    // Keep the user's edits from a previous stop
+    let merge_msg = repo.path().join("MERGE_MSG");
+    if session.current.as_deref() == Some(&commit.id().to_string()) && merge_msg.exists() {
+        return Ok(strip_comments(&std::fs::read_to_string(merge_msg)?));
+    }
+    Ok(message)
+}
+
+fn strip_comments(message: &str) -> String {
+    let kept: Vec<&str> = message.lines().filter(|line| !line.starts_with('#')).collect();
+    format!("{}\n", kept.join("\n").trim_end())
+}
+
+fn write_stop_files(
+    repo: &Repository,
+    session: &SequenceSession,
+    commit: &git2::Commit,
+    merged: &git2::Index,
+) -> Result<()> {
+    std::fs::write(
+        repo.path().join(session.action.head_file()),
+        format!("{}\n", commit.id()),
+    )?;
+
+    let mut message = step_message(repo, session, commit)?;
+    if merged.has_conflicts() {
+        message.push_str("\n# Conflicts:\n");
+        for conflict in merged.conflicts()? {
+            let conflict = conflict?;
+            if let Some(entry) = conflict.our.or(conflict.their).or(conflict.ancestor) {
+                message.push_str(&format!("#\t{}\n", String::from_utf8_lossy(&entry.path)));
+            }
+        }
+    }
+    std::fs::write(repo.path().join("MERGE_MSG"), message)?;
+    Ok(())
+}
+
+/// Commit the index for one step; None when the change is already in HEAD
+fn commit_step(
+    repo: &Repository,
+    action: SequenceAction,
+    commit: &git2::Commit,
+    message: &str,
+) -> Result<Option<Oid>> {
+    let mut index = repo.index()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let head = repo.head()?.peel_to_commit()?;
+
+    if tree.id() == head.tree_id() {
+        return Ok(None);
+    }
+
+    let committer = repo.signature()?;
+    let author = match action {
+        SequenceAction::Pick => commit.author(),
+        SequenceAction::Revert => committer.clone(),
+    };
+    let oid = create_commit(repo, Some("HEAD"), &author, &committer, message, &tree, &[&head])?;
+    Ok(Some(oid))
+}
+
+/// Continue a cherry-pick after resolving conflicts
+#[tauri::command]
+pub async fn continue_cherry_pick(path: String) -> std::result::Result<SequencerOutcome, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    continue_sequence(&repo, SequenceAction::Pick).map_err(|e| e.to_string())
+}
+
+/// Continue a revert after resolving conflicts
+#[tauri::command]
+pub async fn continue_revert(path: String) -> std::result::Result<SequencerOutcome, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    continue_sequence(&repo, SequenceAction::Revert).map_err(|e| e.to_string())
+}
+
+fn continue_sequence(repo: &Repository, action: SequenceAction) -> Result<SequencerOutcome> {
+    let mut session = SequenceSession::load(repo, action)?;
+
+    if let Some(current) = session.current.clone() {
+        if repo.index()?.has_conflicts() {
+            return Err(AppError::InvalidInput(format!(
+                "Cannot continue {}: there are still unresolved conflicts",
+                action.name()
+            )));
+        }
+
+        if !session.options.no_commit {
+            let commit = repo.find_commit(Oid::from_str(&current)?)?;
+            let message = step_message(repo, &session, &commit)?;
+            if let Some(created) = commit_step(repo, action, &commit, &message)? {
+                session.created.push(created.to_string());
+            }
+        }
+
+        session.current = None;
+        session.base_tree = None;
+        for name in [action.head_file(), "MERGE_MSG"] {
+            let path = repo.path().join(name);
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
+        }
+        session.save(repo)?;
+    }
+
+    run(repo, session)
+}
+
+/// Skip the commit a cherry-pick stopped on
+#[tauri::command]
+pub async fn skip_cherry_pick(path: String) -> std::result::Result<SequencerOutcome, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    skip_sequence(&repo, SequenceAction::Pick).map_err(|e| e.to_string())
+}
+
+/// Skip the commit a revert stopped on
+#[tauri::command]
+pub async fn skip_revert(path: String) -> std::result::Result<SequencerOutcome, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    skip_sequence(&repo, SequenceAction::Revert).map_err(|e| e.to_string())
+}
+
+fn skip_sequence(repo: &Repository, action: SequenceAction) -> Result<SequencerOutcome> {
+    let mut session = SequenceSession::load(repo, action)?;
+
+    // Put back the index (and with it the working tree) from before the stopped commit
+    if let Some(base_tree) = session.base_tree.take() {
+        let tree = repo.find_tree(Oid::from_str(&base_tree)?)?;
+        let mut checkout = git2::build::CheckoutBuilder::new();
+        checkout.force();
+        repo.checkout_tree(tree.as_object(), Some(&mut checkout))?;
+
+        let mut index = repo.index()?;
+        index.read_tree(&tree)?;
+        index.write()?;
+    }
+
+    // Stopped by an error before the commit was applied: drop it from the list
+    if session.current.take().is_none() && !session.todo.is_empty() {
+        session.todo.remove(0);
+    }
+    for name in [action.head_file(), "MERGE_MSG"] {
+        let path = repo.path().join(name);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+    }
+    session.save(repo)?;
+
+    run(repo, session)
+}
+
+/// Abort a cherry-pick and return to the original HEAD
+#[tauri::command]
+pub async fn abort_cherry_pick(path: String) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    abort_sequence(&repo, SequenceAction::Pick).map_err(|e| e.to_string())
+}
+
+/// Abort a revert and return to the original HEAD
+#[tauri::command]
+pub async fn abort_revert(path: String) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    abort_sequence(&repo, SequenceAction::Revert).map_err(|e| e.to_string())
+}
+
+fn abort_sequence(repo: &Repository, action: SequenceAction) -> Result<()> {
+    let session = SequenceSession::load(repo, action)?;
+    let orig_head = repo.find_commit(Oid::from_str(&session.orig_head)?)?;
+
+    reset_merge(repo, &orig_head)?;
+    clear_state(repo)
+}
+
+/// Abort the cherry-pick or revert in progress; false when neither is
+pub(crate) fn abort_in_progress(repo: &Repository) -> Result<bool> {
+    let Some(session) = SequenceSession::read(repo)? else {
+        return Ok(false);
+    };
+    abort_sequence(repo, session.action)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+#[path = "sequencer_tests.rs"]
+mod sequencer_tests;
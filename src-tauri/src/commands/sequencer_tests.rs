@@ -0,0 +1,273 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::operation::get_repository_operation_state_impl;
+    use crate::commands::repo::complete_merge_impl;
+    use crate::commands::sequencer::{abort_sequence, continue_sequence, skip_sequence, start_sequence, SequenceAction};
+    use crate::commands::test_support::{commit_file, commit_index_as, init_repo, stage};
+    use crate::domain::{RepositoryOperation, SequencerOptions};
+    use git2::{Oid, Repository, Signature};
+    use tempfile::TempDir;
+
+    struct Fixture {
+        dir: TempDir,
+        repo: Repository,
+        main_ref: String,
+    }
+
+    impl Fixture {
+        /// Repo with a base commit on the default branch and a `topic` branch
+        /// starting from it; HEAD stays on the default branch
+        fn new() -> Self {
+            let (dir, repo) = init_repo();
+            let base = commit_file(&repo, "a.txt", "base\n", "base");
+            let main_ref = repo.head().unwrap().name().unwrap().to_string();
+            repo.branch("topic", &repo.find_commit(base).unwrap(), false).unwrap();
+            Self { dir, repo, main_ref }
+        }
+
+        fn commit_file(&self, name: &str, content: &str, message: &str) -> Oid {
+            commit_file(&self.repo, name, content, message)
+        }
+
+        fn commit_as(&self, name: &str, content: &str, message: &str, author: &str) -> Oid {
+            std::fs::write(self.dir.path().join(name), content).unwrap();
+            stage(&self.repo, name);
+            commit_index_as(&self.repo, message, &Signature::now(author, "test@example.com").unwrap())
+        }
+
+        fn checkout(&self, refname: &str) {
+            self.repo.set_head(refname).unwrap();
+            self.repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+        }
+
+        fn read(&self, name: &str) -> String {
+            std::fs::read_to_string(self.dir.path().join(name)).unwrap()
+        }
+
+        fn head(&self) -> git2::Commit<'_> {
+            self.repo.head().unwrap().peel_to_commit().unwrap()
+        }
+    }
+
+    fn ids(oids: &[Oid]) -> Vec<String> {
+        oids.iter().map(|oid| oid.to_string()).collect()
+    }
+
+    #[test]
+    fn test_cherry_pick_applies_commits_in_order() {
+        let f = Fixture::new();
+        f.checkout("refs/heads/topic");
+        let first = f.commit_as("b.txt", "b\n", "add b", "Someone Else");
+        let second = f.commit_file("b.txt", "b2\n", "change b");
+        f.checkout(&f.main_ref);
+
+        let options = SequencerOptions { record_origin: true, ..Default::default() };
+        let outcome = start_sequence(&f.repo, SequenceAction::Pick, &ids(&[first, second]), options).unwrap();
+
+        assert!(outcome.finished);
+        assert_eq!(outcome.created_commits.len(), 2);
+        assert_eq!(f.read("b.txt"), "b2\n");
+
+        let head = f.head();
+        assert_eq!(head.id().to_string(), outcome.created_commits[1]);
+        assert_eq!(
+            head.message().unwrap(),
+            format!("change b\n\n(cherry picked from commit {})\n", second)
+        );
+        let picked_first = head.parent(0).unwrap();
+        assert_eq!(picked_first.author().name(), Some("Someone Else"));
+        assert_eq!(f.repo.state(), git2::RepositoryState::Clean);
+    }
+
+    #[test]
+    fn test_cherry_pick_no_commit_accumulates_changes() {
+        let f = Fixture::new();
+        f.checkout("refs/heads/topic");
+        let first = f.commit_file("b.txt", "b\n", "add b");
+        let second = f.commit_file("c.txt", "c\n", "add c");
+        let third = f.commit_file("b.txt", "b2\n", "change b");
+        f.checkout(&f.main_ref);
+        let orig = f.head().id();
+
+        let options = SequencerOptions { no_commit: true, ..Default::default() };
+        let outcome = start_sequence(&f.repo, SequenceAction::Pick, &ids(&[first, second, third]), options).unwrap();
+
+        assert!(outcome.finished);
+        assert!(outcome.created_commits.is_empty());
+        assert_eq!(f.head().id(), orig);
+        let index = f.repo.index().unwrap();
+        assert!(index.get_path(std::path::Path::new("b.txt"), 0).is_some());
+        assert!(index.get_path(std::path::Path::new("c.txt"), 0).is_some());
+        assert_eq!(f.read("c.txt"), "c\n");
+        assert_eq!(f.read("b.txt"), "b2\n");
+    }
+
+    /// `topic` and the default branch both change a.txt after the base
+    fn conflicting_fixture() -> (Fixture, Oid, Oid) {
+        let f = Fixture::new();
+        f.checkout("refs/heads/topic");
+        let conflicting = f.commit_file("a.txt", "theirs\n", "change a");
+        let clean = f.commit_file("d.txt", "d\n", "add d");
+        f.checkout(&f.main_ref);
+        f.commit_file("a.txt", "ours\n", "our change");
+        (f, conflicting, clean)
+    }
+
+    #[test]
+    fn test_cherry_pick_stops_on_conflict_and_continues() {
+        let (f, conflicting, clean) = conflicting_fixture();
+
+        let outcome = start_sequence(&f.repo, SequenceAction::Pick, &ids(&[conflicting, clean]), SequencerOptions::default()).unwrap();
+
+        assert!(!outcome.finished);
+        assert_eq!(outcome.stopped_at, Some(conflicting.to_string()));
+        assert_eq!(outcome.remaining, 1);
+        assert!(f.repo.index().unwrap().has_conflicts());
+        assert_eq!(f.repo.state(), git2::RepositoryState::CherryPickSequence);
+        assert!(f.read("a.txt").contains("<<<<<<<"));
+
+        // Resolve and stage
+        std::fs::write(f.dir.path().join("a.txt"), "resolved\n").unwrap();
+        let mut index = f.repo.index().unwrap();
+        index.add_path(std::path::Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+
+        let outcome = continue_sequence(&f.repo, SequenceAction::Pick).unwrap();
+
+        assert!(outcome.finished);
+        assert_eq!(outcome.created_commits.len(), 2);
+        assert_eq!(f.head().message(), Some("add d\n"));
+        assert_eq!(f.head().parent(0).unwrap().message(), Some("change a\n"));
+        assert_eq!(f.read("a.txt"), "resolved\n");
+        assert_eq!(f.repo.state(), git2::RepositoryState::Clean);
+    }
+
+    #[test]
+    fn test_error_part_way_keeps_applied_commits_for_continue() {
+        let f = Fixture::new();
+        f.checkout("refs/heads/topic");
+        let first = f.commit_file("b.txt", "b\n", "add b");
+        let second = f.commit_file("c.txt", "c\n", "add c");
+        let third = f.commit_file("d.txt", "d\n", "add d");
+        f.checkout(&f.main_ref);
+        std::fs::write(f.dir.path().join("d.txt"), "untracked\n").unwrap();
+
+        let result = start_sequence(&f.repo, SequenceAction::Pick, &ids(&[first, second, third]), SequencerOptions::default());
+
+        assert!(result.is_err());
+        assert_eq!(f.head().message(), Some("add c\n"));
+        let state = get_repository_operation_state_impl(&f.repo).unwrap();
+        assert_eq!(state.operation, RepositoryOperation::CherryPickSequence);
+        assert!(f.read(".git/sequencer/todo").starts_with(&format!("pick {}", third)));
+
+        std::fs::remove_file(f.dir.path().join("d.txt")).unwrap();
+        let outcome = continue_sequence(&f.repo, SequenceAction::Pick).unwrap();
+
+        assert!(outcome.finished);
+        assert_eq!(f.head().message(), Some("add d\n"));
+        assert_eq!(f.head().parent(0).unwrap().message(), Some("add c\n"));
+        assert!(!f.dir.path().join(".git/sequencer").exists());
+    }
+
+    #[test]
+    fn test_complete_merge_finishes_stopped_cherry_pick() {
+        let (f, conflicting, clean) = conflicting_fixture();
+        let orig = f.head().id();
+        start_sequence(&f.repo, SequenceAction::Pick, &ids(&[conflicting, clean]), SequencerOptions::default()).unwrap();
+
+        std::fs::write(f.dir.path().join("a.txt"), "resolved\n").unwrap();
+        let mut index = f.repo.index().unwrap();
+        index.add_path(std::path::Path::new("a.txt")).unwrap();
+        index.write().unwrap();
+
+        complete_merge_impl(&f.repo, Some("resolved a".to_string())).unwrap();
+
+        let picked = f.head().parent(0).unwrap();
+        assert_eq!(f.head().message(), Some("add d\n"));
+        assert_eq!(picked.message(), Some("resolved a\n"));
+        assert_eq!(picked.parent_count(), 1);
+        assert_eq!(picked.parent_id(0).unwrap(), orig);
+        assert_eq!(f.repo.state(), git2::RepositoryState::Clean);
+    }
+
+    #[test]
+    fn test_continue_refuses_unresolved_conflicts_and_wrong_action() {
+        let (f, conflicting, _) = conflicting_fixture();
+        start_sequence(&f.repo, SequenceAction::Pick, &ids(&[conflicting]), SequencerOptions::default()).unwrap();
+
+        assert!(continue_sequence(&f.repo, SequenceAction::Pick).is_err());
+        assert!(continue_sequence(&f.repo, SequenceAction::Revert).is_err());
+    }
+
+    #[test]
+    fn test_skip_drops_conflicting_commit() {
+        let (f, conflicting, clean) = conflicting_fixture();
+        start_sequence(&f.repo, SequenceAction::Pick, &ids(&[conflicting, clean]), SequencerOptions::default()).unwrap();
+
+        let outcome = skip_sequence(&f.repo, SequenceAction::Pick).unwrap();
+
+        assert!(outcome.finished);
+        assert_eq!(outcome.created_commits.len(), 1);
+        assert_eq!(f.read("a.txt"), "ours\n");
+        assert_eq!(f.head().message(), Some("add d\n"));
+        assert_eq!(f.repo.state(), git2::RepositoryState::Clean);
+    }
+
+    #[test]
+    fn test_abort_restores_original_head() {
+        let (f, conflicting, clean) = conflicting_fixture();
+        let orig = f.head().id();
+        start_sequence(&f.repo, SequenceAction::Pick, &ids(&[clean, conflicting]), SequencerOptions::default()).unwrap();
+        assert_ne!(f.head().id(), orig);
+
+        abort_sequence(&f.repo, SequenceAction::Pick).unwrap();
+
+        assert_eq!(f.head().id(), orig);
+        assert_eq!(f.read("a.txt"), "ours\n");
+        assert!(!f.dir.path().join("d.txt").exists());
+        assert!(!f.repo.index().unwrap().has_conflicts());
+        assert_eq!(f.repo.state(), git2::RepositoryState::Clean);
+    }
+
+    #[test]
+    fn test_revert_undoes_commit() {
+        let f = Fixture::new();
+        let change = f.commit_file("a.txt", "changed\n", "change a");
+
+        let outcome = start_sequence(&f.repo, SequenceAction::Revert, &ids(&[change]), SequencerOptions::default()).unwrap();
+
+        assert!(outcome.finished);
+        assert_eq!(f.read("a.txt"), "base\n");
+        assert_eq!(
+            f.head().message().unwrap(),
+            format!("Revert \"change a\"\n\nThis reverts commit {}.\n", change)
+        );
+    }
+
+    #[test]
+    fn test_merge_commit_requires_mainline() {
+        let f = Fixture::new();
+        f.checkout("refs/heads/topic");
+        let topic = f.commit_file("b.txt", "b\n", "add b");
+        f.checkout(&f.main_ref);
+        let main = f.commit_file("c.txt", "c\n", "add c");
+
+        // Merge topic into the default branch by hand
+        let ours = f.repo.find_commit(main).unwrap();
+        let theirs = f.repo.find_commit(topic).unwrap();
+        let mut merged = f.repo.merge_commits(&ours, &theirs, None).unwrap();
+        let tree = f.repo.find_tree(merged.write_tree_to(&f.repo).unwrap()).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let merge = f.repo.commit(Some("HEAD"), &sig, &sig, "merge topic", &tree, &[&ours, &theirs]).unwrap();
+        f.repo.checkout_head(Some(git2::build::CheckoutBuilder::new().force())).unwrap();
+
+        assert!(start_sequence(&f.repo, SequenceAction::Revert, &ids(&[merge]), SequencerOptions::default()).is_err());
+
+        let options = SequencerOptions { mainline: Some(1), ..Default::default() };
+        start_sequence(&f.repo, SequenceAction::Revert, &ids(&[merge]), options).unwrap();
+
+        assert!(!f.dir.path().join("b.txt").exists());
+        assert!(f.dir.path().join("c.txt").exists());
+        assert!(f.head().message().unwrap().contains(&format!("reversing\nchanges made to {}.", main)));
+    }
+}
@@ -1,5 +1,8 @@
 use std::process::Command;
 
+/// Key types we look for as `~/.ssh/id_<type>`
+pub(crate) const KEY_TYPES: [&str; 4] = ["rsa", "ed25519", "ecdsa", "dsa"];
+
 #[tauri::command]
 pub async fn generate_ssh_key(
     email: String,
@@ -57,10 +60,7 @@ pub async fn get_ssh_keys() -> Result<Vec<SshKeyInfo>, String> {
     
     let mut keys = Vec::new();
     
-    // Common SSH key types
-    let key_types = ["rsa", "ed25519", "ecdsa", "dsa"];
-    
-    for key_type in key_types {
+    for key_type in KEY_TYPES {
         let key_path = ssh_dir.join(format!("id_{}", key_type));
         let pub_key_path = ssh_dir.join(format!("id_{}.pub", key_type));
         
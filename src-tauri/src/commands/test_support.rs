@@ -0,0 +1,60 @@
+//! Repository fixtures shared by the command tests
+
+use git2::{Oid, Repository, Signature, Time};
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Empty repository in a temporary directory, with a committer identity
+pub(crate) fn init_repo() -> (TempDir, Repository) {
+    let dir = TempDir::new().unwrap();
+    let repo = init_repo_at(dir.path());
+    (dir, repo)
+}
+
+/// Empty repository at `path`, with a committer identity
+pub(crate) fn init_repo_at(path: &Path) -> Repository {
+    let repo = Repository::init(path).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Test User").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+    repo
+}
+
+/// Add `name` from the working tree to the index
+pub(crate) fn stage(repo: &Repository, name: &str) {
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(name)).unwrap();
+    index.write().unwrap();
+}
+
+/// Commit the index on top of HEAD (or as the root commit)
+pub(crate) fn commit_index(repo: &Repository, message: &str) -> Oid {
+    commit_index_as(repo, message, &Signature::now("Test User", "test@example.com").unwrap())
+}
+
+/// `commit_index` with `sig` as author and committer
+pub(crate) fn commit_index_as(repo: &Repository, message: &str, sig: &Signature) -> Oid {
+    let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+    let parents: Vec<git2::Commit> = repo.head().ok()
+        .and_then(|h| h.peel_to_commit().ok())
+        .into_iter()
+        .collect();
+    let parents: Vec<&git2::Commit> = parents.iter().collect();
+    repo.commit(Some("HEAD"), sig, sig, message, &tree, &parents).unwrap()
+}
+
+/// Write `name` in the working tree, stage it and commit
+pub(crate) fn commit_file(repo: &Repository, name: &str, content: &str, message: &str) -> Oid {
+    std::fs::write(repo.workdir().unwrap().join(name), content).unwrap();
+    stage(repo, name);
+    commit_index(repo, message)
+}
+
+/// Commit an empty tree at `time` seconds with `parents`, moving `update_ref` to it
+pub(crate) fn commit_at(repo: &Repository, update_ref: Option<&str>, parents: &[Oid], time: i64, message: &str) -> Oid {
+    let tree = repo.find_tree(repo.treebuilder(None).unwrap().write().unwrap()).unwrap();
+    let sig = Signature::new("Test User", "test@example.com", &Time::new(time, 0)).unwrap();
+    let parents: Vec<git2::Commit> = parents.iter().map(|id| repo.find_commit(*id).unwrap()).collect();
+    let parents: Vec<&git2::Commit> = parents.iter().collect();
+    repo.commit(update_ref, &sig, &sig, message, &tree, &parents).unwrap()
+}
@@ -0,0 +1,230 @@
+use crate::domain::WorktreeInfo;
+use crate::error::{AppError, Result};
+use git2::{Repository, StatusOptions, WorktreeAddOptions, WorktreeLockStatus, WorktreePruneOptions};
+use std::path::{Path, PathBuf};
+
+/// The main repository a linked worktree belongs to, which may be bare
+fn open_main_repository(repo: &Repository) -> Result<Repository> {
+    // `.git/worktrees/<name>/commondir` points back at the main `.git`
+    let common = std::fs::read_to_string(repo.path().join("commondir"))?;
+    Ok(Repository::open(repo.path().join(common.trim()))?)
+}
+
+/// Location of the main repository a linked worktree belongs to: its working
+/// directory, or the git directory itself when the main repository is bare
+pub(crate) fn main_worktree_path(repo: &Repository) -> Option<PathBuf> {
+    if !repo.is_worktree() {
+        return None;
+    }
+    let main = open_main_repository(repo).ok()?;
+    Some(main.workdir().unwrap_or(main.path()).to_path_buf())
+}
+
+fn path_string(path: &Path) -> String {
+    let path = path.to_string_lossy();
+    let trimmed = path.trim_end_matches(['/', '\\']);
+    if trimmed.is_empty() { path.to_string() } else { trimmed.to_string() }
+}
+
+fn head_info(repo: &Repository) -> (Option<String>, Option<String>) {
+    match repo.head() {
+        Ok(head) => (
+            head.is_branch().then(|| head.shorthand().map(|s| s.to_string())).flatten(),
+            head.target().map(|oid| oid.to_string()),
+        ),
+        Err(_) => (None, None),
+    }
+}
+
+/// List the main working tree and all linked worktrees
+#[tauri::command]
+pub async fn list_worktrees(path: String) -> std::result::Result<Vec<WorktreeInfo>, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    list_worktrees_impl(&repo).map_err(|e| e.to_string())
+}
+
+fn list_worktrees_impl(repo: &Repository) -> Result<Vec<WorktreeInfo>> {
+    let linked_main;
+    let main = if repo.is_worktree() {
+        linked_main = open_main_repository(repo)?;
+        &linked_main
+    } else {
+        repo
+    };
+
+    // A bare main repository has no working tree of its own to list
+    let mut worktrees = Vec::new();
+    if let Some(main_path) = main.workdir() {
+        let (branch, head) = head_info(main);
+        worktrees.push(WorktreeInfo {
+            name: None,
+            path: path_string(main_path),
+            branch,
+            head,
+            is_main: true,
+            is_locked: false,
+            lock_reason: None,
+            is_prunable: false,
+        });
+    }
+
+    for name in main.worktrees()?.iter().flatten() {
+        let worktree = main.find_worktree(name)?;
+        worktrees.push(worktree_info(name, &worktree)?);
+    }
+
+    Ok(worktrees)
+}
+
+fn worktree_info(name: &str, worktree: &git2::Worktree) -> Result<WorktreeInfo> {
+    let (is_locked, lock_reason) = match worktree.is_locked()? {
+        WorktreeLockStatus::Unlocked => (false, None),
+        WorktreeLockStatus::Locked(reason) => (true, reason.filter(|r| !r.is_empty())),
+    };
+
+    let (branch, head) = match worktree.validate() {
+        Ok(()) => Repository::open_from_worktree(worktree)
+            .map(|repo| head_info(&repo))
+            .unwrap_or((None, None)),
+        Err(_) => (None, None),
+    };
+
+    Ok(WorktreeInfo {
+        name: Some(name.to_string()),
+        path: path_string(worktree.path()),
+        branch,
+        head,
+        is_main: false,
+        is_locked,
+        lock_reason,
+        is_prunable: worktree.is_prunable(None)?,
+    })
+}
+
+/// Add a linked worktree at `worktree_path`.
+///
+/// `branch` is checked out there; with `create_branch` it is first created
+/// from `base` (default HEAD). Without a branch, one named after the worktree
+/// directory is created from HEAD, as `git worktree add <path>` does.
+#[tauri::command]
+pub async fn add_worktree(
+    path: String,
+    worktree_path: String,
+    branch: Option<String>,
+    create_branch: bool,
+    base: Option<String>,
+    lock: bool,
+) -> std::result::Result<WorktreeInfo, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    add_worktree_impl(&repo, Path::new(&worktree_path), branch.as_deref(), create_branch, base.as_deref(), lock)
+        .map_err(|e| e.to_string())
+}
+
+fn add_worktree_impl(
+    repo: &Repository,
+    worktree_path: &Path,
+    branch: Option<&str>,
+    create_branch: bool,
+    base: Option<&str>,
+    lock: bool,
+) -> Result<WorktreeInfo> {
+    let name = worktree_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| AppError::InvalidInput(format!("Invalid worktree path: {}", worktree_path.display())))?;
+
+    if worktree_path.exists() && std::fs::read_dir(worktree_path)?.next().is_some() {
+        return Err(AppError::InvalidInput(format!("{} already exists and is not empty", worktree_path.display())));
+    }
+
+    let reference = match branch {
+        Some(branch) if create_branch => {
+            let base = repo.revparse_single(base.unwrap_or("HEAD"))?.peel_to_commit()?;
+            Some(repo.branch(branch, &base, false)?.into_reference())
+        }
+        Some(branch) => Some(repo.find_branch(branch, git2::BranchType::Local)?.into_reference()),
+        None => None,
+    };
+
+    let mut options = WorktreeAddOptions::new();
+    options.lock(lock);
+    options.reference(reference.as_ref());
+
+    let worktree = repo.worktree(name, worktree_path, Some(&options))?;
+    worktree_info(name, &worktree)
+}
+
+/// Lock a worktree so it is not pruned, e.g. while it lives on removable media
+#[tauri::command]
+pub async fn lock_worktree(path: String, name: String, reason: Option<String>) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    let worktree = repo.find_worktree(&name).map_err(|e| e.to_string())?;
+    worktree.lock(reason.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Unlock a worktree
+#[tauri::command]
+pub async fn unlock_worktree(path: String, name: String) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    let worktree = repo.find_worktree(&name).map_err(|e| e.to_string())?;
+    worktree.unlock().map_err(|e| e.to_string())
+}
+
+/// Drop the administrative data of worktrees whose directory is gone.
+/// Locked worktrees are kept. Returns the names that were pruned.
+#[tauri::command]
+pub async fn prune_worktrees(path: String) -> std::result::Result<Vec<String>, String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    prune_worktrees_impl(&repo).map_err(|e| e.to_string())
+}
+
+fn prune_worktrees_impl(repo: &Repository) -> Result<Vec<String>> {
+    let mut pruned = Vec::new();
+    for name in repo.worktrees()?.iter().flatten() {
+        let worktree = repo.find_worktree(name)?;
+        if worktree.is_prunable(None)? {
+            worktree.prune(None)?;
+            pruned.push(name.to_string());
+        }
+    }
+    Ok(pruned)
+}
+
+/// Delete a linked worktree's directory and its administrative data.
+/// Without `force`, locked worktrees and ones with local changes are refused.
+#[tauri::command]
+pub async fn remove_worktree(path: String, name: String, force: bool) -> std::result::Result<(), String> {
+    let repo = Repository::open(&path).map_err(|e| e.to_string())?;
+    remove_worktree_impl(&repo, &name, force).map_err(|e| e.to_string())
+}
+
+fn remove_worktree_impl(repo: &Repository, name: &str, force: bool) -> Result<()> {
+    let worktree = repo.find_worktree(name)?;
+
+    if !force {
+        if let WorktreeLockStatus::Locked(_) = worktree.is_locked()? {
+            return Err(AppError::InvalidInput(format!("Worktree {} is locked", name)));
+        }
+
+        if worktree.validate().is_ok() {
+            let worktree_repo = Repository::open_from_worktree(&worktree)?;
+            let mut status_opts = StatusOptions::new();
+            status_opts.include_untracked(true);
+            if !worktree_repo.statuses(Some(&mut status_opts))?.is_empty() {
+                return Err(AppError::InvalidInput(format!(
+                    "Worktree {} has local changes; use force to remove it anyway",
+                    name
+                )));
+            }
+        }
+    }
+
+    let mut options = WorktreePruneOptions::new();
+    options.valid(true).locked(force).working_tree(true);
+    worktree.prune(Some(&mut options))?;
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "worktree_tests.rs"]
+mod worktree_tests;
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use crate::commands::worktree::{
+        add_worktree_impl, list_worktrees_impl, main_worktree_path, prune_worktrees_impl, remove_worktree_impl,
+    };
+    use crate::commands::test_support::{commit_at, commit_file, init_repo_at};
+    use git2::Repository;
+    use tempfile::TempDir;
+
+    /// `<tmp>/main` with one commit; worktrees go next to it
+    fn init_repo() -> (TempDir, Repository) {
+        let dir = TempDir::new().unwrap();
+        let repo = init_repo_at(&dir.path().join("main"));
+        commit_file(&repo, "a.txt", "a\n", "initial");
+        (dir, repo)
+    }
+
+    #[test]
+    fn test_add_and_list_worktrees() {
+        let (dir, repo) = init_repo();
+        let wt_path = dir.path().join("feature");
+
+        let added = add_worktree_impl(&repo, &wt_path, Some("feature"), true, None, false).unwrap();
+
+        assert_eq!(added.name.as_deref(), Some("feature"));
+        assert_eq!(added.branch.as_deref(), Some("feature"));
+        assert!(wt_path.join("a.txt").exists());
+
+        let worktrees = list_worktrees_impl(&repo).unwrap();
+        assert_eq!(worktrees.len(), 2);
+        assert!(worktrees[0].is_main);
+        assert!(!worktrees[1].is_main);
+        assert_eq!(worktrees[1].head, worktrees[0].head);
+
+        // Listing from inside the linked worktree gives the same picture
+        let linked = Repository::open(&wt_path).unwrap();
+        assert_eq!(list_worktrees_impl(&linked).unwrap().len(), 2);
+        assert_eq!(
+            main_worktree_path(&linked).unwrap().canonicalize().unwrap(),
+            dir.path().join("main").canonicalize().unwrap()
+        );
+        assert!(main_worktree_path(&repo).is_none());
+    }
+
+    #[test]
+    fn test_list_worktrees_of_bare_repository() {
+        let dir = TempDir::new().unwrap();
+        let bare = Repository::init_bare(dir.path().join("repo.git")).unwrap();
+        let head = commit_at(&bare, Some("refs/heads/main"), &[], 1_000, "initial");
+        bare.set_head("refs/heads/main").unwrap();
+        let wt_path = dir.path().join("feature");
+        bare.worktree("feature", &wt_path, None).unwrap();
+
+        // The bare repository has no working tree entry of its own
+        let worktrees = list_worktrees_impl(&bare).unwrap();
+        assert_eq!(worktrees.len(), 1);
+        assert!(!worktrees[0].is_main);
+        assert_eq!(worktrees[0].name.as_deref(), Some("feature"));
+        assert_eq!(worktrees[0].head, Some(head.to_string()));
+
+        // Listed from inside the worktree it is not mistaken for the main one
+        let linked = Repository::open(&wt_path).unwrap();
+        let from_linked = list_worktrees_impl(&linked).unwrap();
+        assert_eq!(from_linked.len(), 1);
+        assert!(!from_linked[0].is_main);
+        assert_eq!(
+            main_worktree_path(&linked).unwrap().canonicalize().unwrap(),
+            dir.path().join("repo.git").canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_add_refuses_branch_checked_out_elsewhere() {
+        let (dir, repo) = init_repo();
+        let current = repo.head().unwrap().shorthand().unwrap().to_string();
+
+        assert!(add_worktree_impl(&repo, &dir.path().join("dup"), Some(&current), false, None, false).is_err());
+    }
+
+    #[test]
+    fn test_lock_blocks_remove_and_prune() {
+        let (dir, repo) = init_repo();
+        let wt_path = dir.path().join("locked");
+        add_worktree_impl(&repo, &wt_path, None, false, None, false).unwrap();
+
+        repo.find_worktree("locked").unwrap().lock(Some("on usb stick")).unwrap();
+        let info = list_worktrees_impl(&repo).unwrap().remove(1);
+        assert!(info.is_locked);
+        assert_eq!(info.lock_reason.as_deref(), Some("on usb stick"));
+        assert!(remove_worktree_impl(&repo, "locked", false).is_err());
+
+        std::fs::remove_dir_all(&wt_path).unwrap();
+        assert!(prune_worktrees_impl(&repo).unwrap().is_empty());
+
+        repo.find_worktree("locked").unwrap().unlock().unwrap();
+        assert!(list_worktrees_impl(&repo).unwrap()[1].is_prunable);
+        assert_eq!(prune_worktrees_impl(&repo).unwrap(), vec!["locked".to_string()]);
+        assert_eq!(list_worktrees_impl(&repo).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_remove_requires_force_for_local_changes() {
+        let (dir, repo) = init_repo();
+        let wt_path = dir.path().join("dirty");
+        add_worktree_impl(&repo, &wt_path, None, false, None, false).unwrap();
+        std::fs::write(wt_path.join("new.txt"), "x\n").unwrap();
+
+        assert!(remove_worktree_impl(&repo, "dirty", false).is_err());
+        remove_worktree_impl(&repo, "dirty", true).unwrap();
+
+        assert!(!wt_path.exists());
+        assert_eq!(list_worktrees_impl(&repo).unwrap().len(), 1);
+    }
+}
@@ -3,7 +3,7 @@ pub mod status;
 pub mod search;
 pub mod provider;
 
-pub use repository::{BranchInfo, BatchCommitResult, BatchFailure, CommitInfo, LocalBranch, RepositoryInfo, StashInfo, TagInfo, RemoteInfo, DiffLine, DiffHunk, FileDiff, SubmoduleInfo, SubtreeInfo, LfsStatus};
-pub use status::{CommitSuggestion, CommitType, ConflictInfo, MergeState, RebaseState, RebaseTodo, RepoStatus, StatusItem};
+pub use repository::{BranchInfo, BatchCommitResult, BatchFailure, CommitInfo, CommitDetails, CommitDetailsOptions, CommitFileChange, CommitGraphPage, CommitHistoryPage, CommitIdentity, CommitSignature, CommitTrailer, GraphEdge, GraphEdgeKind, GraphLane, GraphOptions, GraphRow, GraphState, HistoryPageOptions, LocalBranch, RepositoryInfo, StashInfo, TagInfo, RemoteInfo, PushRefResult, ReflogEntry, WorktreeInfo, ChangeRange, DiffContentKind, DiffFileInfo, DiffLine, DiffHunk, DiffMode, DiffSource, FileDiff, FileDiffOptions, IntraLineMode, SideBySideRow, SignatureStatus, SubmoduleInfo, SubtreeInfo, LfsStatus};
+pub use status::{BisectState, CommitSuggestion, CommitType, ConflictBlob, ConflictFavor, ConflictInfo, ConflictKind, ConflictMergeOptions, ConflictSide, ConflictStyle, FastForward, FileHistoryEntry, FileHistoryOptions, FileStatus, MergedFile, MergeOptions, MergeRegion, MergeState, OperationAction, OperationState, OperationStep, RebaseState, RebaseTodo, RegionChoice, RenameOptions, RepoStatus, RepositoryOperation, SequencerOptions, SequencerOutcome, StatusItem};
 pub use search::CommitSearchQuery;
 pub use provider::*;
@@ -1,6 +1,50 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use crate::error::Result;
+use crate::domain::status::FileStatus;
+use crate::error::{AppError, Result};
+
+/// Hosting service behind a remote, for hosts that can't be recognised by
+/// name. Set per host with `gat.<host>.provider` in git config
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    GitHub,
+    GitLab,
+    /// Gitea and Forgejo, which share an API
+    Gitea,
+    /// Bitbucket Cloud
+    Bitbucket,
+    /// Bitbucket Server and Data Center
+    #[serde(rename = "bitbucket-server")]
+    BitbucketServer,
+}
+
+impl std::str::FromStr for ProviderKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "github" => Ok(ProviderKind::GitHub),
+            "gitlab" => Ok(ProviderKind::GitLab),
+            "gitea" | "forgejo" => Ok(ProviderKind::Gitea),
+            "bitbucket" | "bitbucket-cloud" => Ok(ProviderKind::Bitbucket),
+            "bitbucket-server" | "bitbucket-datacenter" => Ok(ProviderKind::BitbucketServer),
+            other => Err(format!("Unknown provider kind: {}", other)),
+        }
+    }
+}
+
+impl std::fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ProviderKind::GitHub => "github",
+            ProviderKind::GitLab => "gitlab",
+            ProviderKind::Gitea => "gitea",
+            ProviderKind::Bitbucket => "bitbucket",
+            ProviderKind::BitbucketServer => "bitbucket-server",
+        })
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
@@ -58,12 +102,309 @@ pub struct CreateIssue {
     pub body: Option<String>,
 }
 
+/// Which pull requests or issues to list
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListState {
+    #[default]
+    Open,
+    Closed,
+    /// Pull requests only
+    Merged,
+    All,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListSort {
+    #[default]
+    Created,
+    Updated,
+}
+
+/// Filters and paging for pull request and issue lists. A `cursor` from an
+/// earlier page replaces everything else, since the filters are part of it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ListQuery {
+    pub state: ListState,
+    pub author: Option<String>,
+    pub label: Option<String>,
+    pub assignee: Option<String>,
+    /// Free text, matched against titles and descriptions
+    pub search: Option<String>,
+    pub sort: ListSort,
+    /// Oldest or least recently updated first
+    pub ascending: bool,
+    /// 1-based
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPage<T> {
+    pub items: Vec<T>,
+    /// Pass back as `ListQuery::cursor` for the next page; `None` on the last
+    pub next_cursor: Option<String>,
+}
+
+/// Whether a pull request can be merged now
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeStatus {
+    Clean,
+    Conflicts,
+    /// The base branch has moved on and the service wants a rebase or update
+    Behind,
+    /// Held back by required checks, approvals or unresolved threads
+    Blocked,
+    /// Mergeable, but some checks are failing
+    Unstable,
+    Draft,
+    /// Closed, merged, or still being computed by the service
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReviewState {
+    Approved,
+    ChangesRequested,
+    Commented,
+    Dismissed,
+    /// Started but not submitted
+    Pending,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestReview {
+    pub id: String,
+    pub author: String,
+    pub state: ReviewState,
+    pub body: Option<String>,
+    pub submitted_at: Option<String>,
+}
+
+/// The outcome of a review round: each reviewer's latest approval or
+/// change request counts, and any change request outweighs approvals
+pub fn review_decision(reviews: &[PullRequestReview]) -> Option<ReviewState> {
+    let mut latest: Vec<(&str, ReviewState)> = Vec::new();
+    for review in reviews {
+        if !matches!(review.state, ReviewState::Approved | ReviewState::ChangesRequested | ReviewState::Dismissed) {
+            continue;
+        }
+        match latest.iter_mut().find(|(author, _)| *author == review.author) {
+            Some(entry) => entry.1 = review.state,
+            None => latest.push((&review.author, review.state)),
+        }
+    }
+    let states: Vec<ReviewState> = latest.into_iter().map(|(_, state)| state).collect();
+    if states.contains(&ReviewState::ChangesRequested) {
+        Some(ReviewState::ChangesRequested)
+    } else if states.contains(&ReviewState::Approved) {
+        Some(ReviewState::Approved)
+    } else {
+        None
+    }
+}
+
+/// Counts of a commit's CI checks and statuses by outcome
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckSummary {
+    pub total: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub pending: usize,
+    /// Skipped, neutral and the like
+    pub other: usize,
+}
+
+impl CheckSummary {
+    pub fn from_statuses(statuses: &[CommitStatus]) -> Self {
+        let mut summary = CheckSummary { total: statuses.len(), ..Default::default() };
+        for status in statuses {
+            match status.status.to_lowercase().as_str() {
+                "success" | "successful" | "passed" => summary.passed += 1,
+                "failure" | "failed" | "error" | "timed_out" | "cancelled" | "canceled" | "action_required"
+                | "startup_failure" => summary.failed += 1,
+                "skipped" | "neutral" | "stale" | "manual" | "warning" => summary.other += 1,
+                // pending, queued, in_progress, running, created, waiting_for_resource...
+                _ => summary.pending += 1,
+            }
+        }
+        summary
+    }
+}
+
+/// A pull request with what's needed to review and merge it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestDetails {
+    pub pull_request: PullRequest,
+    pub draft: bool,
+    pub head_sha: String,
+    pub base_sha: Option<String>,
+    pub merge_status: MergeStatus,
+    /// From `review_decision`, or the service's own approval rules
+    pub review_decision: Option<ReviewState>,
+    pub reviews: Vec<PullRequestReview>,
+    /// CI on the head commit
+    pub checks: CheckSummary,
+    pub commits: Option<u64>,
+    pub changed_files: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestFile {
+    pub path: String,
+    pub old_path: Option<String>,
+    pub status: FileStatus,
+    pub additions: u64,
+    pub deletions: u64,
+    /// Unified diff hunks without file headers; `None` for binary files and
+    /// diffs too large for the service to include
+    pub patch: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestCommit {
+    pub sha: String,
+    pub message: String,
+    pub author: String,
+    pub authored_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffSide {
+    /// The base version: removed and unchanged lines
+    Left,
+    /// The head version: added and unchanged lines
+    #[default]
+    Right,
+}
+
+/// Where an inline comment is anchored in the diff
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommentPosition {
+    pub path: String,
+    /// Line number on `side`; `None` once the line is gone from the diff
+    pub line: Option<u32>,
+    pub side: DiffSide,
+    pub commit_sha: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestComment {
+    pub id: String,
+    /// Shared by the comments of one thread; replies go to this id
+    pub thread_id: Option<String>,
+    pub author: String,
+    pub body: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub url: Option<String>,
+    /// `None` for general comments on the pull request
+    pub position: Option<CommentPosition>,
+    /// Only for services with resolvable threads
+    pub resolved: Option<bool>,
+}
+
+/// What a review does besides leaving comments
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReviewEvent {
+    #[default]
+    Comment,
+    Approve,
+    RequestChanges,
+    /// Leave the review unsubmitted (a pending review on GitHub, draft notes
+    /// on GitLab) to be edited and submitted on the service, e.g. for an
+    /// AI-generated review
+    Draft,
+}
+
+/// An inline comment for a new review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewReviewComment {
+    pub path: String,
+    /// The file's path on the base side, if it was renamed
+    pub old_path: Option<String>,
+    /// Line number on `side`, as in `CommentPosition`
+    pub line: u32,
+    #[serde(default)]
+    pub side: DiffSide,
+    /// First line of a multi-line comment, on the same side; GitLab refuses these
+    pub start_line: Option<u32>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CreateReview {
+    /// The review's summary comment
+    pub body: Option<String>,
+    pub event: ReviewEvent,
+    pub comments: Vec<NewReviewComment>,
+    /// Head commit the line numbers refer to; the pull request's current
+    /// head if unset
+    pub commit_sha: Option<String>,
+}
+
+fn unsupported<T>(what: &str) -> Result<T> {
+    Err(AppError::InvalidInput(format!("{} is not supported for this provider", what)))
+}
+
 #[async_trait]
 pub trait GitProvider: Send + Sync {
-    async fn get_pr_list(&self, owner: &str, repo: &str) -> Result<Vec<PullRequest>>;
-    async fn get_issue_list(&self, owner: &str, repo: &str) -> Result<Vec<Issue>>;
+    async fn get_pr_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<PullRequest>>;
+    async fn get_issue_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<Issue>>;
     async fn create_pr(&self, owner: &str, repo: &str, pr: CreatePullRequest) -> Result<PullRequest>;
     async fn create_issue(&self, owner: &str, repo: &str, issue: CreateIssue) -> Result<Issue>;
     async fn get_commit_status(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<CommitStatus>>;
     async fn get_job_logs(&self, owner: &str, repo: &str, job_id: &str) -> Result<String>;
+
+    async fn get_pr_details(&self, _owner: &str, _repo: &str, _number: u64) -> Result<PullRequestDetails> {
+        unsupported("Pull request details")
+    }
+    async fn get_pr_files(&self, _owner: &str, _repo: &str, _number: u64) -> Result<Vec<PullRequestFile>> {
+        unsupported("Pull request files")
+    }
+    async fn get_pr_commits(&self, _owner: &str, _repo: &str, _number: u64) -> Result<Vec<PullRequestCommit>> {
+        unsupported("Pull request commits")
+    }
+    /// General and inline comments, oldest first
+    async fn get_pr_comments(&self, _owner: &str, _repo: &str, _number: u64) -> Result<Vec<PullRequestComment>> {
+        unsupported("Pull request comments")
+    }
+    async fn create_review(&self, _owner: &str, _repo: &str, _number: u64, _review: CreateReview) -> Result<()> {
+        unsupported("Posting reviews")
+    }
+    /// `thread_id` is a `PullRequestComment::thread_id`
+    async fn reply_to_thread(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _number: u64,
+        _thread_id: &str,
+        _body: &str,
+    ) -> Result<PullRequestComment> {
+        unsupported("Replying to review threads")
+    }
+    async fn resolve_thread(&self, _owner: &str, _repo: &str, _number: u64, _thread_id: &str, _resolved: bool) -> Result<()> {
+        unsupported("Resolving review threads")
+    }
 }
+
+#[cfg(test)]
+#[path = "provider_tests.rs"]
+mod provider_tests;
@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::provider::{review_decision, CheckSummary, CommitStatus, PullRequestReview, ReviewState};
+
+    fn review(author: &str, state: ReviewState) -> PullRequestReview {
+        PullRequestReview { id: String::new(), author: author.to_string(), state, body: None, submitted_at: None }
+    }
+
+    fn status(status: &str) -> CommitStatus {
+        CommitStatus { id: String::new(), name: String::new(), status: status.to_string(), url: None, description: None }
+    }
+
+    #[test]
+    fn test_review_decision_uses_each_reviewers_latest_verdict() {
+        use ReviewState::*;
+
+        assert_eq!(review_decision(&[]), None);
+        assert_eq!(review_decision(&[review("a", Commented)]), None);
+        assert_eq!(review_decision(&[review("a", Approved), review("b", Commented)]), Some(Approved));
+        assert_eq!(review_decision(&[review("a", Approved), review("b", ChangesRequested)]), Some(ChangesRequested));
+        // A later approval replaces the same reviewer's change request
+        assert_eq!(
+            review_decision(&[review("a", ChangesRequested), review("a", Commented), review("a", Approved)]),
+            Some(Approved)
+        );
+        // and a dismissal withdraws it
+        assert_eq!(review_decision(&[review("a", ChangesRequested), review("a", Dismissed)]), None);
+    }
+
+    #[test]
+    fn test_check_summary_counts_by_outcome() {
+        let statuses: Vec<CommitStatus> =
+            ["success", "SUCCESSFUL", "failure", "timed_out", "in_progress", "pending", "skipped"].map(status).into();
+        assert_eq!(
+            CheckSummary::from_statuses(&statuses),
+            CheckSummary { total: 7, passed: 2, failed: 2, pending: 2, other: 1 }
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use super::status::{FileStatus, RenameOptions};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,6 +13,9 @@ pub struct RepositoryInfo {
     pub untracked_count: usize,
     pub ahead: usize,
     pub behind: usize,
+    /// Path of the main repository when this is a linked worktree; the git
+    /// directory when the main repository is bare
+    pub main_repository: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +51,184 @@ pub struct CommitInfo {
     pub refs: Vec<String>,
 }
 
+/// Options for `get_commit_history_page`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HistoryPageOptions {
+    /// Serve history from, and keep up to date, an index under `.gat/`
+    pub disk_cache: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitHistoryPage {
+    pub commits: Vec<CommitInfo>,
+    /// Cursor for the following page, `None` at the end of history
+    pub next_cursor: Option<String>,
+}
+
+/// Author or committer of a commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitIdentity {
+    pub name: String,
+    pub email: String,
+    pub timestamp: i64,
+    /// Timezone as minutes east of UTC
+    pub offset_minutes: i32,
+}
+
+/// A `Key: value` line from the last paragraph of a commit message
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitTrailer {
+    pub key: String,
+    pub value: String,
+}
+
+/// Result of checking a commit signature, like git's `%G?`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureStatus {
+    Good,
+    /// Valid signature from a key that is not trusted
+    Untrusted,
+    Bad,
+    /// The signature or the key has expired
+    Expired,
+    Revoked,
+    /// Missing key or verification program
+    Unverified,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitSignature {
+    /// "openpgp", "x509" or "ssh"
+    pub format: String,
+    pub status: SignatureStatus,
+    pub signer: Option<String>,
+    /// Key id or fingerprint
+    pub key: Option<String>,
+}
+
+/// A file changed by a commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitFileChange {
+    pub path: String,
+    pub old_path: Option<String>,
+    pub status: FileStatus,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub is_binary: bool,
+}
+
+/// Options for `get_commit_details`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommitDetailsOptions {
+    /// Parent to list changes against, for merge commits
+    pub parent_index: usize,
+    pub renames: RenameOptions,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitDetails {
+    pub id: String,
+    pub short_id: String,
+    pub summary: String,
+    pub message: String,
+    pub trailers: Vec<CommitTrailer>,
+    pub author: CommitIdentity,
+    pub committer: CommitIdentity,
+    pub parents: Vec<String>,
+    pub signature: Option<CommitSignature>,
+    pub files: Vec<CommitFileChange>,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+/// Options for `get_commit_graph`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GraphOptions {
+    /// Only follow the first parent of merges, like `git log --first-parent`
+    pub first_parent: bool,
+    /// Start from every branch, remote-tracking branch and tag instead of
+    /// HEAD and the local branches
+    pub all_refs: bool,
+    /// Only show commits that a ref points at, connected to their nearest
+    /// shown ancestors, like `--simplify-by-decoration`
+    pub simplify_by_decoration: bool,
+}
+
+/// A line in the graph heading down towards a commit not laid out yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphLane {
+    pub oid: String,
+    pub color: usize,
+}
+
+/// Lanes still open after the last row of a page. Passing it back with the
+/// next page continues the layout where the previous one stopped
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GraphState {
+    /// Lanes by column, `None` for a free column
+    pub lanes: Vec<Option<GraphLane>>,
+    pub next_color: usize,
+    /// Rows laid out so far
+    pub rows: usize,
+    /// Commits of the history walk consumed so far, including the ones
+    /// `simplify_by_decoration` hides
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GraphEdgeKind {
+    /// A lane crossing the row without touching its commit. It bends left
+    /// when the lane is pulled into the commit's column
+    Pass,
+    /// From the top of the row into the commit, coming from one of its
+    /// children. Joins from another column where branches fork off
+    Child,
+    /// From the commit to the bottom of the row, towards one of its
+    /// parents. Leaves to another column for merged branches
+    Parent,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub color: usize,
+    pub kind: GraphEdgeKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphRow {
+    /// With `first_parent` or `simplify_by_decoration`, `parents` lists the
+    /// parents as drawn rather than as recorded in the commit
+    pub commit: CommitInfo,
+    pub column: usize,
+    pub color: usize,
+    pub edges: Vec<GraphEdge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitGraphPage {
+    pub rows: Vec<GraphRow>,
+    pub state: GraphState,
+    pub has_more: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchCommitResult {
     pub successes: Vec<String>,
@@ -85,6 +267,112 @@ pub struct RemoteInfo {
     pub push_url: Option<String>,
 }
 
+/// Outcome of one refspec in a push, as reported by the remote
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PushRefResult {
+    pub refspec: String,
+    pub remote_ref: String,
+    pub accepted: bool,
+    pub message: Option<String>,
+}
+
+/// A working tree of a repository, the main one included
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeInfo {
+    /// Name under `.git/worktrees`; None for the main working tree
+    pub name: Option<String>,
+    pub path: String,
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub is_main: bool,
+    pub is_locked: bool,
+    pub lock_reason: Option<String>,
+    /// The directory is gone and `prune_worktrees` would remove the entry
+    pub is_prunable: bool,
+}
+
+/// One entry of a reference's reflog, newest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReflogEntry {
+    pub index: usize,
+    /// `HEAD@{n}` / `main@{n}` style name of the entry
+    pub selector: String,
+    pub old_id: String,
+    pub new_id: String,
+    pub message: String,
+    pub committer: String,
+    pub committer_email: String,
+    pub timestamp: i64,
+    /// Summary of the commit the entry points to, if it still exists
+    pub summary: Option<String>,
+}
+
+/// Which changes `get_file_diff` shows
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffMode {
+    /// Staged hunks followed by unstaged ones
+    #[default]
+    Combined,
+    Staged,
+    Unstaged,
+}
+
+/// Granularity of the changed ranges within a modified line
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum IntraLineMode {
+    None,
+    #[default]
+    Word,
+    Char,
+}
+
+/// Options for `get_file_diff`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileDiffOptions {
+    pub mode: DiffMode,
+    /// Defaults to 3
+    pub context_lines: Option<u32>,
+    pub ignore_whitespace: bool,
+    pub ignore_whitespace_change: bool,
+    pub ignore_blank_lines: bool,
+    pub intra_line: IntraLineMode,
+    /// Fill `DiffHunk::rows`
+    pub side_by_side: bool,
+    /// Stop adding lines after this many; defaults to 10000
+    pub max_lines: Option<u32>,
+    /// Used to diff a renamed file against its old path
+    pub renames: RenameOptions,
+}
+
+/// Where a hunk comes from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffSource {
+    /// HEAD to index
+    Staged,
+    /// Index to working tree
+    Unstaged,
+    /// A file git does not track yet
+    Untracked,
+    /// A parent of a commit to the commit
+    Commit,
+}
+
+/// Changed part of a line, in UTF-16 code units so it can be used with
+/// `String.prototype.slice` directly
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeRange {
+    pub start: u32,
+    pub end: u32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DiffLine {
@@ -92,20 +380,70 @@ pub struct DiffLine {
     pub origin: char,
     pub old_lineno: Option<u32>,
     pub new_lineno: Option<u32>,
+    /// Intra-line changes for a removed line paired with an added one
+    pub changes: Vec<ChangeRange>,
+}
+
+/// One row of a side-by-side view; indices point into `DiffHunk::lines`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SideBySideRow {
+    pub left: Option<usize>,
+    pub right: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DiffHunk {
     pub header: String,
+    pub source: DiffSource,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
     pub lines: Vec<DiffLine>,
+    /// Only filled when side-by-side rows were requested
+    pub rows: Vec<SideBySideRow>,
+}
+
+/// What `get_file_diff` could show for a file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffContentKind {
+    Text,
+    /// No hunks; compare `old_file` and `new_file` instead
+    Binary,
+    /// Over the size limit; no hunks and no image data
+    TooLarge,
+}
+
+/// One end of a file diff. `id` is the blob id, hashed for working tree files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffFileInfo {
+    pub id: String,
+    pub size: u64,
+    /// Base64 content, only for images
+    pub data: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FileDiff {
     pub path: String,
+    /// Path in HEAD or the index when the file was renamed
+    pub old_path: Option<String>,
+    pub kind: DiffContentKind,
+    /// Set when the text was decoded from something other than UTF-8
+    pub encoding: Option<String>,
+    /// Set for images, e.g. "image/png"
+    pub mime_type: Option<String>,
+    /// Absent when the file does not exist on that side
+    pub old_file: Option<DiffFileInfo>,
+    pub new_file: Option<DiffFileInfo>,
     pub hunks: Vec<DiffHunk>,
+    /// `max_lines` was reached and later lines were dropped
+    pub truncated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
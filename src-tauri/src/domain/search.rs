@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct CommitSearchQuery {
     pub query: Option<String>,
     pub author: Option<String>,
@@ -8,4 +9,6 @@ pub struct CommitSearchQuery {
     pub date_to: Option<i64>,
     pub path: Option<String>,
     pub limit: Option<usize>,
+    /// Search the history index under `.gat/`, building it if needed
+    pub disk_cache: Option<bool>,
 }
@@ -69,6 +69,55 @@ impl From<git2::Status> for FileStatus {
     }
 }
 
+/// Rename and copy detection, like `git diff -M<threshold> -C`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RenameOptions {
+    pub renames: bool,
+    /// Also look for copies of files that did not change
+    pub copies: bool,
+    /// Similarity in percent
+    pub threshold: u16,
+    /// Also pair unstaged deletions with untracked files in status. Off by
+    /// default: staging such a rename by its new path would leave the
+    /// deletion behind
+    pub worktree: bool,
+}
+
+impl Default for RenameOptions {
+    fn default() -> Self {
+        Self { renames: true, copies: false, threshold: 50, worktree: false }
+    }
+}
+
+/// Options for `get_file_history`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileHistoryOptions {
+    /// Continue with the old path when the file was renamed, like `git log --follow`
+    pub follow: bool,
+    pub skip: usize,
+    pub limit: Option<usize>,
+    pub renames: RenameOptions,
+}
+
+impl Default for FileHistoryOptions {
+    fn default() -> Self {
+        Self { follow: true, skip: 0, limit: None, renames: RenameOptions::default() }
+    }
+}
+
+/// A commit that changed a file, with the file's path in that commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHistoryEntry {
+    pub commit: CommitInfo,
+    pub path: String,
+    pub status: FileStatus,
+    /// Set when the commit renamed or copied the file from another path
+    pub old_path: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CommitSuggestion {
@@ -139,17 +188,151 @@ impl CommitSuggestion {
     }
 }
 
+/// How the two sides of a merge disagree about a path, named after the
+/// `git status` short codes (UU, AA, DU, UD, AU, UA, DD)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConflictKind {
+    BothModified,
+    BothAdded,
+    DeletedByUs,
+    DeletedByThem,
+    AddedByUs,
+    AddedByThem,
+    BothDeleted,
+    /// Both sides renamed the ancestor, to different paths
+    RenameRename,
+}
+
+/// One stage (ancestor, ours or theirs) of a conflicted index entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictSide {
+    pub path: String,
+    pub id: String,
+    pub mode: u32,
+}
+
 /// Conflict information for a file
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConflictInfo {
+    /// Path in the working tree; the ancestor path for rename/rename conflicts
     pub path: String,
-    pub current: Option<String>,
-    pub incoming: Option<String>,
-    pub ancestor: Option<String>,
+    pub kind: ConflictKind,
+    pub ancestor: Option<ConflictSide>,
+    pub ours: Option<ConflictSide>,
+    pub theirs: Option<ConflictSide>,
+    pub is_binary: bool,
+    /// The working tree file still contains conflict markers
     pub conflict_markers: bool,
 }
 
+/// Content of one side of a conflict, loaded on demand
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictBlob {
+    pub id: String,
+    pub size: usize,
+    pub is_binary: bool,
+    /// `None` for binary or non UTF-8 content
+    pub content: Option<String>,
+}
+
+/// Marker layout used when merging a conflicted file line by line
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStyle {
+    #[default]
+    Merge,
+    Diff3,
+    Zdiff3,
+}
+
+/// How overlapping changes are settled without asking
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictFavor {
+    /// Leave them as conflicting regions
+    #[default]
+    None,
+    Ours,
+    Theirs,
+    /// Keep both sides, ours first
+    Union,
+}
+
+/// Options for merging the stages of a conflicted file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ConflictMergeOptions {
+    pub style: ConflictStyle,
+    pub favor: ConflictFavor,
+    pub ignore_whitespace: bool,
+}
+
+/// When a merge may fast-forward, as in `merge.ff`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FastForward {
+    /// Fast-forward when possible (`merge.ff = true`)
+    Allow,
+    /// Always create a merge commit (`--no-ff`)
+    Never,
+    /// Refuse to merge unless it is a fast-forward (`--ff-only`)
+    Only,
+}
+
+/// Options for `merge_branch` and the merge half of `pull_branch`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MergeOptions {
+    /// `None` reads `merge.ff` (and `pull.ff` when pulling)
+    pub fast_forward: Option<FastForward>,
+    /// Stage the merged result without committing or recording MERGE_HEAD
+    pub squash: bool,
+    /// Merge commit message instead of "Merge branch '...'"
+    pub message: Option<String>,
+    /// `-X ours`, `-X theirs` or a union of both for overlapping hunks
+    pub favor: ConflictFavor,
+    pub ignore_whitespace: bool,
+    /// `-X no-renames`
+    pub no_renames: bool,
+    /// `-X find-renames=<n>`, similarity in percent
+    pub rename_threshold: Option<u32>,
+}
+
+/// A run of lines in a merged file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum MergeRegion {
+    Clean { content: String },
+    /// `base` is only filled in for the diff3 and zdiff3 styles
+    Conflict { ours: String, base: Option<String>, theirs: String },
+}
+
+/// Result of merging the three stages of a conflicted file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergedFile {
+    pub path: String,
+    pub is_automergeable: bool,
+    pub conflict_count: usize,
+    pub regions: Vec<MergeRegion>,
+}
+
+/// How to resolve one conflicting region
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "choice", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum RegionChoice {
+    Ours,
+    Theirs,
+    Base,
+    OursThenTheirs,
+    TheirsThenOurs,
+    Custom { content: String },
+}
+
 /// Merge state information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -181,6 +364,104 @@ pub struct RebaseState {
     pub current_commit: Option<String>,
 }
 
+/// Multi-step operation the repository is in the middle of, after `git2::RepositoryState`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RepositoryOperation {
+    None,
+    Merge,
+    Revert,
+    RevertSequence,
+    CherryPick,
+    CherryPickSequence,
+    Bisect,
+    Rebase,
+    RebaseInteractive,
+    RebaseMerge,
+    ApplyMailbox,
+    ApplyMailboxOrRebase,
+}
+
+/// Follow-up action that makes sense in the current operation state
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationAction {
+    Continue,
+    Skip,
+    Abort,
+}
+
+/// One line of a rebase todo or done list
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationStep {
+    pub command: String,
+    pub commit: Option<String>,
+    pub summary: String,
+}
+
+/// Everything the UI needs to show and drive an in-progress operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationState {
+    pub operation: RepositoryOperation,
+    /// Commit HEAD points at
+    pub head: Option<String>,
+    /// Branch being rebased, or the current branch otherwise
+    pub head_name: Option<String>,
+    /// MERGE_HEAD, CHERRY_PICK_HEAD, REVERT_HEAD, the rebase onto commit or
+    /// the bad bisect commit
+    pub target: Option<String>,
+    pub merge_message: Option<String>,
+    pub conflict_count: usize,
+    pub todo: Vec<OperationStep>,
+    pub done: Vec<OperationStep>,
+    pub actions: Vec<OperationAction>,
+}
+
+/// Bisect session state, recomputed from `refs/bisect/*`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BisectState {
+    pub is_bisecting: bool,
+    pub bad: Option<String>,
+    pub good: Vec<String>,
+    pub skipped: Vec<String>,
+    /// Commit checked out for testing next
+    pub current: Option<String>,
+    /// Untested commits that could still be the first bad one
+    pub remaining: usize,
+    /// Rough number of tests left (log2 of `remaining`)
+    pub steps_left: usize,
+    /// Set once the first bad commit is known
+    pub first_bad: Option<String>,
+}
+
+/// Options shared by cherry-pick and revert
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SequencerOptions {
+    /// 1-based parent to use as the base for merge commits; ignored for others
+    pub mainline: Option<u32>,
+    /// Append "(cherry picked from commit ...)" like `git cherry-pick -x`
+    pub record_origin: bool,
+    /// Apply the changes to the index and working tree without committing
+    pub no_commit: bool,
+}
+
+/// Result of running (or continuing) a cherry-pick/revert sequence
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequencerOutcome {
+    pub finished: bool,
+    /// Commits created so far, oldest first
+    pub created_commits: Vec<String>,
+    /// Commit that stopped the sequence with conflicts
+    pub stopped_at: Option<String>,
+    /// Commits still to apply after the stopped one
+    pub remaining: usize,
+}
+
 #[cfg(test)]
 #[path = "status_tests.rs"]
 mod status_tests;
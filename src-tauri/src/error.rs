@@ -24,6 +24,29 @@ pub enum AppError {
     #[error("AI API error: {0}")]
     AiApi(String),
 
+    #[error("Authentication required: {0}")]
+    AuthRequired(String),
+
+    /// A hosting service rejected the token (401), or it lacks access (403)
+    #[error("Authentication failed: {0}")]
+    AuthFailed(String),
+
+    /// `reset_at` is an RFC 3339 time, when the service reports one
+    #[error(
+        "{service} API rate limit exceeded{}",
+        .reset_at.as_ref().map(|at| format!(", resets at {}", at)).unwrap_or_default()
+    )]
+    RateLimited { service: String, reset_at: Option<String> },
+
+    #[error("SSH key passphrase required: {0}")]
+    PassphraseRequired(String),
+
+    #[error("Operation cancelled: {0}")]
+    Cancelled(String),
+
+    #[error("Commit signing failed: {0}")]
+    Signing(String),
+
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 }
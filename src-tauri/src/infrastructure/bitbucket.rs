@@ -0,0 +1,516 @@
+use crate::domain::provider::{GitProvider, PullRequest, Issue, CreatePullRequest, CreateIssue, CommitStatus, ListPage, ListQuery, ListSort, ListState};
+use crate::error::{AppError, Result};
+use crate::infrastructure::http;
+use async_trait::async_trait;
+use base64::Engine;
+use reqwest::{Client, RequestBuilder, header};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+const CLOUD_API_URL: &str = "https://api.bitbucket.org";
+
+/// Client with Bitbucket credentials: `user:app_password` is sent as basic
+/// auth, anything else as a bearer access token
+fn client(token: &str) -> Client {
+    let mut headers = header::HeaderMap::new();
+    let auth = if token.contains(':') {
+        format!("Basic {}", base64::engine::general_purpose::STANDARD.encode(token))
+    } else {
+        format!("Bearer {}", token)
+    };
+    let mut auth_value = header::HeaderValue::from_str(&auth).unwrap();
+    auth_value.set_sensitive(true);
+    headers.insert(header::AUTHORIZATION, auth_value);
+    headers.insert(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+
+    Client::builder()
+        .default_headers(headers)
+        .build()
+        .unwrap()
+}
+
+async fn send(request: RequestBuilder) -> Result<reqwest::Response> {
+    let res = request.send().await.map_err(|e| AppError::Http(e.to_string()))?;
+    http::check("Bitbucket", res).await
+}
+
+async fn send_json<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+    send(request).await?.json().await.map_err(|e| AppError::Http(e.to_string()))
+}
+
+/// Build states are the same in both APIs
+fn build_state(state: &str) -> String {
+    match state {
+        "SUCCESSFUL" => "success",
+        "FAILED" => "failure",
+        "INPROGRESS" => "running",
+        "STOPPED" | "CANCELLED" => "canceled",
+        other => return other.to_lowercase(),
+    }
+    .to_string()
+}
+
+/// Pull request states are the same in both APIs; declined and superseded
+/// pull requests are closed
+fn pr_state(state: &str) -> String {
+    match state {
+        "OPEN" => "open",
+        "MERGED" => "merged",
+        _ => "closed",
+    }
+    .to_string()
+}
+
+/// Bitbucket Cloud, through the 2.0 API. `owner` is the workspace
+pub struct BitbucketCloudProvider {
+    client: Client,
+    base_url: String,
+}
+
+impl BitbucketCloudProvider {
+    pub fn new(token: String, base_url: Option<String>) -> Self {
+        let base_url = base_url.unwrap_or_else(|| CLOUD_API_URL.to_string());
+        let base_url = base_url.trim_end_matches('/').to_string();
+        Self { client: client(&token), base_url }
+    }
+
+    fn repo_url(&self, owner: &str, repo: &str, rest: &str) -> String {
+        format!("{}/2.0/repositories/{}/{}/{}", self.base_url, owner, repo, rest)
+    }
+
+    /// Fetch one page of a list. `filters` are BBQL conditions
+    async fn list<T: DeserializeOwned>(
+        &self,
+        url: String,
+        mut params: Vec<(&str, String)>,
+        filters: Vec<String>,
+        query: &ListQuery,
+    ) -> Result<ListPage<T>> {
+        let request = match &query.cursor {
+            Some(cursor) => self.client.get(http::cursor_url(&format!("{}/2.0", self.base_url), cursor)?),
+            None => {
+                if !filters.is_empty() {
+                    params.push(("q", filters.join(" AND ")));
+                }
+                let field = match query.sort {
+                    ListSort::Created => "created_on",
+                    ListSort::Updated => "updated_on",
+                };
+                params.push(("sort", if query.ascending { field.to_string() } else { format!("-{}", field) }));
+                if let Some(page) = query.page {
+                    params.push(("page", page.to_string()));
+                }
+                if let Some(per_page) = query.per_page {
+                    params.push(("pagelen", per_page.to_string()));
+                }
+                self.client.get(url).query(&params)
+            }
+        };
+        let page: CloudPage<T> = send_json(request).await?;
+        Ok(ListPage { items: page.values, next_cursor: page.next })
+    }
+}
+
+/// A BBQL string literal
+fn bbql(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// A page of a 2.0 API list, with the URL of the next one
+#[derive(Deserialize)]
+struct CloudPage<T> {
+    values: Vec<T>,
+    next: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CloudUser {
+    display_name: String,
+}
+
+#[derive(Deserialize)]
+struct CloudLink {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct CloudLinks {
+    html: CloudLink,
+}
+
+#[derive(Deserialize)]
+struct CloudBranchName {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CloudEndpoint {
+    branch: CloudBranchName,
+}
+
+#[derive(Deserialize)]
+struct CloudPR {
+    id: u64,
+    title: String,
+    description: Option<String>,
+    state: String, // OPEN, MERGED, DECLINED, SUPERSEDED
+    author: CloudUser,
+    created_on: String,
+    updated_on: String,
+    links: CloudLinks,
+    source: CloudEndpoint,
+    destination: CloudEndpoint,
+}
+
+impl From<CloudPR> for PullRequest {
+    fn from(pr: CloudPR) -> Self {
+        PullRequest {
+            id: pr.id.to_string(),
+            number: pr.id,
+            title: pr.title,
+            body: pr.description.filter(|d| !d.is_empty()),
+            state: pr_state(&pr.state),
+            author: pr.author.display_name,
+            created_at: pr.created_on,
+            updated_at: pr.updated_on,
+            url: pr.links.html.href,
+            head_ref: pr.source.branch.name,
+            base_ref: pr.destination.branch.name,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CloudContent {
+    raw: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CloudIssue {
+    id: u64,
+    title: String,
+    content: Option<CloudContent>,
+    state: String, // new, open, on hold, resolved, invalid, duplicate, wontfix, closed
+    reporter: Option<CloudUser>,
+    created_on: String,
+    updated_on: String,
+    links: CloudLinks,
+}
+
+impl From<CloudIssue> for Issue {
+    fn from(issue: CloudIssue) -> Self {
+        let open = matches!(issue.state.as_str(), "new" | "open" | "on hold");
+        Issue {
+            id: issue.id.to_string(),
+            number: issue.id,
+            title: issue.title,
+            body: issue.content.and_then(|c| c.raw).filter(|b| !b.is_empty()),
+            state: if open { "open" } else { "closed" }.to_string(),
+            // Anonymous reports have no reporter
+            author: issue.reporter.map(|r| r.display_name).unwrap_or_default(),
+            created_at: issue.created_on,
+            updated_at: issue.updated_on,
+            url: issue.links.html.href,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct BuildStatus {
+    key: String,
+    name: Option<String>,
+    state: String,
+    url: Option<String>,
+    description: Option<String>,
+}
+
+impl From<BuildStatus> for CommitStatus {
+    fn from(status: BuildStatus) -> Self {
+        CommitStatus {
+            name: status.name.unwrap_or_else(|| status.key.clone()),
+            id: status.key,
+            status: build_state(&status.state),
+            url: status.url,
+            description: status.description.filter(|d| !d.is_empty()),
+        }
+    }
+}
+
+#[async_trait]
+impl GitProvider for BitbucketCloudProvider {
+    async fn get_pr_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<PullRequest>> {
+        if query.label.is_some() || query.assignee.is_some() {
+            return Err(AppError::InvalidInput("Bitbucket pull requests have no labels or assignees".to_string()));
+        }
+        // Without a state only open pull requests are listed
+        let states: &[&str] = match query.state {
+            ListState::Open => &["OPEN"],
+            ListState::Closed => &["DECLINED", "SUPERSEDED"],
+            ListState::Merged => &["MERGED"],
+            ListState::All => &["OPEN", "MERGED", "DECLINED", "SUPERSEDED"],
+        };
+        let params = states.iter().map(|state| ("state", state.to_string())).collect();
+        let mut filters = Vec::new();
+        if let Some(author) = &query.author {
+            filters.push(format!("author.nickname = {}", bbql(author)));
+        }
+        if let Some(search) = &query.search {
+            filters.push(format!("(title ~ {0} OR description ~ {0})", bbql(search)));
+        }
+
+        let url = self.repo_url(owner, repo, "pullrequests");
+        let page: ListPage<CloudPR> = self.list(url, params, filters, query).await?;
+        Ok(ListPage { items: page.items.into_iter().map(PullRequest::from).collect(), next_cursor: page.next_cursor })
+    }
+
+    async fn get_issue_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<Issue>> {
+        if query.label.is_some() {
+            return Err(AppError::InvalidInput("Bitbucket issues have no labels".to_string()));
+        }
+        let mut filters = Vec::new();
+        match query.state {
+            ListState::Open => filters.push(r#"(state = "new" OR state = "open" OR state = "on hold")"#.to_string()),
+            ListState::Closed => filters.push(r#"NOT (state = "new" OR state = "open" OR state = "on hold")"#.to_string()),
+            ListState::Merged => return Err(AppError::InvalidInput("Only pull requests can be merged".to_string())),
+            ListState::All => {}
+        }
+        if let Some(author) = &query.author {
+            filters.push(format!("reporter.nickname = {}", bbql(author)));
+        }
+        if let Some(assignee) = &query.assignee {
+            filters.push(format!("assignee.nickname = {}", bbql(assignee)));
+        }
+        if let Some(search) = &query.search {
+            filters.push(format!("(title ~ {0} OR content.raw ~ {0})", bbql(search)));
+        }
+
+        // Fails with 404 when the repository's issue tracker is disabled
+        let url = self.repo_url(owner, repo, "issues");
+        let page: ListPage<CloudIssue> = self.list(url, Vec::new(), filters, query).await?;
+        Ok(ListPage { items: page.items.into_iter().map(Issue::from).collect(), next_cursor: page.next_cursor })
+    }
+
+    async fn create_pr(&self, owner: &str, repo: &str, pr: CreatePullRequest) -> Result<PullRequest> {
+        let url = self.repo_url(owner, repo, "pullrequests");
+        let body = serde_json::json!({
+            "title": pr.title,
+            "description": pr.body,
+            "source": { "branch": { "name": pr.head } },
+            "destination": { "branch": { "name": pr.base } }
+        });
+        let pr: CloudPR = send_json(self.client.post(&url).json(&body)).await?;
+        Ok(pr.into())
+    }
+
+    async fn create_issue(&self, owner: &str, repo: &str, issue: CreateIssue) -> Result<Issue> {
+        let url = self.repo_url(owner, repo, "issues");
+        let body = serde_json::json!({
+            "title": issue.title,
+            "content": { "raw": issue.body.unwrap_or_default() }
+        });
+        let issue: CloudIssue = send_json(self.client.post(&url).json(&body)).await?;
+        Ok(issue.into())
+    }
+
+    async fn get_commit_status(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<CommitStatus>> {
+        let url = self.repo_url(owner, repo, &format!("commit/{}/statuses", sha));
+        let page: CloudPage<BuildStatus> = send_json(self.client.get(&url)).await?;
+        Ok(page.values.into_iter().map(CommitStatus::from).collect())
+    }
+
+    async fn get_job_logs(&self, owner: &str, repo: &str, job_id: &str) -> Result<String> {
+        // A Pipelines step is identified by its pipeline and step UUIDs
+        let (pipeline, step) = job_id.split_once('/').ok_or_else(|| {
+            AppError::InvalidInput(format!("Expected <pipeline uuid>/<step uuid>, got {}", job_id))
+        })?;
+        let url = self.repo_url(owner, repo, &format!("pipelines/{}/steps/{}/log", pipeline, step));
+        send(self.client.get(&url)).await?
+            .text()
+            .await
+            .map_err(|e| AppError::Http(e.to_string()))
+    }
+}
+
+/// Bitbucket Server and Data Center, through the 1.0 REST API. `owner` is
+/// the project key, or `~user` for personal repositories
+pub struct BitbucketServerProvider {
+    client: Client,
+    base_url: String,
+}
+
+impl BitbucketServerProvider {
+    pub fn new(token: String, base_url: String) -> Self {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        Self { client: client(&token), base_url }
+    }
+
+    fn repo_url(&self, owner: &str, repo: &str, rest: &str) -> String {
+        format!("{}/rest/api/1.0/projects/{}/repos/{}/{}", self.base_url, owner, repo, rest)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerPage<T> {
+    values: Vec<T>,
+    #[serde(default = "last_page")]
+    is_last_page: bool,
+    next_page_start: Option<u64>,
+}
+
+fn last_page() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerUser {
+    name: String,
+    display_name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ServerAuthor {
+    user: ServerUser,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerRef {
+    display_id: String,
+}
+
+#[derive(Deserialize)]
+struct ServerLink {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct ServerLinks {
+    #[serde(rename = "self", default)]
+    self_links: Vec<ServerLink>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerPR {
+    id: u64,
+    title: String,
+    description: Option<String>,
+    state: String, // OPEN, MERGED, DECLINED
+    author: ServerAuthor,
+    created_date: i64,
+    updated_date: i64,
+    links: ServerLinks,
+    from_ref: ServerRef,
+    to_ref: ServerRef,
+}
+
+/// Server timestamps are milliseconds since the epoch
+fn server_date(millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .map(|date| date.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+impl From<ServerPR> for PullRequest {
+    fn from(pr: ServerPR) -> Self {
+        PullRequest {
+            id: pr.id.to_string(),
+            number: pr.id,
+            title: pr.title,
+            body: pr.description.filter(|d| !d.is_empty()),
+            state: pr_state(&pr.state),
+            author: pr.author.user.display_name.unwrap_or(pr.author.user.name),
+            created_at: server_date(pr.created_date),
+            updated_at: server_date(pr.updated_date),
+            url: pr.links.self_links.into_iter().next().map(|l| l.href).unwrap_or_default(),
+            head_ref: pr.from_ref.display_id,
+            base_ref: pr.to_ref.display_id,
+        }
+    }
+}
+
+#[async_trait]
+impl GitProvider for BitbucketServerProvider {
+    async fn get_pr_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<PullRequest>> {
+        if query.label.is_some() || query.assignee.is_some() {
+            return Err(AppError::InvalidInput("Bitbucket pull requests have no labels or assignees".to_string()));
+        }
+        let request = match &query.cursor {
+            Some(cursor) => self.client.get(http::cursor_url(&format!("{}/rest/api/1.0", self.base_url), cursor)?),
+            None => {
+                let state = match query.state {
+                    ListState::Open => "OPEN",
+                    ListState::Closed => "DECLINED",
+                    ListState::Merged => "MERGED",
+                    ListState::All => "ALL",
+                };
+                // Server orders by last update either way
+                let order = if query.ascending { "OLDEST" } else { "NEWEST" };
+                let mut params = vec![("state", state.to_string()), ("order", order.to_string())];
+                if let Some(author) = &query.author {
+                    params.push(("role.1", "AUTHOR".to_string()));
+                    params.push(("username.1", author.clone()));
+                }
+                if let Some(search) = &query.search {
+                    params.push(("filterText", search.clone()));
+                }
+                // Pages are offsets into the list
+                let limit = query.per_page.unwrap_or(25);
+                params.push(("limit", limit.to_string()));
+                if let Some(page) = query.page {
+                    params.push(("start", (page.saturating_sub(1) * limit).to_string()));
+                }
+                self.client.get(self.repo_url(owner, repo, "pull-requests")).query(&params)
+            }
+        };
+
+        let res = send(request).await?;
+        let url = res.url().clone();
+        let page: ServerPage<ServerPR> = res.json().await.map_err(|e| AppError::Http(e.to_string()))?;
+        let next_cursor = match page.next_page_start {
+            Some(start) if !page.is_last_page => Some(http::with_param(&url, "start", &start.to_string())),
+            _ => None,
+        };
+        Ok(ListPage { items: page.values.into_iter().map(PullRequest::from).collect(), next_cursor })
+    }
+
+    async fn get_issue_list(&self, _owner: &str, _repo: &str, _query: &ListQuery) -> Result<ListPage<Issue>> {
+        // Server has no issue tracker of its own (teams use Jira)
+        Ok(ListPage { items: Vec::new(), next_cursor: None })
+    }
+
+    async fn create_pr(&self, owner: &str, repo: &str, pr: CreatePullRequest) -> Result<PullRequest> {
+        let url = self.repo_url(owner, repo, "pull-requests");
+        let body = serde_json::json!({
+            "title": pr.title,
+            "description": pr.body,
+            "fromRef": { "id": format!("refs/heads/{}", pr.head) },
+            "toRef": { "id": format!("refs/heads/{}", pr.base) }
+        });
+        let pr: ServerPR = send_json(self.client.post(&url).json(&body)).await?;
+        Ok(pr.into())
+    }
+
+    async fn create_issue(&self, _owner: &str, _repo: &str, _issue: CreateIssue) -> Result<Issue> {
+        Err(AppError::InvalidInput("Bitbucket Server has no issue tracker".to_string()))
+    }
+
+    async fn get_commit_status(&self, _owner: &str, _repo: &str, sha: &str) -> Result<Vec<CommitStatus>> {
+        // Build statuses are stored per commit, outside the project API
+        let url = format!("{}/rest/build-status/1.0/commits/{}", self.base_url, sha);
+        let page: ServerPage<BuildStatus> = send_json(self.client.get(&url)).await?;
+        Ok(page.values.into_iter().map(CommitStatus::from).collect())
+    }
+
+    async fn get_job_logs(&self, _owner: &str, _repo: &str, _job_id: &str) -> Result<String> {
+        Err(AppError::InvalidInput(
+            "Bitbucket Server does not run builds; open the build's URL instead".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+#[path = "bitbucket_tests.rs"]
+mod bitbucket_tests;
@@ -0,0 +1,288 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::provider::{CreateIssue, CreatePullRequest, GitProvider, ListQuery, ListState};
+    use crate::error::AppError;
+    use crate::infrastructure::bitbucket::{BitbucketCloudProvider, BitbucketServerProvider};
+    use crate::infrastructure::mock_server::{MockResponse, MockServer};
+    use serde_json::json;
+
+    fn cloud_pull(id: u64, state: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "title": format!("PR {}", id),
+            "description": "",
+            "state": state,
+            "author": { "display_name": "Alice" },
+            "created_on": "2024-01-01T00:00:00+00:00",
+            "updated_on": "2024-01-02T00:00:00+00:00",
+            "links": { "html": { "href": format!("https://bitbucket.org/team/app/pull-requests/{}", id) } },
+            "source": { "branch": { "name": "feature" } },
+            "destination": { "branch": { "name": "main" } }
+        })
+    }
+
+    fn cloud_issue(id: u64, state: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "title": "Broken build",
+            "content": { "raw": "It fails" },
+            "state": state,
+            "reporter": null,
+            "created_on": "2024-01-01T00:00:00+00:00",
+            "updated_on": "2024-01-01T00:00:00+00:00",
+            "links": { "html": { "href": format!("https://bitbucket.org/team/app/issues/{}", id) } }
+        })
+    }
+
+    fn cloud_server() -> MockServer {
+        MockServer::start(|request| {
+            let path = request.path.split('?').next().unwrap();
+            match (request.method.as_str(), path) {
+                ("GET", "/2.0/repositories/team/app/pullrequests") => {
+                    MockResponse::json(200, json!({ "values": [cloud_pull(1, "OPEN"), cloud_pull(2, "DECLINED")] }))
+                }
+                ("POST", "/2.0/repositories/team/app/pullrequests") => MockResponse::json(201, cloud_pull(3, "OPEN")),
+                ("GET", "/2.0/repositories/team/app/issues") => {
+                    MockResponse::json(200, json!({ "values": [cloud_issue(4, "on hold")] }))
+                }
+                ("POST", "/2.0/repositories/team/app/issues") => MockResponse::json(201, cloud_issue(5, "resolved")),
+                ("GET", "/2.0/repositories/team/app/commit/abc123/statuses") => MockResponse::json(
+                    200,
+                    json!({ "values": [{
+                        "key": "pipeline-7",
+                        "name": null,
+                        "state": "INPROGRESS",
+                        "url": "https://bitbucket.org/team/app/pipelines/results/7",
+                        "description": ""
+                    }] }),
+                ),
+                ("GET", "/2.0/repositories/team/app/pipelines/p1/steps/s1/log") => {
+                    MockResponse::text(200, "step 1\n")
+                }
+                _ => MockResponse::json(404, json!({ "type": "error", "error": { "message": "Repository not found" } })),
+            }
+        })
+    }
+
+    #[test]
+    fn test_cloud_lists_pull_requests_and_issues() {
+        let server = cloud_server();
+        let provider = BitbucketCloudProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let prs = runtime.block_on(provider.get_pr_list("team", "app", &ListQuery::default())).unwrap().items;
+        assert_eq!(prs.len(), 2);
+        assert_eq!((prs[0].number, prs[0].state.as_str(), prs[0].body.as_deref()), (1, "open", None));
+        assert_eq!((prs[0].head_ref.as_str(), prs[0].base_ref.as_str()), ("feature", "main"));
+        assert_eq!((prs[0].author.as_str(), prs[1].state.as_str()), ("Alice", "closed"));
+
+        let issues = runtime.block_on(provider.get_issue_list("team", "app", &ListQuery::default())).unwrap().items;
+        assert_eq!((issues[0].state.as_str(), issues[0].author.as_str()), ("open", ""));
+        assert_eq!(issues[0].body.as_deref(), Some("It fails"));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/2.0/repositories/team/app/pullrequests?state=OPEN&sort=-created_on");
+        assert_eq!(requests[0].header("authorization"), Some("Bearer secret"));
+    }
+
+    #[test]
+    fn test_cloud_creates_pull_requests_and_issues_with_app_passwords() {
+        let server = cloud_server();
+        let provider = BitbucketCloudProvider::new("alice:app-password".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let pr = CreatePullRequest {
+            title: "Add feature".to_string(),
+            body: Some("Details".to_string()),
+            head: "feature".to_string(),
+            base: "main".to_string(),
+        };
+        assert_eq!(runtime.block_on(provider.create_pr("team", "app", pr)).unwrap().number, 3);
+
+        let issue = CreateIssue { title: "Bug".to_string(), body: None };
+        let created = runtime.block_on(provider.create_issue("team", "app", issue)).unwrap();
+        assert_eq!((created.number, created.state.as_str()), (5, "closed"));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].header("authorization"), Some("Basic YWxpY2U6YXBwLXBhc3N3b3Jk"));
+        assert_eq!(
+            requests[0].json(),
+            json!({
+                "title": "Add feature",
+                "description": "Details",
+                "source": { "branch": { "name": "feature" } },
+                "destination": { "branch": { "name": "main" } }
+            })
+        );
+        assert_eq!(requests[1].json(), json!({ "title": "Bug", "content": { "raw": "" } }));
+    }
+
+    #[test]
+    fn test_cloud_statuses_and_pipeline_logs() {
+        let server = cloud_server();
+        let provider = BitbucketCloudProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let statuses = runtime.block_on(provider.get_commit_status("team", "app", "abc123")).unwrap();
+        assert_eq!(
+            (statuses[0].id.as_str(), statuses[0].name.as_str(), statuses[0].status.as_str()),
+            ("pipeline-7", "pipeline-7", "running")
+        );
+        assert_eq!(statuses[0].description, None);
+
+        let logs = runtime.block_on(provider.get_job_logs("team", "app", "p1/s1")).unwrap();
+        assert_eq!(logs, "step 1\n");
+        assert!(matches!(
+            runtime.block_on(provider.get_job_logs("team", "app", "42")),
+            Err(AppError::InvalidInput(_))
+        ));
+
+        match runtime.block_on(provider.get_pr_list("team", "missing", &ListQuery::default())) {
+            Err(AppError::Http(message)) => assert!(message.contains("404") && message.contains("Repository not found")),
+            other => panic!("expected an HTTP error, got {:?}", other.map(|page| page.items.len())),
+        }
+    }
+
+    fn server_pull(id: u64, state: &str) -> serde_json::Value {
+        json!({
+            "id": id,
+            "title": format!("PR {}", id),
+            "description": "Details",
+            "state": state,
+            "author": { "user": { "name": "alice", "displayName": "Alice" } },
+            "createdDate": 1704067200000i64,
+            "updatedDate": 1704153600000i64,
+            "links": { "self": [{ "href": format!("https://bitbucket.corp/projects/PROJ/repos/app/pull-requests/{}", id) }] },
+            "fromRef": { "id": "refs/heads/feature", "displayId": "feature" },
+            "toRef": { "id": "refs/heads/main", "displayId": "main" }
+        })
+    }
+
+    #[test]
+    fn test_server_pull_requests_and_build_statuses() {
+        let server = MockServer::start(|request| {
+            let path = request.path.split('?').next().unwrap();
+            match (request.method.as_str(), path) {
+                ("GET", "/bitbucket/rest/api/1.0/projects/PROJ/repos/app/pull-requests") => {
+                    MockResponse::json(200, json!({ "values": [server_pull(1, "OPEN")], "isLastPage": true }))
+                }
+                ("POST", "/bitbucket/rest/api/1.0/projects/PROJ/repos/app/pull-requests") => {
+                    MockResponse::json(201, server_pull(2, "OPEN"))
+                }
+                ("GET", "/bitbucket/rest/build-status/1.0/commits/abc123") => MockResponse::json(
+                    200,
+                    json!({ "values": [{
+                        "key": "build-9",
+                        "name": "Nightly",
+                        "state": "FAILED",
+                        "url": "https://ci.corp/9",
+                        "description": "Tests failed"
+                    }] }),
+                ),
+                _ => MockResponse::json(404, json!({ "errors": [{ "message": "Not found" }] })),
+            }
+        });
+        let provider = BitbucketServerProvider::new("secret".to_string(), format!("{}/bitbucket/", server.url));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let prs = runtime.block_on(provider.get_pr_list("PROJ", "app", &ListQuery::default())).unwrap().items;
+        assert_eq!((prs[0].author.as_str(), prs[0].head_ref.as_str(), prs[0].base_ref.as_str()), ("Alice", "feature", "main"));
+        assert_eq!((prs[0].created_at.as_str(), prs[0].updated_at.as_str()), ("2024-01-01T00:00:00Z", "2024-01-02T00:00:00Z"));
+        assert_eq!(prs[0].url, "https://bitbucket.corp/projects/PROJ/repos/app/pull-requests/1");
+
+        let pr = CreatePullRequest {
+            title: "Add feature".to_string(),
+            body: None,
+            head: "feature".to_string(),
+            base: "main".to_string(),
+        };
+        assert_eq!(runtime.block_on(provider.create_pr("PROJ", "app", pr)).unwrap().number, 2);
+
+        let statuses = runtime.block_on(provider.get_commit_status("PROJ", "app", "abc123")).unwrap();
+        assert_eq!((statuses[0].name.as_str(), statuses[0].status.as_str()), ("Nightly", "failure"));
+
+        // No issue tracker and no build logs on the server
+        assert!(runtime.block_on(provider.get_issue_list("PROJ", "app", &ListQuery::default())).unwrap().items.is_empty());
+        let issue = CreateIssue { title: "Bug".to_string(), body: None };
+        assert!(runtime.block_on(provider.create_issue("PROJ", "app", issue)).is_err());
+        assert!(runtime.block_on(provider.get_job_logs("PROJ", "app", "9")).is_err());
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/bitbucket/rest/api/1.0/projects/PROJ/repos/app/pull-requests?state=OPEN&order=NEWEST&limit=25");
+        assert_eq!(
+            requests[1].json(),
+            json!({
+                "title": "Add feature",
+                "description": null,
+                "fromRef": { "id": "refs/heads/feature" },
+                "toRef": { "id": "refs/heads/main" }
+            })
+        );
+        assert_eq!(requests.len(), 3);
+    }
+
+    #[test]
+    fn test_list_filters_and_next_pages() {
+        let server = MockServer::start(|request| {
+            let (path, query) = request.path.split_once('?').unwrap_or((&request.path, ""));
+            let origin = format!("http://{}", request.header("host").unwrap());
+            match path {
+                "/2.0/repositories/team/app/issues" => MockResponse::json(
+                    200,
+                    json!({ "values": [cloud_issue(1, "open")], "next": format!("{}/2.0/repositories/team/app/issues?page=2", origin) }),
+                ),
+                "/rest/api/1.0/projects/PROJ/repos/app/pull-requests" if !query.contains("start=") => MockResponse::json(
+                    200,
+                    json!({ "values": [server_pull(1, "MERGED")], "isLastPage": false, "nextPageStart": 1 }),
+                ),
+                "/rest/api/1.0/projects/PROJ/repos/app/pull-requests" => {
+                    MockResponse::json(200, json!({ "values": [server_pull(2, "MERGED")], "isLastPage": true }))
+                }
+                _ => MockResponse::json(404, json!({})),
+            }
+        });
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let cloud = BitbucketCloudProvider::new("secret".to_string(), Some(server.url.clone()));
+        let query = ListQuery {
+            state: ListState::Closed,
+            assignee: Some("bob".to_string()),
+            search: Some("say \"hi\"".to_string()),
+            per_page: Some(1),
+            ..Default::default()
+        };
+        let page = runtime.block_on(cloud.get_issue_list("team", "app", &query)).unwrap();
+        assert_eq!(page.next_cursor, Some(format!("{}/2.0/repositories/team/app/issues?page=2", server.url)));
+
+        let bitbucket = BitbucketServerProvider::new("secret".to_string(), server.url.clone());
+        let query = ListQuery {
+            state: ListState::Merged,
+            author: Some("alice".to_string()),
+            per_page: Some(1),
+            ..Default::default()
+        };
+        let page = runtime.block_on(bitbucket.get_pr_list("PROJ", "app", &query)).unwrap();
+        assert_eq!(page.items[0].state, "merged");
+        let query = ListQuery { cursor: page.next_cursor, ..Default::default() };
+        let page = runtime.block_on(bitbucket.get_pr_list("PROJ", "app", &query)).unwrap();
+        assert_eq!((page.items[0].number, page.next_cursor), (2, None));
+
+        let requests = server.requests();
+        let url = url::Url::parse(&format!("{}{}", server.url, requests[0].path)).unwrap();
+        let params: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(params[0].0, "q");
+        assert_eq!(
+            params[0].1,
+            r#"NOT (state = "new" OR state = "open" OR state = "on hold") AND assignee.nickname = "bob" AND (title ~ "say \"hi\"" OR content.raw ~ "say \"hi\"")"#
+        );
+        assert_eq!(&params[1..], [("sort".to_string(), "-created_on".to_string()), ("pagelen".to_string(), "1".to_string())]);
+        assert_eq!(
+            requests[1].path,
+            "/rest/api/1.0/projects/PROJ/repos/app/pull-requests?state=MERGED&order=NEWEST&role.1=AUTHOR&username.1=alice&limit=1"
+        );
+        assert_eq!(
+            requests[2].path,
+            "/rest/api/1.0/projects/PROJ/repos/app/pull-requests?state=MERGED&order=NEWEST&role.1=AUTHOR&username.1=alice&limit=1&start=1"
+        );
+    }
+}
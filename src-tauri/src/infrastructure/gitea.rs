@@ -0,0 +1,267 @@
+use crate::domain::provider::{GitProvider, PullRequest, Issue, CreatePullRequest, CreateIssue, CommitStatus, ListPage, ListQuery, ListSort, ListState};
+use crate::error::{AppError, Result};
+use crate::infrastructure::http;
+use async_trait::async_trait;
+use reqwest::{Client, RequestBuilder, header};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+/// Gitea and Forgejo, which serve the same v1 REST API
+pub struct GiteaProvider {
+    client: Client,
+    base_url: String,
+}
+
+impl GiteaProvider {
+    pub fn new(token: String, base_url: String) -> Self {
+        let mut headers = header::HeaderMap::new();
+        // Gitea accepts both "token <token>" and "Bearer <token>"
+        let mut auth_value = header::HeaderValue::from_str(&format!("token {}", token)).unwrap();
+        auth_value.set_sensitive(true);
+        headers.insert(header::AUTHORIZATION, auth_value);
+        headers.insert(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+
+        let client = Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        let base_url = base_url.trim_end_matches('/').to_string();
+        Self { client, base_url }
+    }
+
+    fn repo_url(&self, owner: &str, repo: &str, rest: &str) -> String {
+        format!("{}/api/v1/repos/{}/{}/{}", self.base_url, owner, repo, rest)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        let res = request.send().await.map_err(|e| AppError::Http(e.to_string()))?;
+        // Gitea errors are {"message": "..."}
+        http::check("Gitea", res).await
+    }
+
+    async fn send_json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        self.send(request).await?.json().await.map_err(|e| AppError::Http(e.to_string()))
+    }
+
+    /// Fetch one page of a list, following the query's cursor if it has one
+    async fn list<T: DeserializeOwned>(&self, url: String, params: Vec<(&str, String)>, query: &ListQuery) -> Result<ListPage<T>> {
+        let request = match &query.cursor {
+            Some(cursor) => self.client.get(http::cursor_url(&format!("{}/api/v1", self.base_url), cursor)?),
+            None => self.client.get(url).query(&params),
+        };
+        let res = self.send(request).await?;
+        let next_cursor = http::next_page(res.url(), res.headers());
+        let items = res.json().await.map_err(|e| AppError::Http(e.to_string()))?;
+        Ok(ListPage { items, next_cursor })
+    }
+}
+
+/// Merged pull requests are listed as closed
+fn list_state(state: ListState) -> &'static str {
+    match state {
+        ListState::Open => "open",
+        ListState::Closed | ListState::Merged => "closed",
+        ListState::All => "all",
+    }
+}
+
+fn page_params(params: &mut Vec<(&str, String)>, query: &ListQuery) {
+    if let Some(page) = query.page {
+        params.push(("page", page.to_string()));
+    }
+    if let Some(per_page) = query.per_page {
+        params.push(("limit", per_page.to_string()));
+    }
+}
+
+#[derive(Deserialize)]
+struct GiteaUser {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaBranch {
+    #[serde(rename = "ref")]
+    ref_field: String,
+}
+
+#[derive(Deserialize)]
+struct GiteaPR {
+    id: u64,
+    number: u64,
+    title: String,
+    body: Option<String>,
+    state: String, // open, closed
+    #[serde(default)]
+    merged: bool,
+    user: GiteaUser,
+    created_at: String,
+    updated_at: String,
+    html_url: String,
+    head: GiteaBranch,
+    base: GiteaBranch,
+}
+
+impl From<GiteaPR> for PullRequest {
+    fn from(pr: GiteaPR) -> Self {
+        PullRequest {
+            id: pr.id.to_string(),
+            number: pr.number,
+            title: pr.title,
+            body: pr.body.filter(|b| !b.is_empty()),
+            // Merged pull requests are reported as closed with `merged` set
+            state: if pr.merged { "merged".to_string() } else { pr.state },
+            author: pr.user.login,
+            created_at: pr.created_at,
+            updated_at: pr.updated_at,
+            url: pr.html_url,
+            head_ref: pr.head.ref_field,
+            base_ref: pr.base.ref_field,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GiteaIssue {
+    id: u64,
+    number: u64,
+    title: String,
+    body: Option<String>,
+    state: String, // open, closed
+    user: GiteaUser,
+    created_at: String,
+    updated_at: String,
+    html_url: String,
+}
+
+impl From<GiteaIssue> for Issue {
+    fn from(issue: GiteaIssue) -> Self {
+        Issue {
+            id: issue.id.to_string(),
+            number: issue.number,
+            title: issue.title,
+            body: issue.body.filter(|b| !b.is_empty()),
+            state: issue.state,
+            author: issue.user.login,
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+            url: issue.html_url,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GiteaStatus {
+    id: u64,
+    context: String,
+    status: String, // pending, success, error, failure, warning
+    target_url: Option<String>,
+    description: Option<String>,
+}
+
+#[async_trait]
+impl GitProvider for GiteaProvider {
+    async fn get_pr_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<PullRequest>> {
+        // The pulls endpoint can't filter by label name or search text
+        if query.label.is_some() || query.assignee.is_some() || query.search.is_some() {
+            return Err(AppError::InvalidInput(
+                "Gitea can only filter pull requests by state and author".to_string(),
+            ));
+        }
+        let mut params = vec![("state", list_state(query.state).to_string())];
+        // Newest first is the default
+        let sort = match (query.sort, query.ascending) {
+            (ListSort::Created, false) => None,
+            (ListSort::Created, true) => Some("oldest"),
+            (ListSort::Updated, false) => Some("recentupdate"),
+            (ListSort::Updated, true) => Some("leastupdate"),
+        };
+        if let Some(sort) = sort {
+            params.push(("sort", sort.to_string()));
+        }
+        if let Some(author) = &query.author {
+            params.push(("poster", author.clone()));
+        }
+        page_params(&mut params, query);
+
+        let page: ListPage<GiteaPR> = self.list(self.repo_url(owner, repo, "pulls"), params, query).await?;
+        // Closed but unmerged ones are dropped afterwards, which can leave a page short
+        let items = page.items.into_iter()
+            .filter(|pr| query.state != ListState::Merged || pr.merged)
+            .map(PullRequest::from)
+            .collect();
+        Ok(ListPage { items, next_cursor: page.next_cursor })
+    }
+
+    async fn get_issue_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<Issue>> {
+        if query.state == ListState::Merged {
+            return Err(AppError::InvalidInput("Only pull requests can be merged".to_string()));
+        }
+        // The issues endpoint lists pull requests too unless asked not to.
+        // It only sorts by creation date, newest first
+        let mut params = vec![("state", list_state(query.state).to_string()), ("type", "issues".to_string())];
+        let filters = [
+            ("created_by", &query.author),
+            ("labels", &query.label),
+            ("assigned_by", &query.assignee),
+            ("q", &query.search),
+        ];
+        for (name, value) in filters {
+            if let Some(value) = value {
+                params.push((name, value.clone()));
+            }
+        }
+        page_params(&mut params, query);
+
+        let page: ListPage<GiteaIssue> = self.list(self.repo_url(owner, repo, "issues"), params, query).await?;
+        Ok(ListPage { items: page.items.into_iter().map(Issue::from).collect(), next_cursor: page.next_cursor })
+    }
+
+    async fn create_pr(&self, owner: &str, repo: &str, pr: CreatePullRequest) -> Result<PullRequest> {
+        let url = self.repo_url(owner, repo, "pulls");
+        let body = serde_json::json!({
+            "title": pr.title,
+            "body": pr.body,
+            "head": pr.head,
+            "base": pr.base
+        });
+        let pr: GiteaPR = self.send_json(self.client.post(&url).json(&body)).await?;
+        Ok(pr.into())
+    }
+
+    async fn create_issue(&self, owner: &str, repo: &str, issue: CreateIssue) -> Result<Issue> {
+        let url = self.repo_url(owner, repo, "issues");
+        let body = serde_json::json!({
+            "title": issue.title,
+            "body": issue.body
+        });
+        let issue: GiteaIssue = self.send_json(self.client.post(&url).json(&body)).await?;
+        Ok(issue.into())
+    }
+
+    async fn get_commit_status(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<CommitStatus>> {
+        let url = self.repo_url(owner, repo, &format!("commits/{}/statuses", sha));
+        let statuses: Vec<GiteaStatus> = self.send_json(self.client.get(&url)).await?;
+        Ok(statuses.into_iter().map(|s| CommitStatus {
+            id: s.id.to_string(),
+            name: s.context,
+            status: s.status,
+            url: s.target_url,
+            description: s.description,
+        }).collect())
+    }
+
+    async fn get_job_logs(&self, owner: &str, repo: &str, job_id: &str) -> Result<String> {
+        // Gitea Actions job logs, available since Gitea 1.23 and in Forgejo
+        let url = self.repo_url(owner, repo, &format!("actions/jobs/{}/logs", job_id));
+        self.send(self.client.get(&url)).await?
+            .text()
+            .await
+            .map_err(|e| AppError::Http(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+#[path = "gitea_tests.rs"]
+mod gitea_tests;
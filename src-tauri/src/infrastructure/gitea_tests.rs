@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::provider::{CreateIssue, CreatePullRequest, GitProvider, ListQuery};
+    use crate::error::AppError;
+    use crate::infrastructure::gitea::GiteaProvider;
+    use crate::infrastructure::mock_server::{MockResponse, MockServer};
+    use serde_json::json;
+
+    fn pull(number: u64, state: &str, merged: bool) -> serde_json::Value {
+        json!({
+            "id": 100 + number,
+            "number": number,
+            "title": format!("PR {}", number),
+            "body": "",
+            "state": state,
+            "merged": merged,
+            "user": { "login": "alice" },
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-02T00:00:00Z",
+            "html_url": format!("https://git.example.com/team/app/pulls/{}", number),
+            "head": { "ref": "feature", "sha": "abc" },
+            "base": { "ref": "main", "sha": "def" }
+        })
+    }
+
+    fn issue(number: u64) -> serde_json::Value {
+        json!({
+            "id": 200 + number,
+            "number": number,
+            "title": "Broken build",
+            "body": "It fails",
+            "state": "open",
+            "user": { "login": "bob" },
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "html_url": format!("https://git.example.com/team/app/issues/{}", number)
+        })
+    }
+
+    fn server() -> MockServer {
+        MockServer::start(|request| {
+            let path = request.path.split('?').next().unwrap();
+            match (request.method.as_str(), path) {
+                ("GET", "/api/v1/repos/team/app/pulls") => {
+                    MockResponse::json(200, json!([pull(1, "open", false), pull(2, "closed", true)]))
+                }
+                ("POST", "/api/v1/repos/team/app/pulls") => MockResponse::json(201, pull(3, "open", false)),
+                ("GET", "/api/v1/repos/team/app/issues") => MockResponse::json(200, json!([issue(4)])),
+                ("POST", "/api/v1/repos/team/app/issues") => MockResponse::json(201, issue(5)),
+                ("GET", "/api/v1/repos/team/app/commits/abc123/statuses") => MockResponse::json(
+                    200,
+                    json!([{
+                        "id": 7,
+                        "context": "ci/build",
+                        "status": "failure",
+                        "target_url": "https://ci.example.com/7",
+                        "description": "Build failed"
+                    }]),
+                ),
+                ("GET", "/api/v1/repos/team/app/actions/jobs/42/logs") => MockResponse::text(200, "step 1\nstep 2\n"),
+                _ => MockResponse::json(404, json!({ "message": "The target couldn't be found." })),
+            }
+        })
+    }
+
+    #[test]
+    fn test_lists_pull_requests_and_issues() {
+        let server = server();
+        let provider = GiteaProvider::new("secret".to_string(), format!("{}/", server.url));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let prs = runtime.block_on(provider.get_pr_list("team", "app", &ListQuery::default())).unwrap().items;
+        assert_eq!(prs.len(), 2);
+        assert_eq!((prs[0].number, prs[0].state.as_str(), prs[0].body.as_deref()), (1, "open", None));
+        assert_eq!((prs[0].head_ref.as_str(), prs[0].base_ref.as_str()), ("feature", "main"));
+        assert_eq!(prs[1].state, "merged");
+
+        let issues = runtime.block_on(provider.get_issue_list("team", "app", &ListQuery::default())).unwrap().items;
+        assert_eq!(issues.len(), 1);
+        assert_eq!((issues[0].number, issues[0].author.as_str()), (4, "bob"));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/api/v1/repos/team/app/pulls?state=open");
+        assert_eq!(requests[0].header("authorization"), Some("token secret"));
+        assert_eq!(requests[1].path, "/api/v1/repos/team/app/issues?state=open&type=issues");
+    }
+
+    #[test]
+    fn test_creates_pull_requests_and_issues() {
+        let server = server();
+        let provider = GiteaProvider::new("secret".to_string(), server.url.clone());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let pr = CreatePullRequest {
+            title: "Add feature".to_string(),
+            body: Some("Details".to_string()),
+            head: "feature".to_string(),
+            base: "main".to_string(),
+        };
+        let created = runtime.block_on(provider.create_pr("team", "app", pr)).unwrap();
+        assert_eq!(created.number, 3);
+
+        let issue = CreateIssue { title: "Bug".to_string(), body: None };
+        let created = runtime.block_on(provider.create_issue("team", "app", issue)).unwrap();
+        assert_eq!(created.number, 5);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(
+            requests[0].json(),
+            json!({ "title": "Add feature", "body": "Details", "head": "feature", "base": "main" })
+        );
+        assert_eq!(requests[1].json(), json!({ "title": "Bug", "body": null }));
+    }
+
+    #[test]
+    fn test_commit_statuses_and_job_logs() {
+        let server = server();
+        let provider = GiteaProvider::new("secret".to_string(), server.url.clone());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let statuses = runtime.block_on(provider.get_commit_status("team", "app", "abc123")).unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!((statuses[0].name.as_str(), statuses[0].status.as_str()), ("ci/build", "failure"));
+        assert_eq!(statuses[0].url.as_deref(), Some("https://ci.example.com/7"));
+
+        let logs = runtime.block_on(provider.get_job_logs("team", "app", "42")).unwrap();
+        assert_eq!(logs, "step 1\nstep 2\n");
+    }
+
+    #[test]
+    fn test_api_errors_carry_the_status_and_message() {
+        let server = server();
+        let provider = GiteaProvider::new("secret".to_string(), server.url.clone());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        match runtime.block_on(provider.get_pr_list("team", "missing", &ListQuery::default())) {
+            Err(AppError::Http(message)) => {
+                assert!(message.contains("404"));
+                assert!(message.contains("The target couldn't be found."));
+            }
+            other => panic!("expected an HTTP error, got {:?}", other.map(|page| page.items.len())),
+        }
+    }
+}
@@ -1,15 +1,26 @@
-use crate::domain::provider::{GitProvider, PullRequest, Issue, CreatePullRequest, CreateIssue};
+use crate::domain::provider::{
+    review_decision, CheckSummary, CommentPosition, CreateIssue, CreatePullRequest, CreateReview, DiffSide, GitProvider,
+    Issue, ListPage, ListQuery, ListSort, ListState, MergeStatus, PullRequest, PullRequestComment, PullRequestCommit,
+    PullRequestDetails, PullRequestFile, PullRequestReview, ReviewEvent, ReviewState,
+};
+use crate::domain::FileStatus;
 use crate::error::{AppError, Result};
+use crate::infrastructure::http;
 use async_trait::async_trait;
-use reqwest::{Client, header};
+use reqwest::{Client, RequestBuilder, header};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 
+const API_URL: &str = "https://api.github.com";
+
 pub struct GitHubProvider {
     client: Client,
+    api_url: String,
 }
 
 impl GitHubProvider {
-    pub fn new(token: String) -> Self {
+    /// `api_url` is for GitHub Enterprise Server (`https://<host>/api/v3`)
+    pub fn new(token: String, api_url: Option<String>) -> Self {
         let mut headers = header::HeaderMap::new();
         let mut auth_value = header::HeaderValue::from_str(&format!("Bearer {}", token)).unwrap();
         auth_value.set_sensitive(true);
@@ -22,7 +33,157 @@ impl GitHubProvider {
             .build()
             .unwrap();
 
-        Self { client }
+        let api_url = api_url.unwrap_or_else(|| API_URL.to_string());
+        let api_url = api_url.trim_end_matches('/').to_string();
+        Self { client, api_url }
+    }
+
+    fn repo_url(&self, owner: &str, repo: &str, rest: &str) -> String {
+        format!("{}/repos/{}/{}/{}", self.api_url, owner, repo, rest)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        let res = request.send().await.map_err(|e| AppError::Http(e.to_string()))?;
+        http::check("GitHub", res).await
+    }
+
+    async fn send_json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        self.send(request).await?.json().await.map_err(|e| AppError::Http(e.to_string()))
+    }
+
+    /// Fetch one page of a list, and where the next one is
+    async fn send_page<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<(T, Option<String>)> {
+        let res = self.send(request).await?;
+        let next = http::next_page(res.url(), res.headers());
+        let body = res.json().await.map_err(|e| AppError::Http(e.to_string()))?;
+        Ok((body, next))
+    }
+
+    /// GitHub Enterprise Server serves GraphQL at `/api/graphql`, next to
+    /// the REST API's `/api/v3`
+    fn graphql_url(&self) -> String {
+        let root = self.api_url.strip_suffix("/v3").unwrap_or(&self.api_url);
+        format!("{}/graphql", root)
+    }
+
+    /// GraphQL reports errors in a successful response
+    async fn graphql(&self, query: &str, variables: serde_json::Value) -> Result<serde_json::Value> {
+        let body = serde_json::json!({ "query": query, "variables": variables });
+        let mut res: serde_json::Value = self.send_json(self.client.post(self.graphql_url()).json(&body)).await?;
+        if let Some(errors) = res["errors"].as_array().filter(|errors| !errors.is_empty()) {
+            let messages: Vec<&str> = errors.iter().filter_map(|e| e["message"].as_str()).collect();
+            return Err(AppError::Http(format!("GitHub GraphQL Error: {}", messages.join("; "))));
+        }
+        Ok(res["data"].take())
+    }
+
+    /// The node id of the review thread started by comment `thread_id`,
+    /// which the REST API has no way to look up
+    async fn review_thread_id(&self, owner: &str, repo: &str, number: u64, thread_id: &str) -> Result<String> {
+        const QUERY: &str = "query($owner: String!, $repo: String!, $number: Int!, $cursor: String) {
+            repository(owner: $owner, name: $repo) { pullRequest(number: $number) {
+                reviewThreads(first: 100, after: $cursor) {
+                    nodes { id comments(first: 1) { nodes { databaseId } } }
+                    pageInfo { hasNextPage endCursor }
+                }
+            } }
+        }";
+        let comment_id: u64 = thread_id.parse()
+            .map_err(|_| AppError::InvalidInput(format!("Invalid review thread id: {}", thread_id)))?;
+
+        let mut cursor = serde_json::Value::Null;
+        for _ in 0..http::MAX_PAGES {
+            let variables = serde_json::json!({ "owner": owner, "repo": repo, "number": number, "cursor": cursor });
+            let mut data = self.graphql(QUERY, variables).await?;
+            let threads = &mut data["repository"]["pullRequest"]["reviewThreads"];
+            for thread in threads["nodes"].as_array().into_iter().flatten() {
+                if thread["comments"]["nodes"][0]["databaseId"].as_u64() == Some(comment_id) {
+                    if let Some(id) = thread["id"].as_str() {
+                        return Ok(id.to_string());
+                    }
+                }
+            }
+            if threads["pageInfo"]["hasNextPage"].as_bool() != Some(true) {
+                break;
+            }
+            cursor = threads["pageInfo"]["endCursor"].take();
+        }
+        Err(AppError::InvalidInput(format!("No review thread starts with comment {}", thread_id)))
+    }
+
+    /// The request for a query's page: its cursor, or the search API when
+    /// the plain list endpoint can't express the filters
+    fn list_request(&self, owner: &str, repo: &str, kind: &str, query: &ListQuery, endpoint: RequestBuilder) -> Result<ListRequest> {
+        if let Some(cursor) = &query.cursor {
+            let url = http::cursor_url(&self.api_url, cursor)?;
+            let search = url.starts_with(&format!("{}/search/", self.api_url));
+            return Ok(ListRequest { request: self.client.get(url), search });
+        }
+
+        let mut params = page_params(query);
+        params.push(("sort", match query.sort {
+            ListSort::Created => "created",
+            ListSort::Updated => "updated",
+        }.to_string()));
+        let order = if query.ascending { "asc" } else { "desc" };
+
+        let needs_search = query.search.is_some()
+            || (kind == "pr" && (query.author.is_some() || query.label.is_some() || query.assignee.is_some()))
+            || query.state == ListState::Merged;
+        if !needs_search {
+            params.push(("direction", order.to_string()));
+            return Ok(ListRequest { request: endpoint.query(&params), search: false });
+        }
+
+        let mut q = vec![format!("repo:{}/{}", owner, repo), format!("is:{}", kind)];
+        match query.state {
+            ListState::Open => q.push("is:open".to_string()),
+            ListState::Closed => q.push("is:closed".to_string()),
+            ListState::Merged => q.push("is:merged".to_string()),
+            ListState::All => {}
+        }
+        if let Some(author) = &query.author {
+            q.push(format!("author:{}", author));
+        }
+        if let Some(label) = &query.label {
+            q.push(format!("label:\"{}\"", label));
+        }
+        if let Some(assignee) = &query.assignee {
+            q.push(format!("assignee:{}", assignee));
+        }
+        if let Some(search) = &query.search {
+            q.push(search.clone());
+        }
+        params.push(("order", order.to_string()));
+        params.push(("q", q.join(" ")));
+        let url = format!("{}/search/issues", self.api_url);
+        Ok(ListRequest { request: self.client.get(url).query(&params), search: true })
+    }
+}
+
+struct ListRequest {
+    request: RequestBuilder,
+    /// Whether it goes to the search API, which wraps results in `items`
+    search: bool,
+}
+
+fn page_params(query: &ListQuery) -> Vec<(&'static str, String)> {
+    let mut params = Vec::new();
+    if let Some(page) = query.page {
+        params.push(("page", page.to_string()));
+    }
+    if let Some(per_page) = query.per_page {
+        params.push(("per_page", per_page.to_string()));
+    }
+    params
+}
+
+fn list_state(state: ListState) -> Result<&'static str> {
+    match state {
+        ListState::Open => Ok("open"),
+        ListState::Closed => Ok("closed"),
+        ListState::All => Ok("all"),
+        ListState::Merged => Err(AppError::InvalidInput("Only pull requests can be merged".to_string())),
     }
 }
 
@@ -38,6 +199,7 @@ struct GitHubPR {
     title: String,
     body: Option<String>,
     state: String,
+    merged_at: Option<String>,
     user: GitHubUser,
     created_at: String,
     updated_at: String,
@@ -46,6 +208,25 @@ struct GitHubPR {
     base: GitHubRef,
 }
 
+impl From<GitHubPR> for PullRequest {
+    fn from(pr: GitHubPR) -> Self {
+        PullRequest {
+            id: pr.id.to_string(),
+            number: pr.number,
+            title: pr.title,
+            body: pr.body,
+            // Merged pull requests are reported as closed with `merged_at` set
+            state: if pr.merged_at.is_some() { "merged".to_string() } else { pr.state },
+            author: pr.user.login,
+            created_at: pr.created_at,
+            updated_at: pr.updated_at,
+            url: pr.html_url,
+            head_ref: pr.head.ref_name,
+            base_ref: pr.base.ref_name,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct GitHubIssue {
     id: u64,
@@ -57,82 +238,286 @@ struct GitHubIssue {
     created_at: String,
     updated_at: String,
     html_url: String,
-    // GitHub issues API returns PRs too, but they have a pull_request field. 
-    // We might need to filter them out if strictly looking for issues, 
+    // GitHub issues API returns PRs too, but they have a pull_request field.
+    // We might need to filter them out if strictly looking for issues,
     // or just treat them as issues.
-    pull_request: Option<serde_json::Value>,
+    pull_request: Option<GitHubIssuePR>,
 }
 
-#[async_trait]
-impl GitProvider for GitHubProvider {
-    async fn get_pr_list(&self, owner: &str, repo: &str) -> Result<Vec<PullRequest>> {
-        let url = format!("https://api.github.com/repos/{}/{}/pulls", owner, repo);
-        let res = self.client.get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+#[derive(Deserialize)]
+struct GitHubIssuePR {
+    merged_at: Option<String>,
+}
 
-        if !res.status().is_success() {
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitHub API Error: {}", res.status()))));
+impl From<GitHubIssue> for Issue {
+    fn from(issue: GitHubIssue) -> Self {
+        Issue {
+            id: issue.id.to_string(),
+            number: issue.number,
+            title: issue.title,
+            body: issue.body,
+            state: issue.state,
+            author: issue.user.login,
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+            url: issue.html_url,
         }
+    }
+}
 
-        let github_prs: Vec<GitHubPR> = res.json().await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+impl From<GitHubIssue> for PullRequest {
+    /// Search results are issue-shaped, so a pull request found by search
+    /// has no branch names
+    fn from(issue: GitHubIssue) -> Self {
+        let merged = issue.pull_request.as_ref().is_some_and(|pr| pr.merged_at.is_some());
+        PullRequest {
+            id: issue.id.to_string(),
+            number: issue.number,
+            title: issue.title,
+            body: issue.body,
+            state: if merged { "merged".to_string() } else { issue.state },
+            author: issue.user.login,
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+            url: issue.html_url,
+            head_ref: String::new(),
+            base_ref: String::new(),
+        }
+    }
+}
 
-        Ok(github_prs.into_iter().map(|pr| PullRequest {
-            id: pr.id.to_string(),
-            number: pr.number,
-            title: pr.title,
-            body: pr.body,
-            state: pr.state,
-            author: pr.user.login,
-            created_at: pr.created_at,
-            updated_at: pr.updated_at,
-            url: pr.html_url,
-            head_ref: pr.head.ref_name,
-            base_ref: pr.base.ref_name,
-        }).collect())
+#[derive(Deserialize)]
+struct GitHubSearch {
+    items: Vec<GitHubIssue>,
+}
+
+#[derive(Deserialize)]
+struct GitHubPRDetails {
+    #[serde(flatten)]
+    pr: GitHubPR,
+    #[serde(default)]
+    draft: bool,
+    mergeable_state: Option<String>,
+    commits: Option<u64>,
+    changed_files: Option<u64>,
+}
+
+/// `mergeable_state` is computed in the background after a push, and is
+/// "unknown" until then
+fn merge_status(state: Option<&str>) -> MergeStatus {
+    match state.unwrap_or("unknown") {
+        "clean" | "has_hooks" => MergeStatus::Clean,
+        "dirty" => MergeStatus::Conflicts,
+        "behind" => MergeStatus::Behind,
+        "blocked" => MergeStatus::Blocked,
+        "unstable" => MergeStatus::Unstable,
+        "draft" => MergeStatus::Draft,
+        _ => MergeStatus::Unknown,
     }
+}
 
-    async fn get_issue_list(&self, owner: &str, repo: &str) -> Result<Vec<Issue>> {
-        let url = format!("https://api.github.com/repos/{}/{}/issues", owner, repo);
-        let res = self.client.get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+#[derive(Deserialize)]
+struct GitHubReview {
+    id: u64,
+    // Deleted accounts come back as null
+    user: Option<GitHubUser>,
+    state: String,
+    body: Option<String>,
+    submitted_at: Option<String>,
+}
+
+impl From<GitHubReview> for PullRequestReview {
+    fn from(review: GitHubReview) -> Self {
+        PullRequestReview {
+            id: review.id.to_string(),
+            author: review.user.map(|u| u.login).unwrap_or_default(),
+            state: match review.state.as_str() {
+                "APPROVED" => ReviewState::Approved,
+                "CHANGES_REQUESTED" => ReviewState::ChangesRequested,
+                "DISMISSED" => ReviewState::Dismissed,
+                "PENDING" => ReviewState::Pending,
+                _ => ReviewState::Commented,
+            },
+            body: review.body.filter(|b| !b.is_empty()),
+            submitted_at: review.submitted_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GitHubFile {
+    filename: String,
+    previous_filename: Option<String>,
+    status: String, // added, removed, modified, renamed, copied, changed, unchanged
+    additions: u64,
+    deletions: u64,
+    patch: Option<String>,
+}
 
-        if !res.status().is_success() {
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitHub API Error: {}", res.status()))));
+impl From<GitHubFile> for PullRequestFile {
+    fn from(file: GitHubFile) -> Self {
+        PullRequestFile {
+            path: file.filename,
+            old_path: file.previous_filename,
+            status: match file.status.as_str() {
+                "added" => FileStatus::Added,
+                "removed" => FileStatus::Deleted,
+                "modified" | "changed" => FileStatus::Modified,
+                "renamed" => FileStatus::Renamed,
+                "copied" => FileStatus::Copied,
+                _ => FileStatus::Unknown,
+            },
+            additions: file.additions,
+            deletions: file.deletions,
+            patch: file.patch,
         }
+    }
+}
+
+#[derive(Deserialize)]
+struct GitHubCommitAuthor {
+    name: String,
+    date: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubCommitData {
+    message: String,
+    author: GitHubCommitAuthor,
+}
+
+#[derive(Deserialize)]
+struct GitHubCommit {
+    sha: String,
+    commit: GitHubCommitData,
+    /// The linked account, if the author's email belongs to one
+    author: Option<GitHubUser>,
+}
+
+impl From<GitHubCommit> for PullRequestCommit {
+    fn from(commit: GitHubCommit) -> Self {
+        PullRequestCommit {
+            sha: commit.sha,
+            message: commit.commit.message,
+            author: commit.author.map(|u| u.login).unwrap_or(commit.commit.author.name),
+            authored_at: commit.commit.author.date,
+        }
+    }
+}
 
-        let github_issues: Vec<GitHubIssue> = res.json().await
-             .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+/// A comment on the pull request's conversation tab
+#[derive(Deserialize)]
+struct GitHubComment {
+    id: u64,
+    user: Option<GitHubUser>,
+    body: Option<String>,
+    created_at: String,
+    updated_at: String,
+    html_url: String,
+}
 
-        // Filter out PRs (GitHub returns PRs in issues endpoint)
-        let issues = github_issues.into_iter()
+impl From<GitHubComment> for PullRequestComment {
+    fn from(comment: GitHubComment) -> Self {
+        PullRequestComment {
+            id: comment.id.to_string(),
+            thread_id: None,
+            author: comment.user.map(|u| u.login).unwrap_or_default(),
+            body: comment.body.unwrap_or_default(),
+            created_at: comment.created_at,
+            updated_at: comment.updated_at,
+            url: Some(comment.html_url),
+            position: None,
+            resolved: None,
+        }
+    }
+}
+
+/// A comment on a line of the diff
+#[derive(Deserialize)]
+struct GitHubReviewComment {
+    #[serde(flatten)]
+    comment: GitHubComment,
+    /// Replies point at the thread's first comment
+    in_reply_to_id: Option<u64>,
+    path: String,
+    /// Null once the line is no longer part of the diff
+    line: Option<u32>,
+    side: Option<String>, // LEFT, RIGHT
+    commit_id: Option<String>,
+}
+
+impl From<GitHubReviewComment> for PullRequestComment {
+    fn from(review_comment: GitHubReviewComment) -> Self {
+        let thread = review_comment.in_reply_to_id.unwrap_or(review_comment.comment.id);
+        let position = CommentPosition {
+            path: review_comment.path,
+            line: review_comment.line,
+            side: if review_comment.side.as_deref() == Some("LEFT") { DiffSide::Left } else { DiffSide::Right },
+            commit_sha: review_comment.commit_id,
+        };
+        PullRequestComment {
+            thread_id: Some(thread.to_string()),
+            position: Some(position),
+            ..review_comment.comment.into()
+        }
+    }
+}
+
+#[async_trait]
+impl GitProvider for GitHubProvider {
+    async fn get_pr_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<PullRequest>> {
+        let mut endpoint = self.client.get(self.repo_url(owner, repo, "pulls"));
+        if query.state != ListState::Merged {
+            endpoint = endpoint.query(&[("state", list_state(query.state)?)]);
+        }
+        let list = self.list_request(owner, repo, "pr", query, endpoint)?;
+
+        let (items, next_cursor) = if list.search {
+            let (found, next): (GitHubSearch, _) = self.send_page(list.request).await?;
+            (found.items.into_iter().map(PullRequest::from).collect(), next)
+        } else {
+            let (prs, next): (Vec<GitHubPR>, _) = self.send_page(list.request).await?;
+            (prs.into_iter().map(PullRequest::from).collect(), next)
+        };
+        Ok(ListPage { items, next_cursor })
+    }
+
+    async fn get_issue_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<Issue>> {
+        let mut params = vec![("state", list_state(query.state)?.to_string())];
+        if let Some(author) = &query.author {
+            params.push(("creator", author.clone()));
+        }
+        if let Some(label) = &query.label {
+            params.push(("labels", label.clone()));
+        }
+        if let Some(assignee) = &query.assignee {
+            params.push(("assignee", assignee.clone()));
+        }
+        let endpoint = self.client.get(self.repo_url(owner, repo, "issues")).query(&params);
+        let list = self.list_request(owner, repo, "issue", query, endpoint)?;
+
+        let (issues, next_cursor) = if list.search {
+            let (found, next): (GitHubSearch, _) = self.send_page(list.request).await?;
+            (found.items, next)
+        } else {
+            self.send_page::<Vec<GitHubIssue>>(list.request).await?
+        };
+
+        // Filter out PRs (GitHub returns PRs in issues endpoint), which can
+        // leave a page short
+        let items = issues.into_iter()
             .filter(|issue| issue.pull_request.is_none())
-            .map(|issue| Issue {
-                id: issue.id.to_string(),
-                number: issue.number,
-                title: issue.title,
-                body: issue.body,
-                state: issue.state,
-                author: issue.user.login,
-                created_at: issue.created_at,
-                updated_at: issue.updated_at,
-                url: issue.html_url,
-            })
+            .map(Issue::from)
             .collect();
-            
-        Ok(issues)
+        Ok(ListPage { items, next_cursor })
     }
 
     async fn create_pr(&self, owner: &str, repo: &str, pr: CreatePullRequest) -> Result<PullRequest> {
-         let url = format!("https://api.github.com/repos/{}/{}/pulls", owner, repo);
+         let url = self.repo_url(owner, repo, "pulls");
          // GitHub API for create PR:
          // https://docs.github.com/en/rest/pulls/pulls?apiVersion=2022-11-28#create-a-pull-request
          // { "title": "...", "body": "...", "head": "...", "base": "..." }
-         
+
          let body = serde_json::json!({
              "title": pr.title,
              "body": pr.body,
@@ -140,85 +525,24 @@ impl GitProvider for GitHubProvider {
              "base": pr.base
          });
 
-         let res = self.client.post(&url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-
-         if !res.status().is_success() {
-             let error_text = res.text().await.unwrap_or_default();
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitHub API Error: {}", error_text))));
-         }
-
-         let pr: GitHubPR = res.json().await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-
-         Ok(PullRequest {
-            id: pr.id.to_string(),
-            number: pr.number,
-            title: pr.title,
-            body: pr.body,
-            state: pr.state,
-            author: pr.user.login,
-            created_at: pr.created_at,
-            updated_at: pr.updated_at,
-            url: pr.html_url,
-            head_ref: pr.head.ref_name,
-            base_ref: pr.base.ref_name,
-        })
+         let pr: GitHubPR = self.send_json(self.client.post(&url).json(&body)).await?;
+         Ok(pr.into())
     }
 
     async fn create_issue(&self, owner: &str, repo: &str, issue: CreateIssue) -> Result<Issue> {
-         let url = format!("https://api.github.com/repos/{}/{}/issues", owner, repo);
-         
+         let url = self.repo_url(owner, repo, "issues");
+
          let body = serde_json::json!({
              "title": issue.title,
              "body": issue.body,
          });
 
-         let res = self.client.post(&url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-
-         if !res.status().is_success() {
-             let error_text = res.text().await.unwrap_or_default();
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitHub API Error: {}", error_text))));
-         }
-
-         let issue: GitHubIssue = res.json().await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-
-         Ok(Issue {
-                id: issue.id.to_string(),
-                number: issue.number,
-                title: issue.title,
-                body: issue.body,
-                state: issue.state,
-                author: issue.user.login,
-                created_at: issue.created_at,
-                updated_at: issue.updated_at,
-                url: issue.html_url,
-         })
+         let issue: GitHubIssue = self.send_json(self.client.post(&url).json(&body)).await?;
+         Ok(issue.into())
     }
 
     async fn get_commit_status(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<crate::domain::provider::CommitStatus>> {
-        let url = format!("https://api.github.com/repos/{}/{}/commits/{}/check-runs", owner, repo, sha);
-        let res = self.client.get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-
-        if !res.status().is_success() {
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitHub API Error: {}", res.status()))));
-        }
-
-        #[derive(Deserialize)]
-        struct GitHubCheckRuns {
-            check_runs: Vec<GitHubCheckRun>,
-        }
+        let url = self.repo_url(owner, repo, &format!("commits/{}/check-runs?per_page=100", sha));
 
         #[derive(Deserialize)]
         struct GitHubCheckRun {
@@ -229,10 +553,9 @@ impl GitProvider for GitHubProvider {
             html_url: String,
         }
 
-        let checks: GitHubCheckRuns = res.json().await
-             .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+        let checks: Vec<GitHubCheckRun> = http::fetch_all_in(&self.client, "GitHub", &url, "check_runs").await?;
 
-        let mut results: Vec<crate::domain::provider::CommitStatus> = checks.check_runs.into_iter().map(|c| {
+        let mut results: Vec<crate::domain::provider::CommitStatus> = checks.into_iter().map(|c| {
             let status = if c.status == "completed" {
                 c.conclusion.unwrap_or_else(|| "unknown".to_string())
             } else {
@@ -249,7 +572,7 @@ impl GitProvider for GitHubProvider {
         }).collect();
 
         // Also fetch legacy statuses (for Jenkins, etc.)
-        let status_url = format!("https://api.github.com/repos/{}/{}/commits/{}/status", owner, repo, sha);
+        let status_url = self.repo_url(owner, repo, &format!("commits/{}/status", sha));
         let status_res = self.client.get(&status_url).send().await;
         if let Ok(res) = status_res {
              if res.status().is_success() {
@@ -265,7 +588,7 @@ impl GitProvider for GitHubProvider {
                      target_url: Option<String>,
                      description: Option<String>,
                  }
-                 
+
                  if let Ok(combined) = res.json::<GitHubCombinedStatus>().await {
                      for s in combined.statuses {
                          results.push(crate::domain::provider::CommitStatus {
@@ -286,20 +609,126 @@ impl GitProvider for GitHubProvider {
     async fn get_job_logs(&self, owner: &str, repo: &str, job_id: &str) -> Result<String> {
         // For GitHub Actions, we use the jobs API
         // GET /repos/{owner}/{repo}/actions/jobs/{job_id}/logs
-        let url = format!("https://api.github.com/repos/{}/{}/actions/jobs/{}/logs", owner, repo, job_id);
-        let res = self.client.get(&url)
-            .send()
+        let url = self.repo_url(owner, repo, &format!("actions/jobs/{}/logs", job_id));
+        self.send(self.client.get(&url)).await?
+            .text()
             .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+            .map_err(|e| AppError::Http(e.to_string()))
+    }
 
-        if !res.status().is_success() {
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitHub API Error: {}", res.status()))));
+    async fn get_pr_details(&self, owner: &str, repo: &str, number: u64) -> Result<PullRequestDetails> {
+        let url = self.repo_url(owner, repo, &format!("pulls/{}", number));
+        let details: GitHubPRDetails = self.send_json(self.client.get(&url)).await?;
+        let url = self.repo_url(owner, repo, &format!("pulls/{}/reviews?per_page=100", number));
+        let reviews: Vec<GitHubReview> = http::fetch_all(&self.client, "GitHub", &url).await?;
+        let reviews: Vec<PullRequestReview> = reviews.into_iter().map(PullRequestReview::from).collect();
+
+        let head_sha = details.pr.head.sha.clone();
+        let statuses = self.get_commit_status(owner, repo, &head_sha).await?;
+        let base_sha = Some(details.pr.base.sha.clone()).filter(|sha| !sha.is_empty());
+        Ok(PullRequestDetails {
+            draft: details.draft,
+            head_sha,
+            base_sha,
+            merge_status: merge_status(details.mergeable_state.as_deref()),
+            review_decision: review_decision(&reviews),
+            reviews,
+            checks: CheckSummary::from_statuses(&statuses),
+            commits: details.commits,
+            changed_files: details.changed_files,
+            pull_request: details.pr.into(),
+        })
+    }
+
+    async fn get_pr_files(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<PullRequestFile>> {
+        // At most 3000 files are listed
+        let url = self.repo_url(owner, repo, &format!("pulls/{}/files?per_page=100", number));
+        let files: Vec<GitHubFile> = http::fetch_all(&self.client, "GitHub", &url).await?;
+        Ok(files.into_iter().map(PullRequestFile::from).collect())
+    }
+
+    async fn get_pr_commits(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<PullRequestCommit>> {
+        // Oldest first, at most 250
+        let url = self.repo_url(owner, repo, &format!("pulls/{}/commits?per_page=100", number));
+        let commits: Vec<GitHubCommit> = http::fetch_all(&self.client, "GitHub", &url).await?;
+        Ok(commits.into_iter().map(PullRequestCommit::from).collect())
+    }
+
+    async fn get_pr_comments(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<PullRequestComment>> {
+        // General comments live on the pull request's issue
+        let url = self.repo_url(owner, repo, &format!("issues/{}/comments?per_page=100", number));
+        let general: Vec<GitHubComment> = http::fetch_all(&self.client, "GitHub", &url).await?;
+        let url = self.repo_url(owner, repo, &format!("pulls/{}/comments?per_page=100", number));
+        let inline: Vec<GitHubReviewComment> = http::fetch_all(&self.client, "GitHub", &url).await?;
+
+        let mut comments: Vec<PullRequestComment> = general.into_iter().map(PullRequestComment::from)
+            .chain(inline.into_iter().map(PullRequestComment::from))
+            .collect();
+        comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(comments)
+    }
+
+    async fn create_review(&self, owner: &str, repo: &str, number: u64, review: CreateReview) -> Result<()> {
+        let url = self.repo_url(owner, repo, &format!("pulls/{}/reviews", number));
+        let comments: Vec<serde_json::Value> = review.comments.iter().map(|comment| {
+            let mut value = serde_json::json!({
+                "path": comment.path,
+                "line": comment.line,
+                "side": diff_side(comment.side),
+                "body": comment.body,
+            });
+            if let Some(start_line) = comment.start_line.filter(|&start| start < comment.line) {
+                value["start_line"] = start_line.into();
+                value["start_side"] = diff_side(comment.side).into();
+            }
+            value
+        }).collect();
+
+        let mut body = serde_json::json!({ "comments": comments });
+        if let Some(text) = review.body {
+            body["body"] = text.into();
+        }
+        if let Some(sha) = review.commit_sha {
+            body["commit_id"] = sha.into();
+        }
+        // Without an event the review is left pending
+        let event = match review.event {
+            ReviewEvent::Comment => Some("COMMENT"),
+            ReviewEvent::Approve => Some("APPROVE"),
+            ReviewEvent::RequestChanges => Some("REQUEST_CHANGES"),
+            ReviewEvent::Draft => None,
+        };
+        if let Some(event) = event {
+            body["event"] = event.into();
         }
 
-        let logs = res.text().await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+        self.send(self.client.post(&url).json(&body)).await?;
+        Ok(())
+    }
 
-        Ok(logs)
+    async fn reply_to_thread(&self, owner: &str, repo: &str, number: u64, thread_id: &str, body: &str) -> Result<PullRequestComment> {
+        let url = self.repo_url(owner, repo, &format!("pulls/{}/comments/{}/replies", number, thread_id));
+        let body = serde_json::json!({ "body": body });
+        let comment: GitHubReviewComment = self.send_json(self.client.post(&url).json(&body)).await?;
+        Ok(comment.into())
+    }
+
+    async fn resolve_thread(&self, owner: &str, repo: &str, number: u64, thread_id: &str, resolved: bool) -> Result<()> {
+        let id = self.review_thread_id(owner, repo, number, thread_id).await?;
+        let mutation = if resolved {
+            "mutation($id: ID!) { resolveReviewThread(input: { threadId: $id }) { thread { isResolved } } }"
+        } else {
+            "mutation($id: ID!) { unresolveReviewThread(input: { threadId: $id }) { thread { isResolved } } }"
+        };
+        self.graphql(mutation, serde_json::json!({ "id": id })).await?;
+        Ok(())
+    }
+}
+
+fn diff_side(side: DiffSide) -> &'static str {
+    match side {
+        DiffSide::Left => "LEFT",
+        DiffSide::Right => "RIGHT",
     }
 }
 
@@ -308,4 +737,10 @@ impl GitProvider for GitHubProvider {
 struct GitHubRef {
     #[serde(rename = "ref")]
     ref_name: String,
+    #[serde(default)]
+    sha: String,
 }
+
+#[cfg(test)]
+#[path = "github_tests.rs"]
+mod github_tests;
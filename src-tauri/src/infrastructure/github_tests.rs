@@ -0,0 +1,426 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::provider::{
+        CheckSummary, CreateReview, DiffSide, GitProvider, ListQuery, ListSort, ListState, MergeStatus, NewReviewComment,
+        ReviewEvent, ReviewState,
+    };
+    use crate::domain::FileStatus;
+    use crate::error::AppError;
+    use crate::infrastructure::github::GitHubProvider;
+    use crate::infrastructure::mock_server::{MockResponse, MockServer, RecordedRequest};
+    use serde_json::json;
+
+    fn pull(number: u64, merged_at: Option<&str>) -> serde_json::Value {
+        json!({
+            "id": 100 + number,
+            "number": number,
+            "title": format!("PR {}", number),
+            "body": null,
+            "state": if merged_at.is_some() { "closed" } else { "open" },
+            "merged_at": merged_at,
+            "user": { "login": "alice" },
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-02T00:00:00Z",
+            "html_url": format!("https://github.com/o/r/pull/{}", number),
+            "head": { "ref": "feature" },
+            "base": { "ref": "main" }
+        })
+    }
+
+    fn issue(number: u64, pull_request: Option<serde_json::Value>) -> serde_json::Value {
+        json!({
+            "id": 200 + number,
+            "number": number,
+            "title": format!("Issue {}", number),
+            "body": "Details",
+            "state": "open",
+            "user": { "login": "bob" },
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "html_url": format!("https://github.com/o/r/issues/{}", number),
+            "pull_request": pull_request
+        })
+    }
+
+    /// The mock server's own address, for links back into it
+    fn origin(request: &RecordedRequest) -> String {
+        format!("http://{}", request.header("host").unwrap())
+    }
+
+    fn server() -> MockServer {
+        MockServer::start(|request| {
+            let (path, query) = request.path.split_once('?').unwrap_or((&request.path, ""));
+            match path {
+                "/repos/o/r/pulls" if query.split('&').any(|param| param == "page=2") => MockResponse::json(200, json!([pull(2, None)])),
+                "/repos/o/r/pulls" => MockResponse::json(200, json!([pull(1, None), pull(3, Some("2024-01-03T00:00:00Z"))]))
+                    .with_header(
+                        "Link",
+                        &format!(r#"<{}/repos/o/r/pulls?state=open&page=2>; rel="next", <{0}/repos/o/r/pulls?state=open&page=2>; rel="last""#, origin(request)),
+                    ),
+                "/repos/o/r/issues" => MockResponse::json(200, json!([issue(4, None), issue(5, Some(json!({ "merged_at": null })))])),
+                "/search/issues" => MockResponse::json(
+                    200,
+                    json!({ "total_count": 1, "items": [issue(6, Some(json!({ "merged_at": "2024-01-03T00:00:00Z" })))] }),
+                ),
+                "/repos/o/r/actions/jobs/1/logs" => MockResponse::json(401, json!({ "message": "Bad credentials" })),
+                "/repos/o/r/actions/jobs/2/logs" => MockResponse::json(403, json!({ "message": "API rate limit exceeded for user" }))
+                    .with_header("X-RateLimit-Remaining", "0")
+                    .with_header("X-RateLimit-Reset", "1704067200"),
+                "/repos/o/r/actions/jobs/3/logs" => MockResponse::json(403, json!({ "message": "Resource not accessible by integration" })),
+                _ => MockResponse::json(404, json!({ "message": "Not Found" })),
+            }
+        })
+    }
+
+    #[test]
+    fn test_pull_requests_are_paged_through_the_link_header() {
+        let server = server();
+        let provider = GitHubProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let query = ListQuery { per_page: Some(2), ..Default::default() };
+        let page = runtime.block_on(provider.get_pr_list("o", "r", &query)).unwrap();
+        assert_eq!(page.items.iter().map(|pr| pr.state.as_str()).collect::<Vec<_>>(), ["open", "merged"]);
+        let cursor = page.next_cursor.unwrap();
+        assert_eq!(cursor, format!("{}/repos/o/r/pulls?state=open&page=2", server.url));
+
+        let query = ListQuery { cursor: Some(cursor), ..Default::default() };
+        let page = runtime.block_on(provider.get_pr_list("o", "r", &query)).unwrap();
+        assert_eq!((page.items[0].number, page.next_cursor), (2, None));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/repos/o/r/pulls?state=open&per_page=2&sort=created&direction=desc");
+        assert_eq!(requests[1].path, "/repos/o/r/pulls?state=open&page=2");
+
+        // Cursors only ever lead back to the same API
+        let query = ListQuery { cursor: Some("https://evil.test/repos/o/r/pulls".to_string()), ..Default::default() };
+        assert!(matches!(runtime.block_on(provider.get_pr_list("o", "r", &query)), Err(AppError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_filters_go_to_the_issues_endpoint_or_the_search_api() {
+        let server = server();
+        let provider = GitHubProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let query = ListQuery {
+            state: ListState::All,
+            author: Some("bob".to_string()),
+            label: Some("bug".to_string()),
+            sort: ListSort::Updated,
+            ascending: true,
+            ..Default::default()
+        };
+        let issues = runtime.block_on(provider.get_issue_list("o", "r", &query)).unwrap().items;
+        // Pull requests are left out
+        assert_eq!(issues.iter().map(|i| i.number).collect::<Vec<_>>(), [4]);
+
+        // The pulls endpoint can't filter by author, so this needs a search
+        let query = ListQuery { state: ListState::Merged, author: Some("bob".to_string()), ..Default::default() };
+        let prs = runtime.block_on(provider.get_pr_list("o", "r", &query)).unwrap().items;
+        assert_eq!((prs[0].number, prs[0].state.as_str(), prs[0].head_ref.as_str()), (6, "merged", ""));
+
+        let query = ListQuery { search: Some("crash on start".to_string()), ..Default::default() };
+        runtime.block_on(provider.get_issue_list("o", "r", &query)).unwrap();
+
+        let query = ListQuery { state: ListState::Merged, ..Default::default() };
+        assert!(runtime.block_on(provider.get_issue_list("o", "r", &query)).is_err());
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].path,
+            "/repos/o/r/issues?state=all&creator=bob&labels=bug&sort=updated&direction=asc"
+        );
+        assert_eq!(
+            requests[1].path,
+            "/search/issues?sort=created&order=desc&q=repo%3Ao%2Fr+is%3Apr+is%3Amerged+author%3Abob"
+        );
+        assert_eq!(
+            requests[2].path,
+            "/search/issues?sort=created&order=desc&q=repo%3Ao%2Fr+is%3Aissue+is%3Aopen+crash+on+start"
+        );
+        assert_eq!(requests.len(), 3);
+    }
+
+    #[test]
+    fn test_rate_limits_and_rejected_tokens_have_their_own_errors() {
+        let server = server();
+        let provider = GitHubProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        match runtime.block_on(provider.get_job_logs("o", "r", "1")) {
+            Err(AppError::AuthFailed(message)) => assert!(message.contains("Bad credentials")),
+            other => panic!("expected an auth error, got {:?}", other),
+        }
+
+        let error = runtime.block_on(provider.get_job_logs("o", "r", "2")).unwrap_err();
+        match &error {
+            AppError::RateLimited { service, reset_at } => {
+                assert_eq!((service.as_str(), reset_at.as_deref()), ("GitHub", Some("2024-01-01T00:00:00Z")));
+            }
+            other => panic!("expected a rate limit error, got {:?}", other),
+        }
+        assert_eq!(error.to_string(), "GitHub API rate limit exceeded, resets at 2024-01-01T00:00:00Z");
+
+        // A 403 without a rate limit is a permissions problem
+        assert!(matches!(runtime.block_on(provider.get_job_logs("o", "r", "3")), Err(AppError::AuthFailed(_))));
+        assert!(matches!(runtime.block_on(provider.get_job_logs("o", "r", "4")), Err(AppError::Http(_))));
+    }
+
+    fn review_server() -> MockServer {
+        MockServer::start(|request| {
+            let (path, query) = request.path.split_once('?').unwrap_or((&request.path, ""));
+            let comment = |id: u64, created_at: &str| {
+                json!({
+                    "id": id,
+                    "user": { "login": "carol" },
+                    "body": format!("Comment {}", id),
+                    "created_at": created_at,
+                    "updated_at": created_at,
+                    "html_url": format!("https://github.com/o/r/pull/7#comment-{}", id)
+                })
+            };
+            match path {
+                "/repos/o/r/pulls/7" => {
+                    let mut pr = pull(7, None);
+                    pr["draft"] = json!(false);
+                    pr["mergeable_state"] = json!("blocked");
+                    pr["commits"] = json!(2);
+                    pr["changed_files"] = json!(1);
+                    pr["head"] = json!({ "ref": "feature", "sha": "abc123" });
+                    pr["base"] = json!({ "ref": "main", "sha": "def456" });
+                    MockResponse::json(200, pr)
+                }
+                // Reviews come in two pages
+                "/repos/o/r/pulls/7/reviews" if query.contains("page=2") => MockResponse::json(
+                    200,
+                    json!([{ "id": 3, "user": { "login": "bob" }, "state": "APPROVED", "body": "", "submitted_at": "2024-01-03T00:00:00Z" }]),
+                ),
+                "/repos/o/r/pulls/7/reviews" => MockResponse::json(
+                    200,
+                    json!([
+                        { "id": 1, "user": { "login": "bob" }, "state": "CHANGES_REQUESTED", "body": "Needs tests", "submitted_at": "2024-01-02T00:00:00Z" },
+                        { "id": 2, "user": null, "state": "COMMENTED", "body": "", "submitted_at": "2024-01-02T12:00:00Z" }
+                    ]),
+                )
+                .with_header("Link", &format!(r#"<{}/repos/o/r/pulls/7/reviews?per_page=100&page=2>; rel="next""#, origin(request))),
+                // So do check runs, each page wrapped in an object
+                "/repos/o/r/commits/abc123/check-runs" if query.contains("page=2") => MockResponse::json(
+                    200,
+                    json!({ "total_count": 2, "check_runs": [
+                        { "id": 2, "name": "test", "status": "in_progress", "conclusion": null, "html_url": "https://ci/2" }
+                    ] }),
+                ),
+                "/repos/o/r/commits/abc123/check-runs" => MockResponse::json(
+                    200,
+                    json!({ "total_count": 2, "check_runs": [
+                        { "id": 1, "name": "build", "status": "completed", "conclusion": "success", "html_url": "https://ci/1" }
+                    ] }),
+                )
+                .with_header(
+                    "Link",
+                    &format!(r#"<{}/repos/o/r/commits/abc123/check-runs?per_page=100&page=2>; rel="next""#, origin(request)),
+                ),
+                "/repos/o/r/commits/abc123/status" => MockResponse::json(
+                    200,
+                    json!({ "statuses": [{ "id": 3, "context": "jenkins", "state": "failure", "target_url": null, "description": null }] }),
+                ),
+                "/repos/o/r/pulls/7/files" => MockResponse::json(
+                    200,
+                    json!([
+                        { "filename": "src/new.rs", "previous_filename": "src/old.rs", "status": "renamed", "additions": 1, "deletions": 1, "patch": "@@ -1 +1 @@\n-a\n+b" },
+                        { "filename": "logo.png", "status": "added", "additions": 0, "deletions": 0 }
+                    ]),
+                ),
+                "/repos/o/r/pulls/7/commits" => MockResponse::json(
+                    200,
+                    json!([
+                        { "sha": "c1", "commit": { "message": "Start", "author": { "name": "Alice A", "date": "2024-01-01T00:00:00Z" } }, "author": { "login": "alice" } },
+                        { "sha": "c2", "commit": { "message": "Finish", "author": { "name": "Unlinked", "date": "2024-01-02T00:00:00Z" } }, "author": null }
+                    ]),
+                ),
+                "/repos/o/r/issues/7/comments" => MockResponse::json(200, json!([comment(10, "2024-01-02T00:00:00Z")])),
+                "/repos/o/r/pulls/7/comments" => {
+                    let mut root = comment(20, "2024-01-01T00:00:00Z");
+                    root["path"] = json!("src/new.rs");
+                    root["line"] = json!(1);
+                    root["side"] = json!("RIGHT");
+                    root["commit_id"] = json!("abc123");
+                    let mut reply = comment(21, "2024-01-03T00:00:00Z");
+                    reply["in_reply_to_id"] = json!(20);
+                    reply["path"] = json!("src/new.rs");
+                    reply["line"] = json!(null);
+                    reply["side"] = json!("LEFT");
+                    MockResponse::json(200, json!([root, reply]))
+                }
+                _ => MockResponse::json(404, json!({ "message": "Not Found" })),
+            }
+        })
+    }
+
+    #[test]
+    fn test_pull_request_details_combine_reviews_checks_and_mergeability() {
+        let server = review_server();
+        let provider = GitHubProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let details = runtime.block_on(provider.get_pr_details("o", "r", 7)).unwrap();
+        assert_eq!(details.pull_request.number, 7);
+        assert_eq!((details.head_sha.as_str(), details.base_sha.as_deref()), ("abc123", Some("def456")));
+        assert_eq!(details.merge_status, MergeStatus::Blocked);
+        assert_eq!((details.commits, details.changed_files), (Some(2), Some(1)));
+
+        // bob's approval on the second page replaces his change request
+        assert_eq!(details.reviews.len(), 3);
+        assert_eq!((details.reviews[1].author.as_str(), details.reviews[1].body.as_deref()), ("", None));
+        assert_eq!(details.review_decision, Some(ReviewState::Approved));
+
+        // Check runs and legacy statuses both count
+        assert_eq!(details.checks, CheckSummary { total: 3, passed: 1, failed: 1, pending: 1, other: 0 });
+        assert!(server.requests().iter().any(|r| r.path == "/repos/o/r/commits/abc123/check-runs?per_page=100"));
+    }
+
+    #[test]
+    fn test_pull_request_files_commits_and_comments() {
+        let server = review_server();
+        let provider = GitHubProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let files = runtime.block_on(provider.get_pr_files("o", "r", 7)).unwrap();
+        assert_eq!((files[0].status.clone(), files[0].old_path.as_deref()), (FileStatus::Renamed, Some("src/old.rs")));
+        assert_eq!(files[0].patch.as_deref(), Some("@@ -1 +1 @@\n-a\n+b"));
+        assert_eq!((files[1].status.clone(), files[1].patch.as_deref()), (FileStatus::Added, None));
+
+        let commits = runtime.block_on(provider.get_pr_commits("o", "r", 7)).unwrap();
+        assert_eq!(
+            commits.iter().map(|c| (c.sha.as_str(), c.author.as_str())).collect::<Vec<_>>(),
+            [("c1", "alice"), ("c2", "Unlinked")]
+        );
+
+        let comments = runtime.block_on(provider.get_pr_comments("o", "r", 7)).unwrap();
+        assert_eq!(comments.iter().map(|c| c.id.as_str()).collect::<Vec<_>>(), ["20", "10", "21"]);
+        let position = comments[0].position.as_ref().unwrap();
+        assert_eq!((position.path.as_str(), position.line, position.side), ("src/new.rs", Some(1), DiffSide::Right));
+        assert_eq!(position.commit_sha.as_deref(), Some("abc123"));
+        // General comments have no thread; replies join their root's
+        assert_eq!((comments[1].thread_id.as_deref(), comments[1].position.is_none()), (None, true));
+        assert_eq!(comments[2].thread_id.as_deref(), Some("20"));
+        assert_eq!(comments[2].position.as_ref().unwrap().line, None);
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/repos/o/r/pulls/7/files?per_page=100");
+    }
+
+    fn posting_server() -> MockServer {
+        MockServer::start(|request| match request.path.as_str() {
+            "/repos/o/r/pulls/7/reviews" => MockResponse::json(200, json!({ "id": 1, "state": "PENDING" })),
+            "/repos/o/r/pulls/7/comments/20/replies" => MockResponse::json(
+                201,
+                json!({
+                    "id": 22,
+                    "user": { "login": "me" },
+                    "body": "Done",
+                    "created_at": "2024-01-03T00:00:00Z",
+                    "updated_at": "2024-01-03T00:00:00Z",
+                    "html_url": "https://github.com/o/r/pull/7#discussion_r22",
+                    "in_reply_to_id": 20,
+                    "path": "src/new.rs",
+                    "line": 1,
+                    "side": "RIGHT",
+                    "commit_id": "abc123"
+                }),
+            ),
+            "/graphql" if request.json()["query"].as_str().unwrap().contains("reviewThreads") => {
+                let cursor = request.json()["variables"]["cursor"].clone();
+                let (nodes, has_next) = if cursor.is_null() {
+                    (json!([{ "id": "T_1", "comments": { "nodes": [{ "databaseId": 10 }] } }]), true)
+                } else {
+                    (json!([{ "id": "T_2", "comments": { "nodes": [{ "databaseId": 20 }] } }]), false)
+                };
+                MockResponse::json(200, json!({ "data": { "repository": { "pullRequest": { "reviewThreads": {
+                    "nodes": nodes,
+                    "pageInfo": { "hasNextPage": has_next, "endCursor": "c1" }
+                } } } } }))
+            }
+            "/graphql" => MockResponse::json(200, json!({ "data": { "resolveReviewThread": { "thread": { "isResolved": true } } } })),
+            _ => MockResponse::json(404, json!({ "message": "Not Found" })),
+        })
+    }
+
+    #[test]
+    fn test_reviews_are_posted_with_inline_comments() {
+        let server = posting_server();
+        let provider = GitHubProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let review = CreateReview {
+            body: Some("Looks good".to_string()),
+            event: ReviewEvent::RequestChanges,
+            comments: vec![
+                NewReviewComment {
+                    path: "src/new.rs".to_string(),
+                    old_path: None,
+                    line: 4,
+                    side: DiffSide::Right,
+                    start_line: Some(2),
+                    body: "Split this".to_string(),
+                },
+                NewReviewComment {
+                    path: "src/new.rs".to_string(),
+                    old_path: None,
+                    line: 1,
+                    side: DiffSide::Left,
+                    start_line: None,
+                    body: "Why remove this?".to_string(),
+                },
+            ],
+            commit_sha: Some("abc123".to_string()),
+        };
+        runtime.block_on(provider.create_review("o", "r", 7, review)).unwrap();
+        // A draft is left pending by leaving out the event
+        let draft = CreateReview { body: Some("AI review".to_string()), event: ReviewEvent::Draft, ..Default::default() };
+        runtime.block_on(provider.create_review("o", "r", 7, draft)).unwrap();
+
+        let requests = server.requests();
+        assert_eq!((requests[0].method.as_str(), requests[0].path.as_str()), ("POST", "/repos/o/r/pulls/7/reviews"));
+        assert_eq!(
+            requests[0].json(),
+            json!({
+                "body": "Looks good",
+                "event": "REQUEST_CHANGES",
+                "commit_id": "abc123",
+                "comments": [
+                    { "path": "src/new.rs", "line": 4, "side": "RIGHT", "start_line": 2, "start_side": "RIGHT", "body": "Split this" },
+                    { "path": "src/new.rs", "line": 1, "side": "LEFT", "body": "Why remove this?" }
+                ]
+            })
+        );
+        assert_eq!(requests[1].json(), json!({ "body": "AI review", "comments": [] }));
+    }
+
+    #[test]
+    fn test_threads_are_replied_to_and_resolved_by_their_first_comment() {
+        let server = posting_server();
+        let provider = GitHubProvider::new("secret".to_string(), Some(server.url.clone()));
+        let enterprise = GitHubProvider::new("secret".to_string(), Some(format!("{}/v3", server.url)));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let reply = runtime.block_on(provider.reply_to_thread("o", "r", 7, "20", "Done")).unwrap();
+        assert_eq!((reply.id.as_str(), reply.thread_id.as_deref()), ("22", Some("20")));
+
+        // Enterprise servers answer GraphQL next to /v3
+        runtime.block_on(enterprise.resolve_thread("o", "r", 7, "20", true)).unwrap();
+        assert!(matches!(
+            runtime.block_on(provider.resolve_thread("o", "r", 7, "99", true)),
+            Err(AppError::InvalidInput(_))
+        ));
+
+        let requests = server.requests();
+        assert_eq!(requests[0].json(), json!({ "body": "Done" }));
+        assert_eq!(requests[1].path, "/graphql");
+        assert_eq!(requests[2].json()["variables"]["cursor"], "c1");
+        let mutation = requests[3].json();
+        assert!(mutation["query"].as_str().unwrap().contains("resolveReviewThread"));
+        assert_eq!(mutation["variables"], json!({ "id": "T_2" }));
+    }
+}
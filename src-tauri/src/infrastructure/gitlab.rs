@@ -1,7 +1,14 @@
-use crate::domain::provider::{GitProvider, PullRequest, Issue, CreatePullRequest, CreateIssue};
+use crate::domain::provider::{
+    review_decision, CheckSummary, CommentPosition, CreateIssue, CreatePullRequest, CreateReview, DiffSide, GitProvider,
+    Issue, ListPage, ListQuery, ListSort, ListState, MergeStatus, NewReviewComment, PullRequest, PullRequestComment,
+    PullRequestCommit, PullRequestDetails, PullRequestFile, PullRequestReview, ReviewEvent, ReviewState,
+};
+use crate::domain::FileStatus;
 use crate::error::{AppError, Result};
+use crate::infrastructure::http;
 use async_trait::async_trait;
-use reqwest::{Client, header};
+use reqwest::{Client, RequestBuilder, header};
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 
 pub struct GitLabProvider {
@@ -23,7 +30,7 @@ impl GitLabProvider {
             .default_headers(headers)
             .build()
             .unwrap();
-        
+
         // Default to gitlab.com if not provided
         let base_url = base_url.unwrap_or_else(|| "https://gitlab.com".to_string());
         // Ensure no trailing slash
@@ -38,6 +45,92 @@ impl GitLabProvider {
         let full_path = format!("{}/{}", owner, repo);
         full_path.replace("/", "%2F")
     }
+
+    fn project_url(&self, owner: &str, repo: &str, rest: &str) -> String {
+        format!("{}/api/v4/projects/{}/{}", self.base_url, self.project_path(owner, repo), rest)
+    }
+
+    /// Add `notes` as draft notes and, with `publish`, publish them one by
+    /// one so the user's other drafts stay drafts. `pending` holds the ids of
+    /// the drafts created here that are not published yet
+    async fn add_review_notes(
+        &self,
+        mr_url: &str,
+        notes: &[serde_json::Value],
+        publish: bool,
+        pending: &mut Vec<u64>,
+    ) -> Result<()> {
+        for note in notes {
+            let draft: GitLabDraftNote = self.send_json(self.client.post(format!("{}/draft_notes", mr_url)).json(note)).await?;
+            pending.push(draft.id);
+        }
+        while publish && !pending.is_empty() {
+            self.send(self.client.put(format!("{}/draft_notes/{}/publish", mr_url, pending[0]))).await?;
+            pending.remove(0);
+        }
+        Ok(())
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response> {
+        let res = request.send().await.map_err(|e| AppError::Http(e.to_string()))?;
+        http::check("GitLab", res).await
+    }
+
+    async fn send_json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        self.send(request).await?.json().await.map_err(|e| AppError::Http(e.to_string()))
+    }
+
+    /// Fetch one page of a list, following the query's cursor if it has one
+    async fn list<T: DeserializeOwned>(&self, url: String, query: &ListQuery) -> Result<ListPage<T>> {
+        let request = match &query.cursor {
+            Some(cursor) => self.client.get(http::cursor_url(&format!("{}/api/v4", self.base_url), cursor)?),
+            None => self.client.get(url).query(&list_params(query)),
+        };
+        let res = self.send(request).await?;
+        let next_cursor = http::next_page(res.url(), res.headers());
+        let items = res.json().await.map_err(|e| AppError::Http(e.to_string()))?;
+        Ok(ListPage { items, next_cursor })
+    }
+}
+
+fn list_params(query: &ListQuery) -> Vec<(&'static str, String)> {
+    let state = match query.state {
+        ListState::Open => "opened",
+        ListState::Closed => "closed",
+        ListState::Merged => "merged",
+        ListState::All => "all",
+    };
+    let mut params = vec![
+        ("state", state.to_string()),
+        ("order_by", match query.sort {
+            ListSort::Created => "created_at",
+            ListSort::Updated => "updated_at",
+        }.to_string()),
+        ("sort", if query.ascending { "asc" } else { "desc" }.to_string()),
+    ];
+    let filters = [
+        ("author_username", &query.author),
+        ("labels", &query.label),
+        ("assignee_username", &query.assignee),
+        ("search", &query.search),
+    ];
+    for (name, value) in filters {
+        if let Some(value) = value {
+            params.push((name, value.clone()));
+        }
+    }
+    if let Some(page) = query.page {
+        params.push(("page", page.to_string()));
+    }
+    if let Some(per_page) = query.per_page {
+        params.push(("per_page", per_page.to_string()));
+    }
+    params
+}
+
+/// GitLab says "opened" where the other services say "open"
+fn state(state: String) -> String {
+    if state == "opened" { "open".to_string() } else { state }
 }
 
 #[derive(Deserialize)]
@@ -59,6 +152,24 @@ struct GitLabMR {
     target_branch: String,
 }
 
+impl From<GitLabMR> for PullRequest {
+    fn from(mr: GitLabMR) -> Self {
+        PullRequest {
+            id: mr.iid.to_string(), // we use iid (internal ID) which is the visible number
+            number: mr.iid,
+            title: mr.title,
+            body: mr.description,
+            state: state(mr.state),
+            author: mr.author.username, // or name?
+            created_at: mr.created_at,
+            updated_at: mr.updated_at,
+            url: mr.web_url,
+            head_ref: mr.source_branch,
+            base_ref: mr.target_branch,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 struct GitLabIssue {
     iid: u64,
@@ -71,71 +182,266 @@ struct GitLabIssue {
     web_url: String,
 }
 
-#[async_trait]
-impl GitProvider for GitLabProvider {
-    async fn get_pr_list(&self, owner: &str, repo: &str) -> Result<Vec<PullRequest>> {
-        let project_path = self.project_path(owner, repo);
-        let url = format!("{}/api/v4/projects/{}/merge_requests?state=opened", self.base_url, project_path);
-        
-        let res = self.client.get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+impl From<GitLabIssue> for Issue {
+    fn from(issue: GitLabIssue) -> Self {
+        Issue {
+            id: issue.iid.to_string(),
+            number: issue.iid,
+            title: issue.title,
+            body: issue.description,
+            state: state(issue.state),
+            author: issue.author.username,
+            created_at: issue.created_at,
+            updated_at: issue.updated_at,
+            url: issue.web_url,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GitLabDiffRefs {
+    base_sha: Option<String>,
+    head_sha: String,
+    start_sha: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitLabMRDetails {
+    #[serde(flatten)]
+    mr: GitLabMR,
+    #[serde(default)]
+    draft: bool,
+    diff_refs: Option<GitLabDiffRefs>,
+    sha: Option<String>,
+    /// GitLab 15.6 and later
+    detailed_merge_status: Option<String>,
+    merge_status: Option<String>,
+    /// A number as a string, or "1000+"
+    changes_count: Option<String>,
+}
 
-        if !res.status().is_success() {
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitLab API Error: {}", res.status()))));
+impl GitLabMRDetails {
+    fn merge_status(&self) -> MergeStatus {
+        if self.mr.state != "opened" {
+            return MergeStatus::Unknown;
+        }
+        match (self.detailed_merge_status.as_deref(), self.merge_status.as_deref()) {
+            (Some("mergeable"), _) => MergeStatus::Clean,
+            (Some("conflict"), _) => MergeStatus::Conflicts,
+            (Some("need_rebase"), _) => MergeStatus::Behind,
+            (Some("draft_status"), _) => MergeStatus::Draft,
+            (Some("unchecked" | "checking" | "preparing" | "approvals_syncing"), _) => MergeStatus::Unknown,
+            // ci_must_pass, not_approved, discussions_not_resolved, blocked_status...
+            (Some(_), _) => MergeStatus::Blocked,
+            (None, Some("can_be_merged")) => MergeStatus::Clean,
+            (None, Some("cannot_be_merged")) => MergeStatus::Conflicts,
+            (None, _) => MergeStatus::Unknown,
         }
+    }
+}
 
-        let mrs: Vec<GitLabMR> = res.json().await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+#[derive(Deserialize)]
+struct GitLabApprover {
+    user: GitLabUser,
+}
 
-        Ok(mrs.into_iter().map(|mr| PullRequest {
-            id: mr.iid.to_string(), // we use iid (internal ID) which is the visible number
-            number: mr.iid,
-            title: mr.title,
-            body: mr.description,
-            state: mr.state,
-            author: mr.author.username, // or name?
-            created_at: mr.created_at,
-            updated_at: mr.updated_at,
-            url: mr.web_url,
-            head_ref: mr.source_branch,
-            base_ref: mr.target_branch,
-        }).collect())
+#[derive(Deserialize)]
+struct GitLabApprovals {
+    /// Whether the project's approval rules are met
+    approved: bool,
+    #[serde(default)]
+    approved_by: Vec<GitLabApprover>,
+}
+
+#[derive(Deserialize)]
+struct GitLabDiff {
+    old_path: String,
+    new_path: String,
+    #[serde(default)]
+    new_file: bool,
+    #[serde(default)]
+    renamed_file: bool,
+    #[serde(default)]
+    deleted_file: bool,
+    /// Empty for binary files and collapsed large diffs
+    #[serde(default)]
+    diff: String,
+}
+
+impl From<GitLabDiff> for PullRequestFile {
+    fn from(diff: GitLabDiff) -> Self {
+        let status = if diff.new_file {
+            FileStatus::Added
+        } else if diff.deleted_file {
+            FileStatus::Deleted
+        } else if diff.renamed_file {
+            FileStatus::Renamed
+        } else {
+            FileStatus::Modified
+        };
+        // GitLab doesn't count lines, so count them from the hunks
+        let lines = || diff.diff.lines().filter(|line| !line.starts_with("+++") && !line.starts_with("---"));
+        let additions = lines().filter(|line| line.starts_with('+')).count() as u64;
+        let deletions = lines().filter(|line| line.starts_with('-')).count() as u64;
+        PullRequestFile {
+            old_path: diff.renamed_file.then(|| diff.old_path.clone()),
+            path: diff.new_path,
+            status,
+            additions,
+            deletions,
+            patch: Some(diff.diff).filter(|patch| !patch.is_empty()),
+        }
+    }
+}
+
+/// Old and new line numbers of the diff line at `line` on `side`. GitLab
+/// needs both to anchor a comment on an unchanged line; lines outside the
+/// hunks are taken to be on `side` only
+fn line_numbers(patch: &str, side: DiffSide, line: u32) -> (Option<u32>, Option<u32>) {
+    let (mut old, mut new) = (0, 0);
+    for text in patch.lines() {
+        if let Some(header) = text.strip_prefix("@@ ") {
+            // @@ -old,count +new,count @@
+            let start = |prefix: char| {
+                header.split_whitespace()
+                    .find_map(|range| range.strip_prefix(prefix))
+                    .and_then(|range| range.split(',').next()?.parse().ok())
+                    .unwrap_or(0)
+            };
+            (old, new) = (start('-'), start('+'));
+            continue;
+        }
+        let numbers = match text.chars().next() {
+            Some('+') => (None, Some(new)),
+            Some('-') => (Some(old), None),
+            // "\ No newline at end of file"
+            Some('\\') => continue,
+            _ => (Some(old), Some(new)),
+        };
+        let found = match side {
+            DiffSide::Left => numbers.0 == Some(line),
+            DiffSide::Right => numbers.1 == Some(line),
+        };
+        if found {
+            return numbers;
+        }
+        old += u32::from(numbers.0.is_some());
+        new += u32::from(numbers.1.is_some());
+    }
+    match side {
+        DiffSide::Left => (Some(line), None),
+        DiffSide::Right => (None, Some(line)),
     }
+}
 
-    async fn get_issue_list(&self, owner: &str, repo: &str) -> Result<Vec<Issue>> {
-        let project_path = self.project_path(owner, repo);
-        let url = format!("{}/api/v4/projects/{}/issues?state=opened", self.base_url, project_path);
+/// The `position` of a draft note on a line of the merge request's diff
+fn position(comment: &NewReviewComment, refs: &GitLabDiffRefs, diffs: &[GitLabDiff]) -> serde_json::Value {
+    let diff = diffs.iter().find(|diff| diff.new_path == comment.path);
+    let old_path = comment.old_path.clone()
+        .or_else(|| diff.map(|diff| diff.old_path.clone()))
+        .unwrap_or_else(|| comment.path.clone());
+    let (old_line, new_line) = line_numbers(diff.map_or("", |diff| &diff.diff), comment.side, comment.line);
+    serde_json::json!({
+        "position_type": "text",
+        "base_sha": refs.base_sha,
+        "start_sha": refs.start_sha.as_ref().or(refs.base_sha.as_ref()),
+        "head_sha": refs.head_sha,
+        "old_path": old_path,
+        "new_path": comment.path,
+        "old_line": old_line,
+        "new_line": new_line,
+    })
+}
 
-        let res = self.client.get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+#[derive(Deserialize)]
+struct GitLabDraftNote {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct GitLabCommit {
+    id: String,
+    message: String,
+    author_name: String,
+    authored_date: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabPosition {
+    new_path: Option<String>,
+    old_path: Option<String>,
+    new_line: Option<u32>,
+    old_line: Option<u32>,
+    head_sha: Option<String>,
+}
+
+impl From<GitLabPosition> for CommentPosition {
+    /// Lines that exist on the head side (added or unchanged) are anchored
+    /// there; removed lines only have an old line number
+    fn from(position: GitLabPosition) -> Self {
+        let (path, line, side) = match position.new_line {
+            Some(line) => (position.new_path.or(position.old_path), Some(line), DiffSide::Right),
+            None => (position.old_path.or(position.new_path), position.old_line, DiffSide::Left),
+        };
+        CommentPosition { path: path.unwrap_or_default(), line, side, commit_sha: position.head_sha }
+    }
+}
 
-         if !res.status().is_success() {
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitLab API Error: {}", res.status()))));
+#[derive(Deserialize)]
+struct GitLabNote {
+    id: u64,
+    body: String,
+    author: GitLabUser,
+    created_at: String,
+    updated_at: String,
+    /// Notes GitLab writes itself ("added 2 commits")
+    #[serde(default)]
+    system: bool,
+    #[serde(default)]
+    resolvable: bool,
+    resolved: Option<bool>,
+    position: Option<GitLabPosition>,
+}
+
+impl GitLabNote {
+    fn into_comment(self, thread_id: &str) -> PullRequestComment {
+        PullRequestComment {
+            id: self.id.to_string(),
+            thread_id: Some(thread_id.to_string()),
+            author: self.author.username,
+            body: self.body,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            url: None,
+            position: self.position.map(CommentPosition::from),
+            resolved: self.resolvable.then(|| self.resolved.unwrap_or(false)),
         }
+    }
+}
 
-        let issues: Vec<GitLabIssue> = res.json().await
-             .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+#[derive(Deserialize)]
+struct GitLabDiscussion {
+    id: String,
+    notes: Vec<GitLabNote>,
+}
 
-        Ok(issues.into_iter().map(|issue| Issue {
-            id: issue.iid.to_string(),
-            number: issue.iid,
-            title: issue.title,
-            body: issue.description,
-            state: issue.state,
-            author: issue.author.username,
-            created_at: issue.created_at,
-            updated_at: issue.updated_at,
-            url: issue.web_url,
-        }).collect())
+#[async_trait]
+impl GitProvider for GitLabProvider {
+    async fn get_pr_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<PullRequest>> {
+        let page: ListPage<GitLabMR> = self.list(self.project_url(owner, repo, "merge_requests"), query).await?;
+        Ok(ListPage { items: page.items.into_iter().map(PullRequest::from).collect(), next_cursor: page.next_cursor })
+    }
+
+    async fn get_issue_list(&self, owner: &str, repo: &str, query: &ListQuery) -> Result<ListPage<Issue>> {
+        if query.state == ListState::Merged {
+            return Err(AppError::InvalidInput("Only merge requests can be merged".to_string()));
+        }
+        let page: ListPage<GitLabIssue> = self.list(self.project_url(owner, repo, "issues"), query).await?;
+        Ok(ListPage { items: page.items.into_iter().map(Issue::from).collect(), next_cursor: page.next_cursor })
     }
 
     async fn create_pr(&self, owner: &str, repo: &str, pr: CreatePullRequest) -> Result<PullRequest> {
-        let project_path = self.project_path(owner, repo);
-        let url = format!("{}/api/v4/projects/{}/merge_requests", self.base_url, project_path);
+        let url = self.project_url(owner, repo, "merge_requests");
 
         // GitLab API:
         // source_branch, target_branch, title, description
@@ -146,83 +452,24 @@ impl GitProvider for GitLabProvider {
             "description": pr.body
         });
 
-        let res = self.client.post(&url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-
-        if !res.status().is_success() {
-            let error_text = res.text().await.unwrap_or_default();
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitLab API Error: {}", error_text))));
-        }
-
-        let mr: GitLabMR = res.json().await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-
-        Ok(PullRequest {
-            id: mr.iid.to_string(),
-            number: mr.iid,
-            title: mr.title,
-            body: mr.description,
-            state: mr.state,
-            author: mr.author.username,
-            created_at: mr.created_at,
-            updated_at: mr.updated_at,
-            url: mr.web_url,
-            head_ref: mr.source_branch,
-            base_ref: mr.target_branch,
-        })
+        let mr: GitLabMR = self.send_json(self.client.post(&url).json(&body)).await?;
+        Ok(mr.into())
     }
 
     async fn create_issue(&self, owner: &str, repo: &str, issue: CreateIssue) -> Result<Issue> {
-        let project_path = self.project_path(owner, repo);
-        let url = format!("{}/api/v4/projects/{}/issues", self.base_url, project_path);
+        let url = self.project_url(owner, repo, "issues");
 
         let body = serde_json::json!({
             "title": issue.title,
             "description": issue.body
         });
 
-        let res = self.client.post(&url)
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-
-        if !res.status().is_success() {
-             let error_text = res.text().await.unwrap_or_default();
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitLab API Error: {}", error_text))));
-        }
-
-        let issue: GitLabIssue = res.json().await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-
-        Ok(Issue {
-            id: issue.iid.to_string(),
-            number: issue.iid,
-            title: issue.title,
-            body: issue.description,
-            state: issue.state,
-            author: issue.author.username,
-            created_at: issue.created_at,
-            updated_at: issue.updated_at,
-            url: issue.web_url,
-        })
+        let issue: GitLabIssue = self.send_json(self.client.post(&url).json(&body)).await?;
+        Ok(issue.into())
     }
 
     async fn get_commit_status(&self, owner: &str, repo: &str, sha: &str) -> Result<Vec<crate::domain::provider::CommitStatus>> {
-        let project_path = self.project_path(owner, repo);
-        let url = format!("{}/api/v4/projects/{}/repository/commits/{}/statuses", self.base_url, project_path, sha);
-        
-        let res = self.client.get(&url)
-            .send()
-            .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
-
-        if !res.status().is_success() {
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitLab API Error: {}", res.status()))));
-        }
+        let url = self.project_url(owner, repo, &format!("repository/commits/{}/statuses", sha));
 
         #[derive(Deserialize)]
         struct GitLabStatus {
@@ -233,8 +480,7 @@ impl GitProvider for GitLabProvider {
             description: Option<String>,
         }
 
-        let statuses: Vec<GitLabStatus> = res.json().await
-             .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+        let statuses: Vec<GitLabStatus> = self.send_json(self.client.get(&url)).await?;
 
         Ok(statuses.into_iter().map(|s| {
             crate::domain::provider::CommitStatus {
@@ -248,21 +494,142 @@ impl GitProvider for GitLabProvider {
     }
 
     async fn get_job_logs(&self, owner: &str, repo: &str, job_id: &str) -> Result<String> {
-        let project_path = self.project_path(owner, repo);
-        let url = format!("{}/api/v4/projects/{}/jobs/{}/trace", self.base_url, project_path, job_id);
-        
-        let res = self.client.get(&url)
-            .send()
+        let url = self.project_url(owner, repo, &format!("jobs/{}/trace", job_id));
+        self.send(self.client.get(&url)).await?
+            .text()
             .await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+            .map_err(|e| AppError::Http(e.to_string()))
+    }
 
-        if !res.status().is_success() {
-             return Err(AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, format!("GitLab API Error: {}", res.status()))));
+    async fn get_pr_details(&self, owner: &str, repo: &str, number: u64) -> Result<PullRequestDetails> {
+        let url = self.project_url(owner, repo, &format!("merge_requests/{}", number));
+        let details: GitLabMRDetails = self.send_json(self.client.get(&url)).await?;
+        let url = self.project_url(owner, repo, &format!("merge_requests/{}/approvals", number));
+        let approvals: GitLabApprovals = self.send_json(self.client.get(&url)).await?;
+
+        // Only approvals are recorded as reviews; comments are discussions
+        let reviews: Vec<PullRequestReview> = approvals.approved_by.into_iter().map(|approver| PullRequestReview {
+            id: format!("approval-{}", approver.user.username),
+            author: approver.user.username,
+            state: ReviewState::Approved,
+            body: None,
+            submitted_at: None,
+        }).collect();
+        // Approvals only decide the review once the approval rules are met
+        let decision = if approvals.approved { review_decision(&reviews) } else { None };
+
+        let head_sha = details.diff_refs.as_ref().map(|refs| refs.head_sha.clone())
+            .or_else(|| details.sha.clone())
+            .unwrap_or_default();
+        let statuses = if head_sha.is_empty() { Vec::new() } else { self.get_commit_status(owner, repo, &head_sha).await? };
+        Ok(PullRequestDetails {
+            draft: details.draft,
+            base_sha: details.diff_refs.as_ref().and_then(|refs| refs.base_sha.clone()),
+            head_sha,
+            merge_status: details.merge_status(),
+            review_decision: decision,
+            reviews,
+            checks: CheckSummary::from_statuses(&statuses),
+            commits: None,
+            changed_files: details.changes_count.as_deref().and_then(|count| count.parse().ok()),
+            pull_request: details.mr.into(),
+        })
+    }
+
+    async fn get_pr_files(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<PullRequestFile>> {
+        let url = self.project_url(owner, repo, &format!("merge_requests/{}/diffs?per_page=100", number));
+        let diffs: Vec<GitLabDiff> = http::fetch_all(&self.client, "GitLab", &url).await?;
+        Ok(diffs.into_iter().map(PullRequestFile::from).collect())
+    }
+
+    async fn get_pr_commits(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<PullRequestCommit>> {
+        let url = self.project_url(owner, repo, &format!("merge_requests/{}/commits?per_page=100", number));
+        let commits: Vec<GitLabCommit> = http::fetch_all(&self.client, "GitLab", &url).await?;
+        // Listed newest first
+        Ok(commits.into_iter().rev().map(|commit| PullRequestCommit {
+            sha: commit.id,
+            message: commit.message,
+            author: commit.author_name,
+            authored_at: commit.authored_date,
+        }).collect())
+    }
+
+    async fn get_pr_comments(&self, owner: &str, repo: &str, number: u64) -> Result<Vec<PullRequestComment>> {
+        let url = self.project_url(owner, repo, &format!("merge_requests/{}/discussions?per_page=100", number));
+        let discussions: Vec<GitLabDiscussion> = http::fetch_all(&self.client, "GitLab", &url).await?;
+
+        let mut comments = Vec::new();
+        for discussion in discussions {
+            for note in discussion.notes.into_iter().filter(|note| !note.system) {
+                comments.push(note.into_comment(&discussion.id));
+            }
+        }
+        comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(comments)
+    }
+
+    /// The comments are added as draft notes and published together, which
+    /// is how GitLab's own review UI submits them
+    async fn create_review(&self, owner: &str, repo: &str, number: u64, review: CreateReview) -> Result<()> {
+        if review.event == ReviewEvent::RequestChanges {
+            return Err(AppError::InvalidInput(
+                "GitLab's API can't request changes; comment without approving instead".to_string(),
+            ));
+        }
+        // A multi-line position needs GitLab's line codes, which we can't build
+        if review.comments.iter().any(|comment| comment.start_line.is_some_and(|start| start != comment.line)) {
+            return Err(AppError::InvalidInput(
+                "GitLab review comments can only be anchored to a single line".to_string(),
+            ));
+        }
+        let mr_url = self.project_url(owner, repo, &format!("merge_requests/{}", number));
+
+        let mut notes = Vec::new();
+        if let Some(body) = review.body.filter(|body| !body.trim().is_empty()) {
+            notes.push(serde_json::json!({ "note": body }));
+        }
+        if !review.comments.is_empty() {
+            let details: GitLabMRDetails = self.send_json(self.client.get(&mr_url)).await?;
+            let mut refs = details.diff_refs
+                .ok_or_else(|| AppError::Http("GitLab returned no diff refs for the merge request".to_string()))?;
+            if let Some(sha) = review.commit_sha {
+                refs.head_sha = sha;
+            }
+            let url = format!("{}/diffs?per_page=100", mr_url);
+            let diffs: Vec<GitLabDiff> = http::fetch_all(&self.client, "GitLab", &url).await?;
+            for comment in &review.comments {
+                notes.push(serde_json::json!({ "note": comment.body, "position": position(comment, &refs, &diffs) }));
+            }
         }
 
-        let logs = res.text().await
-            .map_err(|e| AppError::Io(std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))?;
+        let mut pending = Vec::new();
+        let publish = review.event != ReviewEvent::Draft;
+        if let Err(err) = self.add_review_notes(&mr_url, &notes, publish, &mut pending).await {
+            // Don't leave part of the review behind as drafts
+            for id in pending {
+                let _ = self.send(self.client.delete(format!("{}/draft_notes/{}", mr_url, id))).await;
+            }
+            return Err(err);
+        }
+        if review.event == ReviewEvent::Approve {
+            self.send(self.client.post(format!("{}/approve", mr_url))).await?;
+        }
+        Ok(())
+    }
 
-        Ok(logs)
+    async fn reply_to_thread(&self, owner: &str, repo: &str, number: u64, thread_id: &str, body: &str) -> Result<PullRequestComment> {
+        let url = self.project_url(owner, repo, &format!("merge_requests/{}/discussions/{}/notes", number, thread_id));
+        let note: GitLabNote = self.send_json(self.client.post(&url).json(&serde_json::json!({ "body": body }))).await?;
+        Ok(note.into_comment(thread_id))
+    }
+
+    async fn resolve_thread(&self, owner: &str, repo: &str, number: u64, thread_id: &str, resolved: bool) -> Result<()> {
+        let url = self.project_url(owner, repo, &format!("merge_requests/{}/discussions/{}", number, thread_id));
+        self.send(self.client.put(&url).query(&[("resolved", resolved)])).await?;
+        Ok(())
     }
 }
+
+#[cfg(test)]
+#[path = "gitlab_tests.rs"]
+mod gitlab_tests;
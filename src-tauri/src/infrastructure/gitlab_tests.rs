@@ -0,0 +1,354 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::provider::{
+        CheckSummary, CreateReview, DiffSide, GitProvider, ListQuery, ListSort, ListState, MergeStatus, NewReviewComment,
+        ReviewEvent, ReviewState,
+    };
+    use crate::domain::FileStatus;
+    use crate::error::AppError;
+    use crate::infrastructure::gitlab::GitLabProvider;
+    use crate::infrastructure::mock_server::{MockResponse, MockServer};
+    use serde_json::json;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    fn merge_request(iid: u64, state: &str) -> serde_json::Value {
+        json!({
+            "iid": iid,
+            "title": format!("MR {}", iid),
+            "description": null,
+            "state": state,
+            "author": { "username": "alice" },
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-02T00:00:00Z",
+            "web_url": format!("https://gitlab.com/group/sub/app/-/merge_requests/{}", iid),
+            "source_branch": "feature",
+            "target_branch": "main"
+        })
+    }
+
+    fn server() -> MockServer {
+        MockServer::start(|request| {
+            let (path, query) = request.path.split_once('?').unwrap_or((&request.path, ""));
+            match path {
+                "/api/v4/projects/group%2Fsub%2Fapp/merge_requests" if query.contains("page=2") => {
+                    MockResponse::json(200, json!([merge_request(2, "merged")])).with_header("X-Next-Page", "")
+                }
+                "/api/v4/projects/group%2Fsub%2Fapp/merge_requests" => {
+                    MockResponse::json(200, json!([merge_request(1, "opened")])).with_header("X-Next-Page", "2")
+                }
+                "/api/v4/projects/group%2Fsub%2Fapp/issues" => MockResponse::json(429, json!({ "message": "Retry later" }))
+                    .with_header("RateLimit-Reset", "1704067260"),
+                _ => MockResponse::json(401, json!({ "message": "401 Unauthorized" })),
+            }
+        })
+    }
+
+    #[test]
+    fn test_merge_requests_are_filtered_and_paged_by_page_number() {
+        let server = server();
+        let provider = GitLabProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let query = ListQuery {
+            state: ListState::All,
+            author: Some("alice".to_string()),
+            label: Some("bug".to_string()),
+            assignee: Some("bob".to_string()),
+            search: Some("login".to_string()),
+            sort: ListSort::Updated,
+            per_page: Some(1),
+            ..Default::default()
+        };
+        let page = runtime.block_on(provider.get_pr_list("group/sub", "app", &query)).unwrap();
+        // "opened" is reported as "open", like the other services
+        assert_eq!((page.items[0].number, page.items[0].state.as_str()), (1, "open"));
+        let cursor = page.next_cursor.unwrap();
+
+        let page = runtime
+            .block_on(provider.get_pr_list("group/sub", "app", &ListQuery { cursor: Some(cursor), ..Default::default() }))
+            .unwrap();
+        assert_eq!((page.items[0].state.as_str(), page.next_cursor), ("merged", None));
+
+        let requests = server.requests();
+        assert_eq!(
+            requests[0].path,
+            "/api/v4/projects/group%2Fsub%2Fapp/merge_requests?state=all&order_by=updated_at&sort=desc\
+            &author_username=alice&labels=bug&assignee_username=bob&search=login&per_page=1"
+        );
+        assert_eq!(
+            requests[1].path,
+            "/api/v4/projects/group%2Fsub%2Fapp/merge_requests?state=all&order_by=updated_at&sort=desc\
+            &author_username=alice&labels=bug&assignee_username=bob&search=login&per_page=1&page=2"
+        );
+    }
+
+    #[test]
+    fn test_rate_limits_and_rejected_tokens_have_their_own_errors() {
+        let server = server();
+        let provider = GitLabProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        match runtime.block_on(provider.get_issue_list("group/sub", "app", &ListQuery::default())) {
+            Err(AppError::RateLimited { service, reset_at }) => {
+                assert_eq!((service.as_str(), reset_at.as_deref()), ("GitLab", Some("2024-01-01T00:01:00Z")));
+            }
+            other => panic!("expected a rate limit error, got {:?}", other.map(|page| page.items.len())),
+        }
+        assert!(matches!(
+            runtime.block_on(provider.get_commit_status("group/sub", "app", "abc")),
+            Err(AppError::AuthFailed(_))
+        ));
+
+        let query = ListQuery { state: ListState::Merged, ..Default::default() };
+        assert!(matches!(
+            runtime.block_on(provider.get_issue_list("group/sub", "app", &query)),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    fn review_server(approved: bool) -> MockServer {
+        let next_draft = AtomicU64::new(1);
+        MockServer::start(move |request| {
+            let path = request.path.split('?').next().unwrap();
+            let note = |id: u64, created_at: &str, position: serde_json::Value| {
+                json!({
+                    "id": id,
+                    "body": format!("Note {}", id),
+                    "author": { "username": "carol" },
+                    "created_at": created_at,
+                    "updated_at": created_at,
+                    "system": false,
+                    "resolvable": !position.is_null(),
+                    "resolved": false,
+                    "position": position
+                })
+            };
+            match path {
+                "/api/v4/projects/o%2Fr/merge_requests/5" => {
+                    let mut mr = merge_request(5, "opened");
+                    mr["draft"] = json!(false);
+                    mr["sha"] = json!("abc123");
+                    mr["diff_refs"] = json!({ "base_sha": "def456", "head_sha": "abc123", "start_sha": "def456" });
+                    mr["detailed_merge_status"] = json!("discussions_not_resolved");
+                    mr["merge_status"] = json!("can_be_merged");
+                    mr["changes_count"] = json!("2");
+                    MockResponse::json(200, mr)
+                }
+                "/api/v4/projects/o%2Fr/merge_requests/5/approvals" => MockResponse::json(
+                    200,
+                    json!({ "approved": approved, "approvals_left": 0, "approved_by": [{ "user": { "username": "bob" } }] }),
+                ),
+                "/api/v4/projects/o%2Fr/repository/commits/abc123/statuses" => MockResponse::json(
+                    200,
+                    json!([
+                        { "id": 1, "name": "build", "status": "success", "target_url": null, "description": null },
+                        { "id": 2, "name": "deploy", "status": "manual", "target_url": null, "description": null }
+                    ]),
+                ),
+                "/api/v4/projects/o%2Fr/merge_requests/5/diffs" => MockResponse::json(
+                    200,
+                    json!([
+                        { "old_path": "a.txt", "new_path": "b.txt", "new_file": false, "renamed_file": true, "deleted_file": false, "diff": "@@ -1,2 +1,2 @@\n-one\n+uno\n+dos\n two\n" },
+                        { "old_path": "img.png", "new_path": "img.png", "new_file": true, "renamed_file": false, "deleted_file": false, "diff": "" }
+                    ]),
+                ),
+                "/api/v4/projects/o%2Fr/merge_requests/5/commits" => MockResponse::json(
+                    200,
+                    json!([
+                        { "id": "c2", "message": "Finish", "author_name": "Alice", "authored_date": "2024-01-02T00:00:00Z" },
+                        { "id": "c1", "message": "Start", "author_name": "Alice", "authored_date": "2024-01-01T00:00:00Z" }
+                    ]),
+                ),
+                "/api/v4/projects/o%2Fr/merge_requests/5/discussions" => MockResponse::json(
+                    200,
+                    json!([
+                        { "id": "d1", "individual_note": true, "notes": [note(1, "2024-01-01T00:00:00Z", json!(null))] },
+                        { "id": "d2", "individual_note": false, "notes": [
+                            note(2, "2024-01-02T00:00:00Z", json!({ "new_path": "b.txt", "old_path": "a.txt", "new_line": null, "old_line": 1, "head_sha": "abc123" })),
+                            { "id": 3, "body": "added 1 commit", "author": { "username": "carol" }, "created_at": "2024-01-02T01:00:00Z", "updated_at": "2024-01-02T01:00:00Z", "system": true }
+                        ] }
+                    ]),
+                ),
+                "/api/v4/projects/o%2Fr/merge_requests/5/draft_notes" if request.json()["note"] == "rejected" => {
+                    MockResponse::json(400, json!({ "message": "400 Bad request" }))
+                }
+                "/api/v4/projects/o%2Fr/merge_requests/5/draft_notes" => {
+                    MockResponse::json(201, json!({ "id": next_draft.fetch_add(1, Ordering::SeqCst) }))
+                }
+                path if path.starts_with("/api/v4/projects/o%2Fr/merge_requests/5/draft_notes/") => MockResponse::text(204, ""),
+                "/api/v4/projects/o%2Fr/merge_requests/5/approve" => MockResponse::json(201, json!({ "approved": true })),
+                "/api/v4/projects/o%2Fr/merge_requests/5/discussions/d2/notes" => {
+                    MockResponse::json(201, note(4, "2024-01-03T00:00:00Z", json!(null)))
+                }
+                "/api/v4/projects/o%2Fr/merge_requests/5/discussions/d2" => {
+                    MockResponse::json(200, json!({ "id": "d2", "notes": [] }))
+                }
+                _ => MockResponse::json(404, json!({ "message": "404 Not found" })),
+            }
+        })
+    }
+
+    #[test]
+    fn test_merge_request_details_files_commits_and_discussions() {
+        let server = review_server(true);
+        let provider = GitLabProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let details = runtime.block_on(provider.get_pr_details("o", "r", 5)).unwrap();
+        assert_eq!((details.head_sha.as_str(), details.base_sha.as_deref()), ("abc123", Some("def456")));
+        assert_eq!((details.merge_status, details.changed_files), (MergeStatus::Blocked, Some(2)));
+        assert_eq!((details.reviews[0].author.as_str(), details.review_decision), ("bob", Some(ReviewState::Approved)));
+        assert_eq!(details.checks, CheckSummary { total: 2, passed: 1, failed: 0, pending: 0, other: 1 });
+
+        let files = runtime.block_on(provider.get_pr_files("o", "r", 5)).unwrap();
+        assert_eq!((files[0].path.as_str(), files[0].old_path.as_deref()), ("b.txt", Some("a.txt")));
+        assert_eq!((files[0].status.clone(), files[0].additions, files[0].deletions), (FileStatus::Renamed, 2, 1));
+        assert_eq!((files[1].status.clone(), files[1].patch.as_deref()), (FileStatus::Added, None));
+
+        let commits = runtime.block_on(provider.get_pr_commits("o", "r", 5)).unwrap();
+        assert_eq!(commits.iter().map(|c| c.sha.as_str()).collect::<Vec<_>>(), ["c1", "c2"]);
+
+        // System notes are left out
+        let comments = runtime.block_on(provider.get_pr_comments("o", "r", 5)).unwrap();
+        assert_eq!(comments.len(), 2);
+        assert_eq!((comments[0].thread_id.as_deref(), comments[0].resolved), (Some("d1"), None));
+        assert_eq!((comments[1].thread_id.as_deref(), comments[1].resolved), (Some("d2"), Some(false)));
+        let position = comments[1].position.as_ref().unwrap();
+        assert_eq!((position.path.as_str(), position.line, position.side), ("a.txt", Some(1), DiffSide::Left));
+    }
+
+    #[test]
+    fn test_approvals_decide_the_review_only_once_the_rules_are_met() {
+        let server = review_server(false);
+        let provider = GitLabProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let details = runtime.block_on(provider.get_pr_details("o", "r", 5)).unwrap();
+        assert_eq!((details.reviews.len(), details.review_decision), (1, None));
+    }
+
+    fn comment(line: u32, side: DiffSide, body: &str) -> NewReviewComment {
+        NewReviewComment { path: "b.txt".to_string(), old_path: None, line, side, start_line: None, body: body.to_string() }
+    }
+
+    #[test]
+    fn test_reviews_are_drafted_anchored_to_both_sides_and_published() {
+        let server = review_server(false);
+        let provider = GitLabProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let review = CreateReview {
+            body: Some("Nice".to_string()),
+            event: ReviewEvent::Approve,
+            comments: vec![
+                // An unchanged line, a removed one, and one outside the hunks
+                comment(3, DiffSide::Right, "context"),
+                comment(1, DiffSide::Left, "removed"),
+                comment(10, DiffSide::Right, "outside"),
+            ],
+            commit_sha: None,
+        };
+        runtime.block_on(provider.create_review("o", "r", 5, review)).unwrap();
+
+        let requests = server.requests();
+        let drafts: Vec<serde_json::Value> = requests.iter()
+            .filter(|request| request.path == "/api/v4/projects/o%2Fr/merge_requests/5/draft_notes")
+            .map(|request| request.json())
+            .collect();
+        assert_eq!(drafts[0], json!({ "note": "Nice" }));
+        assert_eq!(
+            drafts[1]["position"],
+            json!({
+                "position_type": "text",
+                "base_sha": "def456",
+                "start_sha": "def456",
+                "head_sha": "abc123",
+                "old_path": "a.txt",
+                "new_path": "b.txt",
+                "old_line": 2,
+                "new_line": 3
+            })
+        );
+        assert_eq!((&drafts[2]["position"]["old_line"], &drafts[2]["position"]["new_line"]), (&json!(1), &json!(null)));
+        assert_eq!((&drafts[3]["position"]["old_line"], &drafts[3]["position"]["new_line"]), (&json!(null), &json!(10)));
+        // Only the drafts created here are published, then the approval
+        let published: Vec<(&str, &str)> = requests.iter()
+            .filter(|request| request.path.contains("/draft_notes/") || request.path.ends_with("/approve"))
+            .map(|request| (request.method.as_str(), request.path.as_str()))
+            .collect();
+        assert_eq!(
+            published,
+            [
+                ("PUT", "/api/v4/projects/o%2Fr/merge_requests/5/draft_notes/1/publish"),
+                ("PUT", "/api/v4/projects/o%2Fr/merge_requests/5/draft_notes/2/publish"),
+                ("PUT", "/api/v4/projects/o%2Fr/merge_requests/5/draft_notes/3/publish"),
+                ("PUT", "/api/v4/projects/o%2Fr/merge_requests/5/draft_notes/4/publish"),
+                ("POST", "/api/v4/projects/o%2Fr/merge_requests/5/approve"),
+            ]
+        );
+
+        // Drafts stay unpublished, and changes can't be requested
+        let draft = CreateReview { body: Some("AI review".to_string()), event: ReviewEvent::Draft, ..Default::default() };
+        runtime.block_on(provider.create_review("o", "r", 5, draft)).unwrap();
+        assert_eq!(server.requests().last().unwrap().path, "/api/v4/projects/o%2Fr/merge_requests/5/draft_notes");
+        let request_changes = CreateReview { event: ReviewEvent::RequestChanges, ..Default::default() };
+        assert!(matches!(
+            runtime.block_on(provider.create_review("o", "r", 5, request_changes)),
+            Err(AppError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_failed_review_removes_its_drafts_and_multi_line_comments_are_refused() {
+        let server = review_server(false);
+        let provider = GitLabProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let review = CreateReview {
+            body: Some("Looks good".to_string()),
+            comments: vec![comment(3, DiffSide::Right, "context"), comment(1, DiffSide::Left, "rejected")],
+            ..Default::default()
+        };
+        assert!(runtime.block_on(provider.create_review("o", "r", 5, review)).is_err());
+
+        let requests = server.requests();
+        let cleanup: Vec<(&str, &str)> = requests.iter()
+            .filter(|request| request.path.contains("/draft_notes/"))
+            .map(|request| (request.method.as_str(), request.path.as_str()))
+            .collect();
+        assert_eq!(
+            cleanup,
+            [
+                ("DELETE", "/api/v4/projects/o%2Fr/merge_requests/5/draft_notes/1"),
+                ("DELETE", "/api/v4/projects/o%2Fr/merge_requests/5/draft_notes/2"),
+            ]
+        );
+
+        let sent = requests.len();
+        let multi_line = NewReviewComment { start_line: Some(1), ..comment(3, DiffSide::Right, "range") };
+        let review = CreateReview { comments: vec![multi_line], ..Default::default() };
+        assert!(matches!(
+            runtime.block_on(provider.create_review("o", "r", 5, review)),
+            Err(AppError::InvalidInput(_))
+        ));
+        assert_eq!(server.requests().len(), sent);
+    }
+
+    #[test]
+    fn test_discussions_are_replied_to_and_resolved() {
+        let server = review_server(false);
+        let provider = GitLabProvider::new("secret".to_string(), Some(server.url.clone()));
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let reply = runtime.block_on(provider.reply_to_thread("o", "r", 5, "d2", "Fixed")).unwrap();
+        assert_eq!((reply.id.as_str(), reply.thread_id.as_deref()), ("4", Some("d2")));
+        runtime.block_on(provider.resolve_thread("o", "r", 5, "d2", true)).unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].json(), json!({ "body": "Fixed" }));
+        assert_eq!(
+            (requests[1].method.as_str(), requests[1].path.as_str()),
+            ("PUT", "/api/v4/projects/o%2Fr/merge_requests/5/discussions/d2?resolved=true")
+        );
+    }
+}
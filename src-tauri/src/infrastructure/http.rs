@@ -0,0 +1,150 @@
+//! Response handling shared by the providers' REST clients
+
+use crate::error::{AppError, Result};
+use reqwest::{header::HeaderMap, Client, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use url::Url;
+
+/// Stop following next links after this many pages
+pub(crate) const MAX_PAGES: usize = 50;
+
+/// Turn an unsuccessful response into an error. Rate limits and rejected
+/// credentials get their own variants; anything else is `AppError::Http`
+/// with the status and the service's message
+pub(crate) async fn check(service: &str, res: Response) -> Result<Response> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+    let headers = res.headers().clone();
+    let message = res.text().await.unwrap_or_default();
+
+    if is_rate_limited(status, &headers, &message) {
+        return Err(AppError::RateLimited { service: service.to_string(), reset_at: rate_limit_reset(&headers) });
+    }
+    let error = format!("{} API Error: {} {}", service, status, message);
+    if matches!(status, StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN) {
+        return Err(AppError::AuthFailed(error));
+    }
+    Err(AppError::Http(error))
+}
+
+/// GitHub answers 403 with no requests left (or with a message, for its
+/// secondary limits); everyone else uses 429
+fn is_rate_limited(status: StatusCode, headers: &HeaderMap, message: &str) -> bool {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::FORBIDDEN => {
+            header(headers, "x-ratelimit-remaining") == Some("0")
+                || headers.contains_key("retry-after")
+                || message.to_lowercase().contains("rate limit")
+        }
+        _ => false,
+    }
+}
+
+/// When the limit resets: an epoch time in `X-RateLimit-Reset` (GitHub) or
+/// `RateLimit-Reset` (GitLab), or a delay in seconds in `Retry-After`
+fn rate_limit_reset(headers: &HeaderMap) -> Option<String> {
+    let at = ["x-ratelimit-reset", "ratelimit-reset"]
+        .iter()
+        .find_map(|name| header(headers, name)?.parse::<i64>().ok())
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .or_else(|| {
+            let delay = header(headers, "retry-after")?.parse::<i64>().ok()?;
+            Some(chrono::Utc::now() + chrono::Duration::seconds(delay))
+        })?;
+    Some(at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name)?.to_str().ok()
+}
+
+/// The URL of the page after `url`: the `rel="next"` entry of a `Link`
+/// header, or failing that GitLab's `X-Next-Page` number
+pub(crate) fn next_page(url: &Url, headers: &HeaderMap) -> Option<String> {
+    if let Some(next) = header(headers, "link").and_then(next_link) {
+        return Some(next);
+    }
+    let page = header(headers, "x-next-page").filter(|page| !page.is_empty())?;
+    Some(with_param(url, "page", page))
+}
+
+/// `url` with one query parameter replaced
+pub(crate) fn with_param(url: &Url, name: &str, value: &str) -> String {
+    let mut next = url.clone();
+    let query: Vec<(String, String)> =
+        url.query_pairs().into_owned().filter(|(k, _)| k != name).collect();
+    next.query_pairs_mut().clear().extend_pairs(query).append_pair(name, value);
+    next.to_string()
+}
+
+/// Fetch a whole list, following next links from `url` (which should ask
+/// for the largest page size the service allows)
+pub(crate) async fn fetch_all<T: DeserializeOwned>(client: &Client, service: &str, url: &str) -> Result<Vec<T>> {
+    fetch_pages(client, service, url, None).await
+}
+
+/// `fetch_all` for lists that each page wraps in an object, under `field`,
+/// like GitHub's `{"total_count": 2, "check_runs": [...]}`
+pub(crate) async fn fetch_all_in<T: DeserializeOwned>(
+    client: &Client,
+    service: &str,
+    url: &str,
+    field: &str,
+) -> Result<Vec<T>> {
+    fetch_pages(client, service, url, Some(field)).await
+}
+
+async fn fetch_pages<T: DeserializeOwned>(
+    client: &Client,
+    service: &str,
+    url: &str,
+    field: Option<&str>,
+) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    let mut next = Some(url.to_string());
+    for _ in 0..MAX_PAGES {
+        let Some(url) = next.take() else { break };
+        let res = client.get(&url).send().await.map_err(|e| AppError::Http(e.to_string()))?;
+        let res = check(service, res).await?;
+        next = next_page(res.url(), res.headers());
+        let page: Vec<T> = match field {
+            None => res.json().await.map_err(|e| AppError::Http(e.to_string()))?,
+            Some(field) => {
+                let mut page: serde_json::Value = res.json().await.map_err(|e| AppError::Http(e.to_string()))?;
+                let list = page.get_mut(field).map(serde_json::Value::take).unwrap_or_default();
+                serde_json::from_value(list).map_err(|e| AppError::Http(e.to_string()))?
+            }
+        };
+        items.extend(page);
+    }
+    Ok(items)
+}
+
+/// `<https://...?page=2>; rel="next", <https://...?page=5>; rel="last"`
+pub(crate) fn next_link(link: &str) -> Option<String> {
+    link.split(',').find_map(|entry| {
+        let (target, params) = entry.split_once(';')?;
+        let is_next = params.split(';').any(|param| {
+            param.trim().strip_prefix("rel=").is_some_and(|rel| rel.trim_matches('"').split_whitespace().any(|r| r == "next"))
+        });
+        let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+        is_next.then(|| target.to_string())
+    })
+}
+
+/// Check that a cursor from an earlier page points back into the same API,
+/// so the token is never sent anywhere else
+pub(crate) fn cursor_url(api_root: &str, cursor: &str) -> Result<String> {
+    if cursor.starts_with(&format!("{}/", api_root.trim_end_matches('/'))) {
+        Ok(cursor.to_string())
+    } else {
+        Err(AppError::InvalidInput(format!("Page cursor is not a {} URL", api_root)))
+    }
+}
+
+#[cfg(test)]
+#[path = "http_tests.rs"]
+mod http_tests;
@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod tests {
+    use crate::error::AppError;
+    use crate::infrastructure::http::{cursor_url, next_link, next_page, with_param};
+    use reqwest::header::{HeaderMap, HeaderValue};
+    use url::Url;
+
+    #[test]
+    fn test_next_link_is_taken_from_the_link_header() {
+        let link = r#"<https://api.github.com/repositories/1/pulls?page=1>; rel="prev", <https://api.github.com/repositories/1/pulls?page=3>; rel="next", <https://api.github.com/repositories/1/pulls?page=9>; rel="last""#;
+        assert_eq!(next_link(link).as_deref(), Some("https://api.github.com/repositories/1/pulls?page=3"));
+
+        assert_eq!(next_link(r#"<https://x/?page=1>; rel="first", <https://x/?page=1>; rel="prev""#), None);
+        assert_eq!(next_link(r#"<https://x/?page=2>; type="json"; rel=next"#).as_deref(), Some("https://x/?page=2"));
+    }
+
+    #[test]
+    fn test_next_page_falls_back_to_the_page_number_header() {
+        let url = Url::parse("https://gitlab.com/api/v4/projects/a%2Fb/issues?state=opened&page=1&per_page=2").unwrap();
+        let mut headers = HeaderMap::new();
+        assert_eq!(next_page(&url, &headers), None);
+
+        // Empty on the last page
+        headers.insert("x-next-page", HeaderValue::from_static(""));
+        assert_eq!(next_page(&url, &headers), None);
+
+        headers.insert("x-next-page", HeaderValue::from_static("2"));
+        assert_eq!(
+            next_page(&url, &headers).as_deref(),
+            Some("https://gitlab.com/api/v4/projects/a%2Fb/issues?state=opened&per_page=2&page=2")
+        );
+
+        headers.insert("link", HeaderValue::from_static(r#"<https://gitlab.com/api/v4/next>; rel="next""#));
+        assert_eq!(next_page(&url, &headers).as_deref(), Some("https://gitlab.com/api/v4/next"));
+
+        let url = Url::parse("https://host/rest?limit=25").unwrap();
+        assert_eq!(with_param(&url, "start", "25"), "https://host/rest?limit=25&start=25");
+    }
+
+    #[test]
+    fn test_cursors_must_point_into_the_api() {
+        assert_eq!(
+            cursor_url("https://api.github.com/", "https://api.github.com/search/issues?page=2").unwrap(),
+            "https://api.github.com/search/issues?page=2"
+        );
+        for cursor in ["https://api.github.com.evil.test/x", "https://evil.test/https://api.github.com/x", "page=2"] {
+            assert!(matches!(cursor_url("https://api.github.com", cursor), Err(AppError::InvalidInput(_))));
+        }
+    }
+}
@@ -1,2 +1,8 @@
 pub mod github;
 pub mod gitlab;
+pub mod gitea;
+pub mod bitbucket;
+pub(crate) mod http;
+
+#[cfg(test)]
+pub(crate) mod mock_server;
@@ -21,6 +21,9 @@ pub fn run() {
             get_branch_info,
             get_commit_history,
             get_commit_history_paginated,
+            get_commit_history_page,
+            get_commit_graph,
+            clear_history_cache,
             get_local_branches,
             switch_branch,
             publish_branch,
@@ -30,12 +33,20 @@ pub fn run() {
             rename_branch,
             create_branch,
             get_file_diff,
+            get_file_history,
+            get_commit_details,
+            get_commit_file_diff,
             merge_branch,
             fetch_remote,
             pull_branch,
+            cancel_operation,
+            get_repository_operation_state,
             get_merge_state,
             resolve_conflict,
             get_conflict_diff,
+            get_conflict_blob,
+            merge_conflict_file,
+            resolve_conflict_regions,
             abort_merge,
             complete_merge,
             write_conflict_file,
@@ -45,6 +56,33 @@ pub fn run() {
             skip_rebase,
             abort_rebase,
             amend_rebase_commit,
+            // Reflog commands
+            get_reflog_refs,
+            get_reflog,
+            restore_from_reflog,
+            // Cherry-pick / revert commands
+            cherry_pick_commits,
+            revert_commits,
+            continue_cherry_pick,
+            continue_revert,
+            skip_cherry_pick,
+            skip_revert,
+            abort_cherry_pick,
+            abort_revert,
+            // Bisect commands
+            start_bisect,
+            bisect_mark,
+            get_bisect_state,
+            get_bisect_log,
+            reset_bisect,
+            run_bisect,
+            // Worktree commands
+            list_worktrees,
+            add_worktree,
+            lock_worktree,
+            unlock_worktree,
+            prune_worktrees,
+            remove_worktree,
             // Commit commands
             search_commits,
             stage_files,
@@ -85,6 +123,14 @@ pub fn run() {
             create_issue,
             fetch_commit_status,
             fetch_job_logs,
+            fetch_pr_details,
+            fetch_pr_files,
+            fetch_pr_commits,
+            fetch_pr_comments,
+            create_pr_review,
+            reply_to_pr_thread,
+            resolve_pr_thread,
+            set_provider_host,
             // Editor command
             open_in_external_editor,
             // Git extension commands
@@ -316,7 +316,7 @@ export function RepoView({ repoPath }: RepoViewProps) {
         repoPath,
         "origin",
         undefined,
-        false,
+        undefined,
         gitUsername,
         gitPassword,
       );
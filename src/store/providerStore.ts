@@ -1,6 +1,6 @@
 import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/core';
-import type { PullRequest, Issue, CreatePullRequest, CreateIssue, CommitStatus } from '../types';
+import type { PullRequest, Issue, CreatePullRequest, CreateIssue, CommitStatus, ListPage } from '../types';
 import { useSettingsStore } from './settingsStore';
 
 interface ProviderStore {
@@ -60,8 +60,8 @@ export const useProviderStore = create<ProviderStore>((set, get) => ({
     const { token, domain } = await getProviderConfig();
 
     try {
-      const prs = await invoke<PullRequest[]>('fetch_pr_list', { path, token, domain });
-      set({ pullRequests: prs, isLoading: false });
+      const page = await invoke<ListPage<PullRequest>>('fetch_pr_list', { path, token, domain });
+      set({ pullRequests: page.items, isLoading: false });
     } catch (e) {
       set({ error: String(e), isLoading: false });
     }
@@ -72,8 +72,8 @@ export const useProviderStore = create<ProviderStore>((set, get) => ({
     const { token, domain } = await getProviderConfig();
 
     try {
-      const issues = await invoke<Issue[]>('fetch_issue_list', { path, token, domain });
-      set({ issues, isLoading: false });
+      const page = await invoke<ListPage<Issue>>('fetch_issue_list', { path, token, domain });
+      set({ issues: page.items, isLoading: false });
     } catch (e) {
       set({ error: String(e), isLoading: false });
     }
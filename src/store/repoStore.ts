@@ -13,8 +13,10 @@ import type {
   StashInfo,
   TagInfo,
   RemoteInfo,
+  PushRefResult,
   RemoteBranch,
   MergeState,
+  MergeOptions,
   ConflictResolution,
   RebaseState,
   RebaseTodo,
@@ -24,6 +26,14 @@ import type {
   LfsStatus,
 } from '../types';
 
+// The remote can reject individual refs while the push itself succeeds
+function ensurePushAccepted(results: PushRefResult[]) {
+  const rejected = results.filter(r => !r.accepted);
+  if (rejected.length > 0) {
+    throw new Error(rejected.map(r => `${r.remoteRef}: ${r.message ?? 'rejected'}`).join('\n'));
+  }
+}
+
 interface RepoStore {
   // State
   repositories: Repository[];
@@ -83,14 +93,15 @@ interface RepoStore {
   batchCommit: (repoPaths: string[], message: string) => Promise<BatchCommitResult>;
 
   switchBranch: (path: string, branchName: string) => Promise<void>;
-  publishBranch: (path: string, branchName: string, remote?: string, username?: string, password?: string) => Promise<void>;
-  pushBranch: (path: string, branchName: string, remote?: string, username?: string, password?: string) => Promise<void>;
+  publishBranch: (path: string, branchName: string, remote?: string, username?: string, password?: string) => Promise<PushRefResult[]>;
+  pushBranch: (path: string, branchName: string, remote?: string, username?: string, password?: string, forceWithLease?: boolean) => Promise<PushRefResult[]>;
   deleteBranch: (path: string, branchName: string) => Promise<void>;
   renameBranch: (path: string, oldName: string, newName: string) => Promise<void>;
   createBranch: (path: string, newBranchName: string, baseBranchName: string) => Promise<void>;
-  mergeBranch: (path: string, branchName: string) => Promise<void>;
+  mergeBranch: (path: string, branchName: string, options?: MergeOptions) => Promise<void>;
   fetch: (path: string, remote?: string, username?: string, password?: string) => Promise<void>;
-  pull: (path: string, remote?: string, branch?: string, useRebase?: boolean, username?: string, password?: string) => Promise<void>;
+  // Without useRebase, pull.rebase / branch.<name>.rebase decide
+  pull: (path: string, remote?: string, branch?: string, useRebase?: boolean, username?: string, password?: string, mergeOptions?: MergeOptions) => Promise<void>;
   cloneRepository: (url: string, destination: string, username?: string, password?: string) => Promise<void>;
   
   stashSave: (path: string, message?: string, includeUntracked?: boolean) => Promise<void>;
@@ -100,8 +111,8 @@ interface RepoStore {
 
   createTag: (path: string, name: string, message?: string, target?: string) => Promise<void>;
   deleteTag: (path: string, name: string) => Promise<void>;
-  pushTag: (path: string, tagName: string, remote?: string, username?: string, password?: string) => Promise<void>;
-  deleteRemoteTag: (path: string, tagName: string, remote?: string, username?: string, password?: string) => Promise<void>;
+  pushTag: (path: string, tagName: string, remote?: string, username?: string, password?: string) => Promise<PushRefResult[]>;
+  deleteRemoteTag: (path: string, tagName: string, remote?: string, username?: string, password?: string) => Promise<PushRefResult[]>;
 
   addRemote: (path: string, name: string, url: string) => Promise<void>;
   removeRemote: (path: string, name: string) => Promise<void>;
@@ -640,14 +651,18 @@ export const useRepoStore = create<RepoStore>((set, get) => ({
   },
 
   publishBranch: async (path, branchName, remote = 'origin', username?: string, password?: string) => {
-    await invoke('publish_branch', { path, branchName, remote, username, password });
+    const results = await invoke<PushRefResult[]>('publish_branch', { path, branchName, remote, username, password });
+    ensurePushAccepted(results);
     await get().refreshBranchInfo(path);
     await get().loadLocalBranches(path);
+    return results;
   },
 
-  pushBranch: async (path, branchName, remote = 'origin', username?: string, password?: string) => {
-    await invoke('push_branch', { path, branchName, remote, username, password });
+  pushBranch: async (path, branchName, remote = 'origin', username?: string, password?: string, forceWithLease?: boolean) => {
+    const results = await invoke<PushRefResult[]>('push_branch', { path, branchName, remote, username, password, forceWithLease });
+    ensurePushAccepted(results);
     await get().refreshBranchInfo(path);
+    return results;
   },
 
   deleteBranch: async (path, branchName) => {
@@ -673,8 +688,8 @@ export const useRepoStore = create<RepoStore>((set, get) => ({
     await get().loadLocalBranches(path);
   },
 
-  mergeBranch: async (path, branchName) => {
-    await invoke('merge_branch', { path, branchName });
+  mergeBranch: async (path, branchName, options) => {
+    await invoke('merge_branch', { path, branchName, options });
     await get().refreshStatus(path);
     await get().refreshBranchInfo(path);
     await get().loadCommitHistory(path);
@@ -686,7 +701,7 @@ export const useRepoStore = create<RepoStore>((set, get) => ({
     await get().loadCommitHistory(path);
   },
 
-  pull: async (path, remote = 'origin', branch, useRebase = false, username, password) => {
+  pull: async (path, remote = 'origin', branch, useRebase, username, password, mergeOptions) => {
     // If branch is not specified, use current branch
     const targetBranch = branch || get().currentBranchInfo?.current;
     if (!targetBranch) throw new Error("No branch selected");
@@ -696,6 +711,7 @@ export const useRepoStore = create<RepoStore>((set, get) => ({
       remote, 
       branch: targetBranch, 
       useRebase, 
+      mergeOptions,
       username, 
       password 
     });
@@ -872,11 +888,15 @@ export const useRepoStore = create<RepoStore>((set, get) => ({
   },
 
   pushTag: async (path, tagName, remote = 'origin', username, password) => {
-    await invoke('push_tag', { path, tagName, remote, username, password });
+    const results = await invoke<PushRefResult[]>('push_tag', { path, tagName, remote, username, password });
+    ensurePushAccepted(results);
+    return results;
   },
 
   deleteRemoteTag: async (path, tagName, remote = 'origin', username, password) => {
-    await invoke('delete_remote_tag', { path, tagName, remote, username, password });
+    const results = await invoke<PushRefResult[]>('delete_remote_tag', { path, tagName, remote, username, password });
+    ensurePushAccepted(results);
+    return results;
   },
 
   loadRemotes: async (path) => {
@@ -9,6 +9,8 @@ export interface Repository {
   untrackedCount: number;
   ahead: number;
   behind: number;
+  // Set for linked worktrees: path of the repository they belong to
+  mainRepository: string | null;
 }
 
 export interface RepoStatus {
@@ -33,15 +35,206 @@ export type FileStatus =
   | "unmerged"
   | "unknown";
 
+export interface RenameOptions {
+  renames?: boolean;
+  copies?: boolean;
+  threshold?: number; // similarity in percent, 50 by default
+  worktree?: boolean; // unstaged renames in status, off by default
+}
+
+export interface FileHistoryOptions {
+  follow?: boolean; // true by default
+  skip?: number;
+  limit?: number;
+  renames?: RenameOptions;
+}
+
+export interface FileHistoryEntry {
+  commit: CommitInfo;
+  path: string;
+  status: FileStatus;
+  oldPath: string | null;
+}
+
+// Commit detail types
+export interface CommitIdentity {
+  name: string;
+  email: string;
+  timestamp: number;
+  offsetMinutes: number; // timezone as minutes east of UTC
+}
+
+export interface CommitTrailer {
+  key: string;
+  value: string;
+}
+
+export type SignatureStatus =
+  | "good"
+  | "untrusted"
+  | "bad"
+  | "expired"
+  | "revoked"
+  | "unverified";
+
+export interface CommitSignature {
+  format: "openpgp" | "x509" | "ssh";
+  status: SignatureStatus;
+  signer: string | null;
+  key: string | null;
+}
+
+export interface CommitFileChange {
+  path: string;
+  oldPath: string | null;
+  status: FileStatus;
+  insertions: number;
+  deletions: number;
+  isBinary: boolean;
+}
+
+export interface CommitDetailsOptions {
+  parentIndex?: number; // parent to diff against for merge commits
+  renames?: RenameOptions;
+}
+
+export interface CommitDetails {
+  id: string;
+  shortId: string;
+  summary: string;
+  message: string;
+  trailers: CommitTrailer[];
+  author: CommitIdentity;
+  committer: CommitIdentity;
+  parents: string[];
+  signature: CommitSignature | null;
+  files: CommitFileChange[];
+  insertions: number;
+  deletions: number;
+}
+
+// Commit graph types
+export interface GraphOptions {
+  firstParent?: boolean;
+  allRefs?: boolean; // include remote branches and tags
+  simplifyByDecoration?: boolean;
+}
+
+export interface GraphLane {
+  oid: string;
+  color: number;
+}
+
+// Pass back with the next page to continue the layout
+export interface GraphState {
+  lanes: (GraphLane | null)[];
+  nextColor: number;
+  rows: number;
+  position: number;
+}
+
+export type GraphEdgeKind = "pass" | "child" | "parent";
+
+export interface GraphEdge {
+  from: number;
+  to: number;
+  color: number;
+  kind: GraphEdgeKind;
+}
+
+export interface GraphRow {
+  commit: CommitInfo;
+  column: number;
+  color: number;
+  edges: GraphEdge[];
+}
+
+export interface CommitGraphPage {
+  rows: GraphRow[];
+  state: GraphState;
+  hasMore: boolean;
+}
+
 // Conflict types
+export type ConflictKind =
+  | "bothModified"
+  | "bothAdded"
+  | "deletedByUs"
+  | "deletedByThem"
+  | "addedByUs"
+  | "addedByThem"
+  | "bothDeleted"
+  | "renameRename";
+
+export interface ConflictSide {
+  path: string;
+  id: string;
+  mode: number;
+}
+
 export interface ConflictInfo {
   path: string;
-  current: string | null;
-  incoming: string | null;
-  ancestor: string | null;
+  kind: ConflictKind;
+  ancestor: ConflictSide | null;
+  ours: ConflictSide | null;
+  theirs: ConflictSide | null;
+  isBinary: boolean;
   conflictMarkers: boolean;
 }
 
+// One side of a conflict, fetched with get_conflict_blob
+export interface ConflictBlob {
+  id: string;
+  size: number;
+  isBinary: boolean;
+  content: string | null;
+}
+
+export type ConflictStyle = "merge" | "diff3" | "zdiff3";
+
+export type ConflictFavor = "none" | "ours" | "theirs" | "union";
+
+export interface ConflictMergeOptions {
+  style?: ConflictStyle;
+  favor?: ConflictFavor;
+  ignoreWhitespace?: boolean;
+}
+
+// "allow" = merge.ff true, "never" = --no-ff, "only" = --ff-only
+export type FastForward = "allow" | "never" | "only";
+
+export interface MergeOptions {
+  // Unset reads merge.ff (and pull.ff when pulling)
+  fastForward?: FastForward;
+  squash?: boolean;
+  message?: string;
+  favor?: ConflictFavor;
+  ignoreWhitespace?: boolean;
+  noRenames?: boolean;
+  renameThreshold?: number;
+}
+
+// `base` is only set for the diff3 and zdiff3 styles
+export type MergeRegion =
+  | { kind: "clean"; content: string }
+  | { kind: "conflict"; ours: string; base: string | null; theirs: string };
+
+export interface MergedFile {
+  path: string;
+  isAutomergeable: boolean;
+  conflictCount: number;
+  regions: MergeRegion[];
+}
+
+// One per conflicting region, in order
+export type RegionChoice =
+  | { choice: "ours" }
+  | { choice: "theirs" }
+  | { choice: "base" }
+  | { choice: "oursThenTheirs" }
+  | { choice: "theirsThenOurs" }
+  | { choice: "custom"; content: string };
+
 export interface MergeState {
   isMergeInProgress: boolean;
   conflictCount: number;
@@ -69,6 +262,41 @@ export interface RebaseState {
   currentCommit: string | null;
 }
 
+export type RepositoryOperation =
+  | "none"
+  | "merge"
+  | "revert"
+  | "revertSequence"
+  | "cherryPick"
+  | "cherryPickSequence"
+  | "bisect"
+  | "rebase"
+  | "rebaseInteractive"
+  | "rebaseMerge"
+  | "applyMailbox"
+  | "applyMailboxOrRebase";
+
+export type OperationAction = "continue" | "skip" | "abort";
+
+export interface OperationStep {
+  command: string;
+  commit: string | null;
+  summary: string;
+}
+
+export interface OperationState {
+  operation: RepositoryOperation;
+  head: string | null;
+  headName: string | null;
+  // MERGE_HEAD, CHERRY_PICK_HEAD, REVERT_HEAD, rebase onto or bisect bad commit
+  target: string | null;
+  mergeMessage: string | null;
+  conflictCount: number;
+  todo: OperationStep[];
+  done: OperationStep[];
+  actions: OperationAction[];
+}
+
 export interface BranchInfo {
   current: string;
   ahead: number;
@@ -106,6 +334,16 @@ export interface CommitSearchQuery {
   dateTo?: number;
   path?: string;
   limit?: number;
+  diskCache?: boolean; // search the history index under .gat/
+}
+
+export interface HistoryPageOptions {
+  diskCache?: boolean; // keep a history index under .gat/
+}
+
+export interface CommitHistoryPage {
+  commits: CommitInfo[];
+  nextCursor: string | null; // pass back as `cursor` for the next page
 }
 
 export interface CommitSuggestion {
@@ -163,6 +401,13 @@ export interface RemoteInfo {
   pushUrl: string | null;
 }
 
+export interface PushRefResult {
+  refspec: string;
+  remoteRef: string;
+  accepted: boolean;
+  message: string | null;
+}
+
 // Provider types
 export interface CommitStatus {
   id: string;
@@ -172,6 +417,9 @@ export interface CommitStatus {
   description: string | null;
 }
 
+// Service behind a self-hosted remote, stored as gat.<host>.provider in git config
+export type ProviderKind = "github" | "gitlab" | "gitea" | "bitbucket" | "bitbucket-server";
+
 export interface PullRequest {
   id: string;
   number: number;
@@ -210,6 +458,120 @@ export interface CreateIssue {
   body?: string;
 }
 
+export type ListState = "open" | "closed" | "merged" | "all";
+
+// Filters and paging for fetch_pr_list / fetch_issue_list; a cursor replaces the rest
+export interface ListQuery {
+  state?: ListState;
+  author?: string;
+  label?: string;
+  assignee?: string;
+  search?: string;
+  sort?: "created" | "updated";
+  ascending?: boolean;
+  page?: number;
+  perPage?: number;
+  cursor?: string;
+}
+
+export interface ListPage<T> {
+  items: T[];
+  nextCursor: string | null;
+}
+
+export type MergeStatus = "clean" | "conflicts" | "behind" | "blocked" | "unstable" | "draft" | "unknown";
+
+export type ReviewState = "approved" | "changesRequested" | "commented" | "dismissed" | "pending";
+
+export interface PullRequestReview {
+  id: string;
+  author: string;
+  state: ReviewState;
+  body: string | null;
+  submittedAt: string | null;
+}
+
+export interface CheckSummary {
+  total: number;
+  passed: number;
+  failed: number;
+  pending: number;
+  other: number;
+}
+
+export interface PullRequestDetails {
+  pullRequest: PullRequest;
+  draft: boolean;
+  headSha: string;
+  baseSha: string | null;
+  mergeStatus: MergeStatus;
+  reviewDecision: ReviewState | null;
+  reviews: PullRequestReview[];
+  checks: CheckSummary;
+  commits: number | null;
+  changedFiles: number | null;
+}
+
+export interface PullRequestFile {
+  path: string;
+  oldPath: string | null;
+  status: FileStatus;
+  additions: number;
+  deletions: number;
+  // Hunks without file headers; null for binary or oversized diffs
+  patch: string | null;
+}
+
+export interface PullRequestCommit {
+  sha: string;
+  message: string;
+  author: string;
+  authoredAt: string;
+}
+
+export type DiffSide = "left" | "right";
+
+export interface CommentPosition {
+  path: string;
+  line: number | null;
+  side: DiffSide;
+  commitSha: string | null;
+}
+
+export interface PullRequestComment {
+  id: string;
+  threadId: string | null;
+  author: string;
+  body: string;
+  createdAt: string;
+  updatedAt: string;
+  url: string | null;
+  // null for general comments
+  position: CommentPosition | null;
+  resolved: boolean | null;
+}
+
+// "draft" leaves the review unsubmitted on the service
+export type ReviewEvent = "comment" | "approve" | "requestChanges" | "draft";
+
+export interface NewReviewComment {
+  path: string;
+  oldPath?: string | null;
+  line: number;
+  side?: DiffSide;
+  // First line of a multi-line comment (not on GitLab)
+  startLine?: number | null;
+  body: string;
+}
+
+export interface CreateReview {
+  body?: string | null;
+  event?: ReviewEvent;
+  comments?: NewReviewComment[];
+  // Defaults to the pull request's head
+  commitSha?: string | null;
+}
+
 // Settings types
 export interface Settings {
   workDir: string | null;
@@ -239,21 +601,73 @@ export interface Settings {
   externalEditor: "code" | "cursor" | "idea" | "sublime" | "webstorm" | string | null;
 }
 
+export type DiffMode = "combined" | "staged" | "unstaged";
+
+export type IntraLineMode = "none" | "word" | "char";
+
+export interface FileDiffOptions {
+  mode?: DiffMode;
+  contextLines?: number;
+  ignoreWhitespace?: boolean;
+  ignoreWhitespaceChange?: boolean;
+  ignoreBlankLines?: boolean;
+  intraLine?: IntraLineMode;
+  sideBySide?: boolean;
+  maxLines?: number;
+  renames?: RenameOptions;
+}
+
+export type DiffSource = "staged" | "unstaged" | "untracked" | "commit";
+
+// UTF-16 offsets, usable with String.prototype.slice
+export interface ChangeRange {
+  start: number;
+  end: number;
+}
+
 export interface DiffLine {
   content: string;
   origin: string; // char in rust, string in js
   oldLineno: number | null;
   newLineno: number | null;
+  changes: ChangeRange[];
+}
+
+// Indices into DiffHunk.lines
+export interface SideBySideRow {
+  left: number | null;
+  right: number | null;
 }
 
 export interface DiffHunk {
   header: string;
+  source: DiffSource;
+  oldStart: number;
+  oldLines: number;
+  newStart: number;
+  newLines: number;
   lines: DiffLine[];
+  rows: SideBySideRow[];
+}
+
+export type DiffContentKind = "text" | "binary" | "tooLarge";
+
+export interface DiffFileInfo {
+  id: string;
+  size: number;
+  data: string | null; // base64, images only
 }
 
 export interface FileDiff {
   path: string;
+  oldPath: string | null;
+  kind: DiffContentKind;
+  encoding: string | null;
+  mimeType: string | null;
+  oldFile: DiffFileInfo | null;
+  newFile: DiffFileInfo | null;
   hunks: DiffHunk[];
+  truncated: boolean;
 }
 
 export interface SubmoduleInfo {